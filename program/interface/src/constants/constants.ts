@@ -14,9 +14,15 @@ export class Constants {
 
   static toRewardTokenRaw = Math.pow(10, Constants.rewardTokenDecimals);
 
-  static cwarPoolBytes = 416;
+  static cwarPoolBytes = 1024;
 
-  static userStorageBytes = 114;
+  static userStorageBytes = 256;
+
+  static maxExtraRewardStreams = 2;
+
+  static maxLockTiers = 4;
+
+  static maxPenalitySteps = 4;
 
   static u64MaxStrValue = '18446744073709551615';
 
@@ -8,6 +8,115 @@ import {extendBorsh} from '../data/borsch';
 import {Constants} from '../constants';
 import {bigDivWithPrecision} from '../utils';
 
+export class RewardStreamData {
+  rewardMint: StringPublicKey;
+  rewardVault: StringPublicKey;
+  rewardRate: BN;
+  rewardDurationEnd: BN;
+  lastUpdateTime: BN;
+  rewardPerTokenStored: BN;
+  rewardsOwed: BN;
+
+  constructor(args: {
+    rewardMint: StringPublicKey;
+    rewardVault: StringPublicKey;
+    rewardRate: BN;
+    rewardDurationEnd: BN;
+    lastUpdateTime: BN;
+    rewardPerTokenStored: BN;
+    rewardsOwed: BN;
+  }) {
+    this.rewardMint = args.rewardMint;
+    this.rewardVault = args.rewardVault;
+    this.rewardRate = args.rewardRate;
+    this.rewardDurationEnd = args.rewardDurationEnd;
+    this.lastUpdateTime = args.lastUpdateTime;
+    this.rewardPerTokenStored = args.rewardPerTokenStored;
+    this.rewardsOwed = args.rewardsOwed;
+  }
+
+  isActive(): boolean {
+    return !new PublicKey(this.rewardMint).equals(PublicKey.default);
+  }
+
+  getRewardMintPubkey(): PublicKey {
+    return new PublicKey(this.rewardMint);
+  }
+
+  getRewardVaultPubkey(): PublicKey {
+    return new PublicKey(this.rewardVault);
+  }
+}
+
+export class LockTierData {
+  duration: BN;
+  multiplierBasisPoints: number;
+
+  constructor(args: { duration: BN; multiplierBasisPoints: number }) {
+    this.duration = args.duration;
+    this.multiplierBasisPoints = args.multiplierBasisPoints;
+  }
+
+  isActive(): boolean {
+    return !this.duration.isZero();
+  }
+}
+
+export enum PenalityScheduleKind {
+  Cliff = 0,
+  Linear = 1,
+  Stepped = 2,
+}
+
+export class PenalityStepData {
+  stakeAgeBelow: BN;
+  basisPoints: number;
+
+  constructor(args: { stakeAgeBelow: BN; basisPoints: number }) {
+    this.stakeAgeBelow = args.stakeAgeBelow;
+    this.basisPoints = args.basisPoints;
+  }
+}
+
+export class PenalityScheduleData {
+  kind: PenalityScheduleKind;
+  decayDuration: BN;
+  steps: PenalityStepData[];
+
+  constructor(args: {
+    kind: PenalityScheduleKind;
+    decayDuration: BN;
+    steps: PenalityStepData[];
+  }) {
+    this.kind = args.kind;
+    this.decayDuration = args.decayDuration;
+    this.steps = args.steps;
+  }
+}
+
+export class PenalityRoutingData {
+  burnBasisPoints: number;
+  rewardVaultBasisPoints: number;
+  redistributeBasisPoints: number;
+  treasuryBasisPoints: number;
+
+  constructor(args: {
+    burnBasisPoints: number;
+    rewardVaultBasisPoints: number;
+    redistributeBasisPoints: number;
+    treasuryBasisPoints: number;
+  }) {
+    this.burnBasisPoints = args.burnBasisPoints;
+    this.rewardVaultBasisPoints = args.rewardVaultBasisPoints;
+    this.redistributeBasisPoints = args.redistributeBasisPoints;
+    this.treasuryBasisPoints = args.treasuryBasisPoints;
+  }
+
+  usesRewardVault(): boolean {
+    return this.rewardVaultBasisPoints !== 0 || this.redistributeBasisPoints !== 0;
+  }
+}
+
 export class CwarPoolData {
   accountType: number;
   ownerWallet: StringPublicKey;
@@ -26,6 +135,30 @@ export class CwarPoolData {
   unstakePenalityBasisPoints: number;
   lockingDuration: BN;
   authorityPenalityDepositATA: StringPublicKey;
+  totalStaked: BN;
+  rewardsOwed: BN;
+  pendingOwner: StringPublicKey;
+  pauser: StringPublicKey;
+  pausedOperations: number;
+  timelockDelay: BN;
+  nextActionId: BN;
+  rewardStreams: RewardStreamData[];
+  unbondingDuration: BN;
+  totalUnbonding: BN;
+  lockTiers: LockTierData[];
+  totalLocked: BN;
+  totalLockedWeight: BN;
+  penalitySchedule: PenalityScheduleData;
+  penalityRouting: PenalityRoutingData;
+  minStake: BN;
+  maxStakePerUser: BN;
+  maxTotalStaked: BN;
+  allowlistRoot: Uint8Array;
+  receiptMint: StringPublicKey;
+  rewardSchedule: StringPublicKey;
+  scheduledRewardsLeft: BN;
+  rewardScheduleLastUpdateTime: BN;
+  rewardScheduleEnd: BN;
 
   constructor(args: {
     accountType: number;
@@ -45,6 +178,30 @@ export class CwarPoolData {
     unstakePenalityBasisPoints: number;
     lockingDuration: BN;
     authorityPenalityDepositATA: StringPublicKey;
+    totalStaked: BN;
+    rewardsOwed: BN;
+    pendingOwner: StringPublicKey;
+    pauser: StringPublicKey;
+    pausedOperations: number;
+    timelockDelay: BN;
+    nextActionId: BN;
+    rewardStreams: RewardStreamData[];
+    unbondingDuration: BN;
+    totalUnbonding: BN;
+    lockTiers: LockTierData[];
+    totalLocked: BN;
+    totalLockedWeight: BN;
+    penalitySchedule: PenalityScheduleData;
+    penalityRouting: PenalityRoutingData;
+    minStake: BN;
+    maxStakePerUser: BN;
+    maxTotalStaked: BN;
+    allowlistRoot: Uint8Array;
+    receiptMint: StringPublicKey;
+    rewardSchedule: StringPublicKey;
+    scheduledRewardsLeft: BN;
+    rewardScheduleLastUpdateTime: BN;
+    rewardScheduleEnd: BN;
   }) {
     this.accountType = args.accountType;
     this.ownerWallet = args.ownerWallet;
@@ -63,6 +220,30 @@ export class CwarPoolData {
     this.unstakePenalityBasisPoints = args.unstakePenalityBasisPoints;
     this.lockingDuration = args.lockingDuration;
     this.authorityPenalityDepositATA = args.authorityPenalityDepositATA;
+    this.totalStaked = args.totalStaked;
+    this.rewardsOwed = args.rewardsOwed;
+    this.pendingOwner = args.pendingOwner;
+    this.pauser = args.pauser;
+    this.pausedOperations = args.pausedOperations;
+    this.timelockDelay = args.timelockDelay;
+    this.nextActionId = args.nextActionId;
+    this.rewardStreams = args.rewardStreams;
+    this.unbondingDuration = args.unbondingDuration;
+    this.totalUnbonding = args.totalUnbonding;
+    this.lockTiers = args.lockTiers;
+    this.totalLocked = args.totalLocked;
+    this.totalLockedWeight = args.totalLockedWeight;
+    this.penalitySchedule = args.penalitySchedule;
+    this.penalityRouting = args.penalityRouting;
+    this.minStake = args.minStake;
+    this.maxStakePerUser = args.maxStakePerUser;
+    this.maxTotalStaked = args.maxTotalStaked;
+    this.allowlistRoot = args.allowlistRoot;
+    this.receiptMint = args.receiptMint;
+    this.rewardSchedule = args.rewardSchedule;
+    this.scheduledRewardsLeft = args.scheduledRewardsLeft;
+    this.rewardScheduleLastUpdateTime = args.rewardScheduleLastUpdateTime;
+    this.rewardScheduleEnd = args.rewardScheduleEnd;
   }
 
   getAuthorityPubkey(): PublicKey {
@@ -140,6 +321,84 @@ export class CwarPoolData {
     return new PublicKey(this.authorityPenalityDepositATA);
   }
 
+  getTotalStaked(): number {
+    return bigDivWithPrecision(this.totalStaked, new BN(Constants.toCwarRaw));
+  }
+
+  getRewardsOwed(): number {
+    return bigDivWithPrecision(
+      this.rewardsOwed,
+      new BN(Constants.toRewardTokenRaw)
+    );
+  }
+
+  getPendingOwnerPubkey(): PublicKey {
+    return new PublicKey(this.pendingOwner);
+  }
+
+  getPauserPubkey(): PublicKey {
+    return new PublicKey(this.pauser);
+  }
+
+  getPausedOperations(): number {
+    return this.pausedOperations;
+  }
+
+  getTimelockDelay(): number {
+    return this.timelockDelay.toNumber();
+  }
+
+  getNextActionId(): number {
+    return this.nextActionId.toNumber();
+  }
+
+  getReceiptMintPubkey(): PublicKey {
+    return new PublicKey(this.receiptMint);
+  }
+
+  hasReceiptMint(): boolean {
+    return !this.getReceiptMintPubkey().equals(PublicKey.default);
+  }
+
+  getRewardSchedulePubkey(): PublicKey {
+    return new PublicKey(this.rewardSchedule);
+  }
+
+  hasRewardSchedule(): boolean {
+    return !this.getRewardSchedulePubkey().equals(PublicKey.default);
+  }
+
+  getScheduledRewardsLeft(): number {
+    return this.scheduledRewardsLeft.toNumber();
+  }
+
+  getRewardScheduleEnd(): number {
+    return this.rewardScheduleEnd.toNumber();
+  }
+
+  // schedule pools take their reward schedule right after the fixed accounts of every
+  // instruction that checkpoints rewards
+  getRewardScheduleKeys(): {
+    pubkey: PublicKey;
+    isSigner: boolean;
+    isWritable: boolean;
+  }[] {
+    if (!this.hasRewardSchedule()) {
+      return [];
+    }
+    return [
+      {
+        pubkey: this.getRewardSchedulePubkey(),
+        isSigner: false,
+        isWritable: false,
+      },
+    ];
+  }
+
+  getActiveRewardStreams(): RewardStreamData[] {
+    return this.rewardStreams.filter(rewardStream => rewardStream.isActive());
+  }
+
   printPoolInfo(): void {
     console.log('accountType: ', this.accountType);
     console.log('ownerWallet: ', this.getAuthorityPubkey().toString());
@@ -167,6 +426,27 @@ export class CwarPoolData {
       'authorityPenalityDepositATA: ',
       this.getAuthorityPenalityDepositATA().toString()
     );
+    console.log('totalStaked: ', this.getTotalStaked());
+    console.log('rewardsOwed: ', this.getRewardsOwed());
+    console.log('pendingOwner: ', this.getPendingOwnerPubkey().toString());
+    console.log('pauser: ', this.getPauserPubkey().toString());
+    console.log('pausedOperations: ', this.getPausedOperations());
+    console.log('timelockDelay: ', this.getTimelockDelay());
+    console.log('nextActionId: ', this.getNextActionId());
+    console.log('receiptMint: ', this.getReceiptMintPubkey().toString());
+    console.log(
+      'rewardSchedule: ',
+      this.getRewardSchedulePubkey().toString()
+    );
+    console.log('scheduledRewardsLeft: ', this.getScheduledRewardsLeft());
+    console.log('rewardScheduleEnd: ', this.getRewardScheduleEnd());
+    this.getActiveRewardStreams().forEach(rewardStream => {
+      console.log(
+        'rewardStream: ',
+        rewardStream.getRewardMintPubkey().toString(),
+        rewardStream.getRewardVaultPubkey().toString()
+      );
+    });
   }
 
   static async fromAccount(account: PublicKey): Promise<CwarPoolData | null> {
@@ -216,6 +496,88 @@ export const CWAR_POOL_DATA_ON_CHAIN_SCHEMA = new Map([
         ['unstakePenalityBasisPoints', 'u16'],
         ['lockingDuration', 'u64'],
         ['authorityPenalityDepositATA', 'pubkeyAsString'],
+        ['totalStaked', 'u64'],
+        ['rewardsOwed', 'u64'],
+        ['pendingOwner', 'pubkeyAsString'],
+        ['pauser', 'pubkeyAsString'],
+        ['pausedOperations', 'u8'],
+        ['timelockDelay', 'u64'],
+        ['nextActionId', 'u64'],
+        ['rewardStreams', [RewardStreamData, Constants.maxExtraRewardStreams]],
+        ['unbondingDuration', 'u64'],
+        ['totalUnbonding', 'u64'],
+        ['lockTiers', [LockTierData, Constants.maxLockTiers]],
+        ['totalLocked', 'u64'],
+        ['totalLockedWeight', 'u64'],
+        ['penalitySchedule', PenalityScheduleData],
+        ['penalityRouting', PenalityRoutingData],
+        ['minStake', 'u64'],
+        ['maxStakePerUser', 'u64'],
+        ['maxTotalStaked', 'u64'],
+        ['allowlistRoot', [32]],
+        ['receiptMint', 'pubkeyAsString'],
+        ['rewardSchedule', 'pubkeyAsString'],
+        ['scheduledRewardsLeft', 'u64'],
+        ['rewardScheduleLastUpdateTime', 'u64'],
+        ['rewardScheduleEnd', 'u64'],
+      ],
+    },
+  ],
+  [
+    RewardStreamData,
+    {
+      kind: 'struct',
+      fields: [
+        ['rewardMint', 'pubkeyAsString'],
+        ['rewardVault', 'pubkeyAsString'],
+        ['rewardRate', 'u64'],
+        ['rewardDurationEnd', 'u64'],
+        ['lastUpdateTime', 'u64'],
+        ['rewardPerTokenStored', 'u128'],
+        ['rewardsOwed', 'u64'],
+      ],
+    },
+  ],
+  [
+    LockTierData,
+    {
+      kind: 'struct',
+      fields: [
+        ['duration', 'u64'],
+        ['multiplierBasisPoints', 'u32'],
+      ],
+    },
+  ],
+  [
+    PenalityScheduleData,
+    {
+      kind: 'struct',
+      fields: [
+        ['kind', 'u8'],
+        ['decayDuration', 'u64'],
+        ['steps', [PenalityStepData, Constants.maxPenalitySteps]],
+      ],
+    },
+  ],
+  [
+    PenalityStepData,
+    {
+      kind: 'struct',
+      fields: [
+        ['stakeAgeBelow', 'u64'],
+        ['basisPoints', 'u16'],
+      ],
+    },
+  ],
+  [
+    PenalityRoutingData,
+    {
+      kind: 'struct',
+      fields: [
+        ['burnBasisPoints', 'u16'],
+        ['rewardVaultBasisPoints', 'u16'],
+        ['redistributeBasisPoints', 'u16'],
+        ['treasuryBasisPoints', 'u16'],
       ],
     },
   ],
@@ -9,4 +9,42 @@ export enum CwarStakingInstructions {
   FundPool = 7,
   ClosePool = 8,
   CloseUser = 9,
+  MigratePool = 10,
+  RecoverSurplus = 11,
+  MigrateUser = 12,
+  ProposeNewOwner = 13,
+  AcceptOwnership = 14,
+  CancelOwnershipTransfer = 15,
+  SetPauser = 16,
+  SetPausedOperations = 17,
+  EmergencyUnstake = 18,
+  UpdatePoolConfig = 19,
+  SetTimelockDelay = 20,
+  QueueAction = 21,
+  ExecuteAction = 22,
+  CancelAction = 23,
+  ReduceRewards = 24,
+  CancelRewards = 25,
+  AddRewardStream = 26,
+  FundRewardStream = 27,
+  CloseRewardStream = 28,
+  CompoundRewards = 29,
+  RequestUnstake = 30,
+  WithdrawUnstaked = 31,
+  CancelUnstakeRequest = 32,
+  SetLockTiers = 33,
+  StakeLocked = 34,
+  UnstakeLocked = 35,
+  SetPenalitySchedule = 36,
+  SetPenalityRouting = 37,
+  SetStakeLimits = 38,
+  SetAllowlistRoot = 39,
+  CreateAllowlistedUser = 40,
+  Exit = 41,
+  ClaimRewardsMulti = 42,
+  TransferPosition = 43,
+  SetReceiptMint = 44,
+  SettleReceipt = 45,
+  AddRewardPhase = 46,
+  ExpireLockPosition = 47,
 }
@@ -2,7 +2,6 @@ import {PublicKey} from '@solana/web3.js';
 import BN from 'bn.js';
 import {CwarPoolData, UserData} from '.';
 import {Constants, Pubkeys} from '../constants';
-import {getStakingVaultBalanceRaw} from '../data';
 import {getUserStorageAccount} from '../utils';
 
 export async function getUserPendingRewards(
@@ -22,7 +21,7 @@ export async function getUserPendingRewards(
   if (userData === null) {
     return 0;
   }
-  const totalTokensStakedRaw = await getStakingVaultBalanceRaw();
+  const totalTokensStakedRaw = cwarPoolData.totalStaked;
   if (!lastApplicableTime)
     lastApplicableTime = Math.min(
       Math.floor(Date.now() / 1000),
@@ -8,6 +8,19 @@ import {extendBorsh} from '../data/borsch';
 import {Constants} from '../constants';
 import {bigDivWithPrecision} from '../utils';
 
+export class UserRewardStreamData {
+  rewardPerTokenPending: BN;
+  rewardsPerTokenCompleted: BN;
+
+  constructor(args: {
+    rewardPerTokenPending: BN;
+    rewardsPerTokenCompleted: BN;
+  }) {
+    this.rewardPerTokenPending = args.rewardPerTokenPending;
+    this.rewardsPerTokenCompleted = args.rewardsPerTokenCompleted;
+  }
+}
+
 export class UserData {
   accountType: number;
   userWallet: StringPublicKey;
@@ -18,6 +31,14 @@ export class UserData {
   rewardsPerTokenCompleted: BN;
   unstakePenalityDurationEnd: BN;
   lastStakedTimestamp: BN;
+  rewardStreams: UserRewardStreamData[];
+  unbondingAmount: BN;
+  nextUnbondingRequestId: BN;
+  lockedAmount: BN;
+  lockedWeight: BN;
+  nextLockPositionId: BN;
+  allowlistMaxStake: BN;
+  receiptDebt: BN;
 
   constructor(args: {
     accountType: number;
@@ -29,6 +50,14 @@ export class UserData {
     rewardsPerTokenCompleted: BN;
     unstakePenalityDurationEnd: BN;
     lastStakedTimestamp: BN;
+    rewardStreams: UserRewardStreamData[];
+    unbondingAmount: BN;
+    nextUnbondingRequestId: BN;
+    lockedAmount: BN;
+    lockedWeight: BN;
+    nextLockPositionId: BN;
+    allowlistMaxStake: BN;
+    receiptDebt: BN;
   }) {
     this.accountType = args.accountType;
     this.userWallet = args.userWallet;
@@ -39,6 +68,14 @@ export class UserData {
     this.rewardsPerTokenCompleted = args.rewardsPerTokenCompleted;
     this.unstakePenalityDurationEnd = args.unstakePenalityDurationEnd;
     this.lastStakedTimestamp = args.lastStakedTimestamp;
+    this.rewardStreams = args.rewardStreams;
+    this.unbondingAmount = args.unbondingAmount;
+    this.nextUnbondingRequestId = args.nextUnbondingRequestId;
+    this.lockedAmount = args.lockedAmount;
+    this.lockedWeight = args.lockedWeight;
+    this.nextLockPositionId = args.nextLockPositionId;
+    this.allowlistMaxStake = args.allowlistMaxStake;
+    this.receiptDebt = args.receiptDebt;
   }
 
   getUserWalletPubkey(): PublicKey {
@@ -135,6 +172,27 @@ export const USER_STORAGE_DATA_ON_CHAIN_SCHEMA = new Map([
         ['rewardsPerTokenCompleted', 'u128'],
         ['unstakePenalityDurationEnd', 'u64'],
         ['lastStakedTimestamp', 'u64'],
+        [
+          'rewardStreams',
+          [UserRewardStreamData, Constants.maxExtraRewardStreams],
+        ],
+        ['unbondingAmount', 'u64'],
+        ['nextUnbondingRequestId', 'u64'],
+        ['lockedAmount', 'u64'],
+        ['lockedWeight', 'u64'],
+        ['nextLockPositionId', 'u64'],
+        ['allowlistMaxStake', 'u64'],
+        ['receiptDebt', 'u64'],
+      ],
+    },
+  ],
+  [
+    UserRewardStreamData,
+    {
+      kind: 'struct',
+      fields: [
+        ['rewardPerTokenPending', 'u64'],
+        ['rewardsPerTokenCompleted', 'u128'],
       ],
     },
   ],
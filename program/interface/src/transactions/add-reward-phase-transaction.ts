@@ -0,0 +1,88 @@
+import {
+  PublicKey,
+  SystemProgram,
+  Transaction,
+  TransactionInstruction,
+} from '@solana/web3.js';
+import {findAssociatedTokenAddress} from '../utils';
+import {Constants, Pubkeys} from '../constants';
+import {ConnectionService} from '../config';
+import {CwarStakingInstructions} from '../models';
+import {TOKEN_PROGRAM_ID} from '@solana/spl-token';
+import BN from 'bn.js';
+
+// schedules `amount` rewards to be emitted evenly over `durationInDays` from `startTime`, the
+// funder pays for the pool reward schedule account when it is the first phase
+export async function addRewardPhaseTransaction(
+  funderWallet: PublicKey,
+  amount: number,
+  startTime: number,
+  durationInDays: number
+): Promise<Transaction> {
+  const connection = ConnectionService.getConnection();
+  const duration = durationInDays * Constants.secondsInOneDay;
+  const rewardsATAPubkey = await findAssociatedTokenAddress(
+    funderWallet,
+    Pubkeys.rewardsMintPubkey
+  );
+  const rewardSchedulePubkey = (
+    await PublicKey.findProgramAddress(
+      [
+        Buffer.from('reward_schedule'),
+        Pubkeys.cwarPoolStoragePubkey.toBuffer(),
+      ],
+      Pubkeys.cwarStakingProgramId
+    )
+  )[0];
+  const amountToFund = new BN(amount * Constants.decimalPrecision).mul(
+    new BN(Constants.toRewardTokenRaw / Constants.decimalPrecision)
+  );
+  const rewardRate = amountToFund.div(new BN(duration));
+  const addRewardPhaseIx = new TransactionInstruction({
+    programId: Pubkeys.cwarStakingProgramId,
+    keys: [
+      {
+        pubkey: funderWallet,
+        isSigner: true,
+        isWritable: true,
+      },
+
+      {
+        pubkey: Pubkeys.cwarPoolStoragePubkey,
+        isSigner: false,
+        isWritable: true,
+      },
+
+      {
+        pubkey: rewardSchedulePubkey,
+        isSigner: false,
+        isWritable: true,
+      },
+      {
+        pubkey: Pubkeys.cwarRewardsVaultPubkey,
+        isSigner: false,
+        isWritable: true,
+      },
+      {
+        pubkey: rewardsATAPubkey,
+        isSigner: false,
+        isWritable: true,
+      },
+      {pubkey: TOKEN_PROGRAM_ID, isSigner: false, isWritable: false},
+      {pubkey: SystemProgram.programId, isSigner: false, isWritable: false},
+    ],
+    data: Buffer.from([
+      CwarStakingInstructions.AddRewardPhase,
+      ...new BN(startTime).toArray('le', 8),
+      ...new BN(startTime + duration).toArray('le', 8),
+      ...rewardRate.toArray('le', 8),
+    ]),
+  });
+  const addRewardPhaseTx = new Transaction().add(addRewardPhaseIx);
+  addRewardPhaseTx.recentBlockhash = (
+    await connection.getRecentBlockhash()
+  ).blockhash;
+  addRewardPhaseTx.feePayer = funderWallet;
+
+  return addRewardPhaseTx;
+}
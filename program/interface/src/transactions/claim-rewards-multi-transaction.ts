@@ -0,0 +1,94 @@
+import {
+  AccountMeta,
+  PublicKey,
+  SystemProgram,
+  Transaction,
+  TransactionInstruction,
+} from '@solana/web3.js';
+import {findAssociatedTokenAddress} from '../utils';
+import {Pubkeys} from '../constants';
+import {ConnectionService} from '../config';
+import {CwarPoolData, CwarStakingInstructions} from '../models';
+import {ASSOCIATED_TOKEN_PROGRAM_ID, TOKEN_PROGRAM_ID} from '@solana/spl-token';
+
+// claims the user's rewards from every pool in `cwarPoolStoragePubkeys` with a single signature,
+// the program creates missing rewards ATAs paid for by the user
+export async function claimRewardsMultiTransaction(
+  userWallet: PublicKey,
+  cwarPoolStoragePubkeys: PublicKey[]
+): Promise<Transaction> {
+  const connection = ConnectionService.getConnection();
+
+  const poolKeys: AccountMeta[] = [];
+  for (const cwarPoolStoragePubkey of cwarPoolStoragePubkeys) {
+    const cwarPoolData = await CwarPoolData.fromAccount(cwarPoolStoragePubkey);
+    if (cwarPoolData === null) {
+      throw new Error('Pool Does Not Exist');
+    }
+    const userStoragePubkey = (
+      await PublicKey.findProgramAddress(
+        [userWallet.toBuffer(), cwarPoolStoragePubkey.toBuffer()],
+        Pubkeys.cwarStakingProgramId
+      )
+    )[0];
+    const poolSignerPda = (
+      await PublicKey.findProgramAddress(
+        [cwarPoolStoragePubkey.toBuffer()],
+        Pubkeys.cwarStakingProgramId
+      )
+    )[0];
+    const rewardsATAPubkey = await findAssociatedTokenAddress(
+      userWallet,
+      cwarPoolData.getRewardMintPubkey()
+    );
+    poolKeys.push(
+      {pubkey: userStoragePubkey, isSigner: false, isWritable: true},
+      {pubkey: cwarPoolStoragePubkey, isSigner: false, isWritable: true},
+      {
+        pubkey: cwarPoolData.getStakingVaultPubkey(),
+        isSigner: false,
+        isWritable: true,
+      },
+      {
+        pubkey: cwarPoolData.getRewardVaultPubkey(),
+        isSigner: false,
+        isWritable: true,
+      },
+      {pubkey: rewardsATAPubkey, isSigner: false, isWritable: true},
+      {pubkey: poolSignerPda, isSigner: false, isWritable: false},
+      {
+        pubkey: cwarPoolData.getRewardMintPubkey(),
+        isSigner: false,
+        isWritable: false,
+      },
+      ...cwarPoolData.getRewardScheduleKeys()
+    );
+  }
+
+  const claimRewardsMultiIx = new TransactionInstruction({
+    programId: Pubkeys.cwarStakingProgramId,
+    keys: [
+      {
+        pubkey: userWallet,
+        isSigner: true,
+        isWritable: true,
+      },
+      {pubkey: TOKEN_PROGRAM_ID, isSigner: false, isWritable: false},
+      {pubkey: SystemProgram.programId, isSigner: false, isWritable: false},
+      {
+        pubkey: ASSOCIATED_TOKEN_PROGRAM_ID,
+        isSigner: false,
+        isWritable: false,
+      },
+      ...poolKeys,
+    ],
+    data: Buffer.from([CwarStakingInstructions.ClaimRewardsMulti]),
+  });
+  const claimRewardsMultiTx = new Transaction().add(claimRewardsMultiIx);
+  claimRewardsMultiTx.recentBlockhash = (
+    await connection.getRecentBlockhash()
+  ).blockhash;
+  claimRewardsMultiTx.feePayer = userWallet;
+
+  return claimRewardsMultiTx;
+}
@@ -1,6 +1,7 @@
 import {
   Connection,
   PublicKey,
+  SystemProgram,
   Transaction,
   TransactionInstruction,
 } from '@solana/web3.js';
@@ -11,18 +12,19 @@ import {
 } from '../utils';
 import {Pubkeys} from '../constants';
 import {ConnectionService} from '../config';
-import {CwarStakingInstructions} from '../models';
-import {
-  ASSOCIATED_TOKEN_PROGRAM_ID,
-  Token,
-  TOKEN_PROGRAM_ID,
-} from '@solana/spl-token';
+import {CwarPoolData, CwarStakingInstructions} from '../models';
+import {ASSOCIATED_TOKEN_PROGRAM_ID, TOKEN_PROGRAM_ID} from '@solana/spl-token';
 export async function claimRewardsTransaction(
-  userWallet: PublicKey
+  userWallet: PublicKey,
+  recipientWallet?: PublicKey
 ): Promise<Transaction> {
   const connection = ConnectionService.getConnection();
 
-  const claimRewardsIxs = await createClaimIxs(connection, userWallet);
+  const claimRewardsIxs = await createClaimIxs(
+    connection,
+    userWallet,
+    recipientWallet
+  );
   const claimRewardsTx = new Transaction().add(...claimRewardsIxs);
   claimRewardsTx.recentBlockhash = (
     await connection.getRecentBlockhash()
@@ -32,44 +34,35 @@ export async function claimRewardsTransaction(
   return claimRewardsTx;
 }
 
+// the program creates the recipient's rewards ATA when it is missing, paid for by the user
 export async function createClaimIxs(
   connection: Connection,
-  userWallet: PublicKey
+  userWallet: PublicKey,
+  recipientWallet: PublicKey = userWallet
 ): Promise<TransactionInstruction[]> {
   const userStoragePubkey = await getUserStorageAccount(userWallet);
 
   const rewardsATAPubkey = await findAssociatedTokenAddress(
-    userWallet,
+    recipientWallet,
     Pubkeys.rewardsMintPubkey
   );
 
-  const rewardsAtaInfo = await connection.getAccountInfo(rewardsATAPubkey);
-
-  const doesRewardsAtaExist = rewardsAtaInfo?.owner !== undefined;
+  const poolSignerPda = await getPoolSignerPDA();
 
-  const claimRewardsIxs: TransactionInstruction[] = [];
-  if (!doesRewardsAtaExist) {
-    const createFantAssociatedAccountIx =
-      Token.createAssociatedTokenAccountInstruction(
-        ASSOCIATED_TOKEN_PROGRAM_ID,
-        TOKEN_PROGRAM_ID,
-        Pubkeys.rewardsMintPubkey,
-        rewardsATAPubkey,
-        userWallet,
-        userWallet
-      );
-    claimRewardsIxs.push(createFantAssociatedAccountIx);
+  const cwarPoolData = await CwarPoolData.fromAccount(
+    Pubkeys.cwarPoolStoragePubkey
+  );
+  if (cwarPoolData === null) {
+    throw new Error('Pool Does Not Exist');
   }
 
-  const poolSignerPda = await getPoolSignerPDA();
-
   const claimRewardsIx = new TransactionInstruction({
     programId: Pubkeys.cwarStakingProgramId,
     keys: [
       {
         pubkey: userWallet,
         isSigner: true,
-        isWritable: false,
+        isWritable: true,
       },
 
       {
@@ -105,10 +98,27 @@ export async function createClaimIxs(
         isWritable: false,
       },
       {pubkey: TOKEN_PROGRAM_ID, isSigner: false, isWritable: false},
+      // the recipient accounts, led by the associated token program
+      {
+        pubkey: ASSOCIATED_TOKEN_PROGRAM_ID,
+        isSigner: false,
+        isWritable: false,
+      },
+      {pubkey: SystemProgram.programId, isSigner: false, isWritable: false},
+      {
+        pubkey: recipientWallet,
+        isSigner: false,
+        isWritable: false,
+      },
+      {
+        pubkey: Pubkeys.rewardsMintPubkey,
+        isSigner: false,
+        isWritable: false,
+      },
+      ...cwarPoolData.getRewardScheduleKeys(),
     ],
     data: Buffer.from([CwarStakingInstructions.ClaimRewards]),
   });
-  claimRewardsIxs.push(claimRewardsIx);
 
-  return claimRewardsIxs;
+  return [claimRewardsIx];
 }
@@ -0,0 +1,135 @@
+import {
+  PublicKey,
+  SystemProgram,
+  Transaction,
+  TransactionInstruction,
+} from '@solana/web3.js';
+import {
+  findAssociatedTokenAddress,
+  getPoolSignerPDA,
+  getUserStorageAccount,
+} from '../utils';
+import {Pubkeys} from '../constants';
+import {ConnectionService} from '../config';
+import {CwarPoolData, CwarStakingInstructions} from '../models';
+import {ASSOCIATED_TOKEN_PROGRAM_ID, TOKEN_PROGRAM_ID} from '@solana/spl-token';
+
+// unstakes everything, claims all rewards and closes the user storage account in one go
+export async function exitTransaction(
+  userWallet: PublicKey
+): Promise<Transaction> {
+  const connection = ConnectionService.getConnection();
+
+  const userStoragePubkey = await getUserStorageAccount(userWallet);
+
+  // created by the program when missing, paid for by the user
+  const stakingATAPubkey = await findAssociatedTokenAddress(
+    userWallet,
+    Pubkeys.stakingMintPubkey
+  );
+  const rewardsATAPubkey = await findAssociatedTokenAddress(
+    userWallet,
+    Pubkeys.rewardsMintPubkey
+  );
+
+  const poolSignerPda = await getPoolSignerPDA();
+
+  const cwarPoolData = await CwarPoolData.fromAccount(
+    Pubkeys.cwarPoolStoragePubkey
+  );
+  if (cwarPoolData === null) {
+    throw new Error('Pool Does Not Exist');
+  }
+  // receipt pools burn the whole stake from the user's receipt ATA
+  const receiptKeys = [];
+  if (cwarPoolData.hasReceiptMint()) {
+    const receiptMintPubkey = cwarPoolData.getReceiptMintPubkey();
+    receiptKeys.push(
+      {pubkey: receiptMintPubkey, isSigner: false, isWritable: true},
+      {
+        pubkey: await findAssociatedTokenAddress(userWallet, receiptMintPubkey),
+        isSigner: false,
+        isWritable: true,
+      }
+    );
+  }
+
+  const exitIx = new TransactionInstruction({
+    programId: Pubkeys.cwarStakingProgramId,
+    keys: [
+      {
+        pubkey: userWallet,
+        isSigner: true,
+        isWritable: true,
+      },
+
+      {
+        pubkey: userStoragePubkey,
+        isSigner: false,
+        isWritable: true,
+      },
+
+      {
+        pubkey: Pubkeys.cwarPoolStoragePubkey,
+        isSigner: false,
+        isWritable: true,
+      },
+
+      {
+        pubkey: Pubkeys.cwarStakingVaultPubkey,
+        isSigner: false,
+        isWritable: true,
+      },
+      {
+        pubkey: Pubkeys.cwarRewardsVaultPubkey,
+        isSigner: false,
+        isWritable: true,
+      },
+      {
+        pubkey: stakingATAPubkey,
+        isSigner: false,
+        isWritable: true,
+      },
+      {
+        pubkey: rewardsATAPubkey,
+        isSigner: false,
+        isWritable: true,
+      },
+      {
+        pubkey: poolSignerPda,
+        isSigner: false,
+        isWritable: false,
+      },
+      {pubkey: TOKEN_PROGRAM_ID, isSigner: false, isWritable: false},
+      {
+        pubkey: Pubkeys.unstakePenalityATAPubkey,
+        isSigner: false,
+        isWritable: true,
+      },
+      {
+        pubkey: Pubkeys.stakingMintPubkey,
+        isSigner: false,
+        isWritable: true,
+      },
+      {
+        pubkey: Pubkeys.rewardsMintPubkey,
+        isSigner: false,
+        isWritable: false,
+      },
+      {pubkey: SystemProgram.programId, isSigner: false, isWritable: false},
+      {
+        pubkey: ASSOCIATED_TOKEN_PROGRAM_ID,
+        isSigner: false,
+        isWritable: false,
+      },
+      ...cwarPoolData.getRewardScheduleKeys(),
+      ...receiptKeys,
+    ],
+    data: Buffer.from([CwarStakingInstructions.Exit]),
+  });
+  const exitTx = new Transaction().add(exitIx);
+  exitTx.recentBlockhash = (await connection.getRecentBlockhash()).blockhash;
+  exitTx.feePayer = userWallet;
+
+  return exitTx;
+}
@@ -2,7 +2,7 @@ import {PublicKey, Transaction, TransactionInstruction} from '@solana/web3.js';
 import {findAssociatedTokenAddress} from '../utils';
 import {Constants, Pubkeys} from '../constants';
 import {ConnectionService} from '../config';
-import {CwarStakingInstructions} from '../models';
+import {CwarPoolData, CwarStakingInstructions} from '../models';
 import {TOKEN_PROGRAM_ID} from '@solana/spl-token';
 import BN from 'bn.js';
 export async function fundPoolTransaction(
@@ -19,6 +19,12 @@ export async function fundPoolTransaction(
   const amountToFund = new BN(amount * Constants.decimalPrecision).mul(
     new BN(Constants.toRewardTokenRaw / Constants.decimalPrecision)
   );
+  const cwarPoolData = await CwarPoolData.fromAccount(
+    Pubkeys.cwarPoolStoragePubkey
+  );
+  if (cwarPoolData === null) {
+    throw new Error('Pool Does Not Exist');
+  }
   const fundPoolIx = new TransactionInstruction({
     programId: Pubkeys.cwarStakingProgramId,
     keys: [
@@ -50,6 +56,7 @@ export async function fundPoolTransaction(
         isWritable: true,
       },
       {pubkey: TOKEN_PROGRAM_ID, isSigner: false, isWritable: false},
+      ...cwarPoolData.getRewardScheduleKeys(),
     ],
     data: Buffer.from([
       CwarStakingInstructions.FundPool,
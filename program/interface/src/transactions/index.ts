@@ -1,6 +1,7 @@
 export * from './send-transaction-with-retries';
 export * from './create-initialize-pool-transaction';
 export * from './claim-rewards-transaction';
+export * from './claim-rewards-multi-transaction';
 export * from './add-funder-transaction';
 export * from './close-pool-transaction';
 export * from './close-user-transaction';
@@ -9,3 +10,7 @@ export * from './fund-pool-transaction';
 export * from './remove-funder-transaction';
 export * from './stake-cwar-transaction';
 export * from './unstake-cwar-transaction';
+export * from './exit-transaction';
+export * from './transfer-position-transaction';
+export * from './settle-receipt-transaction';
+export * from './add-reward-phase-transaction';
@@ -0,0 +1,102 @@
+import {
+  AccountMeta,
+  PublicKey,
+  SystemProgram,
+  Transaction,
+  TransactionInstruction,
+} from '@solana/web3.js';
+import {findAssociatedTokenAddress, getUserStorageAccount} from '../utils';
+import {Pubkeys} from '../constants';
+import {ConnectionService} from '../config';
+import {CwarPoolData, CwarStakingInstructions} from '../models';
+
+// moves the stake behind receipts received from sourceWallet to the user, anyone can send it.
+// The program creates the user storage when missing, paid for by the payer
+export async function settleReceiptTransaction(
+  userWallet: PublicKey,
+  sourceWallet: PublicKey,
+  payer: PublicKey = userWallet
+): Promise<Transaction> {
+  const connection = ConnectionService.getConnection();
+
+  const cwarPoolData = await CwarPoolData.fromAccount(
+    Pubkeys.cwarPoolStoragePubkey
+  );
+  if (cwarPoolData === null) {
+    throw new Error('Pool Does Not Exist');
+  }
+  if (!cwarPoolData.hasReceiptMint()) {
+    throw new Error('Pool Has No Receipt Mint');
+  }
+  const receiptMintPubkey = cwarPoolData.getReceiptMintPubkey();
+
+  const userStoragePubkey = await getUserStorageAccount(userWallet);
+  const sourceStoragePubkey = await getUserStorageAccount(sourceWallet);
+  const userReceiptATAPubkey = await findAssociatedTokenAddress(
+    userWallet,
+    receiptMintPubkey
+  );
+  const sourceReceiptATAPubkey = await findAssociatedTokenAddress(
+    sourceWallet,
+    receiptMintPubkey
+  );
+
+  const userPays = payer.equals(userWallet);
+  const payerKeys: AccountMeta[] = userPays
+    ? []
+    : [{pubkey: payer, isSigner: true, isWritable: true}];
+
+  const settleReceiptIx = new TransactionInstruction({
+    programId: Pubkeys.cwarStakingProgramId,
+    keys: [
+      {
+        pubkey: userWallet,
+        isSigner: userPays,
+        isWritable: true,
+      },
+
+      {
+        pubkey: userStoragePubkey,
+        isSigner: false,
+        isWritable: true,
+      },
+
+      {
+        pubkey: Pubkeys.cwarPoolStoragePubkey,
+        isSigner: false,
+        isWritable: true,
+      },
+      {
+        pubkey: userReceiptATAPubkey,
+        isSigner: false,
+        isWritable: false,
+      },
+      {
+        pubkey: sourceWallet,
+        isSigner: false,
+        isWritable: false,
+      },
+      {
+        pubkey: sourceStoragePubkey,
+        isSigner: false,
+        isWritable: true,
+      },
+      {
+        pubkey: sourceReceiptATAPubkey,
+        isSigner: false,
+        isWritable: false,
+      },
+      {pubkey: SystemProgram.programId, isSigner: false, isWritable: false},
+      ...cwarPoolData.getRewardScheduleKeys(),
+      ...payerKeys,
+    ],
+    data: Buffer.from([CwarStakingInstructions.SettleReceipt]),
+  });
+  const settleReceiptTx = new Transaction().add(settleReceiptIx);
+  settleReceiptTx.recentBlockhash = (
+    await connection.getRecentBlockhash()
+  ).blockhash;
+  settleReceiptTx.feePayer = payer;
+
+  return settleReceiptTx;
+}
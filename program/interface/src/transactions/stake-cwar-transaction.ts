@@ -1,10 +1,19 @@
-import {PublicKey, Transaction, TransactionInstruction} from '@solana/web3.js';
-import {findAssociatedTokenAddress, getUserStorageAccount} from '../utils';
+import {
+  PublicKey,
+  SystemProgram,
+  Transaction,
+  TransactionInstruction,
+} from '@solana/web3.js';
+import {
+  findAssociatedTokenAddress,
+  getPoolSignerPDA,
+  getUserStorageAccount,
+} from '../utils';
 import {Constants, Pubkeys} from '../constants';
 import {ConnectionService} from '../config';
-import {CwarStakingInstructions} from '../models';
+import {CwarPoolData, CwarStakingInstructions} from '../models';
 import BN from 'bn.js';
-import {TOKEN_PROGRAM_ID} from '@solana/spl-token';
+import {ASSOCIATED_TOKEN_PROGRAM_ID, TOKEN_PROGRAM_ID} from '@solana/spl-token';
 export async function stakeCwarTransaction(
   userWallet: PublicKey,
   amountToDeposit: number
@@ -22,13 +31,40 @@ export async function stakeCwarTransaction(
     amountToDeposit * Constants.decimalPrecision
   ).mul(new BN(Constants.toCwarRaw / Constants.decimalPrecision));
 
+  const cwarPoolData = await CwarPoolData.fromAccount(
+    Pubkeys.cwarPoolStoragePubkey
+  );
+  if (cwarPoolData === null) {
+    throw new Error('Pool Does Not Exist');
+  }
+  // receipt pools mint the deposit to the user's receipt ATA, created by the program when missing
+  const receiptKeys = [];
+  if (cwarPoolData.hasReceiptMint()) {
+    const receiptMintPubkey = cwarPoolData.getReceiptMintPubkey();
+    receiptKeys.push(
+      {pubkey: receiptMintPubkey, isSigner: false, isWritable: true},
+      {
+        pubkey: await findAssociatedTokenAddress(userWallet, receiptMintPubkey),
+        isSigner: false,
+        isWritable: true,
+      },
+      {pubkey: await getPoolSignerPDA(), isSigner: false, isWritable: false},
+      {pubkey: SystemProgram.programId, isSigner: false, isWritable: false},
+      {
+        pubkey: ASSOCIATED_TOKEN_PROGRAM_ID,
+        isSigner: false,
+        isWritable: false,
+      }
+    );
+  }
+
   const stakeCwarIx = new TransactionInstruction({
     programId: Pubkeys.cwarStakingProgramId,
     keys: [
       {
         pubkey: userWallet,
         isSigner: true,
-        isWritable: false,
+        isWritable: cwarPoolData.hasReceiptMint(),
       },
 
       {
@@ -54,6 +90,8 @@ export async function stakeCwarTransaction(
         isWritable: true,
       },
       {pubkey: TOKEN_PROGRAM_ID, isSigner: false, isWritable: false},
+      ...cwarPoolData.getRewardScheduleKeys(),
+      ...receiptKeys,
     ],
     data: Buffer.from([
       CwarStakingInstructions.StakeCwar,
@@ -0,0 +1,109 @@
+import {
+  AccountMeta,
+  PublicKey,
+  SystemProgram,
+  Transaction,
+  TransactionInstruction,
+} from '@solana/web3.js';
+import BN from 'bn.js';
+import {getLockPositionAccount, getUserStorageAccount} from '../utils';
+import {Pubkeys} from '../constants';
+import {ConnectionService} from '../config';
+import {CwarPoolData, CwarStakingInstructions, UserData} from '../models';
+
+// the program creates the recipient's user storage when missing, paid for by the user, and
+// moves every open lock position of the user under the recipient
+export async function transferPositionTransaction(
+  userWallet: PublicKey,
+  recipientWallet: PublicKey
+): Promise<Transaction> {
+  const connection = ConnectionService.getConnection();
+
+  const userStoragePubkey = await getUserStorageAccount(userWallet);
+  const recipientStoragePubkey = await getUserStorageAccount(recipientWallet);
+
+  const cwarPoolData = await CwarPoolData.fromAccount(
+    Pubkeys.cwarPoolStoragePubkey
+  );
+  if (cwarPoolData === null) {
+    throw new Error('Pool Does Not Exist');
+  }
+  const userData = await UserData.fromAccount(userStoragePubkey);
+  if (userData === null) {
+    throw new Error('User Does Not Exist');
+  }
+  const recipientData = await UserData.fromAccount(recipientStoragePubkey);
+  let recipientPositionId =
+    recipientData === null ? new BN(0) : recipientData.nextLockPositionId;
+
+  const lockPositionKeys: AccountMeta[] = [];
+  for (
+    let positionId = new BN(0);
+    positionId.lt(userData.nextLockPositionId);
+    positionId = positionId.addn(1)
+  ) {
+    const lockPositionPubkey = await getLockPositionAccount(
+      userStoragePubkey,
+      positionId
+    );
+    if ((await connection.getAccountInfo(lockPositionPubkey)) === null) {
+      continue;
+    }
+    lockPositionKeys.push(
+      {pubkey: lockPositionPubkey, isSigner: false, isWritable: true},
+      {
+        pubkey: await getLockPositionAccount(
+          recipientStoragePubkey,
+          recipientPositionId
+        ),
+        isSigner: false,
+        isWritable: true,
+      }
+    );
+    recipientPositionId = recipientPositionId.addn(1);
+  }
+
+  const transferPositionIx = new TransactionInstruction({
+    programId: Pubkeys.cwarStakingProgramId,
+    keys: [
+      {
+        pubkey: userWallet,
+        isSigner: true,
+        isWritable: true,
+      },
+
+      {
+        pubkey: userStoragePubkey,
+        isSigner: false,
+        isWritable: true,
+      },
+
+      {
+        pubkey: Pubkeys.cwarPoolStoragePubkey,
+        isSigner: false,
+        isWritable: true,
+      },
+      {
+        pubkey: recipientWallet,
+        isSigner: false,
+        isWritable: false,
+      },
+      {
+        pubkey: recipientStoragePubkey,
+        isSigner: false,
+        isWritable: true,
+      },
+      {pubkey: SystemProgram.programId, isSigner: false, isWritable: false},
+      ...cwarPoolData.getRewardScheduleKeys(),
+      ...lockPositionKeys,
+    ],
+    data: Buffer.from([CwarStakingInstructions.TransferPosition]),
+  });
+  const transferPositionTx = new Transaction().add(transferPositionIx);
+  transferPositionTx.recentBlockhash = (
+    await connection.getRecentBlockhash()
+  ).blockhash;
+  transferPositionTx.feePayer = userWallet;
+
+  return transferPositionTx;
+}
@@ -1,4 +1,9 @@
-import {PublicKey, Transaction, TransactionInstruction} from '@solana/web3.js';
+import {
+  PublicKey,
+  SystemProgram,
+  Transaction,
+  TransactionInstruction,
+} from '@solana/web3.js';
 import {
   findAssociatedTokenAddress,
   getPoolSignerPDA,
@@ -6,24 +11,23 @@ import {
 } from '../utils';
 import {Constants, Pubkeys} from '../constants';
 import {ConnectionService} from '../config';
-import {CwarStakingInstructions} from '../models';
+import {CwarPoolData, CwarStakingInstructions} from '../models';
 import BN from 'bn.js';
-import {
-  ASSOCIATED_TOKEN_PROGRAM_ID,
-  Token,
-  TOKEN_PROGRAM_ID,
-} from '@solana/spl-token';
+import {ASSOCIATED_TOKEN_PROGRAM_ID, TOKEN_PROGRAM_ID} from '@solana/spl-token';
+import {createClaimIxs} from './claim-rewards-transaction';
 
 export async function unstakeCwarTransaction(
   userWallet: PublicKey,
-  amountToWithdraw: number
+  amountToWithdraw: number,
+  recipientWallet: PublicKey = userWallet
 ): Promise<Transaction> {
   const connection = ConnectionService.getConnection();
 
   const userStoragePubkey = await getUserStorageAccount(userWallet);
 
+  // created by the program when missing, paid for by the user
   const stakingATAPubkey = await findAssociatedTokenAddress(
-    userWallet,
+    recipientWallet,
     Pubkeys.stakingMintPubkey
   );
 
@@ -33,14 +37,49 @@ export async function unstakeCwarTransaction(
 
   const poolSignerPda = await getPoolSignerPDA();
 
-  const unstakeCwarIxs: TransactionInstruction[] = [];
+  const cwarPoolData = await CwarPoolData.fromAccount(
+    Pubkeys.cwarPoolStoragePubkey
+  );
+  if (cwarPoolData === null) {
+    throw new Error('Pool Does Not Exist');
+  }
+  // only needed when the pool routes penalties away from the treasury
+  const penalityRoutingKeys = [];
+  if (cwarPoolData.penalityRouting.burnBasisPoints !== 0) {
+    penalityRoutingKeys.push({
+      pubkey: Pubkeys.stakingMintPubkey,
+      isSigner: false,
+      isWritable: true,
+    });
+  }
+  if (cwarPoolData.penalityRouting.usesRewardVault()) {
+    penalityRoutingKeys.push({
+      pubkey: Pubkeys.cwarRewardsVaultPubkey,
+      isSigner: false,
+      isWritable: true,
+    });
+  }
+  // receipt pools burn the withdrawn amount from the user's receipt ATA
+  const receiptKeys = [];
+  if (cwarPoolData.hasReceiptMint()) {
+    const receiptMintPubkey = cwarPoolData.getReceiptMintPubkey();
+    receiptKeys.push(
+      {pubkey: receiptMintPubkey, isSigner: false, isWritable: true},
+      {
+        pubkey: await findAssociatedTokenAddress(userWallet, receiptMintPubkey),
+        isSigner: false,
+        isWritable: true,
+      }
+    );
+  }
+
   const unstakeCwarIx = new TransactionInstruction({
     programId: Pubkeys.cwarStakingProgramId,
     keys: [
       {
         pubkey: userWallet,
         isSigner: true,
-        isWritable: false,
+        isWritable: true,
       },
 
       {
@@ -76,83 +115,40 @@ export async function unstakeCwarTransaction(
         isSigner: false,
         isWritable: true,
       },
-    ],
-    data: Buffer.from([
-      CwarStakingInstructions.UnstakeCwar,
-      ...amountToWithdrawRaw.toArray('le', 8),
-    ]),
-  });
-  const rewardsATAPubkey = await findAssociatedTokenAddress(
-    userWallet,
-    Pubkeys.rewardsMintPubkey
-  );
-  const rewardsAtaInfo = await connection.getAccountInfo(rewardsATAPubkey);
-
-  const doesRewardsAtaExist = rewardsAtaInfo?.owner !== undefined;
-
-  if (!doesRewardsAtaExist) {
-    const createFantAssociatedAccountIx =
-      Token.createAssociatedTokenAccountInstruction(
-        ASSOCIATED_TOKEN_PROGRAM_ID,
-        TOKEN_PROGRAM_ID,
-        Pubkeys.rewardsMintPubkey,
-        rewardsATAPubkey,
-        userWallet,
-        userWallet
-      );
-    unstakeCwarIxs.push(createFantAssociatedAccountIx);
-  }
-
-  const claimRewardsIx = new TransactionInstruction({
-    programId: Pubkeys.cwarStakingProgramId,
-    keys: [
+      // the recipient accounts, led by the associated token program
       {
-        pubkey: userWallet,
-        isSigner: true,
-        isWritable: false,
-      },
-
-      {
-        pubkey: userStoragePubkey,
-        isSigner: false,
-        isWritable: true,
-      },
-
-      {
-        pubkey: Pubkeys.cwarPoolStoragePubkey,
-        isSigner: false,
-        isWritable: true,
-      },
-
-      {
-        pubkey: Pubkeys.cwarStakingVaultPubkey,
+        pubkey: ASSOCIATED_TOKEN_PROGRAM_ID,
         isSigner: false,
-        isWritable: true,
-      },
-      {
-        pubkey: Pubkeys.cwarRewardsVaultPubkey,
-        isSigner: false,
-        isWritable: true,
+        isWritable: false,
       },
+      {pubkey: SystemProgram.programId, isSigner: false, isWritable: false},
       {
-        pubkey: rewardsATAPubkey,
+        pubkey: recipientWallet,
         isSigner: false,
-        isWritable: true,
+        isWritable: false,
       },
       {
-        pubkey: poolSignerPda,
+        pubkey: Pubkeys.stakingMintPubkey,
         isSigner: false,
         isWritable: false,
       },
-      {pubkey: TOKEN_PROGRAM_ID, isSigner: false, isWritable: false},
+      ...cwarPoolData.getRewardScheduleKeys(),
+      ...penalityRoutingKeys,
+      ...receiptKeys,
     ],
-    data: Buffer.from([CwarStakingInstructions.ClaimRewards]),
+    data: Buffer.from([
+      CwarStakingInstructions.UnstakeCwar,
+      ...amountToWithdrawRaw.toArray('le', 8),
+    ]),
   });
 
-  unstakeCwarIxs.push(unstakeCwarIx);
-  unstakeCwarIxs.push(claimRewardsIx);
+  const claimRewardsIxs = await createClaimIxs(
+    connection,
+    userWallet,
+    recipientWallet
+  );
 
-  const unstakeCwarTx = new Transaction().add(...unstakeCwarIxs);
+  const unstakeCwarTx = new Transaction().add(unstakeCwarIx, ...claimRewardsIxs);
   unstakeCwarTx.recentBlockhash = (
     await connection.getRecentBlockhash()
   ).blockhash;
@@ -0,0 +1,19 @@
+import BN from 'bn.js';
+import {PublicKey} from '@solana/web3.js';
+import {Pubkeys} from '../constants';
+
+export async function getLockPositionAccount(
+  userStorage: PublicKey,
+  positionId: BN
+): Promise<PublicKey> {
+  return (
+    await PublicKey.findProgramAddress(
+      [
+        Buffer.from('lock_position'),
+        userStorage.toBuffer(),
+        positionId.toArrayLike(Buffer, 'le', 8),
+      ],
+      Pubkeys.cwarStakingProgramId
+    )
+  )[0];
+}
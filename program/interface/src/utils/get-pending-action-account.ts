@@ -0,0 +1,18 @@
+import BN from 'bn.js';
+import {PublicKey} from '@solana/web3.js';
+import {Pubkeys} from '../constants';
+
+export async function getPendingActionAccount(
+  actionId: BN
+): Promise<PublicKey> {
+  return (
+    await PublicKey.findProgramAddress(
+      [
+        Buffer.from('pending_action'),
+        Pubkeys.cwarPoolStoragePubkey.toBuffer(),
+        actionId.toArrayLike(Buffer, 'le', 8),
+      ],
+      Pubkeys.cwarStakingProgramId
+    )
+  )[0];
+}
@@ -3,3 +3,5 @@ export * from './conversions';
 export * from './get-user-storage-account';
 export * from './get-pool-signer-pda';
 export * from './big-div-with-precision';
+export * from './get-lock-position-account';
+export * from './get-pending-action-account';
@@ -0,0 +1,83 @@
+use solana_program::{keccak::hashv, pubkey::Pubkey};
+
+/// Longest proof `CreateAllowlistedUser` accepts, enough for 2^32 wallets
+pub const MAX_ALLOWLIST_PROOF_LENGTH: usize = 32;
+
+// leaves and inner nodes hash differently so a node can never pass as a leaf
+const LEAF_PREFIX: &[u8] = &[0u8];
+const NODE_PREFIX: &[u8] = &[1u8];
+
+/// Leaf of `user_wallet`, `max_stake` caps the stake of that wallet and zero leaves it to the pool
+pub fn get_allowlist_leaf(user_wallet: &Pubkey, max_stake: u64) -> [u8; 32] {
+    hashv(&[
+        LEAF_PREFIX,
+        &user_wallet.to_bytes(),
+        &max_stake.to_le_bytes(),
+    ])
+    .to_bytes()
+}
+
+/// Pairs hash in sorted order, so a proof is just the sibling hashes from the leaf up
+fn hash_pair(left: &[u8; 32], right: &[u8; 32]) -> [u8; 32] {
+    let (first, second) = if left <= right {
+        (left, right)
+    } else {
+        (right, left)
+    };
+    hashv(&[NODE_PREFIX, first, second]).to_bytes()
+}
+
+pub fn verify_allowlist_proof(root: &[u8; 32], leaf: [u8; 32], proof: &[[u8; 32]]) -> bool {
+    let computed_root = proof
+        .iter()
+        .fold(leaf, |node, sibling| hash_pair(&node, sibling));
+    computed_root == *root
+}
+
+/// Builds allowlist roots and proofs off-chain, a node without a sibling moves up a layer as is
+pub struct AllowlistTree {
+    layers: Vec<Vec<[u8; 32]>>,
+}
+
+impl AllowlistTree {
+    /// `entries` holds every allowed wallet with its stake cap, it cannot be empty
+    pub fn new(entries: &[(Pubkey, u64)]) -> Self {
+        assert!(
+            !entries.is_empty(),
+            "an allowlist needs at least one wallet"
+        );
+        let mut layers = vec![entries
+            .iter()
+            .map(|(user_wallet, max_stake)| get_allowlist_leaf(user_wallet, *max_stake))
+            .collect::<Vec<_>>()];
+        while layers[layers.len() - 1].len() > 1 {
+            let next_layer = layers[layers.len() - 1]
+                .chunks(2)
+                .map(|pair| match pair {
+                    [left, right] => hash_pair(left, right),
+                    [node] => *node,
+                    _ => unreachable!(),
+                })
+                .collect();
+            layers.push(next_layer);
+        }
+        Self { layers }
+    }
+
+    pub fn root(&self) -> [u8; 32] {
+        self.layers[self.layers.len() - 1][0]
+    }
+
+    /// Proof for the entry at `index` in the order the tree was built with
+    pub fn proof(&self, index: usize) -> Vec<[u8; 32]> {
+        let mut proof = Vec::new();
+        let mut index = index;
+        for layer in &self.layers[..self.layers.len() - 1] {
+            if let Some(sibling) = layer.get(index ^ 1) {
+                proof.push(*sibling);
+            }
+            index /= 2;
+        }
+        proof
+    }
+}
@@ -127,7 +127,153 @@ pub enum CryowarError {
     ///Invalid Transfer Operation
     #[error("Invalid Transfer Operation")]
     InvalidTransferOperation,
-    
+    ///Invalid Instruction Data Length
+    #[error("Invalid Instruction Data Length")]
+    InvalidInstructionDataLength,
+    ///Account Needs Migration
+    #[error("Account Needs Migration")]
+    AccountNeedsMigration,
+    ///No Surplus To Recover
+    #[error("No Surplus To Recover")]
+    NoSurplusToRecover,
+    ///Invalid New Owner
+    #[error("Invalid New Owner")]
+    InvalidNewOwner,
+    ///No Pending Ownership Transfer
+    #[error("No Pending Ownership Transfer")]
+    NoPendingOwnershipTransfer,
+    ///Pending Owner Mismatched
+    #[error("Pending Owner Mismatched")]
+    PendingOwnerMismatched,
+    ///Pool Paused
+    #[error("Pool Paused")]
+    PoolPaused,
+    ///Pause Authority Mismatched
+    #[error("Pause Authority Mismatched")]
+    PauseAuthorityMismatched,
+    ///Invalid Pause Flags
+    #[error("Invalid Pause Flags")]
+    InvalidPauseFlags,
+    ///Unstake Penality Too High
+    #[error("Unstake Penality Too High")]
+    UnstakePenalityTooHigh,
+    ///Pool Config Change Not Allowed While Staked
+    #[error("Pool Config Change Not Allowed While Staked")]
+    PoolConfigChangeNotAllowedWhileStaked,
+    ///Timelock Required
+    #[error("Timelock Required")]
+    TimelockRequired,
+    ///Timelock Not Expired
+    #[error("Timelock Not Expired")]
+    TimelockNotExpired,
+    ///Invalid Timelock Delay
+    #[error("Invalid Timelock Delay")]
+    InvalidTimelockDelay,
+    ///Pending Action Pool Mismatched
+    #[error("Pending Action Pool Mismatched")]
+    PendingActionPoolMismatched,
+    ///Rent Receiver Mismatched
+    #[error("Rent Receiver Mismatched")]
+    RentReceiverMismatched,
+    ///Invalid Rewards Reduction
+    #[error("Invalid Rewards Reduction")]
+    InvalidRewardsReduction,
+    ///Invalid Reward Stream
+    #[error("Invalid Reward Stream")]
+    InvalidRewardStream,
+    ///Max Reward Streams Reached
+    #[error("Max Reward Streams Reached")]
+    MaxRewardStreamsReached,
+    ///Reward Stream Already Present
+    #[error("Reward Stream Already Present")]
+    RewardStreamAlreadyPresent,
+    ///Compounding Not Supported
+    #[error("Compounding Not Supported")]
+    CompoundingNotSupported,
+    ///Invalid Unbonding Duration
+    #[error("Invalid Unbonding Duration")]
+    InvalidUnbondingDuration,
+    ///Unbonding Not Enabled
+    #[error("Unbonding Not Enabled")]
+    UnbondingNotEnabled,
+    ///Unbonding Required
+    #[error("Unbonding Required")]
+    UnbondingRequired,
+    ///Unbonding Not Finished
+    #[error("Unbonding Not Finished")]
+    UnbondingNotFinished,
+    ///Unbonding Request User Mismatched
+    #[error("Unbonding Request User Mismatched")]
+    UnbondingRequestUserMismatched,
+    ///Invalid Lock Tier
+    #[error("Invalid Lock Tier")]
+    InvalidLockTier,
+    ///Lock Position Not Unlocked
+    #[error("Lock Position Not Unlocked")]
+    LockPositionNotUnlocked,
+    ///Lock Position User Mismatched
+    #[error("Lock Position User Mismatched")]
+    LockPositionUserMismatched,
+    ///Invalid Penality Schedule
+    #[error("Invalid Penality Schedule")]
+    InvalidPenalitySchedule,
+    ///Invalid Penality Routing
+    #[error("Invalid Penality Routing")]
+    InvalidPenalityRouting,
+    ///Invalid Stake Limits
+    #[error("Invalid Stake Limits")]
+    InvalidStakeLimits,
+    ///Stake Below Minimum
+    #[error("Stake Below Minimum")]
+    StakeBelowMinimum,
+    ///User Stake Limit Exceeded
+    #[error("User Stake Limit Exceeded")]
+    UserStakeLimitExceeded,
+    ///Pool Stake Cap Exceeded
+    #[error("Pool Stake Cap Exceeded")]
+    PoolStakeCapExceeded,
+    ///Invalid Allowlist Proof
+    #[error("Invalid Allowlist Proof")]
+    InvalidAllowlistProof,
+    ///Wallet Not Allowlisted
+    #[error("Wallet Not Allowlisted")]
+    WalletNotAllowlisted,
+    ///Invalid Associated Token Program
+    #[error("Invalid Associated Token Program")]
+    InvalidAssociatedTokenProgram,
+    ///Position Not Transferable
+    #[error("Position Not Transferable")]
+    PositionNotTransferable,
+    ///Invalid Position Recipient
+    #[error("Invalid Position Recipient")]
+    InvalidPositionRecipient,
+    ///Invalid Receipt Mint
+    #[error("Invalid Receipt Mint")]
+    InvalidReceiptMint,
+    ///Receipt Mint Change Not Allowed
+    #[error("Receipt Mint Change Not Allowed")]
+    ReceiptMintChangeNotAllowed,
+    ///Not Supported By Receipt Pools
+    #[error("Not Supported By Receipt Pools")]
+    NotSupportedByReceiptPools,
+    ///Nothing To Settle
+    #[error("Nothing To Settle")]
+    NothingToSettle,
+    ///Invalid Reward Schedule
+    #[error("Invalid Reward Schedule")]
+    InvalidRewardSchedule,
+    ///Invalid Reward Phase
+    #[error("Invalid Reward Phase")]
+    InvalidRewardPhase,
+    ///Reward Schedule Full
+    #[error("Reward Schedule Full")]
+    RewardScheduleFull,
+    ///Allowlisted User Required
+    #[error("Allowlisted User Required")]
+    AllowlistedUserRequired,
+    ///Action Expired
+    #[error("Action Expired")]
+    ActionExpired,
 }
 
 impl From<CryowarError> for ProgramError {
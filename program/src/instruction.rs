@@ -1,6 +1,23 @@
-use crate::error::CryowarError::InvalidInstruction;
-use solana_program::program_error::ProgramError;
-use std::convert::TryInto;
+use crate::error::CryowarError::{InvalidInstruction, InvalidInstructionDataLength};
+use crate::processor::create_user::get_user_storage_address_and_bump_seed;
+use crate::state::{LockTier, PenalityRouting, PenalitySchedule, PoolAction, MAX_LOCK_TIERS};
+use crate::utils::{
+    get_lock_position_address_and_bump_seed, get_pending_action_address_and_bump_seed,
+    get_pool_signer_address_and_bump_seed, get_reward_schedule_address_and_bump_seed,
+    get_unbonding_request_address_and_bump_seed,
+};
+use borsh::{BorshDeserialize, BorshSerialize};
+use solana_program::{
+    instruction::{AccountMeta, Instruction},
+    program_error::ProgramError,
+    pubkey::Pubkey,
+    system_program,
+};
+use spl_associated_token_account::get_associated_token_address;
+
+/// Instructions that checkpoint rewards take the pool `RewardSchedule` right after their fixed
+/// accounts when the pool has one, ahead of any other optional account
+#[derive(BorshSerialize, BorshDeserialize, Clone, Debug, PartialEq)]
 pub enum CryowarInstruction {
     /// Accounts Expected:
     ///
@@ -19,38 +36,89 @@ pub enum CryowarInstruction {
         locking_duration: u64,
     },
 
-    /// 0. `[signer]` User Wallet Account
+    /// 0. `[signer, writable]` User Wallet Account
     /// 1. `[writable]` User Storage Account [user wallet, pool storage, program id]
     /// 2. `[writable]` CWAR Pool Storage Account
     /// 3. `[]` System Program
     CreateUser { nonce: u8 },
 
-    /// 0. `[signer]` User Wallet Account
+    /// Receipt pools mint receipts for the deposit once the user's `receipt_debt` is paid back
+    ///
+    /// 0. `[signer]` User Wallet Account, writable in receipt pools to pay for the receipt ATA
     /// 1. `[writable]` User Storage Account
     /// 2. `[writable]` CWAR Pool Storage Account
     /// 3. `[writable]` CWAR Staking Vault
     /// 4. `[writable]` CWAR ATA to Debit
     /// 5. `[]` Token Program
+    ///
+    /// Only when the pool has a reward schedule
+    /// 6. `[]` Reward Schedule ["reward_schedule", pool storage, program id]
+    ///
+    /// Only when the pool has a receipt mint, one index later with a reward schedule
+    /// 6. `[writable]` Receipt Mint
+    /// 7. `[writable]` User Receipt ATA to Credit, created if missing
+    /// 8. `[]` Pool Signer [pool storage, program id]
+    /// 9. `[]` System Program
+    /// 10. `[]` Associated Token Program
     StakeCwar { amount_to_deposit: u64 },
 
-    /// 0. `[signer]` User Wallet Account
+    /// 0. `[signer]` User Wallet Account, writable when it pays for a missing recipient ATA
     /// 1. `[writable]` User Storage Account [user wallet, pool storage, program id] findProgramAddress
     /// 2. `[writable]` CWAR Pool Storage Account
     /// 3. `[writable]` CWAR Staking Vault
-    /// 4. `[writable]` CWAR ATA to Credit
+    /// 4. `[writable]` CWAR ATA to Credit, the recipient ATA with the recipient accounts
     /// 5. `[]` Pool Signer [pool storage, program id]
     /// 6. `[]` Token Program
     /// 7. `[writable]` Authority Unstake Penality Deposit ATA
+    ///
+    /// The optional accounts follow from index 8 in this order, each group only when it applies,
+    /// so every group starts right after the last one passed
+    ///
+    /// Only to pay another wallet or create a missing ATA, the recipient accounts
+    /// - `[]` Associated Token Program
+    /// - `[]` System Program
+    /// - `[]` Recipient Wallet
+    /// - `[]` CWAR Staking Mint
+    ///
+    /// Only when the pool has a reward schedule
+    /// - `[]` Reward Schedule ["reward_schedule", pool storage, program id]
+    ///
+    /// Only when the pool burns penalties
+    /// - `[writable]` CWAR Staking Mint
+    ///
+    /// Only when the pool routes penalties to the reward vault or redistributes them
+    /// - `[writable]` CWAR Reward Vault
+    ///
+    /// Only when the pool has a receipt mint
+    /// - `[writable]` Receipt Mint
+    /// - `[writable]` User Receipt ATA to Debit
     UnstakeCwar { amount_to_withdraw: u64 },
 
-    /// 0. `[signer]` User Wallet Account
+    /// 0. `[signer]` User Wallet Account, writable when it pays for a missing recipient ATA
     /// 1. `[writable]` User Storage Account
     /// 2. `[writable]` CWAR Pool Storage Account
     /// 3. `[writable]` CWAR Staking Vault
     /// 4. `[writable]` CWAR Reward Vault
-    /// 5. `[writable]` User Rewards ATA to Credit
+    /// 5. `[writable]` User Rewards ATA to Credit, the recipient ATA with the recipient accounts
     /// 6. `[]` Pool Signer [pool storage, program id] findProgramAddress
     /// 7. `[]` Token Program
+    ///
+    /// The optional accounts follow from index 8 in this order, each group only when it applies,
+    /// so every group starts right after the last one passed
+    ///
+    /// Only to pay another wallet or create missing ATAs, the recipient accounts
+    /// - `[]` Associated Token Program
+    /// - `[]` System Program
+    /// - `[]` Recipient Wallet
+    /// - `[]` CWAR Rewards Mint
+    ///
+    /// Only when the pool has a reward schedule
+    /// - `[]` Reward Schedule ["reward_schedule", pool storage, program id]
+    ///
+    /// Then for each extra reward stream to claim from
+    /// - `[writable]` Reward Stream Vault
+    /// - `[]` Reward Stream Mint, only with the recipient accounts
+    /// - `[writable]` Token Account to Credit (Reward Stream Token)
     ClaimRewards {},
 
     /// 0. `[signer]` Pool Owner Wallet Account
@@ -69,9 +137,14 @@ pub enum CryowarInstruction {
     /// 3. `[writable]` CWAR Reward Vault
     /// 4. `[writable]` CWAR ATA to Debit (Reward Token)
     /// 5. `[]` Token Program
+    ///
+    /// Only when the pool has a reward schedule
+    /// 6. `[]` Reward Schedule ["reward_schedule", pool storage, program id]
     FundPool { amount: u64, extend_duration: u64 },
 
-    /// 0. `[signer]` Pool Owner Wallet Account
+    /// Closes a finished pool without stake, sweeping both vaults to the refund ATAs
+    ///
+    /// 0. `[signer, writable]` Pool Owner Wallet Account
     /// 1. `[writable]` CWAR Staking Vault
     /// 2. `[writable]` CWAR Staking Refund ATA
     /// 3. `[writable]` CWAR Rewards Vault
@@ -81,63 +154,2066 @@ pub enum CryowarInstruction {
     /// 7. `[]` Token Program
     ClosePool {},
 
-    /// 0. `[signer]` User Wallet Account
+    /// 0. `[signer, writable]` User Wallet Account
     /// 1. `[writable]` User Storage Account
     /// 2. `[writable]` CWAR Pool Storage Account
     CloseUser {},
+
+    /// Upgrades a V1 pool in place to the V2 layout, anyone can call it. `total_staked` is seeded
+    /// from the staking vault balance, or zero once every user left. `rewards_owed` is seeded
+    /// from the rewards vault balance not needed by the current stream. Pools sharing one vault
+    /// for stake and rewards can't be migrated
+    ///
+    /// 0. `[signer, writable]` Payer Wallet Account, tops up the pool storage rent
+    /// 1. `[writable]` CWAR Pool Storage Account
+    /// 2. `[]` CWAR Staking Vault
+    /// 3. `[]` CWAR Rewards Vault
+    /// 4. `[]` System Program
+    MigratePool {},
+
+    /// Sends staking vault tokens above `total_staked` and rewards vault tokens above
+    /// outstanding reward liabilities to the given token accounts
+    ///
+    /// 0. `[signer]` Pool Owner Wallet Account
+    /// 1. `[writable]` CWAR Pool Storage Account
+    /// 2. `[writable]` CWAR Staking Vault
+    /// 3. `[writable]` CWAR Staking Surplus ATA to Credit
+    /// 4. `[writable]` CWAR Rewards Vault
+    /// 5. `[writable]` CWAR Rewards Surplus ATA to Credit
+    /// 6. `[]` Pool Signer [pool storage, program id]
+    /// 7. `[]` Token Program
+    ///
+    /// Only when the pool has a reward schedule
+    /// 8. `[]` Reward Schedule ["reward_schedule", pool storage, program id]
+    RecoverSurplus {},
+
+    /// Upgrades a V1 user account in place to the V2 layout, keeping all balances and checkpoints
+    ///
+    /// 0. `[signer, writable]` User Wallet Account
+    /// 1. `[writable]` User Storage Account [user wallet, pool storage, program id]
+    /// 2. `[]` CWAR Pool Storage Account
+    /// 3. `[]` System Program
+    MigrateUser {},
+
+    /// Nominates a new pool owner, who has to accept before the current owner loses control
+    ///
+    /// 0. `[signer]` Pool Owner Wallet Account
+    /// 1. `[writable]` CWAR Pool Storage Account
+    /// 2. `[]` New Owner Wallet To Propose
+    ProposeNewOwner {},
+
+    /// Hands the pool over to the pending owner, along with the penalty deposit account
+    ///
+    /// 0. `[signer]` Pending Owner Wallet Account
+    /// 1. `[writable]` CWAR Pool Storage Account
+    /// 2. `[]` New Authority Unstake Penality Deposit ATA, a staking mint account of the new owner
+    AcceptOwnership {},
+
+    /// 0. `[signer]` Pool Owner Wallet Account
+    /// 1. `[writable]` CWAR Pool Storage Account
+    CancelOwnershipTransfer {},
+
+    /// Sets the key that may pause operations alongside the owner, the default pubkey removes it
+    ///
+    /// 0. `[signer]` Pool Owner Wallet Account
+    /// 1. `[writable]` CWAR Pool Storage Account
+    /// 2. `[]` New Pauser Wallet
+    SetPauser {},
+
+    /// Replaces the pool's `PAUSE_*` bitmask, the pauser can only add flags
+    ///
+    /// 0. `[signer]` Pool Owner or Pauser Wallet Account
+    /// 1. `[writable]` CWAR Pool Storage Account
+    SetPausedOperations { paused_operations: u8 },
+
+    /// Withdraws the whole stake without penalty even while the pool is paused or the locking
+    /// period is running, forfeiting all pending rewards. Unbonding pools use `RequestUnstake`
+    /// instead. In receipt pools the receipts the user no longer holds are recorded as the user's
+    /// `receipt_debt` rather than failing the burn
+    ///
+    /// 0. `[signer]` User Wallet Account
+    /// 1. `[writable]` User Storage Account [user wallet, pool storage, program id]
+    /// 2. `[writable]` CWAR Pool Storage Account
+    /// 3. `[writable]` CWAR Staking Vault
+    /// 4. `[writable]` CWAR ATA to Credit
+    /// 5. `[]` Pool Signer [pool storage, program id]
+    /// 6. `[]` Token Program
+    ///
+    /// Only when the pool has a reward schedule
+    /// 7. `[]` Reward Schedule ["reward_schedule", pool storage, program id]
+    ///
+    /// Only when the pool has a receipt mint, one index later with a reward schedule
+    /// 7. `[writable]` Receipt Mint
+    /// 8. `[writable]` User Receipt ATA to Debit
+    EmergencyUnstake {},
+
+    /// Updates the given pool parameters, `None` keeps the current value. While anything is
+    /// staked the penalty, locking and unbonding durations can only be lowered
+    ///
+    /// 0. `[signer]` Pool Owner Wallet Account
+    /// 1. `[writable]` CWAR Pool Storage Account
+    /// 2. `[]` New Authority Unstake Penality Deposit ATA, only when it is being updated
+    UpdatePoolConfig {
+        unstake_penality_basis_points: Option<u16>,
+        locking_duration: Option<u64>,
+        authority_penality_deposit_ata: Option<Pubkey>,
+        unbonding_duration: Option<u64>,
+    },
+
+    /// Raises the delay queued actions have to wait, lowering it has to be queued as well
+    ///
+    /// 0. `[signer]` Pool Owner Wallet Account
+    /// 1. `[writable]` CWAR Pool Storage Account
+    SetTimelockDelay { timelock_delay: u64 },
+
+    /// Queues an owner action that anyone can execute once the pool's timelock delay has passed,
+    /// for `ACTION_EXECUTION_GRACE_PERIOD` after that. An expired action can only be cancelled
+    ///
+    /// 0. `[signer, writable]` Pool Owner Wallet Account
+    /// 1. `[writable]` CWAR Pool Storage Account
+    /// 2. `[writable]` Pending Action Account ["pending_action", pool storage, next action id,
+    ///    program id]
+    /// 3. `[]` System Program
+    QueueAction { action: PoolAction },
+
+    /// 0. `[signer]` Executor Wallet Account
+    /// 1. `[writable]` CWAR Pool Storage Account
+    /// 2. `[writable]` Pending Action Account
+    /// 3. `[writable]` Rent Receiver, the wallet that queued the action
+    ///
+    /// followed by the accounts of the action:
+    ///
+    /// `UpdatePoolConfig` with a new ATA
+    /// 4. `[]` New Authority Unstake Penality Deposit ATA
+    ///
+    /// `ClosePool`
+    /// 4. `[writable]` Pool Owner Wallet Account
+    /// 5. `[writable]` CWAR Staking Vault
+    /// 6. `[writable]` CWAR Staking Refund ATA
+    /// 7. `[writable]` CWAR Rewards Vault
+    /// 8. `[writable]` CWAR Rewards Refund ATA
+    /// 9. `[]` Pool Signer [pool storage, program id]
+    /// 10. `[]` Token Program
+    ///
+    /// `ReduceRewards` and `CancelRewards`
+    /// 4. `[writable]` CWAR Rewards Vault
+    /// 5. `[writable]` CWAR Rewards Refund ATA
+    /// 6. `[]` Pool Signer [pool storage, program id]
+    /// 7. `[]` Token Program
+    /// 8. `[]` Reward Schedule, only when the pool has one
+    ///
+    /// `SetReceiptMint`
+    /// 4. `[]` Receipt Mint
+    /// 5. `[]` CWAR Staking Mint
+    ExecuteAction {},
+
+    /// 0. `[signer]` Pool Owner Wallet Account
+    /// 1. `[]` CWAR Pool Storage Account
+    /// 2. `[writable]` Pending Action Account
+    /// 3. `[writable]` Rent Receiver, the wallet that queued the action
+    CancelAction {},
+
+    /// Takes `amount` back out of the rewards the current stream has yet to emit and ends it at
+    /// `new_reward_duration_end`, rewards already earned by stakers are never touched
+    ///
+    /// 0. `[signer]` Pool Owner Wallet Account
+    /// 1. `[writable]` CWAR Pool Storage Account
+    /// 2. `[writable]` CWAR Rewards Vault
+    /// 3. `[writable]` CWAR Rewards Refund ATA
+    /// 4. `[]` Pool Signer [pool storage, program id]
+    /// 5. `[]` Token Program
+    ///
+    /// Only when the pool has a reward schedule
+    /// 6. `[]` Reward Schedule ["reward_schedule", pool storage, program id]
+    ReduceRewards {
+        amount: u64,
+        new_reward_duration_end: u64,
+    },
+
+    /// Ends the current stream now and returns every reward it had yet to emit
+    ///
+    /// 0. `[signer]` Pool Owner Wallet Account
+    /// 1. `[writable]` CWAR Pool Storage Account
+    /// 2. `[writable]` CWAR Rewards Vault
+    /// 3. `[writable]` CWAR Rewards Refund ATA
+    /// 4. `[]` Pool Signer [pool storage, program id]
+    /// 5. `[]` Token Program
+    ///
+    /// Only when the pool has a reward schedule
+    /// 6. `[]` Reward Schedule ["reward_schedule", pool storage, program id]
+    CancelRewards {},
+
+    /// Registers an extra reward token in the next unused reward stream slot
+    ///
+    /// 0. `[signer]` Pool Owner Wallet Account
+    /// 1. `[writable]` CWAR Pool Storage Account
+    /// 2. `[]` Reward Stream Mint
+    /// 3. `[writable]` Reward Stream Vault, handed over to the pool signer
+    /// 4. `[]` Token Program
+    AddRewardStream {},
+
+    /// 0. `[signer]` Funder Wallet Account
+    /// 1. `[writable]` CWAR Pool Storage Account
+    /// 2. `[writable]` Reward Stream Vault
+    /// 3. `[writable]` ATA to Debit (Reward Stream Token)
+    /// 4. `[]` Token Program
+    ///
+    /// Only when the pool has a reward schedule
+    /// 5. `[]` Reward Schedule ["reward_schedule", pool storage, program id]
+    FundRewardStream {
+        stream_index: u8,
+        amount: u64,
+        extend_duration: u64,
+    },
+
+    /// Empties and closes the vault of an ended reward stream once the pool has no users left,
+    /// freeing its slot
+    ///
+    /// 0. `[signer]` Pool Owner Wallet Account
+    /// 1. `[writable]` CWAR Pool Storage Account
+    /// 2. `[writable]` Reward Stream Vault
+    /// 3. `[writable]` Rewards Refund ATA (Reward Stream Token)
+    /// 4. `[]` Pool Signer [pool storage, program id]
+    /// 5. `[]` Token Program
+    CloseRewardStream { stream_index: u8 },
+
+    /// Stakes the pending primary rewards of a pool whose reward mint is its staking mint,
+    /// without restarting the locking period or the unstake penalty window, receipt pools
+    /// don't support it
+    ///
+    /// 0. `[signer]` User Wallet Account
+    /// 1. `[writable]` User Storage Account
+    /// 2. `[writable]` CWAR Pool Storage Account
+    /// 3. `[writable]` CWAR Staking Vault
+    /// 4. `[writable]` CWAR Reward Vault
+    /// 5. `[]` Pool Signer [pool storage, program id]
+    /// 6. `[]` Token Program
+    ///
+    /// Only when the pool has a reward schedule
+    /// 7. `[]` Reward Schedule ["reward_schedule", pool storage, program id]
+    CompoundRewards {},
+
+    /// Moves an amount of stake into a new unbonding request of a pool in unbonding mode. The
+    /// amount stops earning rewards and can be withdrawn once the unbonding duration has passed.
+    /// Runs even while `PAUSE_UNSTAKE` is set, unbonding pools have no `EmergencyUnstake`
+    ///
+    /// 0. `[signer, writable]` User Wallet Account
+    /// 1. `[writable]` User Storage Account
+    /// 2. `[writable]` CWAR Pool Storage Account
+    /// 3. `[writable]` Unbonding Request Account [user storage, request id, program id]
+    /// 4. `[]` System Program
+    ///
+    /// Only when the pool has a reward schedule
+    /// 5. `[]` Reward Schedule ["reward_schedule", pool storage, program id]
+    RequestUnstake { amount: u64 },
+
+    /// Transfers the amount of a finished unbonding request to the user and closes the request
+    ///
+    /// 0. `[signer, writable]` User Wallet Account
+    /// 1. `[writable]` User Storage Account
+    /// 2. `[writable]` CWAR Pool Storage Account
+    /// 3. `[writable]` Unbonding Request Account
+    /// 4. `[writable]` CWAR Staking Vault
+    /// 5. `[writable]` User CWAR ATA to Credit
+    /// 6. `[]` Pool Signer [pool storage, program id]
+    /// 7. `[]` Token Program
+    WithdrawUnstaked {},
+
+    /// Puts the amount of an unbonding request back into the user's stake and closes the request
+    ///
+    /// 0. `[signer, writable]` User Wallet Account
+    /// 1. `[writable]` User Storage Account
+    /// 2. `[writable]` CWAR Pool Storage Account
+    /// 3. `[writable]` Unbonding Request Account
+    ///
+    /// Only when the pool has a reward schedule
+    /// 4. `[]` Reward Schedule ["reward_schedule", pool storage, program id]
+    CancelUnstakeRequest {},
+
+    /// Replaces the lock tiers offered to new lock positions, has to be queued while the pool
+    /// has a timelock
+    ///
+    /// 0. `[signer]` Pool Owner Wallet Account
+    /// 1. `[writable]` CWAR Pool Storage Account
+    SetLockTiers {
+        lock_tiers: [LockTier; MAX_LOCK_TIERS],
+    },
+
+    /// Deposits into a new lock position that earns with the weight of the chosen lock tier
+    /// and can be withdrawn once the tier duration has passed, `ExpireLockPosition` ends the
+    /// boost from then on
+    ///
+    /// 0. `[signer, writable]` User Wallet Account
+    /// 1. `[writable]` User Storage Account
+    /// 2. `[writable]` CWAR Pool Storage Account
+    /// 3. `[writable]` CWAR Staking Vault
+    /// 4. `[writable]` CWAR ATA to Debit
+    /// 5. `[writable]` Lock Position Account ["lock_position", user storage, position id,
+    ///    program id]
+    /// 6. `[]` Token Program
+    /// 7. `[]` System Program
+    ///
+    /// Only when the pool has a reward schedule
+    /// 8. `[]` Reward Schedule ["reward_schedule", pool storage, program id]
+    StakeLocked { amount: u64, lock_tier_index: u8 },
+
+    /// Withdraws an unlocked lock position in full and closes it, even while the pool is paused.
+    /// Pending rewards stay in the user storage
+    ///
+    /// 0. `[signer, writable]` User Wallet Account
+    /// 1. `[writable]` User Storage Account
+    /// 2. `[writable]` CWAR Pool Storage Account
+    /// 3. `[writable]` Lock Position Account
+    /// 4. `[writable]` CWAR Staking Vault
+    /// 5. `[writable]` User CWAR ATA to Credit
+    /// 6. `[]` Pool Signer [pool storage, program id]
+    /// 7. `[]` Token Program
+    ///
+    /// Only when the pool has a reward schedule
+    /// 8. `[]` Reward Schedule ["reward_schedule", pool storage, program id]
+    UnstakeLocked {},
+
+    /// Sets how the unstake penalty decays with the age of a user's stake, has to be queued
+    /// while the pool has a timelock
+    ///
+    /// 0. `[signer]` Pool Owner Wallet Account
+    /// 1. `[writable]` CWAR Pool Storage Account
+    SetPenalitySchedule { penality_schedule: PenalitySchedule },
+
+    /// Sets how unstake penalties are split between burning, the reward vault, the stakers and
+    /// the treasury, has to be queued while the pool has a timelock
+    ///
+    /// 0. `[signer]` Pool Owner Wallet Account
+    /// 1. `[writable]` CWAR Pool Storage Account
+    SetPenalityRouting { penality_routing: PenalityRouting },
+
+    /// Sets the minimum and maximum stake of a user and the pool-wide stake cap, zero turns a
+    /// limit off, has to be queued while the pool has a timelock
+    ///
+    /// 0. `[signer]` Pool Owner Wallet Account
+    /// 1. `[writable]` CWAR Pool Storage Account
+    SetStakeLimits {
+        min_stake: u64,
+        max_stake_per_user: u64,
+        max_total_staked: u64,
+    },
+
+    /// Sets the Merkle root of the wallets allowed to join the pool, all zero opens it to
+    /// everyone, has to be queued while the pool has a timelock
+    ///
+    /// 0. `[signer]` Pool Owner Wallet Account
+    /// 1. `[writable]` CWAR Pool Storage Account
+    SetAllowlistRoot { allowlist_root: [u8; 32] },
+
+    /// `CreateUser` for allowlisted pools, proves the wallet's leaf and its stake cap
+    ///
+    /// 0. `[signer, writable]` User Wallet Account
+    /// 1. `[writable]` User Storage Account [user wallet, pool storage, program id]
+    /// 2. `[writable]` CWAR Pool Storage Account
+    /// 3. `[]` System Program
+    CreateAllowlistedUser {
+        nonce: u8,
+        allowlist_max_stake: u64,
+        allowlist_proof: Vec<[u8; 32]>,
+    },
+
+    /// Unstakes everything, claims all rewards and closes the user storage account, lock
+    /// positions and unbonding requests have to be settled first
+    ///
+    /// 0. `[signer, writable]` User Wallet Account, receives the user storage rent
+    /// 1. `[writable]` User Storage Account
+    /// 2. `[writable]` CWAR Pool Storage Account
+    /// 3. `[writable]` CWAR Staking Vault
+    /// 4. `[writable]` CWAR Reward Vault
+    /// 5. `[writable]` User CWAR ATA to Credit, created if missing
+    /// 6. `[writable]` User Rewards ATA to Credit, created if missing
+    /// 7. `[]` Pool Signer [pool storage, program id]
+    /// 8. `[]` Token Program
+    /// 9. `[writable]` Authority Unstake Penality Deposit ATA
+    /// 10. `[writable]` CWAR Staking Mint
+    /// 11. `[]` CWAR Rewards Mint
+    /// 12. `[]` System Program
+    /// 13. `[]` Associated Token Program
+    ///
+    /// Only when the pool has a reward schedule
+    /// 14. `[]` Reward Schedule ["reward_schedule", pool storage, program id]
+    ///
+    /// Only when the pool has a receipt mint, one index later with a reward schedule
+    /// 14. `[writable]` Receipt Mint
+    /// 15. `[writable]` User Receipt ATA to Debit
+    ///
+    /// Then for each extra reward stream the user has rewards pending in, one index later with
+    /// a reward schedule
+    /// 14. `[writable]` Reward Stream Vault, or 16. in receipt pools
+    /// 15. `[writable]` User ATA to Credit (Reward Stream Token), or 17. in receipt pools
+    Exit {},
+
+    /// Claims the rewards of the user in several pools, with the same checks as `ClaimRewards`,
+    /// extra reward streams aren't claimed
+    ///
+    /// 0. `[signer, writable]` User Wallet Account
+    /// 1. `[]` Token Program
+    /// 2. `[]` System Program
+    /// 3. `[]` Associated Token Program
+    ///
+    /// Then for each pool to claim from
+    /// 4. `[writable]` User Storage Account
+    /// 5. `[writable]` CWAR Pool Storage Account
+    /// 6. `[writable]` CWAR Staking Vault
+    /// 7. `[writable]` CWAR Reward Vault
+    /// 8. `[writable]` User Rewards ATA to Credit, created if missing
+    /// 9. `[]` Pool Signer [pool storage, program id]
+    /// 10. `[]` CWAR Rewards Mint
+    /// 11. `[]` Reward Schedule, only when the pool has one
+    ClaimRewardsMulti {},
+
+    /// Moves the user's stake, lock positions and pending rewards to the user account of another
+    /// wallet, creating it when missing unless the pool has an allowlist. Every lock position of
+    /// the user has to be passed, each is closed and opened again under the recipient with the
+    /// next free recipient position id. Unbonding requests are out of scope and have to be
+    /// withdrawn first, receipt pools don't support it since their positions follow the receipts.
+    ///
+    /// 0. `[signer, writable]` User Wallet Account, pays for the recipient user storage
+    /// 1. `[writable]` User Storage Account
+    /// 2. `[writable]` CWAR Pool Storage Account
+    /// 3. `[]` Recipient Wallet Account
+    /// 4. `[writable]` Recipient User Storage Account [recipient wallet, pool storage]
+    /// 5. `[]` System Program
+    ///
+    /// Only when the pool has a reward schedule
+    /// 6. `[]` Reward Schedule ["reward_schedule", pool storage, program id]
+    ///
+    /// Then for each lock position of the user, one index later with a reward schedule
+    /// 6. `[writable]` Lock Position ["lock_position", user storage, position id]
+    /// 7. `[writable]` Recipient Lock Position ["lock_position", recipient storage, position id]
+    TransferPosition {},
+
+    /// Sets the receipt token minted 1:1 for unlocked stake, only once and while nothing is
+    /// staked. The mint has no supply, the pool signer as mint authority, no freeze authority and
+    /// the decimals of the staking mint. Has to be queued while the pool has a timelock.
+    ///
+    /// 0. `[signer]` Pool Owner Wallet Account
+    /// 1. `[writable]` CWAR Pool Storage Account
+    /// 2. `[]` Receipt Mint
+    /// 3. `[]` CWAR Staking Mint
+    SetReceiptMint { receipt_mint: Pubkey },
+
+    /// Moves unlocked stake from a wallet whose receipt ATA holds less than it has staked to the
+    /// holder, whose receipt ATA holds more, so rewards follow the receipts. Anyone can call it.
+    /// The holder's user account is created when missing, unless the pool has an allowlist.
+    ///
+    /// 0. `[writable]` Holder Wallet Account, signer when it pays for the holder user storage
+    /// 1. `[writable]` Holder User Storage Account [holder wallet, pool storage]
+    /// 2. `[writable]` CWAR Pool Storage Account
+    /// 3. `[]` Holder Receipt ATA
+    /// 4. `[]` Source Wallet Account
+    /// 5. `[writable]` Source User Storage Account [source wallet, pool storage]
+    /// 6. `[]` Source Receipt ATA
+    /// 7. `[]` System Program
+    ///
+    /// Only when the pool has a reward schedule
+    /// 8. `[]` Reward Schedule ["reward_schedule", pool storage, program id]
+    ///
+    /// Only when the holder user storage is missing and the holder does not sign, one index
+    /// later with a reward schedule
+    /// 8. `[signer, writable]` Payer Account, pays for the holder user storage
+    SettleReceipt {},
+
+    /// Appends a phase emitting `reward_rate` per second from `start` until `end` to the pool
+    /// reward schedule, on top of the current reward stream. The phase can't start in the past
+    /// or before the last one ends, and its whole emission is taken from the funder up front.
+    ///
+    /// 0. `[signer, writable]` Funder Wallet Account, pays for the reward schedule if missing
+    /// 1. `[writable]` CWAR Pool Storage Account
+    /// 2. `[writable]` Reward Schedule ["reward_schedule", pool storage, program id]
+    /// 3. `[writable]` CWAR Reward Vault
+    /// 4. `[writable]` CWAR ATA to Debit (Reward Token)
+    /// 5. `[]` Token Program
+    /// 6. `[]` System Program
+    AddRewardPhase {
+        start: u64,
+        end: u64,
+        reward_rate: u64,
+    },
+
+    /// Drops the reward weight of an unlocked lock position to one per token, anyone can call it
+    ///
+    /// 0. `[]` User Wallet Account of the position
+    /// 1. `[writable]` User Storage Account
+    /// 2. `[writable]` CWAR Pool Storage Account
+    /// 3. `[writable]` Lock Position Account
+    ///
+    /// Only when the pool has a reward schedule
+    /// 4. `[]` Reward Schedule ["reward_schedule", pool storage, program id]
+    ExpireLockPosition {},
 }
 
 impl CryowarInstruction {
+    /// Decodes instruction data, rejecting unknown discriminants, payloads that don't decode and
+    /// payloads that are short or have trailing bytes
     pub fn unpack(input: &[u8]) -> Result<Self, ProgramError> {
-        Ok(match input[0] {
-            0 => Self::InitializeCwarPool {
-                reward_duration: Self::unpack_to_u64(&input[1..9])?,
-                pool_nonce: input[9],
-                unstake_penality_basis_points: Self::unpack_to_u16(&input[10..12])?,
-                locking_duration: Self::unpack_to_u64(&input[12..20])?,
-            },
-            1 => Self::CreateUser { nonce: input[1] },
-            2 => Self::StakeCwar {
-                amount_to_deposit: Self::unpack_to_u64(&input[1..9])?,
-            },
-            3 => Self::UnstakeCwar {
-                amount_to_withdraw: Self::unpack_to_u64(&input[1..9])?,
-            },
+        if input.is_empty() {
+            return Err(InvalidInstruction.into());
+        }
+        let mut data = input;
+        let instruction = Self::deserialize(&mut data).map_err(|error| {
+            // borsh reports every failure as an `InvalidInput` error, only the message tells a
+            // short payload or an unknown variant apart
+            let message = error.to_string();
+            if message.starts_with(BORSH_UNEXPECTED_LENGTH) {
+                InvalidInstructionDataLength.into()
+            } else if message.starts_with(BORSH_UNEXPECTED_VARIANT) {
+                InvalidInstruction.into()
+            } else {
+                ProgramError::InvalidInstructionData
+            }
+        })?;
+        if !data.is_empty() {
+            return Err(InvalidInstructionDataLength.into());
+        }
+        Ok(instruction)
+    }
+
+    pub fn pack(&self) -> Vec<u8> {
+        self.try_to_vec().unwrap()
+    }
+}
+
+const BORSH_UNEXPECTED_LENGTH: &str = "Unexpected length of input";
+const BORSH_UNEXPECTED_VARIANT: &str = "Unexpected variant index";
+
+/// Creates an `InitializeCwarPool` instruction, deriving the pool signer nonce
+#[allow(clippy::too_many_arguments)]
+pub fn initialize_cwar_pool(
+    program_id: &Pubkey,
+    pool_owner_wallet: &Pubkey,
+    cwar_pool_storage: &Pubkey,
+    staking_mint: &Pubkey,
+    staking_vault: &Pubkey,
+    rewards_mint: &Pubkey,
+    rewards_vault: &Pubkey,
+    authority_penality_deposit_ata: &Pubkey,
+    reward_duration: u64,
+    unstake_penality_basis_points: u16,
+    locking_duration: u64,
+) -> Instruction {
+    let (_pool_signer, pool_nonce) =
+        get_pool_signer_address_and_bump_seed(cwar_pool_storage, program_id);
+    let data = CryowarInstruction::InitializeCwarPool {
+        reward_duration,
+        pool_nonce,
+        unstake_penality_basis_points,
+        locking_duration,
+    }
+    .pack();
+    let accounts = vec![
+        AccountMeta::new_readonly(*pool_owner_wallet, true),
+        AccountMeta::new(*cwar_pool_storage, false),
+        AccountMeta::new_readonly(*staking_mint, false),
+        AccountMeta::new(*staking_vault, false),
+        AccountMeta::new_readonly(*rewards_mint, false),
+        AccountMeta::new(*rewards_vault, false),
+        AccountMeta::new_readonly(spl_token::id(), false),
+        AccountMeta::new_readonly(*authority_penality_deposit_ata, false),
+    ];
+    Instruction {
+        program_id: *program_id,
+        accounts,
+        data,
+    }
+}
+
+/// Creates a `CreateUser` instruction, deriving the user storage address
+pub fn create_user(
+    program_id: &Pubkey,
+    user_wallet: &Pubkey,
+    cwar_pool_storage: &Pubkey,
+) -> Instruction {
+    let (user_storage, nonce) =
+        get_user_storage_address_and_bump_seed(user_wallet, cwar_pool_storage, program_id);
+    let data = CryowarInstruction::CreateUser { nonce }.pack();
+    let accounts = vec![
+        AccountMeta::new(*user_wallet, true),
+        AccountMeta::new(user_storage, false),
+        AccountMeta::new(*cwar_pool_storage, false),
+        AccountMeta::new_readonly(system_program::id(), false),
+    ];
+    Instruction {
+        program_id: *program_id,
+        accounts,
+        data,
+    }
+}
+
+/// Creates a `StakeCwar` instruction
+#[allow(clippy::too_many_arguments)]
+pub fn stake_cwar(
+    program_id: &Pubkey,
+    user_wallet: &Pubkey,
+    cwar_pool_storage: &Pubkey,
+    staking_vault: &Pubkey,
+    user_cwar_ata: &Pubkey,
+    has_reward_schedule: bool,
+    receipt_mint: Option<&Pubkey>,
+    amount_to_deposit: u64,
+) -> Instruction {
+    let (user_storage, _nonce) =
+        get_user_storage_address_and_bump_seed(user_wallet, cwar_pool_storage, program_id);
+    let data = CryowarInstruction::StakeCwar { amount_to_deposit }.pack();
+    let mut accounts = vec![
+        AccountMeta {
+            pubkey: *user_wallet,
+            is_signer: true,
+            is_writable: receipt_mint.is_some(),
+        },
+        AccountMeta::new(user_storage, false),
+        AccountMeta::new(*cwar_pool_storage, false),
+        AccountMeta::new(*staking_vault, false),
+        AccountMeta::new(*user_cwar_ata, false),
+        AccountMeta::new_readonly(spl_token::id(), false),
+    ];
+    accounts.extend(reward_schedule_account_metas(
+        cwar_pool_storage,
+        has_reward_schedule,
+        program_id,
+    ));
+    if let Some(receipt_mint) = receipt_mint {
+        let (pool_signer, _pool_nonce) =
+            get_pool_signer_address_and_bump_seed(cwar_pool_storage, program_id);
+        accounts.extend(receipt_account_metas(user_wallet, receipt_mint));
+        accounts.push(AccountMeta::new_readonly(pool_signer, false));
+        accounts.push(AccountMeta::new_readonly(system_program::id(), false));
+        accounts.push(AccountMeta::new_readonly(
+            spl_associated_token_account::id(),
+            false,
+        ));
+    }
+    Instruction {
+        program_id: *program_id,
+        accounts,
+        data,
+    }
+}
+
+/// Creates an `UnstakeCwar` instruction paying out to the staking mint ATA of `recipient_wallet`,
+/// `penality_routing_accounts` holds the staking mint if the pool burns penalties, followed by the
+/// reward vault if it routes penalties to its stakers
+#[allow(clippy::too_many_arguments)]
+pub fn unstake_cwar(
+    program_id: &Pubkey,
+    user_wallet: &Pubkey,
+    cwar_pool_storage: &Pubkey,
+    staking_vault: &Pubkey,
+    staking_mint: &Pubkey,
+    authority_penality_deposit_ata: &Pubkey,
+    recipient_wallet: &Pubkey,
+    has_reward_schedule: bool,
+    penality_routing_accounts: &[Pubkey],
+    receipt_mint: Option<&Pubkey>,
+    amount_to_withdraw: u64,
+) -> Instruction {
+    let (user_storage, _nonce) =
+        get_user_storage_address_and_bump_seed(user_wallet, cwar_pool_storage, program_id);
+    let (pool_signer, _pool_nonce) =
+        get_pool_signer_address_and_bump_seed(cwar_pool_storage, program_id);
+    let data = CryowarInstruction::UnstakeCwar { amount_to_withdraw }.pack();
+    let mut accounts = vec![
+        AccountMeta::new(*user_wallet, true),
+        AccountMeta::new(user_storage, false),
+        AccountMeta::new(*cwar_pool_storage, false),
+        AccountMeta::new(*staking_vault, false),
+        AccountMeta::new(
+            get_associated_token_address(recipient_wallet, staking_mint),
+            false,
+        ),
+        AccountMeta::new_readonly(pool_signer, false),
+        AccountMeta::new_readonly(spl_token::id(), false),
+        AccountMeta::new(*authority_penality_deposit_ata, false),
+    ];
+    accounts.extend(recipient_account_metas(recipient_wallet, staking_mint));
+    accounts.extend(reward_schedule_account_metas(
+        cwar_pool_storage,
+        has_reward_schedule,
+        program_id,
+    ));
+    accounts.extend(
+        penality_routing_accounts
+            .iter()
+            .map(|account| AccountMeta::new(*account, false)),
+    );
+    if let Some(receipt_mint) = receipt_mint {
+        accounts.extend(receipt_account_metas(user_wallet, receipt_mint));
+    }
+    Instruction {
+        program_id: *program_id,
+        accounts,
+        data,
+    }
+}
+
+/// Creates a `ClaimRewards` instruction paying out to the reward mint ATA of `recipient_wallet`,
+/// `reward_stream_accounts` holds the reward stream vault and reward mint of every extra stream to
+/// claim from
+#[allow(clippy::too_many_arguments)]
+pub fn claim_rewards(
+    program_id: &Pubkey,
+    user_wallet: &Pubkey,
+    cwar_pool_storage: &Pubkey,
+    staking_vault: &Pubkey,
+    rewards_vault: &Pubkey,
+    reward_mint: &Pubkey,
+    recipient_wallet: &Pubkey,
+    has_reward_schedule: bool,
+    reward_stream_accounts: &[(Pubkey, Pubkey)],
+) -> Instruction {
+    let (user_storage, _nonce) =
+        get_user_storage_address_and_bump_seed(user_wallet, cwar_pool_storage, program_id);
+    let (pool_signer, _pool_nonce) =
+        get_pool_signer_address_and_bump_seed(cwar_pool_storage, program_id);
+    let data = CryowarInstruction::ClaimRewards {}.pack();
+    let mut accounts = vec![
+        AccountMeta::new(*user_wallet, true),
+        AccountMeta::new(user_storage, false),
+        AccountMeta::new(*cwar_pool_storage, false),
+        AccountMeta::new(*staking_vault, false),
+        AccountMeta::new(*rewards_vault, false),
+        AccountMeta::new(
+            get_associated_token_address(recipient_wallet, reward_mint),
+            false,
+        ),
+        AccountMeta::new_readonly(pool_signer, false),
+        AccountMeta::new_readonly(spl_token::id(), false),
+    ];
+    accounts.extend(recipient_account_metas(recipient_wallet, reward_mint));
+    accounts.extend(reward_schedule_account_metas(
+        cwar_pool_storage,
+        has_reward_schedule,
+        program_id,
+    ));
+    for (reward_stream_vault, reward_stream_mint) in reward_stream_accounts {
+        accounts.push(AccountMeta::new(*reward_stream_vault, false));
+        accounts.push(AccountMeta::new_readonly(*reward_stream_mint, false));
+        accounts.push(AccountMeta::new(
+            get_associated_token_address(recipient_wallet, reward_stream_mint),
+            false,
+        ));
+    }
+    Instruction {
+        program_id: *program_id,
+        accounts,
+        data,
+    }
+}
+
+/// Creates an `AddFunder` instruction
+pub fn add_funder(
+    program_id: &Pubkey,
+    pool_owner_wallet: &Pubkey,
+    cwar_pool_storage: &Pubkey,
+    new_funder_wallet: &Pubkey,
+) -> Instruction {
+    let data = CryowarInstruction::AddFunder {}.pack();
+    let accounts = vec![
+        AccountMeta::new_readonly(*pool_owner_wallet, true),
+        AccountMeta::new(*cwar_pool_storage, false),
+        AccountMeta::new_readonly(*new_funder_wallet, false),
+    ];
+    Instruction {
+        program_id: *program_id,
+        accounts,
+        data,
+    }
+}
+
+/// Creates a `RemoveFunder` instruction
+pub fn remove_funder(
+    program_id: &Pubkey,
+    pool_owner_wallet: &Pubkey,
+    cwar_pool_storage: &Pubkey,
+    funder_wallet_to_remove: &Pubkey,
+) -> Instruction {
+    let data = CryowarInstruction::RemoveFunder {}.pack();
+    let accounts = vec![
+        AccountMeta::new_readonly(*pool_owner_wallet, true),
+        AccountMeta::new(*cwar_pool_storage, false),
+        AccountMeta::new_readonly(*funder_wallet_to_remove, false),
+    ];
+    Instruction {
+        program_id: *program_id,
+        accounts,
+        data,
+    }
+}
+
+/// Creates a `FundPool` instruction
+#[allow(clippy::too_many_arguments)]
+pub fn fund_pool(
+    program_id: &Pubkey,
+    funder_wallet: &Pubkey,
+    cwar_pool_storage: &Pubkey,
+    staking_vault: &Pubkey,
+    rewards_vault: &Pubkey,
+    funder_rewards_ata: &Pubkey,
+    has_reward_schedule: bool,
+    amount: u64,
+    extend_duration: u64,
+) -> Instruction {
+    let data = CryowarInstruction::FundPool {
+        amount,
+        extend_duration,
+    }
+    .pack();
+    let mut accounts = vec![
+        AccountMeta::new_readonly(*funder_wallet, true),
+        AccountMeta::new(*cwar_pool_storage, false),
+        AccountMeta::new(*staking_vault, false),
+        AccountMeta::new(*rewards_vault, false),
+        AccountMeta::new(*funder_rewards_ata, false),
+        AccountMeta::new_readonly(spl_token::id(), false),
+    ];
+    accounts.extend(reward_schedule_account_metas(
+        cwar_pool_storage,
+        has_reward_schedule,
+        program_id,
+    ));
+    Instruction {
+        program_id: *program_id,
+        accounts,
+        data,
+    }
+}
+
+/// Creates a `ClosePool` instruction
+pub fn close_pool(
+    program_id: &Pubkey,
+    pool_owner_wallet: &Pubkey,
+    cwar_pool_storage: &Pubkey,
+    staking_vault: &Pubkey,
+    staking_refund_ata: &Pubkey,
+    rewards_vault: &Pubkey,
+    rewards_refund_ata: &Pubkey,
+) -> Instruction {
+    let (pool_signer, _pool_nonce) =
+        get_pool_signer_address_and_bump_seed(cwar_pool_storage, program_id);
+    let data = CryowarInstruction::ClosePool {}.pack();
+    let accounts = vec![
+        AccountMeta::new(*pool_owner_wallet, true),
+        AccountMeta::new(*staking_vault, false),
+        AccountMeta::new(*staking_refund_ata, false),
+        AccountMeta::new(*rewards_vault, false),
+        AccountMeta::new(*rewards_refund_ata, false),
+        AccountMeta::new(*cwar_pool_storage, false),
+        AccountMeta::new_readonly(pool_signer, false),
+        AccountMeta::new_readonly(spl_token::id(), false),
+    ];
+    Instruction {
+        program_id: *program_id,
+        accounts,
+        data,
+    }
+}
+
+/// Creates a `CloseUser` instruction
+pub fn close_user(
+    program_id: &Pubkey,
+    user_wallet: &Pubkey,
+    cwar_pool_storage: &Pubkey,
+) -> Instruction {
+    let (user_storage, _nonce) =
+        get_user_storage_address_and_bump_seed(user_wallet, cwar_pool_storage, program_id);
+    let data = CryowarInstruction::CloseUser {}.pack();
+    let accounts = vec![
+        AccountMeta::new(*user_wallet, true),
+        AccountMeta::new(user_storage, false),
+        AccountMeta::new(*cwar_pool_storage, false),
+    ];
+    Instruction {
+        program_id: *program_id,
+        accounts,
+        data,
+    }
+}
+
+/// Creates a `MigratePool` instruction
+pub fn migrate_pool(
+    program_id: &Pubkey,
+    payer_wallet: &Pubkey,
+    cwar_pool_storage: &Pubkey,
+    staking_vault: &Pubkey,
+    rewards_vault: &Pubkey,
+) -> Instruction {
+    let data = CryowarInstruction::MigratePool {}.pack();
+    let accounts = vec![
+        AccountMeta::new(*payer_wallet, true),
+        AccountMeta::new(*cwar_pool_storage, false),
+        AccountMeta::new_readonly(*staking_vault, false),
+        AccountMeta::new_readonly(*rewards_vault, false),
+        AccountMeta::new_readonly(system_program::id(), false),
+    ];
+    Instruction {
+        program_id: *program_id,
+        accounts,
+        data,
+    }
+}
+
+/// Creates a `RecoverSurplus` instruction
+#[allow(clippy::too_many_arguments)]
+pub fn recover_surplus(
+    program_id: &Pubkey,
+    pool_owner_wallet: &Pubkey,
+    cwar_pool_storage: &Pubkey,
+    staking_vault: &Pubkey,
+    staking_surplus_ata: &Pubkey,
+    rewards_vault: &Pubkey,
+    rewards_surplus_ata: &Pubkey,
+    has_reward_schedule: bool,
+) -> Instruction {
+    let (pool_signer, _pool_nonce) =
+        get_pool_signer_address_and_bump_seed(cwar_pool_storage, program_id);
+    let data = CryowarInstruction::RecoverSurplus {}.pack();
+    let mut accounts = vec![
+        AccountMeta::new_readonly(*pool_owner_wallet, true),
+        AccountMeta::new(*cwar_pool_storage, false),
+        AccountMeta::new(*staking_vault, false),
+        AccountMeta::new(*staking_surplus_ata, false),
+        AccountMeta::new(*rewards_vault, false),
+        AccountMeta::new(*rewards_surplus_ata, false),
+        AccountMeta::new_readonly(pool_signer, false),
+        AccountMeta::new_readonly(spl_token::id(), false),
+    ];
+    accounts.extend(reward_schedule_account_metas(
+        cwar_pool_storage,
+        has_reward_schedule,
+        program_id,
+    ));
+    Instruction {
+        program_id: *program_id,
+        accounts,
+        data,
+    }
+}
+
+/// Creates a `MigrateUser` instruction
+pub fn migrate_user(
+    program_id: &Pubkey,
+    user_wallet: &Pubkey,
+    cwar_pool_storage: &Pubkey,
+) -> Instruction {
+    let (user_storage, _nonce) =
+        get_user_storage_address_and_bump_seed(user_wallet, cwar_pool_storage, program_id);
+    let data = CryowarInstruction::MigrateUser {}.pack();
+    let accounts = vec![
+        AccountMeta::new(*user_wallet, true),
+        AccountMeta::new(user_storage, false),
+        AccountMeta::new_readonly(*cwar_pool_storage, false),
+        AccountMeta::new_readonly(system_program::id(), false),
+    ];
+    Instruction {
+        program_id: *program_id,
+        accounts,
+        data,
+    }
+}
+
+/// Creates a `ProposeNewOwner` instruction
+pub fn propose_new_owner(
+    program_id: &Pubkey,
+    pool_owner_wallet: &Pubkey,
+    cwar_pool_storage: &Pubkey,
+    new_owner_wallet: &Pubkey,
+) -> Instruction {
+    let data = CryowarInstruction::ProposeNewOwner {}.pack();
+    let accounts = vec![
+        AccountMeta::new_readonly(*pool_owner_wallet, true),
+        AccountMeta::new(*cwar_pool_storage, false),
+        AccountMeta::new_readonly(*new_owner_wallet, false),
+    ];
+    Instruction {
+        program_id: *program_id,
+        accounts,
+        data,
+    }
+}
+
+/// Creates an `AcceptOwnership` instruction
+pub fn accept_ownership(
+    program_id: &Pubkey,
+    pending_owner_wallet: &Pubkey,
+    cwar_pool_storage: &Pubkey,
+    authority_penality_deposit_ata: &Pubkey,
+) -> Instruction {
+    let data = CryowarInstruction::AcceptOwnership {}.pack();
+    let accounts = vec![
+        AccountMeta::new_readonly(*pending_owner_wallet, true),
+        AccountMeta::new(*cwar_pool_storage, false),
+        AccountMeta::new_readonly(*authority_penality_deposit_ata, false),
+    ];
+    Instruction {
+        program_id: *program_id,
+        accounts,
+        data,
+    }
+}
+
+/// Creates a `CancelOwnershipTransfer` instruction
+pub fn cancel_ownership_transfer(
+    program_id: &Pubkey,
+    pool_owner_wallet: &Pubkey,
+    cwar_pool_storage: &Pubkey,
+) -> Instruction {
+    let data = CryowarInstruction::CancelOwnershipTransfer {}.pack();
+    let accounts = vec![
+        AccountMeta::new_readonly(*pool_owner_wallet, true),
+        AccountMeta::new(*cwar_pool_storage, false),
+    ];
+    Instruction {
+        program_id: *program_id,
+        accounts,
+        data,
+    }
+}
+
+/// Creates a `SetPauser` instruction
+pub fn set_pauser(
+    program_id: &Pubkey,
+    pool_owner_wallet: &Pubkey,
+    cwar_pool_storage: &Pubkey,
+    new_pauser_wallet: &Pubkey,
+) -> Instruction {
+    let data = CryowarInstruction::SetPauser {}.pack();
+    let accounts = vec![
+        AccountMeta::new_readonly(*pool_owner_wallet, true),
+        AccountMeta::new(*cwar_pool_storage, false),
+        AccountMeta::new_readonly(*new_pauser_wallet, false),
+    ];
+    Instruction {
+        program_id: *program_id,
+        accounts,
+        data,
+    }
+}
+
+/// Creates a `SetPausedOperations` instruction
+pub fn set_paused_operations(
+    program_id: &Pubkey,
+    pause_authority_wallet: &Pubkey,
+    cwar_pool_storage: &Pubkey,
+    paused_operations: u8,
+) -> Instruction {
+    let data = CryowarInstruction::SetPausedOperations { paused_operations }.pack();
+    let accounts = vec![
+        AccountMeta::new_readonly(*pause_authority_wallet, true),
+        AccountMeta::new(*cwar_pool_storage, false),
+    ];
+    Instruction {
+        program_id: *program_id,
+        accounts,
+        data,
+    }
+}
+
+/// Creates an `EmergencyUnstake` instruction
+pub fn emergency_unstake(
+    program_id: &Pubkey,
+    user_wallet: &Pubkey,
+    cwar_pool_storage: &Pubkey,
+    staking_vault: &Pubkey,
+    user_cwar_ata: &Pubkey,
+    has_reward_schedule: bool,
+    receipt_mint: Option<&Pubkey>,
+) -> Instruction {
+    let (user_storage, _nonce) =
+        get_user_storage_address_and_bump_seed(user_wallet, cwar_pool_storage, program_id);
+    let (pool_signer, _pool_nonce) =
+        get_pool_signer_address_and_bump_seed(cwar_pool_storage, program_id);
+    let data = CryowarInstruction::EmergencyUnstake {}.pack();
+    let mut accounts = vec![
+        AccountMeta::new_readonly(*user_wallet, true),
+        AccountMeta::new(user_storage, false),
+        AccountMeta::new(*cwar_pool_storage, false),
+        AccountMeta::new(*staking_vault, false),
+        AccountMeta::new(*user_cwar_ata, false),
+        AccountMeta::new_readonly(pool_signer, false),
+        AccountMeta::new_readonly(spl_token::id(), false),
+    ];
+    accounts.extend(reward_schedule_account_metas(
+        cwar_pool_storage,
+        has_reward_schedule,
+        program_id,
+    ));
+    if let Some(receipt_mint) = receipt_mint {
+        accounts.extend(receipt_account_metas(user_wallet, receipt_mint));
+    }
+    Instruction {
+        program_id: *program_id,
+        accounts,
+        data,
+    }
+}
 
-            4 => Self::ClaimRewards {},
+/// Creates an `UpdatePoolConfig` instruction
+pub fn update_pool_config(
+    program_id: &Pubkey,
+    pool_owner_wallet: &Pubkey,
+    cwar_pool_storage: &Pubkey,
+    unstake_penality_basis_points: Option<u16>,
+    locking_duration: Option<u64>,
+    authority_penality_deposit_ata: Option<&Pubkey>,
+    unbonding_duration: Option<u64>,
+) -> Instruction {
+    let data = CryowarInstruction::UpdatePoolConfig {
+        unstake_penality_basis_points,
+        locking_duration,
+        authority_penality_deposit_ata: authority_penality_deposit_ata.copied(),
+        unbonding_duration,
+    }
+    .pack();
+    let mut accounts = vec![
+        AccountMeta::new_readonly(*pool_owner_wallet, true),
+        AccountMeta::new(*cwar_pool_storage, false),
+    ];
+    if let Some(authority_penality_deposit_ata) = authority_penality_deposit_ata {
+        accounts.push(AccountMeta::new_readonly(
+            *authority_penality_deposit_ata,
+            false,
+        ));
+    }
+    Instruction {
+        program_id: *program_id,
+        accounts,
+        data,
+    }
+}
 
-            5 => Self::AddFunder {},
+/// Creates a `SetTimelockDelay` instruction
+pub fn set_timelock_delay(
+    program_id: &Pubkey,
+    pool_owner_wallet: &Pubkey,
+    cwar_pool_storage: &Pubkey,
+    timelock_delay: u64,
+) -> Instruction {
+    let data = CryowarInstruction::SetTimelockDelay { timelock_delay }.pack();
+    let accounts = vec![
+        AccountMeta::new_readonly(*pool_owner_wallet, true),
+        AccountMeta::new(*cwar_pool_storage, false),
+    ];
+    Instruction {
+        program_id: *program_id,
+        accounts,
+        data,
+    }
+}
 
-            6 => Self::RemoveFunder {},
+/// Creates a `QueueAction` instruction, `action_id` is the pool's current `next_action_id`
+pub fn queue_action(
+    program_id: &Pubkey,
+    pool_owner_wallet: &Pubkey,
+    cwar_pool_storage: &Pubkey,
+    action_id: u64,
+    action: PoolAction,
+) -> Instruction {
+    let (pending_action, _bump_seed) =
+        get_pending_action_address_and_bump_seed(cwar_pool_storage, action_id, program_id);
+    let data = CryowarInstruction::QueueAction { action }.pack();
+    let accounts = vec![
+        AccountMeta::new(*pool_owner_wallet, true),
+        AccountMeta::new(*cwar_pool_storage, false),
+        AccountMeta::new(pending_action, false),
+        AccountMeta::new_readonly(system_program::id(), false),
+    ];
+    Instruction {
+        program_id: *program_id,
+        accounts,
+        data,
+    }
+}
 
-            7 => Self::FundPool {
-                amount: Self::unpack_to_u64(&input[1..9])?,
-                extend_duration: Self::unpack_to_u64(&input[9..17])?,
-            },
+/// Creates an `ExecuteAction` instruction, `action_accounts` are the accounts the queued
+/// action needs as listed on `CryowarInstruction::ExecuteAction`
+pub fn execute_action(
+    program_id: &Pubkey,
+    executor_wallet: &Pubkey,
+    cwar_pool_storage: &Pubkey,
+    action_id: u64,
+    rent_receiver: &Pubkey,
+    action_accounts: Vec<AccountMeta>,
+) -> Instruction {
+    let (pending_action, _bump_seed) =
+        get_pending_action_address_and_bump_seed(cwar_pool_storage, action_id, program_id);
+    let data = CryowarInstruction::ExecuteAction {}.pack();
+    let mut accounts = vec![
+        AccountMeta::new_readonly(*executor_wallet, true),
+        AccountMeta::new(*cwar_pool_storage, false),
+        AccountMeta::new(pending_action, false),
+        AccountMeta::new(*rent_receiver, false),
+    ];
+    accounts.extend(action_accounts);
+    Instruction {
+        program_id: *program_id,
+        accounts,
+        data,
+    }
+}
 
-            8 => Self::ClosePool {},
+/// Creates a `CancelAction` instruction
+pub fn cancel_action(
+    program_id: &Pubkey,
+    pool_owner_wallet: &Pubkey,
+    cwar_pool_storage: &Pubkey,
+    action_id: u64,
+    rent_receiver: &Pubkey,
+) -> Instruction {
+    let (pending_action, _bump_seed) =
+        get_pending_action_address_and_bump_seed(cwar_pool_storage, action_id, program_id);
+    let data = CryowarInstruction::CancelAction {}.pack();
+    let accounts = vec![
+        AccountMeta::new_readonly(*pool_owner_wallet, true),
+        AccountMeta::new_readonly(*cwar_pool_storage, false),
+        AccountMeta::new(pending_action, false),
+        AccountMeta::new(*rent_receiver, false),
+    ];
+    Instruction {
+        program_id: *program_id,
+        accounts,
+        data,
+    }
+}
+
+/// Creates a `ReduceRewards` instruction
+#[allow(clippy::too_many_arguments)]
+pub fn reduce_rewards(
+    program_id: &Pubkey,
+    pool_owner_wallet: &Pubkey,
+    cwar_pool_storage: &Pubkey,
+    rewards_vault: &Pubkey,
+    rewards_refund_ata: &Pubkey,
+    has_reward_schedule: bool,
+    amount: u64,
+    new_reward_duration_end: u64,
+) -> Instruction {
+    let (pool_signer, _pool_nonce) =
+        get_pool_signer_address_and_bump_seed(cwar_pool_storage, program_id);
+    let data = CryowarInstruction::ReduceRewards {
+        amount,
+        new_reward_duration_end,
+    }
+    .pack();
+    let mut accounts = vec![
+        AccountMeta::new_readonly(*pool_owner_wallet, true),
+        AccountMeta::new(*cwar_pool_storage, false),
+        AccountMeta::new(*rewards_vault, false),
+        AccountMeta::new(*rewards_refund_ata, false),
+        AccountMeta::new_readonly(pool_signer, false),
+        AccountMeta::new_readonly(spl_token::id(), false),
+    ];
+    accounts.extend(reward_schedule_account_metas(
+        cwar_pool_storage,
+        has_reward_schedule,
+        program_id,
+    ));
+    Instruction {
+        program_id: *program_id,
+        accounts,
+        data,
+    }
+}
+
+/// Creates a `CancelRewards` instruction
+pub fn cancel_rewards(
+    program_id: &Pubkey,
+    pool_owner_wallet: &Pubkey,
+    cwar_pool_storage: &Pubkey,
+    rewards_vault: &Pubkey,
+    rewards_refund_ata: &Pubkey,
+    has_reward_schedule: bool,
+) -> Instruction {
+    let (pool_signer, _pool_nonce) =
+        get_pool_signer_address_and_bump_seed(cwar_pool_storage, program_id);
+    let data = CryowarInstruction::CancelRewards {}.pack();
+    let mut accounts = vec![
+        AccountMeta::new_readonly(*pool_owner_wallet, true),
+        AccountMeta::new(*cwar_pool_storage, false),
+        AccountMeta::new(*rewards_vault, false),
+        AccountMeta::new(*rewards_refund_ata, false),
+        AccountMeta::new_readonly(pool_signer, false),
+        AccountMeta::new_readonly(spl_token::id(), false),
+    ];
+    accounts.extend(reward_schedule_account_metas(
+        cwar_pool_storage,
+        has_reward_schedule,
+        program_id,
+    ));
+    Instruction {
+        program_id: *program_id,
+        accounts,
+        data,
+    }
+}
 
-            9 => Self::CloseUser {},
+/// Creates an `AddRewardStream` instruction
+pub fn add_reward_stream(
+    program_id: &Pubkey,
+    pool_owner_wallet: &Pubkey,
+    cwar_pool_storage: &Pubkey,
+    reward_mint: &Pubkey,
+    reward_vault: &Pubkey,
+) -> Instruction {
+    let data = CryowarInstruction::AddRewardStream {}.pack();
+    let accounts = vec![
+        AccountMeta::new_readonly(*pool_owner_wallet, true),
+        AccountMeta::new(*cwar_pool_storage, false),
+        AccountMeta::new_readonly(*reward_mint, false),
+        AccountMeta::new(*reward_vault, false),
+        AccountMeta::new_readonly(spl_token::id(), false),
+    ];
+    Instruction {
+        program_id: *program_id,
+        accounts,
+        data,
+    }
+}
 
-            _ => return Err(InvalidInstruction.into()),
-        })
+/// Creates a `FundRewardStream` instruction
+#[allow(clippy::too_many_arguments)]
+pub fn fund_reward_stream(
+    program_id: &Pubkey,
+    funder_wallet: &Pubkey,
+    cwar_pool_storage: &Pubkey,
+    reward_vault: &Pubkey,
+    rewards_ata_to_debit: &Pubkey,
+    has_reward_schedule: bool,
+    stream_index: u8,
+    amount: u64,
+    extend_duration: u64,
+) -> Instruction {
+    let data = CryowarInstruction::FundRewardStream {
+        stream_index,
+        amount,
+        extend_duration,
+    }
+    .pack();
+    let mut accounts = vec![
+        AccountMeta::new_readonly(*funder_wallet, true),
+        AccountMeta::new(*cwar_pool_storage, false),
+        AccountMeta::new(*reward_vault, false),
+        AccountMeta::new(*rewards_ata_to_debit, false),
+        AccountMeta::new_readonly(spl_token::id(), false),
+    ];
+    accounts.extend(reward_schedule_account_metas(
+        cwar_pool_storage,
+        has_reward_schedule,
+        program_id,
+    ));
+    Instruction {
+        program_id: *program_id,
+        accounts,
+        data,
     }
+}
 
-    fn unpack_to_u64(input: &[u8]) -> Result<u64, ProgramError> {
-        let out_value = input
-            .get(..8)
-            .and_then(|slice| slice.try_into().ok())
-            .map(u64::from_le_bytes)
-            .ok_or(InvalidInstruction)?;
-        Ok(out_value)
+/// Creates a `CloseRewardStream` instruction
+pub fn close_reward_stream(
+    program_id: &Pubkey,
+    pool_owner_wallet: &Pubkey,
+    cwar_pool_storage: &Pubkey,
+    reward_vault: &Pubkey,
+    rewards_refund_ata: &Pubkey,
+    stream_index: u8,
+) -> Instruction {
+    let (pool_signer, _pool_nonce) =
+        get_pool_signer_address_and_bump_seed(cwar_pool_storage, program_id);
+    let data = CryowarInstruction::CloseRewardStream { stream_index }.pack();
+    let accounts = vec![
+        AccountMeta::new(*pool_owner_wallet, true),
+        AccountMeta::new(*cwar_pool_storage, false),
+        AccountMeta::new(*reward_vault, false),
+        AccountMeta::new(*rewards_refund_ata, false),
+        AccountMeta::new_readonly(pool_signer, false),
+        AccountMeta::new_readonly(spl_token::id(), false),
+    ];
+    Instruction {
+        program_id: *program_id,
+        accounts,
+        data,
     }
+}
+
+/// Creates a `CompoundRewards` instruction
+pub fn compound_rewards(
+    program_id: &Pubkey,
+    user_wallet: &Pubkey,
+    cwar_pool_storage: &Pubkey,
+    staking_vault: &Pubkey,
+    rewards_vault: &Pubkey,
+    has_reward_schedule: bool,
+) -> Instruction {
+    let (user_storage, _nonce) =
+        get_user_storage_address_and_bump_seed(user_wallet, cwar_pool_storage, program_id);
+    let (pool_signer, _pool_nonce) =
+        get_pool_signer_address_and_bump_seed(cwar_pool_storage, program_id);
+    let data = CryowarInstruction::CompoundRewards {}.pack();
+    let mut accounts = vec![
+        AccountMeta::new_readonly(*user_wallet, true),
+        AccountMeta::new(user_storage, false),
+        AccountMeta::new(*cwar_pool_storage, false),
+        AccountMeta::new(*staking_vault, false),
+        AccountMeta::new(*rewards_vault, false),
+        AccountMeta::new_readonly(pool_signer, false),
+        AccountMeta::new_readonly(spl_token::id(), false),
+    ];
+    accounts.extend(reward_schedule_account_metas(
+        cwar_pool_storage,
+        has_reward_schedule,
+        program_id,
+    ));
+    Instruction {
+        program_id: *program_id,
+        accounts,
+        data,
+    }
+}
+
+/// Creates a `RequestUnstake` instruction, `request_id` is the user's `next_unbonding_request_id`
+pub fn request_unstake(
+    program_id: &Pubkey,
+    user_wallet: &Pubkey,
+    cwar_pool_storage: &Pubkey,
+    request_id: u64,
+    has_reward_schedule: bool,
+    amount: u64,
+) -> Instruction {
+    let (user_storage, _nonce) =
+        get_user_storage_address_and_bump_seed(user_wallet, cwar_pool_storage, program_id);
+    let (unbonding_request, _request_nonce) =
+        get_unbonding_request_address_and_bump_seed(&user_storage, request_id, program_id);
+    let data = CryowarInstruction::RequestUnstake { amount }.pack();
+    let mut accounts = vec![
+        AccountMeta::new(*user_wallet, true),
+        AccountMeta::new(user_storage, false),
+        AccountMeta::new(*cwar_pool_storage, false),
+        AccountMeta::new(unbonding_request, false),
+        AccountMeta::new_readonly(system_program::id(), false),
+    ];
+    accounts.extend(reward_schedule_account_metas(
+        cwar_pool_storage,
+        has_reward_schedule,
+        program_id,
+    ));
+    Instruction {
+        program_id: *program_id,
+        accounts,
+        data,
+    }
+}
+
+/// Creates a `WithdrawUnstaked` instruction
+pub fn withdraw_unstaked(
+    program_id: &Pubkey,
+    user_wallet: &Pubkey,
+    cwar_pool_storage: &Pubkey,
+    request_id: u64,
+    staking_vault: &Pubkey,
+    user_cwar_ata: &Pubkey,
+) -> Instruction {
+    let (user_storage, _nonce) =
+        get_user_storage_address_and_bump_seed(user_wallet, cwar_pool_storage, program_id);
+    let (unbonding_request, _request_nonce) =
+        get_unbonding_request_address_and_bump_seed(&user_storage, request_id, program_id);
+    let (pool_signer, _pool_nonce) =
+        get_pool_signer_address_and_bump_seed(cwar_pool_storage, program_id);
+    let data = CryowarInstruction::WithdrawUnstaked {}.pack();
+    let accounts = vec![
+        AccountMeta::new(*user_wallet, true),
+        AccountMeta::new(user_storage, false),
+        AccountMeta::new(*cwar_pool_storage, false),
+        AccountMeta::new(unbonding_request, false),
+        AccountMeta::new(*staking_vault, false),
+        AccountMeta::new(*user_cwar_ata, false),
+        AccountMeta::new_readonly(pool_signer, false),
+        AccountMeta::new_readonly(spl_token::id(), false),
+    ];
+    Instruction {
+        program_id: *program_id,
+        accounts,
+        data,
+    }
+}
+
+/// Creates a `CancelUnstakeRequest` instruction
+pub fn cancel_unstake_request(
+    program_id: &Pubkey,
+    user_wallet: &Pubkey,
+    cwar_pool_storage: &Pubkey,
+    request_id: u64,
+    has_reward_schedule: bool,
+) -> Instruction {
+    let (user_storage, _nonce) =
+        get_user_storage_address_and_bump_seed(user_wallet, cwar_pool_storage, program_id);
+    let (unbonding_request, _request_nonce) =
+        get_unbonding_request_address_and_bump_seed(&user_storage, request_id, program_id);
+    let data = CryowarInstruction::CancelUnstakeRequest {}.pack();
+    let mut accounts = vec![
+        AccountMeta::new(*user_wallet, true),
+        AccountMeta::new(user_storage, false),
+        AccountMeta::new(*cwar_pool_storage, false),
+        AccountMeta::new(unbonding_request, false),
+    ];
+    accounts.extend(reward_schedule_account_metas(
+        cwar_pool_storage,
+        has_reward_schedule,
+        program_id,
+    ));
+    Instruction {
+        program_id: *program_id,
+        accounts,
+        data,
+    }
+}
+
+/// Creates a `SetLockTiers` instruction
+pub fn set_lock_tiers(
+    program_id: &Pubkey,
+    pool_owner_wallet: &Pubkey,
+    cwar_pool_storage: &Pubkey,
+    lock_tiers: [LockTier; MAX_LOCK_TIERS],
+) -> Instruction {
+    let data = CryowarInstruction::SetLockTiers { lock_tiers }.pack();
+    let accounts = vec![
+        AccountMeta::new_readonly(*pool_owner_wallet, true),
+        AccountMeta::new(*cwar_pool_storage, false),
+    ];
+    Instruction {
+        program_id: *program_id,
+        accounts,
+        data,
+    }
+}
+
+/// Creates a `StakeLocked` instruction, `position_id` is the user's `next_lock_position_id`
+#[allow(clippy::too_many_arguments)]
+pub fn stake_locked(
+    program_id: &Pubkey,
+    user_wallet: &Pubkey,
+    cwar_pool_storage: &Pubkey,
+    staking_vault: &Pubkey,
+    user_cwar_ata: &Pubkey,
+    position_id: u64,
+    has_reward_schedule: bool,
+    amount: u64,
+    lock_tier_index: u8,
+) -> Instruction {
+    let (user_storage, _nonce) =
+        get_user_storage_address_and_bump_seed(user_wallet, cwar_pool_storage, program_id);
+    let (lock_position, _position_nonce) =
+        get_lock_position_address_and_bump_seed(&user_storage, position_id, program_id);
+    let data = CryowarInstruction::StakeLocked {
+        amount,
+        lock_tier_index,
+    }
+    .pack();
+    let mut accounts = vec![
+        AccountMeta::new(*user_wallet, true),
+        AccountMeta::new(user_storage, false),
+        AccountMeta::new(*cwar_pool_storage, false),
+        AccountMeta::new(*staking_vault, false),
+        AccountMeta::new(*user_cwar_ata, false),
+        AccountMeta::new(lock_position, false),
+        AccountMeta::new_readonly(spl_token::id(), false),
+        AccountMeta::new_readonly(system_program::id(), false),
+    ];
+    accounts.extend(reward_schedule_account_metas(
+        cwar_pool_storage,
+        has_reward_schedule,
+        program_id,
+    ));
+    Instruction {
+        program_id: *program_id,
+        accounts,
+        data,
+    }
+}
+
+/// Creates an `UnstakeLocked` instruction
+pub fn unstake_locked(
+    program_id: &Pubkey,
+    user_wallet: &Pubkey,
+    cwar_pool_storage: &Pubkey,
+    position_id: u64,
+    staking_vault: &Pubkey,
+    user_cwar_ata: &Pubkey,
+    has_reward_schedule: bool,
+) -> Instruction {
+    let (user_storage, _nonce) =
+        get_user_storage_address_and_bump_seed(user_wallet, cwar_pool_storage, program_id);
+    let (lock_position, _position_nonce) =
+        get_lock_position_address_and_bump_seed(&user_storage, position_id, program_id);
+    let (pool_signer, _pool_nonce) =
+        get_pool_signer_address_and_bump_seed(cwar_pool_storage, program_id);
+    let data = CryowarInstruction::UnstakeLocked {}.pack();
+    let mut accounts = vec![
+        AccountMeta::new(*user_wallet, true),
+        AccountMeta::new(user_storage, false),
+        AccountMeta::new(*cwar_pool_storage, false),
+        AccountMeta::new(lock_position, false),
+        AccountMeta::new(*staking_vault, false),
+        AccountMeta::new(*user_cwar_ata, false),
+        AccountMeta::new_readonly(pool_signer, false),
+        AccountMeta::new_readonly(spl_token::id(), false),
+    ];
+    accounts.extend(reward_schedule_account_metas(
+        cwar_pool_storage,
+        has_reward_schedule,
+        program_id,
+    ));
+    Instruction {
+        program_id: *program_id,
+        accounts,
+        data,
+    }
+}
+
+/// Creates an `ExpireLockPosition` instruction for a lock position of `user_wallet`
+pub fn expire_lock_position(
+    program_id: &Pubkey,
+    user_wallet: &Pubkey,
+    cwar_pool_storage: &Pubkey,
+    position_id: u64,
+    has_reward_schedule: bool,
+) -> Instruction {
+    let (user_storage, _nonce) =
+        get_user_storage_address_and_bump_seed(user_wallet, cwar_pool_storage, program_id);
+    let (lock_position, _position_nonce) =
+        get_lock_position_address_and_bump_seed(&user_storage, position_id, program_id);
+    let data = CryowarInstruction::ExpireLockPosition {}.pack();
+    let mut accounts = vec![
+        AccountMeta::new_readonly(*user_wallet, false),
+        AccountMeta::new(user_storage, false),
+        AccountMeta::new(*cwar_pool_storage, false),
+        AccountMeta::new(lock_position, false),
+    ];
+    accounts.extend(reward_schedule_account_metas(
+        cwar_pool_storage,
+        has_reward_schedule,
+        program_id,
+    ));
+    Instruction {
+        program_id: *program_id,
+        accounts,
+        data,
+    }
+}
+
+/// Creates a `SetPenalitySchedule` instruction
+pub fn set_penality_schedule(
+    program_id: &Pubkey,
+    pool_owner_wallet: &Pubkey,
+    cwar_pool_storage: &Pubkey,
+    penality_schedule: PenalitySchedule,
+) -> Instruction {
+    let data = CryowarInstruction::SetPenalitySchedule { penality_schedule }.pack();
+    let accounts = vec![
+        AccountMeta::new_readonly(*pool_owner_wallet, true),
+        AccountMeta::new(*cwar_pool_storage, false),
+    ];
+    Instruction {
+        program_id: *program_id,
+        accounts,
+        data,
+    }
+}
+
+/// Creates a `SetPenalityRouting` instruction
+pub fn set_penality_routing(
+    program_id: &Pubkey,
+    pool_owner_wallet: &Pubkey,
+    cwar_pool_storage: &Pubkey,
+    penality_routing: PenalityRouting,
+) -> Instruction {
+    let data = CryowarInstruction::SetPenalityRouting { penality_routing }.pack();
+    let accounts = vec![
+        AccountMeta::new_readonly(*pool_owner_wallet, true),
+        AccountMeta::new(*cwar_pool_storage, false),
+    ];
+    Instruction {
+        program_id: *program_id,
+        accounts,
+        data,
+    }
+}
+
+/// Creates a `SetStakeLimits` instruction
+pub fn set_stake_limits(
+    program_id: &Pubkey,
+    pool_owner_wallet: &Pubkey,
+    cwar_pool_storage: &Pubkey,
+    min_stake: u64,
+    max_stake_per_user: u64,
+    max_total_staked: u64,
+) -> Instruction {
+    let data = CryowarInstruction::SetStakeLimits {
+        min_stake,
+        max_stake_per_user,
+        max_total_staked,
+    }
+    .pack();
+    let accounts = vec![
+        AccountMeta::new_readonly(*pool_owner_wallet, true),
+        AccountMeta::new(*cwar_pool_storage, false),
+    ];
+    Instruction {
+        program_id: *program_id,
+        accounts,
+        data,
+    }
+}
+
+/// Creates a `SetAllowlistRoot` instruction
+pub fn set_allowlist_root(
+    program_id: &Pubkey,
+    pool_owner_wallet: &Pubkey,
+    cwar_pool_storage: &Pubkey,
+    allowlist_root: [u8; 32],
+) -> Instruction {
+    let data = CryowarInstruction::SetAllowlistRoot { allowlist_root }.pack();
+    let accounts = vec![
+        AccountMeta::new_readonly(*pool_owner_wallet, true),
+        AccountMeta::new(*cwar_pool_storage, false),
+    ];
+    Instruction {
+        program_id: *program_id,
+        accounts,
+        data,
+    }
+}
+
+/// Creates a `CreateAllowlistedUser` instruction, `allowlist_proof` comes from
+/// `AllowlistTree::proof`
+pub fn create_allowlisted_user(
+    program_id: &Pubkey,
+    user_wallet: &Pubkey,
+    cwar_pool_storage: &Pubkey,
+    allowlist_max_stake: u64,
+    allowlist_proof: Vec<[u8; 32]>,
+) -> Instruction {
+    let (user_storage, nonce) =
+        get_user_storage_address_and_bump_seed(user_wallet, cwar_pool_storage, program_id);
+    let data = CryowarInstruction::CreateAllowlistedUser {
+        nonce,
+        allowlist_max_stake,
+        allowlist_proof,
+    }
+    .pack();
+    let accounts = vec![
+        AccountMeta::new(*user_wallet, true),
+        AccountMeta::new(user_storage, false),
+        AccountMeta::new(*cwar_pool_storage, false),
+        AccountMeta::new_readonly(system_program::id(), false),
+    ];
+    Instruction {
+        program_id: *program_id,
+        accounts,
+        data,
+    }
+}
+
+/// Creates an `Exit` instruction, `receipt_mint` is needed in receipt pools and
+/// `reward_stream_accounts` holds the reward stream vault and user ATA of every extra stream the
+/// user has rewards pending in
+#[allow(clippy::too_many_arguments)]
+pub fn exit(
+    program_id: &Pubkey,
+    user_wallet: &Pubkey,
+    cwar_pool_storage: &Pubkey,
+    staking_vault: &Pubkey,
+    staking_mint: &Pubkey,
+    rewards_vault: &Pubkey,
+    reward_mint: &Pubkey,
+    authority_penality_deposit_ata: &Pubkey,
+    has_reward_schedule: bool,
+    receipt_mint: Option<&Pubkey>,
+    reward_stream_accounts: &[(Pubkey, Pubkey)],
+) -> Instruction {
+    let (user_storage, _nonce) =
+        get_user_storage_address_and_bump_seed(user_wallet, cwar_pool_storage, program_id);
+    let (pool_signer, _pool_nonce) =
+        get_pool_signer_address_and_bump_seed(cwar_pool_storage, program_id);
+    let data = CryowarInstruction::Exit {}.pack();
+    let mut accounts = vec![
+        AccountMeta::new(*user_wallet, true),
+        AccountMeta::new(user_storage, false),
+        AccountMeta::new(*cwar_pool_storage, false),
+        AccountMeta::new(*staking_vault, false),
+        AccountMeta::new(*rewards_vault, false),
+        AccountMeta::new(
+            get_associated_token_address(user_wallet, staking_mint),
+            false,
+        ),
+        AccountMeta::new(
+            get_associated_token_address(user_wallet, reward_mint),
+            false,
+        ),
+        AccountMeta::new_readonly(pool_signer, false),
+        AccountMeta::new_readonly(spl_token::id(), false),
+        AccountMeta::new(*authority_penality_deposit_ata, false),
+        AccountMeta::new(*staking_mint, false),
+        AccountMeta::new_readonly(*reward_mint, false),
+        AccountMeta::new_readonly(system_program::id(), false),
+        AccountMeta::new_readonly(spl_associated_token_account::id(), false),
+    ];
+    accounts.extend(reward_schedule_account_metas(
+        cwar_pool_storage,
+        has_reward_schedule,
+        program_id,
+    ));
+    if let Some(receipt_mint) = receipt_mint {
+        accounts.extend(receipt_account_metas(user_wallet, receipt_mint));
+    }
+    for (reward_stream_vault, user_reward_stream_ata) in reward_stream_accounts {
+        accounts.push(AccountMeta::new(*reward_stream_vault, false));
+        accounts.push(AccountMeta::new(*user_reward_stream_ata, false));
+    }
+    Instruction {
+        program_id: *program_id,
+        accounts,
+        data,
+    }
+}
+
+/// Creates a `ClaimRewardsMulti` instruction, `pools` holds the pool storage, staking vault,
+/// reward vault and reward mint of every pool to claim from and whether it has a reward schedule
+pub fn claim_rewards_multi(
+    program_id: &Pubkey,
+    user_wallet: &Pubkey,
+    pools: &[(Pubkey, Pubkey, Pubkey, Pubkey, bool)],
+) -> Instruction {
+    let data = CryowarInstruction::ClaimRewardsMulti {}.pack();
+    let mut accounts = vec![
+        AccountMeta::new(*user_wallet, true),
+        AccountMeta::new_readonly(spl_token::id(), false),
+        AccountMeta::new_readonly(system_program::id(), false),
+        AccountMeta::new_readonly(spl_associated_token_account::id(), false),
+    ];
+    for (cwar_pool_storage, staking_vault, rewards_vault, reward_mint, has_reward_schedule) in pools
+    {
+        let (user_storage, _nonce) =
+            get_user_storage_address_and_bump_seed(user_wallet, cwar_pool_storage, program_id);
+        let (pool_signer, _pool_nonce) =
+            get_pool_signer_address_and_bump_seed(cwar_pool_storage, program_id);
+        accounts.push(AccountMeta::new(user_storage, false));
+        accounts.push(AccountMeta::new(*cwar_pool_storage, false));
+        accounts.push(AccountMeta::new(*staking_vault, false));
+        accounts.push(AccountMeta::new(*rewards_vault, false));
+        accounts.push(AccountMeta::new(
+            get_associated_token_address(user_wallet, reward_mint),
+            false,
+        ));
+        accounts.push(AccountMeta::new_readonly(pool_signer, false));
+        accounts.push(AccountMeta::new_readonly(*reward_mint, false));
+        accounts.extend(reward_schedule_account_metas(
+            cwar_pool_storage,
+            *has_reward_schedule,
+            program_id,
+        ));
+    }
+    Instruction {
+        program_id: *program_id,
+        accounts,
+        data,
+    }
+}
+
+/// Creates a `TransferPosition` instruction, `lock_position_ids` has to list every open lock
+/// position of the user, they are renumbered from the recipient's `next_lock_position_id`
+pub fn transfer_position(
+    program_id: &Pubkey,
+    user_wallet: &Pubkey,
+    cwar_pool_storage: &Pubkey,
+    recipient_wallet: &Pubkey,
+    lock_position_ids: &[u64],
+    recipient_next_lock_position_id: u64,
+    has_reward_schedule: bool,
+) -> Instruction {
+    let (user_storage, _nonce) =
+        get_user_storage_address_and_bump_seed(user_wallet, cwar_pool_storage, program_id);
+    let (recipient_storage, _recipient_nonce) =
+        get_user_storage_address_and_bump_seed(recipient_wallet, cwar_pool_storage, program_id);
+    let data = CryowarInstruction::TransferPosition {}.pack();
+    let mut accounts = vec![
+        AccountMeta::new(*user_wallet, true),
+        AccountMeta::new(user_storage, false),
+        AccountMeta::new(*cwar_pool_storage, false),
+        AccountMeta::new_readonly(*recipient_wallet, false),
+        AccountMeta::new(recipient_storage, false),
+        AccountMeta::new_readonly(system_program::id(), false),
+    ];
+    accounts.extend(reward_schedule_account_metas(
+        cwar_pool_storage,
+        has_reward_schedule,
+        program_id,
+    ));
+    for (recipient_position_id, position_id) in
+        (recipient_next_lock_position_id..).zip(lock_position_ids.iter())
+    {
+        let (lock_position, _bump_seed) =
+            get_lock_position_address_and_bump_seed(&user_storage, *position_id, program_id);
+        let (recipient_lock_position, _recipient_bump_seed) =
+            get_lock_position_address_and_bump_seed(
+                &recipient_storage,
+                recipient_position_id,
+                program_id,
+            );
+        accounts.push(AccountMeta::new(lock_position, false));
+        accounts.push(AccountMeta::new(recipient_lock_position, false));
+    }
+    Instruction {
+        program_id: *program_id,
+        accounts,
+        data,
+    }
+}
+
+/// Creates a `SetReceiptMint` instruction
+pub fn set_receipt_mint(
+    program_id: &Pubkey,
+    pool_owner_wallet: &Pubkey,
+    cwar_pool_storage: &Pubkey,
+    receipt_mint: &Pubkey,
+    staking_mint: &Pubkey,
+) -> Instruction {
+    let data = CryowarInstruction::SetReceiptMint {
+        receipt_mint: *receipt_mint,
+    }
+    .pack();
+    let accounts = vec![
+        AccountMeta::new_readonly(*pool_owner_wallet, true),
+        AccountMeta::new(*cwar_pool_storage, false),
+        AccountMeta::new_readonly(*receipt_mint, false),
+        AccountMeta::new_readonly(*staking_mint, false),
+    ];
+    Instruction {
+        program_id: *program_id,
+        accounts,
+        data,
+    }
+}
+
+/// Creates a `SettleReceipt` instruction moving stake from `source_wallet` to `holder_wallet`,
+/// `payer` signs it and pays for the holder user storage if it has to be created
+pub fn settle_receipt(
+    program_id: &Pubkey,
+    payer: &Pubkey,
+    holder_wallet: &Pubkey,
+    cwar_pool_storage: &Pubkey,
+    source_wallet: &Pubkey,
+    receipt_mint: &Pubkey,
+    has_reward_schedule: bool,
+) -> Instruction {
+    let (holder_storage, _holder_nonce) =
+        get_user_storage_address_and_bump_seed(holder_wallet, cwar_pool_storage, program_id);
+    let (source_storage, _source_nonce) =
+        get_user_storage_address_and_bump_seed(source_wallet, cwar_pool_storage, program_id);
+    let data = CryowarInstruction::SettleReceipt {}.pack();
+    let holder_pays = payer == holder_wallet;
+    let mut accounts = vec![
+        AccountMeta::new(*holder_wallet, holder_pays),
+        AccountMeta::new(holder_storage, false),
+        AccountMeta::new(*cwar_pool_storage, false),
+        AccountMeta::new_readonly(
+            get_associated_token_address(holder_wallet, receipt_mint),
+            false,
+        ),
+        AccountMeta::new_readonly(*source_wallet, false),
+        AccountMeta::new(source_storage, false),
+        AccountMeta::new_readonly(
+            get_associated_token_address(source_wallet, receipt_mint),
+            false,
+        ),
+        AccountMeta::new_readonly(system_program::id(), false),
+    ];
+    accounts.extend(reward_schedule_account_metas(
+        cwar_pool_storage,
+        has_reward_schedule,
+        program_id,
+    ));
+    if !holder_pays {
+        accounts.push(AccountMeta::new(*payer, true));
+    }
+    Instruction {
+        program_id: *program_id,
+        accounts,
+        data,
+    }
+}
+
+/// Creates an `AddRewardPhase` instruction
+#[allow(clippy::too_many_arguments)]
+pub fn add_reward_phase(
+    program_id: &Pubkey,
+    funder_wallet: &Pubkey,
+    cwar_pool_storage: &Pubkey,
+    rewards_vault: &Pubkey,
+    funder_rewards_ata: &Pubkey,
+    start: u64,
+    end: u64,
+    reward_rate: u64,
+) -> Instruction {
+    let (reward_schedule, _bump_seed) =
+        get_reward_schedule_address_and_bump_seed(cwar_pool_storage, program_id);
+    let data = CryowarInstruction::AddRewardPhase {
+        start,
+        end,
+        reward_rate,
+    }
+    .pack();
+    let accounts = vec![
+        AccountMeta::new(*funder_wallet, true),
+        AccountMeta::new(*cwar_pool_storage, false),
+        AccountMeta::new(reward_schedule, false),
+        AccountMeta::new(*rewards_vault, false),
+        AccountMeta::new(*funder_rewards_ata, false),
+        AccountMeta::new_readonly(spl_token::id(), false),
+        AccountMeta::new_readonly(system_program::id(), false),
+    ];
+    Instruction {
+        program_id: *program_id,
+        accounts,
+        data,
+    }
+}
+
+// receipt mint and the user's receipt ATA, appended by the instructions that mint or burn receipts
+fn receipt_account_metas(user_wallet: &Pubkey, receipt_mint: &Pubkey) -> Vec<AccountMeta> {
+    vec![
+        AccountMeta::new(*receipt_mint, false),
+        AccountMeta::new(
+            get_associated_token_address(user_wallet, receipt_mint),
+            false,
+        ),
+    ]
+}
+
+// recipient accounts of `UnstakeCwar` and `ClaimRewards`, paying `mint` out to the ATA of
+// `recipient_wallet` and creating it when missing
+fn recipient_account_metas(recipient_wallet: &Pubkey, mint: &Pubkey) -> Vec<AccountMeta> {
+    vec![
+        AccountMeta::new_readonly(spl_associated_token_account::id(), false),
+        AccountMeta::new_readonly(system_program::id(), false),
+        AccountMeta::new_readonly(*recipient_wallet, false),
+        AccountMeta::new_readonly(*mint, false),
+    ]
+}
 
-    fn unpack_to_u16(input: &[u8]) -> Result<u16, ProgramError> {
-        let out_value = input
-            .get(..2)
-            .and_then(|slice| slice.try_into().ok())
-            .map(u16::from_le_bytes)
-            .ok_or(InvalidInstruction)?;
-        Ok(out_value)
+// the pool's `RewardSchedule`, passed right after the fixed accounts by pools that have one
+fn reward_schedule_account_metas(
+    cwar_pool_storage: &Pubkey,
+    has_reward_schedule: bool,
+    program_id: &Pubkey,
+) -> Vec<AccountMeta> {
+    if !has_reward_schedule {
+        return vec![];
     }
+    let (reward_schedule, _bump_seed) =
+        get_reward_schedule_address_and_bump_seed(cwar_pool_storage, program_id);
+    vec![AccountMeta::new_readonly(reward_schedule, false)]
 }
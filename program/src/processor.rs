@@ -1,23 +1,81 @@
 use crate::instruction::CryowarInstruction;
 
 use {
-    add_funder::process_add_funder, claim_rewards::process_claim_rewards,
-    close_pool::process_close_pool, close_user::process_close_user,
-    create_user::process_create_user, fund_pool::process_fund_pool,
-    initialize_cwar_pool::process_initialize_cwar_pool, remove_funder::process_remove_funder,
-    stake_cwar::process_stake_cwar, unstake_cwar::process_unstake_cwar,
+    accept_ownership::process_accept_ownership, add_funder::process_add_funder,
+    add_reward_phase::process_add_reward_phase, add_reward_stream::process_add_reward_stream,
+    cancel_action::process_cancel_action,
+    cancel_ownership_transfer::process_cancel_ownership_transfer,
+    cancel_rewards::process_cancel_rewards, cancel_unstake_request::process_cancel_unstake_request,
+    claim_rewards::process_claim_rewards, claim_rewards_multi::process_claim_rewards_multi,
+    close_pool::process_close_pool, close_reward_stream::process_close_reward_stream,
+    close_user::process_close_user, compound_rewards::process_compound_rewards,
+    create_user::process_create_user, emergency_unstake::process_emergency_unstake,
+    execute_action::process_execute_action, exit::process_exit,
+    expire_lock_position::process_expire_lock_position, fund_pool::process_fund_pool,
+    fund_reward_stream::process_fund_reward_stream,
+    initialize_cwar_pool::process_initialize_cwar_pool, migrate_pool::process_migrate_pool,
+    migrate_user::process_migrate_user, propose_new_owner::process_propose_new_owner,
+    queue_action::process_queue_action, recover_surplus::process_recover_surplus,
+    reduce_rewards::process_reduce_rewards, remove_funder::process_remove_funder,
+    request_unstake::process_request_unstake, set_allowlist_root::process_set_allowlist_root,
+    set_lock_tiers::process_set_lock_tiers, set_paused_operations::process_set_paused_operations,
+    set_pauser::process_set_pauser, set_penality_routing::process_set_penality_routing,
+    set_penality_schedule::process_set_penality_schedule,
+    set_receipt_mint::process_set_receipt_mint, set_stake_limits::process_set_stake_limits,
+    set_timelock_delay::process_set_timelock_delay, settle_receipt::process_settle_receipt,
+    stake_cwar::process_stake_cwar, stake_locked::process_stake_locked,
+    transfer_position::process_transfer_position, unstake_cwar::process_unstake_cwar,
+    unstake_locked::process_unstake_locked, update_pool_config::process_update_pool_config,
+    withdraw_unstaked::process_withdraw_unstaked,
 };
 
+pub mod accept_ownership;
 pub mod add_funder;
+pub mod add_reward_phase;
+pub mod add_reward_stream;
+pub mod cancel_action;
+pub mod cancel_ownership_transfer;
+pub mod cancel_rewards;
+pub mod cancel_unstake_request;
 pub mod claim_rewards;
+pub mod claim_rewards_multi;
 pub mod close_pool;
+pub mod close_reward_stream;
 pub mod close_user;
+pub mod compound_rewards;
 pub mod create_user;
+pub mod emergency_unstake;
+pub mod execute_action;
+pub mod exit;
+pub mod expire_lock_position;
 pub mod fund_pool;
+pub mod fund_reward_stream;
 pub mod initialize_cwar_pool;
+pub mod migrate_pool;
+pub mod migrate_user;
+pub mod propose_new_owner;
+pub mod queue_action;
+pub mod recover_surplus;
+pub mod reduce_rewards;
 pub mod remove_funder;
+pub mod request_unstake;
+pub mod set_allowlist_root;
+pub mod set_lock_tiers;
+pub mod set_paused_operations;
+pub mod set_pauser;
+pub mod set_penality_routing;
+pub mod set_penality_schedule;
+pub mod set_receipt_mint;
+pub mod set_stake_limits;
+pub mod set_timelock_delay;
+pub mod settle_receipt;
 pub mod stake_cwar;
+pub mod stake_locked;
+pub mod transfer_position;
 pub mod unstake_cwar;
+pub mod unstake_locked;
+pub mod update_pool_config;
+pub mod withdraw_unstaked;
 use solana_program::{account_info::AccountInfo, entrypoint::ProgramResult, msg, pubkey::Pubkey};
 
 pub struct Processor;
@@ -47,7 +105,7 @@ impl Processor {
             }
             CryowarInstruction::CreateUser { nonce } => {
                 msg!("CryowarInstruction::CreateUser");
-                process_create_user(accounts, nonce, program_id)
+                process_create_user(accounts, nonce, 0u64, &[], program_id)
             }
 
             CryowarInstruction::StakeCwar { amount_to_deposit } => {
@@ -92,6 +150,247 @@ impl Processor {
                 msg!("CryowarInstruction::CloseUser");
                 process_close_user(accounts, program_id)
             }
+
+            CryowarInstruction::MigratePool {} => {
+                msg!("CryowarInstruction::MigratePool");
+                process_migrate_pool(accounts, program_id)
+            }
+
+            CryowarInstruction::RecoverSurplus {} => {
+                msg!("CryowarInstruction::RecoverSurplus");
+                process_recover_surplus(accounts, program_id)
+            }
+
+            CryowarInstruction::MigrateUser {} => {
+                msg!("CryowarInstruction::MigrateUser");
+                process_migrate_user(accounts, program_id)
+            }
+
+            CryowarInstruction::ProposeNewOwner {} => {
+                msg!("CryowarInstruction::ProposeNewOwner");
+                process_propose_new_owner(accounts, program_id)
+            }
+
+            CryowarInstruction::AcceptOwnership {} => {
+                msg!("CryowarInstruction::AcceptOwnership");
+                process_accept_ownership(accounts, program_id)
+            }
+
+            CryowarInstruction::CancelOwnershipTransfer {} => {
+                msg!("CryowarInstruction::CancelOwnershipTransfer");
+                process_cancel_ownership_transfer(accounts, program_id)
+            }
+
+            CryowarInstruction::SetPauser {} => {
+                msg!("CryowarInstruction::SetPauser");
+                process_set_pauser(accounts, program_id)
+            }
+
+            CryowarInstruction::SetPausedOperations { paused_operations } => {
+                msg!("CryowarInstruction::SetPausedOperations");
+                process_set_paused_operations(accounts, paused_operations, program_id)
+            }
+
+            CryowarInstruction::EmergencyUnstake {} => {
+                msg!("CryowarInstruction::EmergencyUnstake");
+                process_emergency_unstake(accounts, program_id)
+            }
+
+            CryowarInstruction::UpdatePoolConfig {
+                unstake_penality_basis_points,
+                locking_duration,
+                authority_penality_deposit_ata,
+                unbonding_duration,
+            } => {
+                msg!("CryowarInstruction::UpdatePoolConfig");
+                process_update_pool_config(
+                    accounts,
+                    unstake_penality_basis_points,
+                    locking_duration,
+                    authority_penality_deposit_ata,
+                    unbonding_duration,
+                    program_id,
+                )
+            }
+
+            CryowarInstruction::SetTimelockDelay { timelock_delay } => {
+                msg!("CryowarInstruction::SetTimelockDelay");
+                process_set_timelock_delay(accounts, timelock_delay, program_id)
+            }
+
+            CryowarInstruction::QueueAction { action } => {
+                msg!("CryowarInstruction::QueueAction");
+                process_queue_action(accounts, action, program_id)
+            }
+
+            CryowarInstruction::ExecuteAction {} => {
+                msg!("CryowarInstruction::ExecuteAction");
+                process_execute_action(accounts, program_id)
+            }
+
+            CryowarInstruction::CancelAction {} => {
+                msg!("CryowarInstruction::CancelAction");
+                process_cancel_action(accounts, program_id)
+            }
+
+            CryowarInstruction::ReduceRewards {
+                amount,
+                new_reward_duration_end,
+            } => {
+                msg!("CryowarInstruction::ReduceRewards");
+                process_reduce_rewards(accounts, amount, new_reward_duration_end, program_id)
+            }
+
+            CryowarInstruction::CancelRewards {} => {
+                msg!("CryowarInstruction::CancelRewards");
+                process_cancel_rewards(accounts, program_id)
+            }
+
+            CryowarInstruction::AddRewardStream {} => {
+                msg!("CryowarInstruction::AddRewardStream");
+                process_add_reward_stream(accounts, program_id)
+            }
+
+            CryowarInstruction::FundRewardStream {
+                stream_index,
+                amount,
+                extend_duration,
+            } => {
+                msg!("CryowarInstruction::FundRewardStream");
+                process_fund_reward_stream(
+                    accounts,
+                    stream_index,
+                    amount,
+                    extend_duration,
+                    program_id,
+                )
+            }
+
+            CryowarInstruction::CloseRewardStream { stream_index } => {
+                msg!("CryowarInstruction::CloseRewardStream");
+                process_close_reward_stream(accounts, stream_index, program_id)
+            }
+
+            CryowarInstruction::CompoundRewards {} => {
+                msg!("CryowarInstruction::CompoundRewards");
+                process_compound_rewards(accounts, program_id)
+            }
+
+            CryowarInstruction::RequestUnstake { amount } => {
+                msg!("CryowarInstruction::RequestUnstake");
+                process_request_unstake(accounts, amount, program_id)
+            }
+
+            CryowarInstruction::WithdrawUnstaked {} => {
+                msg!("CryowarInstruction::WithdrawUnstaked");
+                process_withdraw_unstaked(accounts, program_id)
+            }
+
+            CryowarInstruction::CancelUnstakeRequest {} => {
+                msg!("CryowarInstruction::CancelUnstakeRequest");
+                process_cancel_unstake_request(accounts, program_id)
+            }
+
+            CryowarInstruction::SetLockTiers { lock_tiers } => {
+                msg!("CryowarInstruction::SetLockTiers");
+                process_set_lock_tiers(accounts, lock_tiers, program_id)
+            }
+
+            CryowarInstruction::StakeLocked {
+                amount,
+                lock_tier_index,
+            } => {
+                msg!("CryowarInstruction::StakeLocked");
+                process_stake_locked(accounts, amount, lock_tier_index, program_id)
+            }
+
+            CryowarInstruction::UnstakeLocked {} => {
+                msg!("CryowarInstruction::UnstakeLocked");
+                process_unstake_locked(accounts, program_id)
+            }
+
+            CryowarInstruction::SetPenalitySchedule { penality_schedule } => {
+                msg!("CryowarInstruction::SetPenalitySchedule");
+                process_set_penality_schedule(accounts, penality_schedule, program_id)
+            }
+
+            CryowarInstruction::SetPenalityRouting { penality_routing } => {
+                msg!("CryowarInstruction::SetPenalityRouting");
+                process_set_penality_routing(accounts, penality_routing, program_id)
+            }
+
+            CryowarInstruction::SetStakeLimits {
+                min_stake,
+                max_stake_per_user,
+                max_total_staked,
+            } => {
+                msg!("CryowarInstruction::SetStakeLimits");
+                process_set_stake_limits(
+                    accounts,
+                    min_stake,
+                    max_stake_per_user,
+                    max_total_staked,
+                    program_id,
+                )
+            }
+
+            CryowarInstruction::SetAllowlistRoot { allowlist_root } => {
+                msg!("CryowarInstruction::SetAllowlistRoot");
+                process_set_allowlist_root(accounts, allowlist_root, program_id)
+            }
+
+            CryowarInstruction::CreateAllowlistedUser {
+                nonce,
+                allowlist_max_stake,
+                allowlist_proof,
+            } => {
+                msg!("CryowarInstruction::CreateAllowlistedUser");
+                process_create_user(
+                    accounts,
+                    nonce,
+                    allowlist_max_stake,
+                    &allowlist_proof,
+                    program_id,
+                )
+            }
+
+            CryowarInstruction::Exit {} => {
+                msg!("CryowarInstruction::Exit");
+                process_exit(accounts, program_id)
+            }
+
+            CryowarInstruction::ClaimRewardsMulti {} => {
+                msg!("CryowarInstruction::ClaimRewardsMulti");
+                process_claim_rewards_multi(accounts, program_id)
+            }
+
+            CryowarInstruction::TransferPosition {} => {
+                msg!("CryowarInstruction::TransferPosition");
+                process_transfer_position(accounts, program_id)
+            }
+
+            CryowarInstruction::SetReceiptMint { receipt_mint } => {
+                msg!("CryowarInstruction::SetReceiptMint");
+                process_set_receipt_mint(accounts, receipt_mint, program_id)
+            }
+
+            CryowarInstruction::SettleReceipt {} => {
+                msg!("CryowarInstruction::SettleReceipt");
+                process_settle_receipt(accounts, program_id)
+            }
+            CryowarInstruction::AddRewardPhase {
+                start,
+                end,
+                reward_rate,
+            } => {
+                msg!("CryowarInstruction::AddRewardPhase");
+                process_add_reward_phase(accounts, start, end, reward_rate, program_id)
+            }
+
+            CryowarInstruction::ExpireLockPosition {} => {
+                msg!("CryowarInstruction::ExpireLockPosition");
+                process_expire_lock_position(accounts, program_id)
+            }
         }
     }
 }
@@ -0,0 +1,64 @@
+use crate::{
+    error::CryowarError,
+    utils::{
+        assert_penality_deposit_ata_data, assert_pool_storage_account, assert_signer,
+        save_pool_storage_account,
+    },
+};
+use solana_program::{
+    account_info::{next_account_info, AccountInfo},
+    entrypoint::ProgramResult,
+    msg,
+    pubkey::Pubkey,
+};
+
+/// The new owner brings their own penalty deposit account, the previous owner's would otherwise
+/// keep receiving the treasury share of every penalty
+pub fn process_accept_ownership(accounts: &[AccountInfo], program_id: &Pubkey) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+    let pending_owner_wallet_account = next_account_info(account_info_iter)?;
+    let cwar_pool_storage_account = next_account_info(account_info_iter)?;
+    let authority_penality_deposit_ata_account = next_account_info(account_info_iter)?;
+
+    assert_signer(pending_owner_wallet_account)?;
+
+    let mut cwar_pool_data_byte_array = cwar_pool_storage_account.data.try_borrow_mut().unwrap();
+    let mut cwar_pool_data = assert_pool_storage_account(
+        &cwar_pool_data_byte_array,
+        cwar_pool_storage_account,
+        program_id,
+    )?;
+
+    let default_pubkey = Pubkey::default();
+    if cwar_pool_data.pending_owner == default_pubkey {
+        msg!("CryowarError::NoPendingOwnershipTransfer");
+        return Err(CryowarError::NoPendingOwnershipTransfer.into());
+    }
+    if cwar_pool_data.pending_owner != *pending_owner_wallet_account.key {
+        msg!("CryowarError::PendingOwnerMismatched");
+        return Err(CryowarError::PendingOwnerMismatched.into());
+    }
+    assert_penality_deposit_ata_data(
+        authority_penality_deposit_ata_account,
+        &cwar_pool_data.staking_mint,
+        pending_owner_wallet_account.key,
+    )?;
+
+    msg!(
+        "Transferring pool ownership from {} to {}",
+        cwar_pool_data.owner_wallet,
+        cwar_pool_data.pending_owner
+    );
+    msg!(
+        "authority_penality_deposit_ata: {} -> {}",
+        cwar_pool_data.authority_penality_deposit_ata,
+        authority_penality_deposit_ata_account.key
+    );
+    cwar_pool_data.owner_wallet = cwar_pool_data.pending_owner;
+    cwar_pool_data.pending_owner = default_pubkey;
+    cwar_pool_data.authority_penality_deposit_ata = *authority_penality_deposit_ata_account.key;
+
+    save_pool_storage_account(&mut cwar_pool_data_byte_array, &cwar_pool_data)?;
+
+    Ok(())
+}
@@ -0,0 +1,196 @@
+use std::convert::TryInto;
+
+use crate::{
+    error::CryowarError,
+    processor::create_user::create_and_allocate_account_raw,
+    state::{
+        AccTypesWithVersion, CwarPool, RewardPhase, RewardSchedule, MAX_REWARD_PHASES, PAUSE_FUND,
+        REWARD_SCHEDULE_STORAGE_TOTAL_BYTES,
+    },
+    utils::{
+        self, assert_not_paused, assert_pool_storage_account, assert_reward_schedule_account,
+        assert_reward_vault, assert_signer, assert_system_program, assert_token_program,
+        get_pool_signer_address_and_bump_seed, get_reward_schedule_address_and_bump_seed,
+        save_pool_storage_account, save_reward_schedule_account,
+    },
+};
+use solana_program::{
+    account_info::{next_account_info, AccountInfo},
+    clock::Clock,
+    entrypoint::ProgramResult,
+    msg,
+    program::invoke,
+    program_error::ProgramError,
+    program_pack::Pack,
+    pubkey::Pubkey,
+    sysvar::Sysvar,
+};
+use spl_token::state::Account as TokenAccount;
+
+/// The first phase creates the pool reward schedule account, paid for by the funder
+pub fn process_add_reward_phase(
+    accounts: &[AccountInfo],
+    start: u64,
+    end: u64,
+    reward_rate: u64,
+    program_id: &Pubkey,
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+    let funder_wallet_account = next_account_info(account_info_iter)?;
+    let cwar_pool_storage_account = next_account_info(account_info_iter)?;
+    let reward_schedule_account = next_account_info(account_info_iter)?;
+    let cwar_rewards_vault = next_account_info(account_info_iter)?;
+    let cwar_rewards_ata_to_debit = next_account_info(account_info_iter)?;
+    let token_program = next_account_info(account_info_iter)?;
+    let system_program_info = next_account_info(account_info_iter)?;
+
+    assert_signer(funder_wallet_account)?;
+    assert_token_program(token_program)?;
+    assert_system_program(system_program_info)?;
+    let mut cwar_pool_data_byte_array = cwar_pool_storage_account.data.try_borrow_mut().unwrap();
+    let mut cwar_pool_data = assert_pool_storage_account(
+        &cwar_pool_data_byte_array,
+        cwar_pool_storage_account,
+        program_id,
+    )?;
+    assert_not_paused(&cwar_pool_data, PAUSE_FUND)?;
+
+    if *funder_wallet_account.key != cwar_pool_data.owner_wallet
+        && !cwar_pool_data
+            .funders
+            .iter()
+            .any(|x| *x == *funder_wallet_account.key)
+    {
+        msg!("CryowarError::FundingAuthorityMismatched");
+        return Err(CryowarError::FundingAuthorityMismatched.into());
+    }
+
+    let (pool_signer_address, _bump_seed) =
+        get_pool_signer_address_and_bump_seed(cwar_pool_storage_account.key, program_id);
+    let cwar_rewards_vault_data = TokenAccount::unpack(&cwar_rewards_vault.data.borrow())?;
+    assert_reward_vault(
+        cwar_rewards_vault,
+        &cwar_pool_data,
+        &cwar_rewards_vault_data,
+        &pool_signer_address,
+    )?;
+
+    let (reward_schedule_address, bump_seed) =
+        get_reward_schedule_address_and_bump_seed(cwar_pool_storage_account.key, program_id);
+    if reward_schedule_address != *reward_schedule_account.key {
+        msg!("Error: Reward Schedule address does not match seed derivation");
+        return Err(ProgramError::InvalidSeeds);
+    }
+
+    let now: u64 = Clock::get()?.unix_timestamp.try_into().unwrap();
+    let mut reward_schedule_data = if cwar_pool_data.has_reward_schedule() {
+        let reward_schedule_data = assert_reward_schedule_account(
+            reward_schedule_account,
+            &cwar_pool_data,
+            program_id,
+            &reward_schedule_account.data.borrow(),
+        )?;
+        utils::update_rewards(&mut cwar_pool_data, Some(&reward_schedule_data), None, now)?;
+        reward_schedule_data
+    } else {
+        utils::update_rewards(&mut cwar_pool_data, None, None, now)?;
+        create_and_allocate_account_raw(
+            *program_id,
+            reward_schedule_account,
+            system_program_info,
+            funder_wallet_account,
+            REWARD_SCHEDULE_STORAGE_TOTAL_BYTES,
+            &[
+                b"reward_schedule",
+                &cwar_pool_storage_account.key.to_bytes(),
+                &[bump_seed],
+            ],
+        )?;
+        cwar_pool_data.reward_schedule = reward_schedule_address;
+        cwar_pool_data.reward_schedule_last_update_time = now;
+        RewardSchedule {
+            acc_type: AccTypesWithVersion::RewardScheduleDataV1 as u8,
+            cwar_pool: *cwar_pool_storage_account.key,
+            phases: [RewardPhase::default(); MAX_REWARD_PHASES],
+        }
+    };
+
+    let amount = add_reward_phase(
+        &mut cwar_pool_data,
+        &mut reward_schedule_data,
+        RewardPhase {
+            start,
+            end,
+            reward_rate,
+        },
+        now,
+    )?;
+    msg!("amount: {}", amount);
+
+    msg!("Calling the token program to transfer CWAR rewards to Rewards Vault...");
+    invoke(
+        &spl_token::instruction::transfer(
+            token_program.key,
+            cwar_rewards_ata_to_debit.key,
+            cwar_rewards_vault.key,
+            funder_wallet_account.key,
+            &[],
+            amount,
+        )?,
+        &[
+            cwar_rewards_ata_to_debit.clone(),
+            cwar_rewards_vault.clone(),
+            funder_wallet_account.clone(),
+            token_program.clone(),
+        ],
+    )?;
+
+    save_pool_storage_account(&mut cwar_pool_data_byte_array, &cwar_pool_data)?;
+    let mut reward_schedule_byte_array = reward_schedule_account.data.try_borrow_mut().unwrap();
+    save_reward_schedule_account(&mut reward_schedule_byte_array, &reward_schedule_data)?;
+
+    Ok(())
+}
+
+/// Appends `phase` after the last one of a schedule checkpointed at `now`, dropping the phases
+/// that are fully emitted to make room. Returns everything the phase emits, which has to be
+/// funded up front.
+pub fn add_reward_phase(
+    cwar_pool_data: &mut CwarPool,
+    reward_schedule_data: &mut RewardSchedule,
+    phase: RewardPhase,
+    now: u64,
+) -> Result<u64, ProgramError> {
+    if phase.start < now
+        || phase.start < cwar_pool_data.reward_schedule_end
+        || phase.end <= phase.start
+        || phase.reward_rate == 0u64
+    {
+        msg!("CryowarError::InvalidRewardPhase");
+        return Err(CryowarError::InvalidRewardPhase.into());
+    }
+    let amount = (phase.end - phase.start)
+        .checked_mul(phase.reward_rate)
+        .ok_or(CryowarError::AmountOverflow)?;
+
+    let mut phases: Vec<RewardPhase> = reward_schedule_data
+        .phases
+        .iter()
+        .filter(|x| x.is_active() && x.end > cwar_pool_data.reward_schedule_last_update_time)
+        .copied()
+        .collect();
+    if phases.len() >= MAX_REWARD_PHASES {
+        msg!("CryowarError::RewardScheduleFull");
+        return Err(CryowarError::RewardScheduleFull.into());
+    }
+    phases.push(phase);
+    reward_schedule_data.phases = [RewardPhase::default(); MAX_REWARD_PHASES];
+    reward_schedule_data.phases[..phases.len()].copy_from_slice(&phases);
+
+    cwar_pool_data.scheduled_rewards_left = cwar_pool_data
+        .scheduled_rewards_left
+        .checked_add(amount)
+        .ok_or(CryowarError::AmountOverflow)?;
+    cwar_pool_data.reward_schedule_end = phase.end;
+    Ok(amount)
+}
@@ -0,0 +1,126 @@
+use std::convert::TryInto;
+
+use crate::{
+    error::CryowarError,
+    state::RewardStream,
+    utils::{
+        assert_pool_storage_account, assert_signer, assert_token_program,
+        get_pool_signer_address_and_bump_seed, save_pool_storage_account,
+    },
+};
+use solana_program::{
+    account_info::{next_account_info, AccountInfo},
+    clock::Clock,
+    entrypoint::ProgramResult,
+    msg,
+    program::invoke,
+    program_pack::Pack,
+    pubkey::Pubkey,
+    rent::Rent,
+    sysvar::Sysvar,
+};
+use spl_token::state::Account as TokenAccount;
+
+pub fn process_add_reward_stream(accounts: &[AccountInfo], program_id: &Pubkey) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+    let pool_owner_wallet_account = next_account_info(account_info_iter)?;
+    let cwar_pool_storage_account = next_account_info(account_info_iter)?;
+    let reward_mint = next_account_info(account_info_iter)?;
+    let reward_vault = next_account_info(account_info_iter)?;
+    let token_program = next_account_info(account_info_iter)?;
+
+    assert_signer(pool_owner_wallet_account)?;
+    assert_token_program(token_program)?;
+
+    let mut cwar_pool_data_byte_array = cwar_pool_storage_account.data.try_borrow_mut().unwrap();
+    let mut cwar_pool_data = assert_pool_storage_account(
+        &cwar_pool_data_byte_array,
+        cwar_pool_storage_account,
+        program_id,
+    )?;
+
+    if cwar_pool_data.owner_wallet != *pool_owner_wallet_account.key {
+        msg!("CryowarError::PoolOwnerMismatched");
+        return Err(CryowarError::PoolOwnerMismatched.into());
+    }
+
+    if *reward_mint.key == cwar_pool_data.reward_mint
+        || cwar_pool_data
+            .reward_streams
+            .iter()
+            .any(|x| x.reward_mint == *reward_mint.key)
+    {
+        msg!("CryowarError::RewardStreamAlreadyPresent");
+        return Err(CryowarError::RewardStreamAlreadyPresent.into());
+    }
+    if *reward_vault.key == cwar_pool_data.staking_vault
+        || *reward_vault.key == cwar_pool_data.reward_vault
+    {
+        msg!("CryowarError::InvalidRewardsVault");
+        return Err(CryowarError::InvalidRewardsVault.into());
+    }
+    let stream_index = match cwar_pool_data
+        .reward_streams
+        .iter()
+        .position(|x| !x.is_active())
+    {
+        Some(stream_index) => stream_index,
+        None => {
+            msg!("CryowarError::MaxRewardStreamsReached");
+            return Err(CryowarError::MaxRewardStreamsReached.into());
+        }
+    };
+
+    let rent = Rent::get()?;
+    if !rent.is_exempt(reward_vault.lamports(), reward_vault.data_len()) {
+        msg!("CryowarError::RewardsVaultNotRentExempt");
+        return Err(CryowarError::NotRentExempt.into());
+    }
+
+    let (pool_signer_address, _bump_seed) =
+        get_pool_signer_address_and_bump_seed(cwar_pool_storage_account.key, program_id);
+    msg!("Calling the token program to transfer Rewards vault account ownership to Cryowar Pool program...");
+    invoke(
+        &spl_token::instruction::set_authority(
+            token_program.key,
+            reward_vault.key,
+            Some(&pool_signer_address),
+            spl_token::instruction::AuthorityType::AccountOwner,
+            pool_owner_wallet_account.key,
+            &[pool_owner_wallet_account.key],
+        )?,
+        &[
+            reward_vault.clone(),
+            pool_owner_wallet_account.clone(),
+            token_program.clone(),
+        ],
+    )?;
+
+    let reward_vault_data = TokenAccount::unpack(&reward_vault.data.borrow())?;
+    if reward_vault_data.mint != *reward_mint.key {
+        msg!("CryowarError::MintMismatched");
+        return Err(CryowarError::MintMismatched.into());
+    }
+    if reward_vault_data.owner != pool_signer_address {
+        msg!("CryowarError::InvalidRewardsVault");
+        return Err(CryowarError::InvalidRewardsVault.into());
+    }
+    if reward_vault.owner != &spl_token::id() {
+        msg!("CryowarError::AccountOwnerShouldBeTokenProgram");
+        return Err(CryowarError::AccountOwnerShouldBeTokenProgram.into());
+    }
+
+    // users checkpoint unused slots at zero, so a new stream has to start accumulating from zero
+    let now: u64 = Clock::get()?.unix_timestamp.try_into().unwrap();
+    cwar_pool_data.reward_streams[stream_index] = RewardStream {
+        reward_mint: *reward_mint.key,
+        reward_vault: *reward_vault.key,
+        last_update_time: now,
+        ..RewardStream::default()
+    };
+    msg!("Reward stream {} added", stream_index);
+
+    save_pool_storage_account(&mut cwar_pool_data_byte_array, &cwar_pool_data)?;
+
+    Ok(())
+}
@@ -0,0 +1,53 @@
+use crate::{
+    error::CryowarError,
+    utils::{self, assert_pending_action_account, assert_pool_storage_account, assert_signer},
+};
+use solana_program::{
+    account_info::{next_account_info, AccountInfo},
+    entrypoint::ProgramResult,
+    msg,
+    pubkey::Pubkey,
+};
+
+pub fn process_cancel_action(accounts: &[AccountInfo], program_id: &Pubkey) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+    let pool_owner_wallet_account = next_account_info(account_info_iter)?;
+    let cwar_pool_storage_account = next_account_info(account_info_iter)?;
+    let pending_action_account = next_account_info(account_info_iter)?;
+    let rent_receiver_account = next_account_info(account_info_iter)?;
+
+    assert_signer(pool_owner_wallet_account)?;
+
+    let cwar_pool_data = assert_pool_storage_account(
+        &cwar_pool_storage_account.data.borrow(),
+        cwar_pool_storage_account,
+        program_id,
+    )?;
+
+    if cwar_pool_data.owner_wallet != *pool_owner_wallet_account.key {
+        msg!("CryowarError::PoolOwnerMismatched");
+        return Err(CryowarError::PoolOwnerMismatched.into());
+    }
+
+    let mut pending_action_byte_array = pending_action_account.data.try_borrow_mut().unwrap();
+    let pending_action_data = assert_pending_action_account(
+        pending_action_account,
+        cwar_pool_storage_account,
+        program_id,
+        &pending_action_byte_array,
+    )?;
+    if pending_action_data.rent_payer != *rent_receiver_account.key {
+        msg!("CryowarError::RentReceiverMismatched");
+        return Err(CryowarError::RentReceiverMismatched.into());
+    }
+
+    msg!("Cancelling action {}", pending_action_data.action_id);
+    pending_action_byte_array.fill(0u8);
+    utils::close_account(
+        pending_action_account,
+        rent_receiver_account,
+        &mut pending_action_byte_array,
+    )?;
+
+    Ok(())
+}
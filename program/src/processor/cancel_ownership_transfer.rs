@@ -0,0 +1,43 @@
+use crate::{
+    error::CryowarError,
+    utils::{assert_pool_storage_account, assert_signer, save_pool_storage_account},
+};
+use solana_program::{
+    account_info::{next_account_info, AccountInfo},
+    entrypoint::ProgramResult,
+    msg,
+    pubkey::Pubkey,
+};
+
+pub fn process_cancel_ownership_transfer(
+    accounts: &[AccountInfo],
+    program_id: &Pubkey,
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+    let pool_owner_wallet_account = next_account_info(account_info_iter)?;
+    let cwar_pool_storage_account = next_account_info(account_info_iter)?;
+
+    assert_signer(pool_owner_wallet_account)?;
+
+    let mut cwar_pool_data_byte_array = cwar_pool_storage_account.data.try_borrow_mut().unwrap();
+    let mut cwar_pool_data = assert_pool_storage_account(
+        &cwar_pool_data_byte_array,
+        cwar_pool_storage_account,
+        program_id,
+    )?;
+
+    if cwar_pool_data.owner_wallet != *pool_owner_wallet_account.key {
+        msg!("CryowarError::PoolOwnerMismatched");
+        return Err(CryowarError::PoolOwnerMismatched.into());
+    }
+
+    if cwar_pool_data.pending_owner == Pubkey::default() {
+        msg!("CryowarError::NoPendingOwnershipTransfer");
+        return Err(CryowarError::NoPendingOwnershipTransfer.into());
+    }
+    cwar_pool_data.pending_owner = Pubkey::default();
+
+    save_pool_storage_account(&mut cwar_pool_data_byte_array, &cwar_pool_data)?;
+
+    Ok(())
+}
@@ -0,0 +1,72 @@
+use std::convert::TryInto;
+
+use crate::{
+    error::CryowarError,
+    processor::reduce_rewards::{reduce_rewards, refund_rewards},
+    state::CwarPool,
+    utils::{
+        self, assert_not_timelocked, assert_pool_storage_account, assert_signer,
+        get_rewards_left_amount, next_reward_schedule, save_pool_storage_account,
+    },
+};
+use solana_program::{
+    account_info::{next_account_info, AccountInfo},
+    clock::Clock,
+    entrypoint::ProgramResult,
+    msg,
+    program_error::ProgramError,
+    pubkey::Pubkey,
+    sysvar::Sysvar,
+};
+
+pub fn process_cancel_rewards(accounts: &[AccountInfo], program_id: &Pubkey) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+    let pool_owner_wallet_account = next_account_info(account_info_iter)?;
+    let cwar_pool_storage_account = next_account_info(account_info_iter)?;
+    let cwar_rewards_vault = next_account_info(account_info_iter)?;
+    let cwar_rewards_refund_ata = next_account_info(account_info_iter)?;
+    let pool_signer_pda = next_account_info(account_info_iter)?;
+    let token_program = next_account_info(account_info_iter)?;
+
+    assert_signer(pool_owner_wallet_account)?;
+
+    let mut cwar_pool_data_byte_array = cwar_pool_storage_account.data.try_borrow_mut().unwrap();
+    let mut cwar_pool_data = assert_pool_storage_account(
+        &cwar_pool_data_byte_array,
+        cwar_pool_storage_account,
+        program_id,
+    )?;
+    let reward_schedule = next_reward_schedule(account_info_iter, &cwar_pool_data, program_id)?;
+
+    if cwar_pool_data.owner_wallet != *pool_owner_wallet_account.key {
+        msg!("CryowarError::PoolOwnerMismatched");
+        return Err(CryowarError::PoolOwnerMismatched.into());
+    }
+
+    assert_not_timelocked(&cwar_pool_data)?;
+
+    let now: u64 = Clock::get()?.unix_timestamp.try_into().unwrap();
+    utils::update_rewards(&mut cwar_pool_data, reward_schedule.as_ref(), None, now)?;
+    let rewards_to_refund = cancel_rewards(&mut cwar_pool_data)?;
+    refund_rewards(
+        cwar_rewards_vault,
+        cwar_rewards_refund_ata,
+        cwar_pool_storage_account,
+        pool_signer_pda,
+        token_program,
+        &cwar_pool_data,
+        rewards_to_refund,
+        program_id,
+    )?;
+
+    save_pool_storage_account(&mut cwar_pool_data_byte_array, &cwar_pool_data)?;
+
+    Ok(())
+}
+
+/// Ends the current stream at the last checkpoint, returning everything it had yet to emit
+pub fn cancel_rewards(cwar_pool_data: &mut CwarPool) -> Result<u64, ProgramError> {
+    let rewards_left_amount = get_rewards_left_amount(cwar_pool_data)?;
+    let checkpoint = cwar_pool_data.total_stake_last_update_time;
+    reduce_rewards(cwar_pool_data, rewards_left_amount, checkpoint)
+}
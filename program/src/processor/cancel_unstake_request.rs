@@ -0,0 +1,83 @@
+use std::convert::TryInto;
+
+use crate::{
+    processor::request_unstake::settle_unbonding_request,
+    state::PAUSE_STAKE,
+    utils::{
+        self, assert_not_paused, assert_pool_storage_account, assert_signer,
+        assert_unbonding_request_account, assert_user_storage_account, next_reward_schedule,
+        save_pool_storage_account, save_user_storage_account,
+    },
+};
+use solana_program::{
+    account_info::{next_account_info, AccountInfo},
+    clock::Clock,
+    entrypoint::ProgramResult,
+    pubkey::Pubkey,
+    sysvar::Sysvar,
+};
+
+/// Puts an unbonding request back into the user's stake, it earns again from now on
+pub fn process_cancel_unstake_request(
+    accounts: &[AccountInfo],
+    program_id: &Pubkey,
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+    let user_wallet_account = next_account_info(account_info_iter)?;
+    let user_storage_account = next_account_info(account_info_iter)?;
+    let cwar_pool_storage_account = next_account_info(account_info_iter)?;
+    let unbonding_request_account = next_account_info(account_info_iter)?;
+
+    assert_signer(user_wallet_account)?;
+    let mut user_data_byte_array = user_storage_account.data.try_borrow_mut().unwrap();
+    let mut user_storage_data = assert_user_storage_account(
+        user_wallet_account,
+        cwar_pool_storage_account,
+        program_id,
+        user_storage_account,
+        &user_data_byte_array,
+    )?;
+
+    let mut cwar_pool_data_byte_array = cwar_pool_storage_account.data.try_borrow_mut().unwrap();
+    let mut cwar_pool_data = assert_pool_storage_account(
+        &cwar_pool_data_byte_array,
+        cwar_pool_storage_account,
+        program_id,
+    )?;
+    assert_not_paused(&cwar_pool_data, PAUSE_STAKE)?;
+    let reward_schedule = next_reward_schedule(account_info_iter, &cwar_pool_data, program_id)?;
+
+    let mut unbonding_request_byte_array = unbonding_request_account.data.try_borrow_mut().unwrap();
+    let unbonding_request_data = assert_unbonding_request_account(
+        unbonding_request_account,
+        user_storage_account,
+        program_id,
+        &unbonding_request_byte_array,
+    )?;
+
+    let now: u64 = Clock::get()?.unix_timestamp.try_into().unwrap();
+    utils::update_rewards(
+        &mut cwar_pool_data,
+        reward_schedule.as_ref(),
+        Some(&mut user_storage_data),
+        now,
+    )?;
+    settle_unbonding_request(
+        &mut cwar_pool_data,
+        &mut user_storage_data,
+        unbonding_request_data.amount,
+        true,
+    )?;
+
+    save_pool_storage_account(&mut cwar_pool_data_byte_array, &cwar_pool_data)?;
+    save_user_storage_account(&mut user_data_byte_array, &user_storage_data)?;
+
+    unbonding_request_byte_array.fill(0u8);
+    utils::close_account(
+        unbonding_request_account,
+        user_wallet_account,
+        &mut unbonding_request_byte_array,
+    )?;
+
+    Ok(())
+}
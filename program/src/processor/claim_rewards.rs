@@ -2,10 +2,14 @@ use std::convert::TryInto;
 
 use crate::{
     error::CryowarError,
+    state::{CwarPool, User, PAUSE_CLAIM},
     utils::{
-        self, assert_pool_storage_account, assert_reward_vault, assert_signer,
-        assert_staking_vault, assert_token_account_to_be_owned_by_signer, assert_token_program,
-        assert_user_storage_account, save_pool_storage_account, save_user_storage_account,
+        self, assert_not_paused, assert_pool_storage_account, assert_reward_stream_vault,
+        assert_reward_vault, assert_signer, assert_staking_vault, assert_token_account_mint,
+        assert_token_account_to_be_owned_by_signer, assert_token_program,
+        assert_user_storage_account, create_recipient_ata_if_missing,
+        get_pool_signer_address_and_bump_seed, next_recipient_accounts, next_reward_schedule,
+        save_pool_storage_account, save_user_storage_account, RecipientAccounts,
     },
 };
 
@@ -23,6 +27,94 @@ use spl_token::state::Account as TokenAccount;
 
 use super::unstake_cwar::check_locking_period;
 
+/// Pays a checkpointed user what they earned from the extra reward stream held in
+/// `reward_vault`, as much as the vault can cover. `recipient_reward_ata` has to be a token account
+/// of the user for the stream reward mint, unless `recipient` holds the recipient accounts and the
+/// stream reward mint, then it is the recipient ATA, created when missing.
+#[allow(clippy::too_many_arguments)]
+pub fn claim_reward_stream<'a>(
+    reward_vault: &AccountInfo<'a>,
+    recipient_reward_ata: &AccountInfo<'a>,
+    user_wallet_account: &AccountInfo<'a>,
+    recipient: Option<(&RecipientAccounts<'_, 'a>, &AccountInfo<'a>)>,
+    cwar_pool_storage_account: &AccountInfo<'a>,
+    pool_signer_pda: &AccountInfo<'a>,
+    token_program: &AccountInfo<'a>,
+    cwar_pool_data: &mut CwarPool,
+    user_storage_data: &mut User,
+    program_id: &Pubkey,
+) -> ProgramResult {
+    let stream_index = match cwar_pool_data
+        .reward_streams
+        .iter()
+        .position(|x| x.is_active() && x.reward_vault == *reward_vault.key)
+    {
+        Some(stream_index) => stream_index,
+        None => {
+            msg!("CryowarError::InvalidRewardStream");
+            return Err(CryowarError::InvalidRewardStream.into());
+        }
+    };
+    let stream_reward_mint = cwar_pool_data.reward_streams[stream_index].reward_mint;
+    match recipient {
+        Some((recipient_accounts, reward_mint)) => create_recipient_ata_if_missing(
+            recipient_reward_ata,
+            recipient_accounts.recipient_wallet,
+            reward_mint,
+            &stream_reward_mint,
+            user_wallet_account,
+            recipient_accounts.system_program_info,
+            token_program,
+            recipient_accounts.associated_token_program,
+        )?,
+        None => {
+            assert_token_account_to_be_owned_by_signer(recipient_reward_ata, user_wallet_account)?;
+            assert_token_account_mint(recipient_reward_ata, &stream_reward_mint)?;
+        }
+    }
+    let (pool_signer_address, bump_seed) =
+        get_pool_signer_address_and_bump_seed(cwar_pool_storage_account.key, program_id);
+    let reward_vault_data = TokenAccount::unpack(&reward_vault.data.borrow())?;
+    assert_reward_stream_vault(
+        reward_vault,
+        &cwar_pool_data.reward_streams[stream_index],
+        &reward_vault_data,
+        &pool_signer_address,
+    )?;
+
+    let user_reward_stream = &mut user_storage_data.reward_streams[stream_index];
+    let reward_amount = std::cmp::min(
+        user_reward_stream.rewards_amount_pending,
+        reward_vault_data.amount,
+    );
+    if reward_amount == 0u64 {
+        return Ok(());
+    }
+    user_reward_stream.rewards_amount_pending -= reward_amount;
+    let reward_stream = &mut cwar_pool_data.reward_streams[stream_index];
+    reward_stream.rewards_owed = reward_stream.rewards_owed.saturating_sub(reward_amount);
+
+    msg!("Calling the token program to transfer rewards to Recipient from Reward Stream Vault...");
+    invoke_signed(
+        &spl_token::instruction::transfer(
+            token_program.key,
+            reward_vault.key,
+            recipient_reward_ata.key,
+            &pool_signer_address,
+            &[&pool_signer_address],
+            reward_amount,
+        )?,
+        &[
+            reward_vault.clone(),
+            recipient_reward_ata.clone(),
+            pool_signer_pda.clone(),
+            token_program.clone(),
+        ],
+        &[&[&cwar_pool_storage_account.key.to_bytes(), &[bump_seed]]],
+    )?;
+    Ok(())
+}
+
 pub fn process_claim_rewards(accounts: &[AccountInfo], program_id: &Pubkey) -> ProgramResult {
     let account_info_iter = &mut accounts.iter();
     let user_wallet_account = next_account_info(account_info_iter)?;
@@ -33,9 +125,9 @@ pub fn process_claim_rewards(accounts: &[AccountInfo], program_id: &Pubkey) -> P
     let user_rewards_ata = next_account_info(account_info_iter)?;
     let pool_signer_pda = next_account_info(account_info_iter)?;
     let token_program = next_account_info(account_info_iter)?;
+    let recipient_accounts = next_recipient_accounts(account_info_iter)?;
 
     assert_signer(user_wallet_account)?;
-    assert_token_account_to_be_owned_by_signer(user_rewards_ata, user_wallet_account)?;
     assert_token_program(token_program)?;
     let mut user_data_byte_array = user_storage_account.data.try_borrow_mut().unwrap();
     let mut user_storage_data = assert_user_storage_account(
@@ -52,6 +144,8 @@ pub fn process_claim_rewards(accounts: &[AccountInfo], program_id: &Pubkey) -> P
         cwar_pool_storage_account,
         program_id,
     )?;
+    assert_not_paused(&cwar_pool_data, PAUSE_CLAIM)?;
+    let reward_schedule = next_reward_schedule(account_info_iter, &cwar_pool_data, program_id)?;
 
     let (pool_signer_address, bump_seed) =
         Pubkey::find_program_address(&[&cwar_pool_storage_account.key.to_bytes()], program_id);
@@ -68,14 +162,27 @@ pub fn process_claim_rewards(accounts: &[AccountInfo], program_id: &Pubkey) -> P
         &pool_signer_address,
     )?;
 
+    match &recipient_accounts {
+        Some(recipient_accounts) => create_recipient_ata_if_missing(
+            user_rewards_ata,
+            recipient_accounts.recipient_wallet,
+            recipient_accounts.mint,
+            &cwar_pool_data.reward_mint,
+            user_wallet_account,
+            recipient_accounts.system_program_info,
+            token_program,
+            recipient_accounts.associated_token_program,
+        )?,
+        None => assert_token_account_to_be_owned_by_signer(user_rewards_ata, user_wallet_account)?,
+    }
+
     let now: u64 = Clock::get()?.unix_timestamp.try_into().unwrap();
     check_locking_period(&user_storage_data, &cwar_pool_data, now)?;
 
-    let total_cwar_staked = cwar_staking_vault_data.amount;
     utils::update_rewards(
         &mut cwar_pool_data,
+        reward_schedule.as_ref(),
         Some(&mut user_storage_data),
-        total_cwar_staked,
         now,
     )?;
     if user_storage_data.rewards_amount_pending > 0u64 {
@@ -94,7 +201,7 @@ pub fn process_claim_rewards(accounts: &[AccountInfo], program_id: &Pubkey) -> P
         }
 
         if reward_amount > 0 {
-            msg!("Calling the token program to transfer CWAR to User from Rewards Vault...");
+            msg!("Calling the token program to transfer CWAR to Recipient from Rewards Vault...");
             invoke_signed(
                 &spl_token::instruction::transfer(
                     token_program.key,
@@ -114,7 +221,8 @@ pub fn process_claim_rewards(accounts: &[AccountInfo], program_id: &Pubkey) -> P
             )?;
         }
 
-        let cwar_rewards_vault_data_after = TokenAccount::unpack(&cwar_rewards_vault.data.borrow())?;
+        let cwar_rewards_vault_data_after =
+            TokenAccount::unpack(&cwar_rewards_vault.data.borrow())?;
         let actual_amount_withdrawn = cwar_rewards_vault_data
             .amount
             .checked_sub(cwar_rewards_vault_data_after.amount)
@@ -124,6 +232,32 @@ pub fn process_claim_rewards(accounts: &[AccountInfo], program_id: &Pubkey) -> P
             msg!("CryowarError::InvalidTransferOperation");
             return Err(CryowarError::InvalidTransferOperation.into());
         }
+        cwar_pool_data.rewards_owed = cwar_pool_data.rewards_owed.saturating_sub(reward_amount);
+    }
+
+    // extra reward streams are paid for each stream vault and recipient token account pair passed
+    // last, with the stream reward mint in between when the recipient accounts are passed, streams
+    // left out keep their rewards pending
+    while let Some(reward_vault) = account_info_iter.next() {
+        let recipient = match &recipient_accounts {
+            Some(recipient_accounts) => {
+                Some((recipient_accounts, next_account_info(account_info_iter)?))
+            }
+            None => None,
+        };
+        let recipient_reward_ata = next_account_info(account_info_iter)?;
+        claim_reward_stream(
+            reward_vault,
+            recipient_reward_ata,
+            user_wallet_account,
+            recipient,
+            cwar_pool_storage_account,
+            pool_signer_pda,
+            token_program,
+            &mut cwar_pool_data,
+            &mut user_storage_data,
+            program_id,
+        )?;
     }
 
     save_pool_storage_account(&mut cwar_pool_data_byte_array, &cwar_pool_data)?;
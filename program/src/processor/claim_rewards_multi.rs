@@ -0,0 +1,54 @@
+use crate::{
+    processor::claim_rewards::process_claim_rewards,
+    utils::{assert_pool_storage_account, assert_signer},
+};
+use solana_program::{
+    account_info::{next_account_info, AccountInfo},
+    entrypoint::ProgramResult,
+    pubkey::Pubkey,
+};
+
+/// Runs `ClaimRewards` for every pool group passed after the fixed accounts, paying the user's
+/// own rewards ATAs. Pools with a reward schedule take their `RewardSchedule` account at the end
+/// of their group. Extra reward streams aren't claimed here and stay pending.
+pub fn process_claim_rewards_multi(accounts: &[AccountInfo], program_id: &Pubkey) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+    let user_wallet_account = next_account_info(account_info_iter)?;
+    let token_program = next_account_info(account_info_iter)?;
+    let system_program_info = next_account_info(account_info_iter)?;
+    let associated_token_program = next_account_info(account_info_iter)?;
+
+    assert_signer(user_wallet_account)?;
+    while let Some(user_storage_account) = account_info_iter.next() {
+        let cwar_pool_storage_account = next_account_info(account_info_iter)?;
+        let staking_vault = next_account_info(account_info_iter)?;
+        let cwar_rewards_vault = next_account_info(account_info_iter)?;
+        let user_rewards_ata = next_account_info(account_info_iter)?;
+        let pool_signer_pda = next_account_info(account_info_iter)?;
+        let reward_mint = next_account_info(account_info_iter)?;
+        let mut claim_accounts = vec![
+            user_wallet_account.clone(),
+            user_storage_account.clone(),
+            cwar_pool_storage_account.clone(),
+            staking_vault.clone(),
+            cwar_rewards_vault.clone(),
+            user_rewards_ata.clone(),
+            pool_signer_pda.clone(),
+            token_program.clone(),
+            associated_token_program.clone(),
+            system_program_info.clone(),
+            user_wallet_account.clone(),
+            reward_mint.clone(),
+        ];
+        let cwar_pool_data = assert_pool_storage_account(
+            &cwar_pool_storage_account.data.borrow(),
+            cwar_pool_storage_account,
+            program_id,
+        )?;
+        if cwar_pool_data.has_reward_schedule() {
+            claim_accounts.push(next_account_info(account_info_iter)?.clone());
+        }
+        process_claim_rewards(&claim_accounts, program_id)?;
+    }
+    Ok(())
+}
@@ -1,8 +1,9 @@
 use crate::{
     error::CryowarError,
+    state::CwarPool,
     utils::{
-        assert_pool_storage_account, assert_reward_vault, assert_signer, assert_staking_vault,
-        assert_token_program, save_pool_storage_account,
+        assert_not_timelocked, assert_pool_storage_account, assert_reward_vault, assert_signer,
+        assert_staking_vault, assert_token_program, save_pool_storage_account,
     },
 };
 use solana_program::sysvar::clock::Clock;
@@ -39,12 +40,53 @@ pub fn process_close_pool(accounts: &[AccountInfo], program_id: &Pubkey) -> Prog
         program_id,
     )?;
 
+    if cwar_pool_data.owner_wallet != *pool_owner_wallet_account.key {
+        msg!("CryowarError::PoolOwnerMismatched");
+        return Err(CryowarError::PoolOwnerMismatched.into());
+    }
+
+    assert_not_timelocked(&cwar_pool_data)?;
+
+    close_pool(
+        pool_owner_wallet_account,
+        staking_vault,
+        cwar_staking_refund_ata,
+        cwar_rewards_vault,
+        cwar_rewards_refund_ata,
+        cwar_pool_storage_account,
+        pool_signer_pda,
+        token_program,
+        &mut cwar_pool_data,
+        program_id,
+    )?;
+
+    save_pool_storage_account(&mut cwar_pool_data_byte_array, &cwar_pool_data)?;
+
+    Ok(())
+}
+
+/// Empties and closes both vaults of a finished pool, the vault rent goes to the pool owner
+#[allow(clippy::too_many_arguments)]
+pub fn close_pool<'a>(
+    pool_owner_wallet_account: &AccountInfo<'a>,
+    staking_vault: &AccountInfo<'a>,
+    cwar_staking_refund_ata: &AccountInfo<'a>,
+    cwar_rewards_vault: &AccountInfo<'a>,
+    cwar_rewards_refund_ata: &AccountInfo<'a>,
+    cwar_pool_storage_account: &AccountInfo<'a>,
+    pool_signer_pda: &AccountInfo<'a>,
+    token_program: &AccountInfo<'a>,
+    cwar_pool_data: &mut CwarPool,
+    program_id: &Pubkey,
+) -> ProgramResult {
+    assert_token_program(token_program)?;
+
     let cwar_staking_vault_data = TokenAccount::unpack(&staking_vault.data.borrow())?;
     let (pool_signer_address, bump_seed) =
         Pubkey::find_program_address(&[&cwar_pool_storage_account.key.to_bytes()], program_id);
     assert_staking_vault(
         staking_vault,
-        &cwar_pool_data,
+        cwar_pool_data,
         &cwar_staking_vault_data,
         &pool_signer_address,
     )?;
@@ -53,28 +95,15 @@ pub fn process_close_pool(accounts: &[AccountInfo], program_id: &Pubkey) -> Prog
 
     assert_reward_vault(
         cwar_rewards_vault,
-        &cwar_pool_data,
+        cwar_pool_data,
         &cwar_rewards_vault_data,
         &pool_signer_address,
     )?;
 
-    if cwar_pool_data.owner_wallet != *pool_owner_wallet_account.key {
-        msg!("CryowarError::PoolOwnerMismatched");
-        return Err(CryowarError::PoolOwnerMismatched.into());
-    }
-
-    let total_cwar_staked = cwar_staking_vault_data.amount;
-
     let now = Clock::get()?.unix_timestamp;
-    if cwar_pool_data.reward_duration_end <= 0u64
-        || cwar_pool_data.reward_duration_end >= (now as u64)
-        || cwar_pool_data.user_stake_count != 0u32
-        || total_cwar_staked != 0u64
-    {
-        msg!("CryowarError::PoolStillActive");
-        return Err(CryowarError::PoolStillActive.into());
-    }
+    check_pool_closable(cwar_pool_data, now as u64)?;
 
+    // nothing is staked any more, whatever is left in the vault was sent to it directly
     msg!("Calling the token program to transfer CWAR to Staking Refundee from Staking Vault...");
     invoke_signed(
         &spl_token::instruction::transfer(
@@ -151,7 +180,21 @@ pub fn process_close_pool(accounts: &[AccountInfo], program_id: &Pubkey) -> Prog
 
     cwar_pool_data.staking_vault = Pubkey::default();
     cwar_pool_data.reward_vault = Pubkey::default();
-    save_pool_storage_account(&mut cwar_pool_data_byte_array, &cwar_pool_data)?;
 
     Ok(())
 }
+
+/// A pool can be closed once every reward emission is over, or never started, and no stake is
+/// accounted for, tokens sent straight to the staking vault don't keep it open
+pub fn check_pool_closable(cwar_pool_data: &CwarPool, now: u64) -> ProgramResult {
+    if cwar_pool_data.reward_duration_end >= now
+        || cwar_pool_data.reward_schedule_end >= now
+        || cwar_pool_data.user_stake_count != 0u32
+        || cwar_pool_data.total_staked != 0u64
+        || cwar_pool_data.total_unbonding != 0u64
+    {
+        msg!("CryowarError::PoolStillActive");
+        return Err(CryowarError::PoolStillActive.into());
+    }
+    Ok(())
+}
@@ -0,0 +1,117 @@
+use std::convert::TryInto;
+
+use crate::{
+    error::CryowarError,
+    state::RewardStream,
+    utils::{
+        assert_pool_storage_account, assert_reward_stream_vault, assert_signer,
+        assert_token_program, get_pool_signer_address_and_bump_seed, get_reward_stream,
+        save_pool_storage_account,
+    },
+};
+use solana_program::{
+    account_info::{next_account_info, AccountInfo},
+    clock::Clock,
+    entrypoint::ProgramResult,
+    msg,
+    program::invoke_signed,
+    program_pack::Pack,
+    pubkey::Pubkey,
+    sysvar::Sysvar,
+};
+use spl_token::state::Account as TokenAccount;
+
+pub fn process_close_reward_stream(
+    accounts: &[AccountInfo],
+    stream_index: u8,
+    program_id: &Pubkey,
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+    let pool_owner_wallet_account = next_account_info(account_info_iter)?;
+    let cwar_pool_storage_account = next_account_info(account_info_iter)?;
+    let reward_vault = next_account_info(account_info_iter)?;
+    let rewards_refund_ata = next_account_info(account_info_iter)?;
+    let pool_signer_pda = next_account_info(account_info_iter)?;
+    let token_program = next_account_info(account_info_iter)?;
+
+    assert_signer(pool_owner_wallet_account)?;
+    assert_token_program(token_program)?;
+
+    let mut cwar_pool_data_byte_array = cwar_pool_storage_account.data.try_borrow_mut().unwrap();
+    let mut cwar_pool_data = assert_pool_storage_account(
+        &cwar_pool_data_byte_array,
+        cwar_pool_storage_account,
+        program_id,
+    )?;
+
+    if cwar_pool_data.owner_wallet != *pool_owner_wallet_account.key {
+        msg!("CryowarError::PoolOwnerMismatched");
+        return Err(CryowarError::PoolOwnerMismatched.into());
+    }
+
+    let reward_stream = get_reward_stream(&cwar_pool_data, stream_index)?;
+    let (pool_signer_address, bump_seed) =
+        get_pool_signer_address_and_bump_seed(cwar_pool_storage_account.key, program_id);
+    if pool_signer_address != *pool_signer_pda.key {
+        msg!("CryowarError::InvalidSignerPDA");
+        return Err(CryowarError::InvalidSignerPDA.into());
+    }
+    let reward_vault_data = TokenAccount::unpack(&reward_vault.data.borrow())?;
+    assert_reward_stream_vault(
+        reward_vault,
+        &reward_stream,
+        &reward_vault_data,
+        &pool_signer_address,
+    )?;
+
+    // with no user accounts left nobody can still be owed from the stream, and the slot can
+    // start over from a zero accumulator
+    let now: u64 = Clock::get()?.unix_timestamp.try_into().unwrap();
+    if reward_stream.reward_duration_end >= now || cwar_pool_data.user_stake_count != 0u32 {
+        msg!("CryowarError::PoolStillActive");
+        return Err(CryowarError::PoolStillActive.into());
+    }
+
+    msg!("Calling the token program to transfer rewards to Rewards Refundee from Reward Stream Vault...");
+    invoke_signed(
+        &spl_token::instruction::transfer(
+            token_program.key,
+            reward_vault.key,
+            rewards_refund_ata.key,
+            &pool_signer_address,
+            &[&pool_signer_address],
+            reward_vault_data.amount,
+        )?,
+        &[
+            reward_vault.clone(),
+            rewards_refund_ata.clone(),
+            pool_signer_pda.clone(),
+            token_program.clone(),
+        ],
+        &[&[&cwar_pool_storage_account.key.to_bytes()[..], &[bump_seed]]],
+    )?;
+
+    msg!("Calling the token program to close Reward Stream Vault...");
+    invoke_signed(
+        &spl_token::instruction::close_account(
+            token_program.key,
+            reward_vault.key,
+            pool_owner_wallet_account.key,
+            &pool_signer_address,
+            &[&pool_signer_address],
+        )?,
+        &[
+            reward_vault.clone(),
+            pool_owner_wallet_account.clone(),
+            pool_signer_pda.clone(),
+            token_program.clone(),
+        ],
+        &[&[&cwar_pool_storage_account.key.to_bytes()[..], &[bump_seed]]],
+    )?;
+
+    cwar_pool_data.reward_streams[stream_index as usize] = RewardStream::default();
+
+    save_pool_storage_account(&mut cwar_pool_data_byte_array, &cwar_pool_data)?;
+
+    Ok(())
+}
@@ -41,7 +41,14 @@ pub fn process_close_user(accounts: &[AccountInfo], program_id: &Pubkey) -> Prog
     save_pool_storage_account(&mut cwar_pool_data_byte_array, &cwar_pool_data)?;
 
     if user_storage_data.user_cwar_staked_amount != 0u64
+        || user_storage_data.unbonding_amount != 0u64
+        || user_storage_data.locked_amount != 0u64
+        || user_storage_data.receipt_debt != 0u64
         || user_storage_data.rewards_amount_pending != 0
+        || user_storage_data
+            .reward_streams
+            .iter()
+            .any(|x| x.rewards_amount_pending != 0)
     {
         msg!("CryowarError::UserBalanceNonZero");
         return Err(CryowarError::UserBalanceNonZero.into());
@@ -0,0 +1,151 @@
+use std::convert::TryInto;
+
+use crate::{
+    error::CryowarError,
+    state::{CwarPool, User, PAUSE_CLAIM, PAUSE_STAKE},
+    utils::{
+        self, assert_no_receipt_mint, assert_not_paused, assert_pool_storage_account,
+        assert_reward_vault, assert_signer, assert_staking_vault, assert_token_program,
+        assert_user_storage_account, check_stake_limits, get_pool_signer_address_and_bump_seed,
+        next_reward_schedule, save_pool_storage_account, save_user_storage_account,
+    },
+};
+use solana_program::{
+    account_info::{next_account_info, AccountInfo},
+    clock::Clock,
+    entrypoint::ProgramResult,
+    msg,
+    program::invoke_signed,
+    program_error::ProgramError,
+    program_pack::Pack,
+    pubkey::Pubkey,
+    sysvar::Sysvar,
+};
+use spl_token::state::Account as TokenAccount;
+
+pub fn process_compound_rewards(accounts: &[AccountInfo], program_id: &Pubkey) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+    let user_wallet_account = next_account_info(account_info_iter)?;
+    let user_storage_account = next_account_info(account_info_iter)?;
+    let cwar_pool_storage_account = next_account_info(account_info_iter)?;
+    let staking_vault = next_account_info(account_info_iter)?;
+    let cwar_rewards_vault = next_account_info(account_info_iter)?;
+    let pool_signer_pda = next_account_info(account_info_iter)?;
+    let token_program = next_account_info(account_info_iter)?;
+
+    assert_signer(user_wallet_account)?;
+    assert_token_program(token_program)?;
+    let mut user_data_byte_array = user_storage_account.data.try_borrow_mut().unwrap();
+    let mut user_storage_data = assert_user_storage_account(
+        user_wallet_account,
+        cwar_pool_storage_account,
+        program_id,
+        user_storage_account,
+        &user_data_byte_array,
+    )?;
+
+    let mut cwar_pool_data_byte_array = cwar_pool_storage_account.data.try_borrow_mut().unwrap();
+    let mut cwar_pool_data = assert_pool_storage_account(
+        &cwar_pool_data_byte_array,
+        cwar_pool_storage_account,
+        program_id,
+    )?;
+    assert_not_paused(&cwar_pool_data, PAUSE_CLAIM | PAUSE_STAKE)?;
+    let reward_schedule = next_reward_schedule(account_info_iter, &cwar_pool_data, program_id)?;
+
+    if cwar_pool_data.reward_mint != cwar_pool_data.staking_mint {
+        msg!("CryowarError::CompoundingNotSupported");
+        return Err(CryowarError::CompoundingNotSupported.into());
+    }
+    // compounded stake would have no receipts
+    assert_no_receipt_mint(&cwar_pool_data)?;
+
+    let (pool_signer_address, bump_seed) =
+        get_pool_signer_address_and_bump_seed(cwar_pool_storage_account.key, program_id);
+    if pool_signer_address != *pool_signer_pda.key {
+        msg!("CryowarError::InvalidSignerPDA");
+        return Err(CryowarError::InvalidSignerPDA.into());
+    }
+    let cwar_staking_vault_data = TokenAccount::unpack(&staking_vault.data.borrow())?;
+    assert_staking_vault(
+        staking_vault,
+        &cwar_pool_data,
+        &cwar_staking_vault_data,
+        &pool_signer_address,
+    )?;
+    let cwar_rewards_vault_data = TokenAccount::unpack(&cwar_rewards_vault.data.borrow())?;
+    assert_reward_vault(
+        cwar_rewards_vault,
+        &cwar_pool_data,
+        &cwar_rewards_vault_data,
+        &pool_signer_address,
+    )?;
+
+    let now: u64 = Clock::get()?.unix_timestamp.try_into().unwrap();
+    utils::update_rewards(
+        &mut cwar_pool_data,
+        reward_schedule.as_ref(),
+        Some(&mut user_storage_data),
+        now,
+    )?;
+    let amount_to_compound = compound_rewards(&mut cwar_pool_data, &mut user_storage_data)?;
+    msg!("amount_to_compound: {}", amount_to_compound);
+
+    // a pool sharing one vault for stake and rewards only has to move the books
+    if staking_vault.key != cwar_rewards_vault.key {
+        msg!("Calling the token program to transfer CWAR to Staking Vault from Rewards Vault...");
+        invoke_signed(
+            &spl_token::instruction::transfer(
+                token_program.key,
+                cwar_rewards_vault.key,
+                staking_vault.key,
+                &pool_signer_address,
+                &[&pool_signer_address],
+                amount_to_compound,
+            )?,
+            &[
+                cwar_rewards_vault.clone(),
+                staking_vault.clone(),
+                pool_signer_pda.clone(),
+                token_program.clone(),
+            ],
+            &[&[&cwar_pool_storage_account.key.to_bytes(), &[bump_seed]]],
+        )?;
+    }
+
+    save_pool_storage_account(&mut cwar_pool_data_byte_array, &cwar_pool_data)?;
+    save_user_storage_account(&mut user_data_byte_array, &user_storage_data)?;
+
+    Ok(())
+}
+
+/// Turns the pending rewards of a checkpointed user into stake. Compounded rewards were earned
+/// by stake that is already locked, so neither `last_staked_timestamp` nor
+/// `unstake_penality_duration_end` move: the locking period keeps running from the last
+/// deposit, and the compounded amount is penalised like the rest of the stake if it is
+/// unstaked before the penalty window closes. Compounding is allowed during the locking
+/// period since nothing leaves the pool, the stake limits apply as for a deposit.
+pub fn compound_rewards(
+    cwar_pool_data: &mut CwarPool,
+    user_storage_data: &mut User,
+) -> Result<u64, ProgramError> {
+    let amount_to_compound = user_storage_data.rewards_amount_pending;
+    if amount_to_compound == 0u64 {
+        msg!("CryowarError::AmountMustBeGreaterThanZero");
+        return Err(CryowarError::AmountMustBeGreaterThanZero.into());
+    }
+    check_stake_limits(cwar_pool_data, user_storage_data, amount_to_compound)?;
+    user_storage_data.rewards_amount_pending = 0u64;
+    user_storage_data.user_cwar_staked_amount = user_storage_data
+        .user_cwar_staked_amount
+        .checked_add(amount_to_compound)
+        .ok_or(CryowarError::AmountOverflow)?;
+    cwar_pool_data.total_staked = cwar_pool_data
+        .total_staked
+        .checked_add(amount_to_compound)
+        .ok_or(CryowarError::AmountOverflow)?;
+    cwar_pool_data.rewards_owed = cwar_pool_data
+        .rewards_owed
+        .saturating_sub(amount_to_compound);
+    Ok(amount_to_compound)
+}
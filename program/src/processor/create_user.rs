@@ -1,10 +1,10 @@
 use crate::{
     error::CryowarError,
     state,
-    state::User,
+    state::{CwarPool, User, UserRewardStream, MAX_EXTRA_REWARD_STREAMS, PAUSE_CREATE_USER},
     utils::{
-        assert_pool_storage_account, assert_signer, assert_system_program,
-        save_pool_storage_account, save_user_storage_account,
+        assert_allowlisted, assert_not_paused, assert_pool_storage_account, assert_signer,
+        assert_system_program, save_pool_storage_account, save_user_storage_account,
     },
 };
 
@@ -21,9 +21,12 @@ use solana_program::{
 };
 use std::convert::TryInto;
 
+/// `allowlist_max_stake` and `allowlist_proof` are only looked at when the pool has an allowlist
 pub fn process_create_user(
     accounts: &[AccountInfo],
     nonce: u8,
+    allowlist_max_stake: u64,
+    allowlist_proof: &[[u8; 32]],
     program_id: &Pubkey,
 ) -> ProgramResult {
     let account_info_iter = &mut accounts.iter();
@@ -41,7 +44,46 @@ pub fn process_create_user(
         cwar_pool_storage_account,
         program_id,
     )?;
+    assert_not_paused(&cwar_pool_data, PAUSE_CREATE_USER)?;
+    let allowlist_max_stake = assert_allowlisted(
+        &cwar_pool_data,
+        user_wallet_account.key,
+        allowlist_max_stake,
+        allowlist_proof,
+    )?;
+
+    let user_storage_data = create_user_storage_account(
+        user_wallet_account.key,
+        user_storage_account,
+        cwar_pool_storage_account,
+        system_program_info,
+        user_wallet_account,
+        &mut cwar_pool_data,
+        allowlist_max_stake,
+        program_id,
+    )?;
+
+    let mut user_data_byte_array = user_storage_account.data.try_borrow_mut().unwrap();
+
+    save_pool_storage_account(&mut cwar_pool_data_byte_array, &cwar_pool_data)?;
+    save_user_storage_account(&mut user_data_byte_array, &user_storage_data)?;
+
+    Ok(())
+}
 
+/// Creates the user storage PDA of `user_wallet` paid by `payer_info` and counts it in the pool,
+/// the caller saves the returned `User`
+#[allow(clippy::too_many_arguments)]
+pub fn create_user_storage_account<'a>(
+    user_wallet: &Pubkey,
+    user_storage_account: &AccountInfo<'a>,
+    cwar_pool_storage_account: &AccountInfo<'a>,
+    system_program_info: &AccountInfo<'a>,
+    payer_info: &AccountInfo<'a>,
+    cwar_pool_data: &mut CwarPool,
+    allowlist_max_stake: u64,
+    program_id: &Pubkey,
+) -> Result<User, ProgramError> {
     assert_system_program(system_program_info)?;
 
     if !user_storage_account.data_is_empty() || user_storage_account.data_len() != 0 {
@@ -50,7 +92,7 @@ pub fn process_create_user(
     }
 
     let (user_storage_address, bump_seed) = get_user_storage_address_and_bump_seed(
-        user_wallet_account.key,
+        user_wallet,
         cwar_pool_storage_account.key,
         program_id,
     );
@@ -60,7 +102,7 @@ pub fn process_create_user(
     }
 
     let user_storage_account_signer_seeds: &[&[_]] = &[
-        &user_wallet_account.key.to_bytes(),
+        &user_wallet.to_bytes(),
         &cwar_pool_storage_account.key.to_bytes(),
         &[bump_seed],
     ];
@@ -69,15 +111,17 @@ pub fn process_create_user(
         *program_id,
         user_storage_account,
         system_program_info,
-        user_wallet_account,
+        payer_info,
         state::USER_STORAGE_TOTAL_BYTES,
         user_storage_account_signer_seeds,
     )
     .unwrap();
 
-    let user_storage_data = User {
-        acc_type: state::AccTypesWithVersion::UserDataV1 as u8,
-        user_wallet: *user_wallet_account.key,
+    cwar_pool_data.user_stake_count += 1u32;
+
+    Ok(User {
+        acc_type: state::AccTypesWithVersion::UserDataV2 as u8,
+        user_wallet: *user_wallet,
         cwar_pool: *cwar_pool_storage_account.key,
         user_cwar_staked_amount: 0u64,
         nonce: bump_seed,
@@ -85,16 +129,16 @@ pub fn process_create_user(
         rewards_per_token_accumulated_at_last_user_interaction: 0u128,
         unstake_penality_duration_end: cwar_pool_data.reward_duration_end,
         last_staked_timestamp: 0u64,
-    };
-
-    let mut user_data_byte_array = user_storage_account.data.try_borrow_mut().unwrap();
-
-    cwar_pool_data.user_stake_count += 1u32;
-
-    save_pool_storage_account(&mut cwar_pool_data_byte_array, &cwar_pool_data)?;
-    save_user_storage_account(&mut user_data_byte_array, &user_storage_data)?;
-
-    Ok(())
+        reward_streams: [UserRewardStream::default(); MAX_EXTRA_REWARD_STREAMS],
+        unbonding_amount: 0u64,
+        next_unbonding_request_id: 0u64,
+        locked_amount: 0u64,
+        locked_weight: 0u64,
+        next_lock_position_id: 0u64,
+        allowlist_max_stake,
+        receipt_debt: 0u64,
+        reserved: [0u8; state::USER_RESERVED_BYTES],
+    })
 }
 
 #[inline(always)]
@@ -0,0 +1,210 @@
+use std::convert::TryInto;
+
+use crate::{
+    error::CryowarError,
+    state::{CwarPool, RewardSchedule, User},
+    utils::{
+        self, add_receipt_debt, assert_pool_storage_account, assert_signer, assert_staking_vault,
+        assert_token_program, assert_user_storage_account, burn_receipt,
+        get_pool_signer_address_and_bump_seed, get_receipt_balance, next_reward_schedule,
+        save_pool_storage_account, save_user_storage_account,
+    },
+};
+
+use solana_program::{
+    account_info::{next_account_info, AccountInfo},
+    clock::Clock,
+    entrypoint::ProgramResult,
+    msg,
+    program::invoke_signed,
+    program_error::ProgramError,
+    program_pack::Pack,
+    pubkey::Pubkey,
+    sysvar::Sysvar,
+};
+use spl_token::state::Account as TokenAccount;
+
+/// Returns the whole stake without penalty and ignores the pause flags and the locking period,
+/// in exchange the user gives up every reward not claimed yet
+pub fn process_emergency_unstake(accounts: &[AccountInfo], program_id: &Pubkey) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+    let user_wallet_account = next_account_info(account_info_iter)?;
+    let user_storage_account = next_account_info(account_info_iter)?;
+    let cwar_pool_storage_account = next_account_info(account_info_iter)?;
+    let staking_vault = next_account_info(account_info_iter)?;
+    let user_cwar_ata = next_account_info(account_info_iter)?;
+    let pool_signer_pda = next_account_info(account_info_iter)?;
+    let token_program = next_account_info(account_info_iter)?;
+
+    assert_signer(user_wallet_account)?;
+    assert_token_program(token_program)?;
+
+    let mut user_data_byte_array = user_storage_account.data.try_borrow_mut().unwrap();
+    let mut user_storage_data = assert_user_storage_account(
+        user_wallet_account,
+        cwar_pool_storage_account,
+        program_id,
+        user_storage_account,
+        &user_data_byte_array,
+    )?;
+
+    let mut cwar_pool_data_byte_array = cwar_pool_storage_account.data.try_borrow_mut().unwrap();
+    let mut cwar_pool_data = assert_pool_storage_account(
+        &cwar_pool_data_byte_array,
+        cwar_pool_storage_account,
+        program_id,
+    )?;
+    let reward_schedule = next_reward_schedule(account_info_iter, &cwar_pool_data, program_id)?;
+
+    // the cooldown is the point of an unbonding pool, it cannot be skipped in an emergency,
+    // `RequestUnstake` ignores the pause flags instead
+    if cwar_pool_data.unbonding_duration > 0u64 {
+        msg!("CryowarError::UnbondingRequired");
+        return Err(CryowarError::UnbondingRequired.into());
+    }
+
+    let cwar_staking_vault_data = TokenAccount::unpack(&staking_vault.data.borrow())?;
+    let (pool_signer_address, bump_seed) =
+        get_pool_signer_address_and_bump_seed(cwar_pool_storage_account.key, program_id);
+    assert_staking_vault(
+        staking_vault,
+        &cwar_pool_data,
+        &cwar_staking_vault_data,
+        &pool_signer_address,
+    )?;
+
+    let now: u64 = Clock::get()?.unix_timestamp.try_into().unwrap();
+    let amount_to_withdraw = emergency_unstake(
+        &mut cwar_pool_data,
+        reward_schedule.as_ref(),
+        &mut user_storage_data,
+        now,
+    )?;
+
+    msg!("Calling the token program to transfer CWAR to User from Staking Vault...");
+    invoke_signed(
+        &spl_token::instruction::transfer(
+            token_program.key,
+            staking_vault.key,
+            user_cwar_ata.key,
+            &pool_signer_address,
+            &[&pool_signer_address],
+            amount_to_withdraw,
+        )?,
+        &[
+            staking_vault.clone(),
+            user_cwar_ata.clone(),
+            pool_signer_pda.clone(),
+            token_program.clone(),
+        ],
+        &[&[&cwar_pool_storage_account.key.to_bytes(), &[bump_seed]]],
+    )?;
+    let cwar_staking_vault_data_after = TokenAccount::unpack(&staking_vault.data.borrow())?;
+    let actual_amount_withdrawn = cwar_staking_vault_data
+        .amount
+        .checked_sub(cwar_staking_vault_data_after.amount)
+        .ok_or(CryowarError::AmountOverflow)?;
+    if actual_amount_withdrawn > amount_to_withdraw {
+        msg!("CryowarError::InvalidTransferOperation");
+        return Err(CryowarError::InvalidTransferOperation.into());
+    }
+
+    if cwar_pool_data.has_receipt_mint() {
+        let receipt_mint = next_account_info(account_info_iter)?;
+        let user_receipt_ata = next_account_info(account_info_iter)?;
+        // receipts the user moved away cannot block the exit, they stay on the user as debt
+        let receipt_balance =
+            get_receipt_balance(user_receipt_ata, user_wallet_account.key, &cwar_pool_data)?;
+        let receipts_to_burn =
+            add_receipt_debt(&mut user_storage_data, amount_to_withdraw, receipt_balance)?;
+        if receipts_to_burn > 0u64 {
+            burn_receipt(
+                receipt_mint,
+                user_receipt_ata,
+                user_wallet_account,
+                token_program,
+                &cwar_pool_data,
+                receipts_to_burn,
+            )?;
+        }
+        if user_storage_data.receipt_debt > 0u64 {
+            msg!("receipt_debt: {}", user_storage_data.receipt_debt);
+        }
+    }
+
+    save_pool_storage_account(&mut cwar_pool_data_byte_array, &cwar_pool_data)?;
+    save_user_storage_account(&mut user_data_byte_array, &user_storage_data)?;
+
+    Ok(())
+}
+
+/// Takes the whole stake of the user off the books and forfeits everything they had earned,
+/// returning the amount to pay out. Reward accounting can never block the withdrawal: a pool
+/// checkpoint that fails freezes the pool accumulators at `now` before the weight drops, so the
+/// remaining stakers are never paid the skipped emissions at the smaller weight, and the user's
+/// reward debt is zeroed either way
+pub fn emergency_unstake(
+    cwar_pool_data: &mut CwarPool,
+    reward_schedule: Option<&RewardSchedule>,
+    user_storage_data: &mut User,
+    now: u64,
+) -> Result<u64, ProgramError> {
+    let amount_to_withdraw = user_storage_data.user_cwar_staked_amount;
+    if amount_to_withdraw == 0u64 {
+        msg!("CryowarError::InsufficientFundsToUnstake");
+        return Err(CryowarError::InsufficientFundsToUnstake.into());
+    }
+
+    // checkpoint the pool only, the remaining stakers keep everything emitted up to now
+    let mut checkpointed_pool_data = *cwar_pool_data;
+    match utils::update_rewards(&mut checkpointed_pool_data, reward_schedule, None, now) {
+        Ok(()) => *cwar_pool_data = checkpointed_pool_data,
+        Err(error) => {
+            msg!("Pool checkpoint skipped: {}", error);
+            utils::freeze_rewards(cwar_pool_data, now);
+        }
+    }
+    // whatever the user had earned is no longer owed and turns into rewards vault surplus,
+    // if it cannot be computed the pool keeps reserving it rather than blocking the withdrawal
+    let user_reward_weight = utils::get_user_reward_weight(user_storage_data).unwrap_or(0u64);
+    let rewards_forfeited = utils::get_user_updated_pending_rewards(
+        user_reward_weight,
+        cwar_pool_data.rewards_per_token_accumulated_till_now,
+        user_storage_data.rewards_per_token_accumulated_at_last_user_interaction,
+        user_storage_data.rewards_amount_pending,
+    )
+    .unwrap_or(0u64);
+    msg!("rewards_forfeited: {}", rewards_forfeited);
+    cwar_pool_data.rewards_owed = cwar_pool_data
+        .rewards_owed
+        .saturating_sub(rewards_forfeited);
+    for (user_reward_stream, reward_stream) in user_storage_data
+        .reward_streams
+        .iter_mut()
+        .zip(cwar_pool_data.reward_streams.iter_mut())
+    {
+        let stream_rewards_forfeited = utils::get_user_updated_pending_rewards(
+            user_reward_weight,
+            reward_stream.rewards_per_token_accumulated_till_now,
+            user_reward_stream.rewards_per_token_accumulated_at_last_user_interaction,
+            user_reward_stream.rewards_amount_pending,
+        )
+        .unwrap_or(0u64);
+        reward_stream.rewards_owed = reward_stream
+            .rewards_owed
+            .saturating_sub(stream_rewards_forfeited);
+        user_reward_stream.rewards_amount_pending = 0u64;
+        user_reward_stream.rewards_per_token_accumulated_at_last_user_interaction =
+            reward_stream.rewards_per_token_accumulated_till_now;
+    }
+
+    user_storage_data.user_cwar_staked_amount = 0u64;
+    user_storage_data.rewards_amount_pending = 0u64;
+    user_storage_data.rewards_per_token_accumulated_at_last_user_interaction =
+        cwar_pool_data.rewards_per_token_accumulated_till_now;
+    cwar_pool_data.total_staked = cwar_pool_data
+        .total_staked
+        .checked_sub(amount_to_withdraw)
+        .ok_or(CryowarError::AmountOverflow)?;
+    Ok(amount_to_withdraw)
+}
@@ -0,0 +1,218 @@
+use std::convert::TryInto;
+
+use crate::{
+    error::CryowarError,
+    processor::{
+        cancel_rewards::cancel_rewards,
+        close_pool::close_pool,
+        reduce_rewards::{reduce_rewards, refund_rewards},
+        remove_funder::remove_funder,
+        set_allowlist_root::set_allowlist_root,
+        set_lock_tiers::set_lock_tiers,
+        set_penality_routing::set_penality_routing,
+        set_penality_schedule::set_penality_schedule,
+        set_receipt_mint::{assert_receipt_mint_account, set_receipt_mint},
+        set_stake_limits::set_stake_limits,
+        set_timelock_delay::set_timelock_delay,
+        update_pool_config::update_pool_config,
+    },
+    state::PoolAction,
+    utils::{
+        self, assert_action_executable, assert_pending_action_account, assert_pool_storage_account,
+        assert_signer, next_reward_schedule, save_pool_storage_account,
+    },
+};
+use solana_program::{
+    account_info::{next_account_info, AccountInfo},
+    clock::Clock,
+    entrypoint::ProgramResult,
+    msg,
+    pubkey::Pubkey,
+    sysvar::Sysvar,
+};
+
+/// Anyone can execute a queued action once its eta has passed, until its grace period runs out
+pub fn process_execute_action(accounts: &[AccountInfo], program_id: &Pubkey) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+    let executor_wallet_account = next_account_info(account_info_iter)?;
+    let cwar_pool_storage_account = next_account_info(account_info_iter)?;
+    let pending_action_account = next_account_info(account_info_iter)?;
+    let rent_receiver_account = next_account_info(account_info_iter)?;
+
+    assert_signer(executor_wallet_account)?;
+
+    let mut cwar_pool_data_byte_array = cwar_pool_storage_account.data.try_borrow_mut().unwrap();
+    let mut cwar_pool_data = assert_pool_storage_account(
+        &cwar_pool_data_byte_array,
+        cwar_pool_storage_account,
+        program_id,
+    )?;
+
+    let mut pending_action_byte_array = pending_action_account.data.try_borrow_mut().unwrap();
+    let pending_action_data = assert_pending_action_account(
+        pending_action_account,
+        cwar_pool_storage_account,
+        program_id,
+        &pending_action_byte_array,
+    )?;
+    if pending_action_data.rent_payer != *rent_receiver_account.key {
+        msg!("CryowarError::RentReceiverMismatched");
+        return Err(CryowarError::RentReceiverMismatched.into());
+    }
+
+    let now: u64 = Clock::get()?.unix_timestamp.try_into().unwrap();
+    assert_action_executable(&pending_action_data, now)?;
+
+    msg!("Executing action {}", pending_action_data.action_id);
+    match pending_action_data.action {
+        PoolAction::RemoveFunder { funder_wallet } => {
+            remove_funder(&mut cwar_pool_data, &funder_wallet)?;
+        }
+        PoolAction::UpdatePoolConfig {
+            unstake_penality_basis_points,
+            locking_duration,
+            authority_penality_deposit_ata,
+            unbonding_duration,
+        } => {
+            let authority_penality_deposit_ata_account = match authority_penality_deposit_ata {
+                Some(_) => Some(next_account_info(account_info_iter)?),
+                None => None,
+            };
+            update_pool_config(
+                &mut cwar_pool_data,
+                unstake_penality_basis_points,
+                locking_duration,
+                authority_penality_deposit_ata,
+                authority_penality_deposit_ata_account,
+                unbonding_duration,
+                pending_action_data.eta > pending_action_data.queued_at,
+            )?;
+        }
+        PoolAction::ClosePool {
+            staking_refund_ata,
+            rewards_refund_ata,
+        } => {
+            let pool_owner_wallet_account = next_account_info(account_info_iter)?;
+            let staking_vault = next_account_info(account_info_iter)?;
+            let cwar_staking_refund_ata = next_account_info(account_info_iter)?;
+            let cwar_rewards_vault = next_account_info(account_info_iter)?;
+            let cwar_rewards_refund_ata = next_account_info(account_info_iter)?;
+            let pool_signer_pda = next_account_info(account_info_iter)?;
+            let token_program = next_account_info(account_info_iter)?;
+
+            if cwar_pool_data.owner_wallet != *pool_owner_wallet_account.key {
+                msg!("CryowarError::PoolOwnerMismatched");
+                return Err(CryowarError::PoolOwnerMismatched.into());
+            }
+            if *cwar_staking_refund_ata.key != staking_refund_ata
+                || *cwar_rewards_refund_ata.key != rewards_refund_ata
+            {
+                msg!("CryowarError::WrongTokenAccountPassed");
+                return Err(CryowarError::WrongTokenAccountPassed.into());
+            }
+            close_pool(
+                pool_owner_wallet_account,
+                staking_vault,
+                cwar_staking_refund_ata,
+                cwar_rewards_vault,
+                cwar_rewards_refund_ata,
+                cwar_pool_storage_account,
+                pool_signer_pda,
+                token_program,
+                &mut cwar_pool_data,
+                program_id,
+            )?;
+        }
+        PoolAction::SetTimelockDelay { timelock_delay } => {
+            set_timelock_delay(&mut cwar_pool_data, timelock_delay)?;
+        }
+        PoolAction::SetLockTiers { lock_tiers } => {
+            set_lock_tiers(&mut cwar_pool_data, lock_tiers)?;
+        }
+        PoolAction::SetPenalitySchedule { penality_schedule } => {
+            set_penality_schedule(
+                &mut cwar_pool_data,
+                penality_schedule,
+                pending_action_data.eta > pending_action_data.queued_at,
+            )?;
+        }
+        PoolAction::SetPenalityRouting { penality_routing } => {
+            set_penality_routing(&mut cwar_pool_data, penality_routing)?;
+        }
+        PoolAction::SetAllowlistRoot { allowlist_root } => {
+            set_allowlist_root(&mut cwar_pool_data, allowlist_root);
+        }
+        PoolAction::SetReceiptMint { receipt_mint } => {
+            let receipt_mint_account = next_account_info(account_info_iter)?;
+            let staking_mint_account = next_account_info(account_info_iter)?;
+            assert_receipt_mint_account(
+                receipt_mint_account,
+                staking_mint_account,
+                receipt_mint,
+                cwar_pool_storage_account.key,
+                &cwar_pool_data,
+                program_id,
+            )?;
+            set_receipt_mint(&mut cwar_pool_data, receipt_mint)?;
+        }
+        PoolAction::SetStakeLimits {
+            min_stake,
+            max_stake_per_user,
+            max_total_staked,
+        } => {
+            set_stake_limits(
+                &mut cwar_pool_data,
+                min_stake,
+                max_stake_per_user,
+                max_total_staked,
+            )?;
+        }
+        PoolAction::ReduceRewards {
+            rewards_refund_ata, ..
+        }
+        | PoolAction::CancelRewards { rewards_refund_ata } => {
+            let cwar_rewards_vault = next_account_info(account_info_iter)?;
+            let cwar_rewards_refund_ata = next_account_info(account_info_iter)?;
+            let pool_signer_pda = next_account_info(account_info_iter)?;
+            let token_program = next_account_info(account_info_iter)?;
+            let reward_schedule =
+                next_reward_schedule(account_info_iter, &cwar_pool_data, program_id)?;
+
+            if *cwar_rewards_refund_ata.key != rewards_refund_ata {
+                msg!("CryowarError::WrongTokenAccountPassed");
+                return Err(CryowarError::WrongTokenAccountPassed.into());
+            }
+            utils::update_rewards(&mut cwar_pool_data, reward_schedule.as_ref(), None, now)?;
+            let rewards_to_refund = match pending_action_data.action {
+                PoolAction::ReduceRewards {
+                    amount,
+                    new_reward_duration_end,
+                    ..
+                } => reduce_rewards(&mut cwar_pool_data, amount, new_reward_duration_end)?,
+                _ => cancel_rewards(&mut cwar_pool_data)?,
+            };
+            refund_rewards(
+                cwar_rewards_vault,
+                cwar_rewards_refund_ata,
+                cwar_pool_storage_account,
+                pool_signer_pda,
+                token_program,
+                &cwar_pool_data,
+                rewards_to_refund,
+                program_id,
+            )?;
+        }
+    }
+
+    save_pool_storage_account(&mut cwar_pool_data_byte_array, &cwar_pool_data)?;
+
+    // wipe the action so it cannot be replayed before the account is garbage collected
+    pending_action_byte_array.fill(0u8);
+    utils::close_account(
+        pending_action_account,
+        rent_receiver_account,
+        &mut pending_action_byte_array,
+    )?;
+
+    Ok(())
+}
@@ -0,0 +1,154 @@
+use crate::{
+    processor::{
+        claim_rewards::process_claim_rewards, close_user::process_close_user,
+        unstake_cwar::process_unstake_cwar,
+    },
+    utils::{
+        assert_pool_storage_account, assert_signer, assert_user_storage_account,
+        create_recipient_ata_if_missing,
+    },
+};
+use solana_program::{
+    account_info::{next_account_info, AccountInfo},
+    entrypoint::ProgramResult,
+    msg,
+    pubkey::Pubkey,
+};
+
+/// Runs `UnstakeCwar` for the whole stake, `ClaimRewards` and `CloseUser` in one go, so every
+/// check of those instructions applies. Lock positions and unbonding requests have to be settled
+/// beforehand, and a staked user of a pool with unbonding enabled can't exit since `UnstakeCwar`
+/// is refused there.
+pub fn process_exit(accounts: &[AccountInfo], program_id: &Pubkey) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+    let user_wallet_account = next_account_info(account_info_iter)?;
+    let user_storage_account = next_account_info(account_info_iter)?;
+    let cwar_pool_storage_account = next_account_info(account_info_iter)?;
+    let staking_vault = next_account_info(account_info_iter)?;
+    let cwar_rewards_vault = next_account_info(account_info_iter)?;
+    let user_cwar_ata = next_account_info(account_info_iter)?;
+    let user_rewards_ata = next_account_info(account_info_iter)?;
+    let pool_signer_pda = next_account_info(account_info_iter)?;
+    let token_program = next_account_info(account_info_iter)?;
+    let authority_penality_deposit_ata_account = next_account_info(account_info_iter)?;
+    let staking_mint_account = next_account_info(account_info_iter)?;
+    let reward_mint_account = next_account_info(account_info_iter)?;
+    let system_program_info = next_account_info(account_info_iter)?;
+    let associated_token_program = next_account_info(account_info_iter)?;
+
+    assert_signer(user_wallet_account)?;
+    let (
+        amount_to_withdraw,
+        penality_routing_burns,
+        penality_routing_uses_reward_vault,
+        has_receipt_mint,
+        has_reward_schedule,
+        staking_mint,
+        reward_mint,
+    ) = {
+        let user_data_byte_array = user_storage_account.data.try_borrow().unwrap();
+        let user_storage_data = assert_user_storage_account(
+            user_wallet_account,
+            cwar_pool_storage_account,
+            program_id,
+            user_storage_account,
+            &user_data_byte_array,
+        )?;
+        let cwar_pool_data_byte_array = cwar_pool_storage_account.data.try_borrow().unwrap();
+        let cwar_pool_data = assert_pool_storage_account(
+            &cwar_pool_data_byte_array,
+            cwar_pool_storage_account,
+            program_id,
+        )?;
+        (
+            user_storage_data.user_cwar_staked_amount,
+            cwar_pool_data.penality_routing.burn_basis_points > 0u16,
+            cwar_pool_data.penality_routing.uses_reward_vault(),
+            cwar_pool_data.has_receipt_mint(),
+            cwar_pool_data.has_reward_schedule(),
+            cwar_pool_data.staking_mint,
+            cwar_pool_data.reward_mint,
+        )
+    };
+    let reward_schedule_accounts = if has_reward_schedule {
+        vec![next_account_info(account_info_iter)?.clone()]
+    } else {
+        vec![]
+    };
+    let receipt_accounts = if has_receipt_mint {
+        vec![
+            next_account_info(account_info_iter)?.clone(),
+            next_account_info(account_info_iter)?.clone(),
+        ]
+    } else {
+        vec![]
+    };
+    let reward_stream_accounts = account_info_iter.as_slice();
+
+    // the user ATAs are created here so both instructions run with their own wallet layout
+    if amount_to_withdraw > 0u64 {
+        msg!("amount_to_withdraw: {}", amount_to_withdraw);
+        create_recipient_ata_if_missing(
+            user_cwar_ata,
+            user_wallet_account,
+            staking_mint_account,
+            &staking_mint,
+            user_wallet_account,
+            system_program_info,
+            token_program,
+            associated_token_program,
+        )?;
+        let mut unstake_accounts = vec![
+            user_wallet_account.clone(),
+            user_storage_account.clone(),
+            cwar_pool_storage_account.clone(),
+            staking_vault.clone(),
+            user_cwar_ata.clone(),
+            pool_signer_pda.clone(),
+            token_program.clone(),
+            authority_penality_deposit_ata_account.clone(),
+        ];
+        unstake_accounts.extend(reward_schedule_accounts.iter().cloned());
+        if penality_routing_burns {
+            unstake_accounts.push(staking_mint_account.clone());
+        }
+        if penality_routing_uses_reward_vault {
+            unstake_accounts.push(cwar_rewards_vault.clone());
+        }
+        unstake_accounts.extend(receipt_accounts);
+        process_unstake_cwar(&unstake_accounts, amount_to_withdraw, program_id)?;
+    }
+
+    create_recipient_ata_if_missing(
+        user_rewards_ata,
+        user_wallet_account,
+        reward_mint_account,
+        &reward_mint,
+        user_wallet_account,
+        system_program_info,
+        token_program,
+        associated_token_program,
+    )?;
+    let mut claim_accounts = vec![
+        user_wallet_account.clone(),
+        user_storage_account.clone(),
+        cwar_pool_storage_account.clone(),
+        staking_vault.clone(),
+        cwar_rewards_vault.clone(),
+        user_rewards_ata.clone(),
+        pool_signer_pda.clone(),
+        token_program.clone(),
+    ];
+    claim_accounts.extend(reward_schedule_accounts);
+    claim_accounts.extend_from_slice(reward_stream_accounts);
+    process_claim_rewards(&claim_accounts, program_id)?;
+
+    process_close_user(
+        &[
+            user_wallet_account.clone(),
+            user_storage_account.clone(),
+            cwar_pool_storage_account.clone(),
+        ],
+        program_id,
+    )
+}
@@ -0,0 +1,106 @@
+use std::convert::TryInto;
+
+use crate::{
+    error::CryowarError,
+    state::{CwarPool, LockPosition, User},
+    utils::{
+        self, assert_lock_position_account, assert_pool_storage_account,
+        assert_user_storage_account, next_reward_schedule, save_lock_position_account,
+        save_pool_storage_account, save_user_storage_account,
+    },
+};
+use solana_program::{
+    account_info::{next_account_info, AccountInfo},
+    clock::Clock,
+    entrypoint::ProgramResult,
+    msg,
+    pubkey::Pubkey,
+    sysvar::Sysvar,
+};
+
+/// Brings an unlocked position back to the weight of unlocked stake. Anyone can call it, so a
+/// lock that has passed stops earning its tier boost without waiting for the user to withdraw it
+pub fn process_expire_lock_position(
+    accounts: &[AccountInfo],
+    program_id: &Pubkey,
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+    let user_wallet_account = next_account_info(account_info_iter)?;
+    let user_storage_account = next_account_info(account_info_iter)?;
+    let cwar_pool_storage_account = next_account_info(account_info_iter)?;
+    let lock_position_account = next_account_info(account_info_iter)?;
+
+    let mut user_data_byte_array = user_storage_account.data.try_borrow_mut().unwrap();
+    let mut user_storage_data = assert_user_storage_account(
+        user_wallet_account,
+        cwar_pool_storage_account,
+        program_id,
+        user_storage_account,
+        &user_data_byte_array,
+    )?;
+
+    let mut cwar_pool_data_byte_array = cwar_pool_storage_account.data.try_borrow_mut().unwrap();
+    let mut cwar_pool_data = assert_pool_storage_account(
+        &cwar_pool_data_byte_array,
+        cwar_pool_storage_account,
+        program_id,
+    )?;
+    let reward_schedule = next_reward_schedule(account_info_iter, &cwar_pool_data, program_id)?;
+
+    let mut lock_position_byte_array = lock_position_account.data.try_borrow_mut().unwrap();
+    let mut lock_position_data = assert_lock_position_account(
+        lock_position_account,
+        user_storage_account,
+        program_id,
+        &lock_position_byte_array,
+    )?;
+
+    let now: u64 = Clock::get()?.unix_timestamp.try_into().unwrap();
+    utils::update_rewards(
+        &mut cwar_pool_data,
+        reward_schedule.as_ref(),
+        Some(&mut user_storage_data),
+        now,
+    )?;
+    expire_lock_position(
+        &mut cwar_pool_data,
+        &mut user_storage_data,
+        &mut lock_position_data,
+        now,
+    )?;
+    msg!("weight: {}", lock_position_data.weight);
+
+    save_lock_position_account(&mut lock_position_byte_array, &lock_position_data)?;
+    save_pool_storage_account(&mut cwar_pool_data_byte_array, &cwar_pool_data)?;
+    save_user_storage_account(&mut user_data_byte_array, &user_storage_data)?;
+
+    Ok(())
+}
+
+/// Drops the reward weight of a checkpointed user's lock position to its amount once `unlock_at`
+/// has passed, an already expired position is left as it is
+pub fn expire_lock_position(
+    cwar_pool_data: &mut CwarPool,
+    user_storage_data: &mut User,
+    lock_position_data: &mut LockPosition,
+    now: u64,
+) -> ProgramResult {
+    if now < lock_position_data.unlock_at {
+        msg!("CryowarError::LockPositionNotUnlocked");
+        msg!("unlock_at: {}", lock_position_data.unlock_at);
+        return Err(CryowarError::LockPositionNotUnlocked.into());
+    }
+    let boost_weight = lock_position_data
+        .weight
+        .saturating_sub(lock_position_data.amount);
+    user_storage_data.locked_weight = user_storage_data
+        .locked_weight
+        .checked_sub(boost_weight)
+        .ok_or(CryowarError::AmountOverflow)?;
+    cwar_pool_data.total_locked_weight = cwar_pool_data
+        .total_locked_weight
+        .checked_sub(boost_weight)
+        .ok_or(CryowarError::AmountOverflow)?;
+    lock_position_data.weight -= boost_weight;
+    Ok(())
+}
@@ -2,9 +2,11 @@ use std::convert::TryInto;
 
 use crate::{
     error::CryowarError,
+    state::PAUSE_FUND,
     utils::{
-        self, assert_pool_storage_account, assert_reward_vault, assert_signer,
-        assert_staking_vault, assert_token_program, save_pool_storage_account,
+        self, assert_not_paused, assert_pool_storage_account, assert_reward_vault, assert_signer,
+        assert_staking_vault, assert_token_program, next_reward_schedule,
+        save_pool_storage_account,
     },
 };
 use solana_program::{
@@ -41,6 +43,8 @@ pub fn process_fund_pool(
         cwar_pool_storage_account,
         program_id,
     )?;
+    assert_not_paused(&cwar_pool_data, PAUSE_FUND)?;
+    let reward_schedule = next_reward_schedule(account_info_iter, &cwar_pool_data, program_id)?;
 
     let mut is_funder_authorised = false;
     if *funder_wallet_account.key == cwar_pool_data.owner_wallet {
@@ -77,8 +81,7 @@ pub fn process_fund_pool(
         &pool_signer_address,
     )?;
     let now: u64 = Clock::get()?.unix_timestamp.try_into().unwrap();
-    let total_cwar_staked = cwar_staking_vault_data.amount;
-    utils::update_rewards(&mut cwar_pool_data, None, total_cwar_staked, now)?;
+    utils::update_rewards(&mut cwar_pool_data, reward_schedule.as_ref(), None, now)?;
 
     msg!("now: {}", now);
     msg!(
@@ -0,0 +1,139 @@
+use std::convert::TryInto;
+
+use crate::{
+    error::CryowarError,
+    state::{RewardStream, PAUSE_FUND},
+    utils::{
+        self, assert_not_paused, assert_pool_storage_account, assert_reward_stream_vault,
+        assert_signer, assert_token_program, get_pool_signer_address_and_bump_seed,
+        get_reward_stream, next_reward_schedule, save_pool_storage_account,
+    },
+};
+use solana_program::{
+    account_info::{next_account_info, AccountInfo},
+    clock::Clock,
+    entrypoint::ProgramResult,
+    msg,
+    program::invoke,
+    program_pack::Pack,
+    pubkey::Pubkey,
+    sysvar::Sysvar,
+};
+use spl_token::state::Account as TokenAccount;
+
+pub fn process_fund_reward_stream(
+    accounts: &[AccountInfo],
+    stream_index: u8,
+    amount: u64,
+    extend_duration: u64,
+    program_id: &Pubkey,
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+    let funder_wallet_account = next_account_info(account_info_iter)?;
+    let cwar_pool_storage_account = next_account_info(account_info_iter)?;
+    let reward_vault = next_account_info(account_info_iter)?;
+    let rewards_ata_to_debit = next_account_info(account_info_iter)?;
+    let token_program = next_account_info(account_info_iter)?;
+
+    assert_signer(funder_wallet_account)?;
+    assert_token_program(token_program)?;
+    let mut cwar_pool_data_byte_array = cwar_pool_storage_account.data.try_borrow_mut().unwrap();
+    let mut cwar_pool_data = assert_pool_storage_account(
+        &cwar_pool_data_byte_array,
+        cwar_pool_storage_account,
+        program_id,
+    )?;
+    assert_not_paused(&cwar_pool_data, PAUSE_FUND)?;
+    let reward_schedule = next_reward_schedule(account_info_iter, &cwar_pool_data, program_id)?;
+
+    if *funder_wallet_account.key != cwar_pool_data.owner_wallet
+        && !cwar_pool_data
+            .funders
+            .iter()
+            .any(|x| *x == *funder_wallet_account.key)
+    {
+        msg!("CryowarError::FundingAuthorityMismatched");
+        return Err(CryowarError::FundingAuthorityMismatched.into());
+    }
+
+    let reward_stream = get_reward_stream(&cwar_pool_data, stream_index)?;
+    let (pool_signer_address, _bump_seed) =
+        get_pool_signer_address_and_bump_seed(cwar_pool_storage_account.key, program_id);
+    let reward_vault_data = TokenAccount::unpack(&reward_vault.data.borrow())?;
+    assert_reward_stream_vault(
+        reward_vault,
+        &reward_stream,
+        &reward_vault_data,
+        &pool_signer_address,
+    )?;
+
+    let now: u64 = Clock::get()?.unix_timestamp.try_into().unwrap();
+    utils::update_rewards(&mut cwar_pool_data, reward_schedule.as_ref(), None, now)?;
+    fund_reward_stream(
+        &mut cwar_pool_data.reward_streams[stream_index as usize],
+        amount,
+        extend_duration,
+        now,
+    )?;
+
+    if amount > 0 {
+        msg!("Calling the token program to transfer rewards to the Reward Stream Vault...");
+        invoke(
+            &spl_token::instruction::transfer(
+                token_program.key,
+                rewards_ata_to_debit.key,
+                reward_vault.key,
+                funder_wallet_account.key,
+                &[],
+                amount,
+            )?,
+            &[
+                rewards_ata_to_debit.clone(),
+                reward_vault.clone(),
+                funder_wallet_account.clone(),
+                token_program.clone(),
+            ],
+        )?;
+    }
+
+    save_pool_storage_account(&mut cwar_pool_data_byte_array, &cwar_pool_data)?;
+    Ok(())
+}
+
+/// Adds `amount` to what a checkpointed stream has yet to emit and spreads it evenly until the
+/// stream end, pushed back by `extend_duration` or restarted from now if it already ended
+pub fn fund_reward_stream(
+    reward_stream: &mut RewardStream,
+    amount: u64,
+    extend_duration: u64,
+    now: u64,
+) -> ProgramResult {
+    let mut updated_reward_amount = amount;
+    if now < reward_stream.reward_duration_end {
+        let rewards_left_amount = (reward_stream.reward_duration_end - now)
+            .checked_mul(reward_stream.reward_rate)
+            .ok_or(CryowarError::AmountOverflow)?;
+        updated_reward_amount = amount
+            .checked_add(rewards_left_amount)
+            .ok_or(CryowarError::AmountOverflow)?;
+        reward_stream.reward_duration_end = reward_stream
+            .reward_duration_end
+            .checked_add(extend_duration)
+            .ok_or(CryowarError::AmountOverflow)?;
+    } else {
+        reward_stream.reward_duration_end = now
+            .checked_add(extend_duration)
+            .ok_or(CryowarError::AmountOverflow)?;
+    }
+    if reward_stream.reward_duration_end <= now {
+        msg!("CryowarError::DurationTooShort");
+        return Err(CryowarError::DurationTooShort.into());
+    }
+
+    reward_stream.reward_rate = updated_reward_amount
+        .checked_div(reward_stream.reward_duration_end - now)
+        .ok_or(CryowarError::AmountOverflow)?;
+    reward_stream.last_update_time = now;
+    msg!("reward_stream.reward_rate: {}", reward_stream.reward_rate);
+    Ok(())
+}
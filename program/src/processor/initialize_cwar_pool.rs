@@ -1,7 +1,10 @@
 use crate::{
     error::CryowarError,
     state::{AccTypesWithVersion, CwarPool, CWAR_POOL_STORAGE_TOTAL_BYTES},
-    utils::{assert_signer, assert_token_program, save_pool_storage_account},
+    utils::{
+        assert_penality_deposit_ata_data, assert_pool_config, assert_signer, assert_token_program,
+        save_pool_storage_account,
+    },
 };
 
 use borsh::BorshDeserialize;
@@ -142,20 +145,11 @@ pub fn process_initialize_cwar_pool(
         return Err(CryowarError::AccountOwnerShouldBeTokenProgram.into());
     }
 
-    let authority_penality_deposit_ata_data =
-        TokenAccount::unpack(&authority_penality_deposit_ata_account.data.borrow())?;
-    if authority_penality_deposit_ata_data.mint != *staking_mint.key {
-        msg!("CryowarError::MintMismatched");
-        return Err(CryowarError::MintMismatched.into());
-    }
-    if authority_penality_deposit_ata_data.owner != *pool_owner_wallet_account.key {
-        msg!("CryowarError::InvalidAuthorityPenalityDepositATA");
-        return Err(CryowarError::InvalidAuthorityPenalityDepositATA.into());
-    }
-    if authority_penality_deposit_ata_account.owner != &spl_token::id() {
-        msg!("CryowarError::AccountOwnerShouldBeTokenProgram");
-        return Err(CryowarError::AccountOwnerShouldBeTokenProgram.into());
-    }
+    assert_penality_deposit_ata_data(
+        authority_penality_deposit_ata_account,
+        staking_mint.key,
+        pool_owner_wallet_account.key,
+    )?;
 
     let mut cwar_pool_data_byte_array = cwar_pool_storage_account.data.try_borrow_mut().unwrap();
     let mut cwar_pool_data: CwarPool =
@@ -166,12 +160,13 @@ pub fn process_initialize_cwar_pool(
         msg!("CryowarError::PoolAddressAlreadyInitialized");
         return Err(CryowarError::PoolAddressAlreadyInitialized.into());
     }
-    if unstake_penality_basis_points_input != 0u16 && locking_duration_input != 0u64 {
-        msg!("CryowarError::PoolEitherCanHaveUnstakePanelityOrLockingDuration");
-        return Err(CryowarError::PoolEitherCanHaveUnstakePanelityOrLockingDuration.into());
-    }
+    assert_pool_config(
+        unstake_penality_basis_points_input,
+        locking_duration_input,
+        0u64,
+    )?;
 
-    cwar_pool_data.acc_type = AccTypesWithVersion::CwarPoolDataV1 as u8;
+    cwar_pool_data.acc_type = AccTypesWithVersion::CwarPoolDataV2 as u8;
     cwar_pool_data.owner_wallet = *pool_owner_wallet_account.key;
     cwar_pool_data.staking_vault = *staking_vault.key;
     cwar_pool_data.staking_mint = *staking_mint.key;
@@ -187,6 +182,13 @@ pub fn process_initialize_cwar_pool(
     cwar_pool_data.unstake_penality_basis_points = unstake_penality_basis_points_input;
     cwar_pool_data.locking_duration = locking_duration_input;
     cwar_pool_data.authority_penality_deposit_ata = *authority_penality_deposit_ata_account.key;
+    cwar_pool_data.total_staked = 0u64;
+    cwar_pool_data.rewards_owed = 0u64;
+    cwar_pool_data.pending_owner = Pubkey::default();
+    cwar_pool_data.pauser = Pubkey::default();
+    cwar_pool_data.paused_operations = 0u8;
+    cwar_pool_data.timelock_delay = 0u64;
+    cwar_pool_data.next_action_id = 0u64;
 
     save_pool_storage_account(&mut cwar_pool_data_byte_array, &cwar_pool_data)?;
 
@@ -0,0 +1,147 @@
+use std::convert::TryInto;
+
+use crate::{
+    error::CryowarError,
+    state::{
+        AccTypesWithVersion, CwarPool, CwarPoolV1, CWAR_POOL_STORAGE_TOTAL_BYTES,
+        CWAR_POOL_V1_STORAGE_TOTAL_BYTES,
+    },
+    utils::{
+        self, assert_reward_vault, assert_signer, assert_staking_vault, assert_system_program,
+        get_pool_signer_address_and_bump_seed, save_pool_storage_account,
+    },
+};
+
+use borsh::BorshDeserialize;
+use solana_program::{
+    account_info::{next_account_info, AccountInfo},
+    clock::Clock,
+    entrypoint::ProgramResult,
+    msg,
+    program::invoke,
+    program_pack::Pack,
+    pubkey::Pubkey,
+    rent::Rent,
+    system_instruction,
+    sysvar::Sysvar,
+};
+use spl_token::state::Account as TokenAccount;
+
+/// Anyone can migrate a pool since nothing of it is up to the caller, so users never wait on the
+/// owner to reach `EmergencyUnstake`
+pub fn process_migrate_pool(accounts: &[AccountInfo], program_id: &Pubkey) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+    let payer_wallet_account = next_account_info(account_info_iter)?;
+    let cwar_pool_storage_account = next_account_info(account_info_iter)?;
+    let staking_vault = next_account_info(account_info_iter)?;
+    let cwar_rewards_vault = next_account_info(account_info_iter)?;
+    let system_program_info = next_account_info(account_info_iter)?;
+
+    assert_signer(payer_wallet_account)?;
+    assert_system_program(system_program_info)?;
+
+    if cwar_pool_storage_account.owner != program_id {
+        msg!("CryowarError::PoolAccountOwnerShouldBeCwarProgram");
+        return Err(CryowarError::AccountOwnerShouldBeCwarProgram.into());
+    }
+    if cwar_pool_storage_account.data_len() != CWAR_POOL_V1_STORAGE_TOTAL_BYTES {
+        msg!("CryowarError::DataSizeNotMatched");
+        return Err(CryowarError::DataSizeNotMatched.into());
+    }
+    let cwar_pool_data_v1 = CwarPoolV1::try_from_slice(
+        &cwar_pool_storage_account.data.borrow()[0usize..CWAR_POOL_V1_STORAGE_TOTAL_BYTES],
+    )
+    .unwrap();
+    if cwar_pool_data_v1.acc_type != AccTypesWithVersion::CwarPoolDataV1 as u8 {
+        msg!("CryowarError::ExpectedAccountTypeMismatched");
+        return Err(CryowarError::ExpectedAccountTypeMismatched.into());
+    }
+
+    let mut cwar_pool_data = cwar_pool_data_v1.into_current();
+    // the vault balance could not tell stake and rewards apart
+    if cwar_pool_data.staking_vault == cwar_pool_data.reward_vault {
+        msg!("CryowarError::InvalidRewardsVault");
+        return Err(CryowarError::InvalidRewardsVault.into());
+    }
+
+    let (pool_signer_address, _bump_seed) =
+        get_pool_signer_address_and_bump_seed(cwar_pool_storage_account.key, program_id);
+    let cwar_staking_vault_data = TokenAccount::unpack(&staking_vault.data.borrow())?;
+    assert_staking_vault(
+        staking_vault,
+        &cwar_pool_data,
+        &cwar_staking_vault_data,
+        &pool_signer_address,
+    )?;
+
+    let cwar_rewards_vault_data = TokenAccount::unpack(&cwar_rewards_vault.data.borrow())?;
+    assert_reward_vault(
+        cwar_rewards_vault,
+        &cwar_pool_data,
+        &cwar_rewards_vault_data,
+        &pool_signer_address,
+    )?;
+
+    let now: u64 = Clock::get()?.unix_timestamp.try_into().unwrap();
+    migrate_pool_totals(
+        &mut cwar_pool_data,
+        cwar_staking_vault_data.amount,
+        cwar_rewards_vault_data.amount,
+        now,
+    )?;
+
+    let rent = Rent::get()?;
+    let required_lamports = rent
+        .minimum_balance(CWAR_POOL_STORAGE_TOTAL_BYTES)
+        .saturating_sub(cwar_pool_storage_account.lamports());
+    if required_lamports > 0 {
+        msg!(
+            "Transfer {} lamports to the pool storage account",
+            required_lamports
+        );
+        invoke(
+            &system_instruction::transfer(
+                payer_wallet_account.key,
+                cwar_pool_storage_account.key,
+                required_lamports,
+            ),
+            &[
+                payer_wallet_account.clone(),
+                cwar_pool_storage_account.clone(),
+                system_program_info.clone(),
+            ],
+        )?;
+    }
+
+    msg!("Reallocating the pool storage account to the V2 layout...");
+    cwar_pool_storage_account.realloc(CWAR_POOL_STORAGE_TOTAL_BYTES, true)?;
+
+    let mut cwar_pool_data_byte_array = cwar_pool_storage_account.data.try_borrow_mut().unwrap();
+    save_pool_storage_account(&mut cwar_pool_data_byte_array, &cwar_pool_data)?;
+
+    Ok(())
+}
+
+/// Seeds the totals V1 pools did not track and switches `cwar_pool_data` over to V2. The staking
+/// vault only holds principal once stake and rewards live in separate vaults, so its balance can
+/// only over-count `total_staked`, never leave user principal to `RecoverSurplus`
+pub fn migrate_pool_totals(
+    cwar_pool_data: &mut CwarPool,
+    staking_vault_balance: u64,
+    rewards_vault_balance: u64,
+    now: u64,
+) -> ProgramResult {
+    // without users whatever the vault holds is surplus
+    cwar_pool_data.total_staked = if cwar_pool_data.user_stake_count == 0u32 {
+        0u64
+    } else {
+        staking_vault_balance
+    };
+    utils::update_rewards(cwar_pool_data, None, None, now)?;
+
+    // unclaimed rewards were never tracked, so treat everything not yet emitted as owed
+    let rewards_left_amount = utils::get_rewards_left_amount(cwar_pool_data)?;
+    cwar_pool_data.rewards_owed = rewards_vault_balance.saturating_sub(rewards_left_amount);
+    cwar_pool_data.acc_type = AccTypesWithVersion::CwarPoolDataV2 as u8;
+    Ok(())
+}
@@ -0,0 +1,80 @@
+use crate::{
+    error::CryowarError,
+    state::{AccTypesWithVersion, USER_STORAGE_TOTAL_BYTES, USER_V1_STORAGE_TOTAL_BYTES},
+    utils::{
+        assert_pool_storage_account, assert_signer, assert_system_program,
+        assert_user_storage_account, save_user_storage_account,
+    },
+};
+
+use solana_program::{
+    account_info::{next_account_info, AccountInfo},
+    entrypoint::ProgramResult,
+    msg,
+    program::invoke,
+    pubkey::Pubkey,
+    rent::Rent,
+    system_instruction,
+    sysvar::Sysvar,
+};
+
+pub fn process_migrate_user(accounts: &[AccountInfo], program_id: &Pubkey) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+    let user_wallet_account = next_account_info(account_info_iter)?;
+    let user_storage_account = next_account_info(account_info_iter)?;
+    let cwar_pool_storage_account = next_account_info(account_info_iter)?;
+    let system_program_info = next_account_info(account_info_iter)?;
+
+    assert_signer(user_wallet_account)?;
+    assert_system_program(system_program_info)?;
+
+    assert_pool_storage_account(
+        &cwar_pool_storage_account.data.borrow(),
+        cwar_pool_storage_account,
+        program_id,
+    )?;
+
+    let mut user_storage_data = assert_user_storage_account(
+        user_wallet_account,
+        cwar_pool_storage_account,
+        program_id,
+        user_storage_account,
+        &user_storage_account.data.borrow(),
+    )?;
+    if user_storage_account.data_len() != USER_V1_STORAGE_TOTAL_BYTES {
+        msg!("CryowarError::ExpectedAccountTypeMismatched");
+        return Err(CryowarError::ExpectedAccountTypeMismatched.into());
+    }
+    user_storage_data.acc_type = AccTypesWithVersion::UserDataV2 as u8;
+
+    let rent = Rent::get()?;
+    let required_lamports = rent
+        .minimum_balance(USER_STORAGE_TOTAL_BYTES)
+        .saturating_sub(user_storage_account.lamports());
+    if required_lamports > 0 {
+        msg!(
+            "Transfer {} lamports to the user storage account",
+            required_lamports
+        );
+        invoke(
+            &system_instruction::transfer(
+                user_wallet_account.key,
+                user_storage_account.key,
+                required_lamports,
+            ),
+            &[
+                user_wallet_account.clone(),
+                user_storage_account.clone(),
+                system_program_info.clone(),
+            ],
+        )?;
+    }
+
+    msg!("Reallocating the user storage account to the V2 layout...");
+    user_storage_account.realloc(USER_STORAGE_TOTAL_BYTES, true)?;
+
+    let mut user_data_byte_array = user_storage_account.data.try_borrow_mut().unwrap();
+    save_user_storage_account(&mut user_data_byte_array, &user_storage_data)?;
+
+    Ok(())
+}
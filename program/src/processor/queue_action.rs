@@ -0,0 +1,139 @@
+use std::convert::TryInto;
+
+use crate::{
+    error::CryowarError,
+    processor::create_user::create_and_allocate_account_raw,
+    state::{AccTypesWithVersion, PendingAction, PoolAction, PENDING_ACTION_STORAGE_TOTAL_BYTES},
+    utils::{
+        assert_lock_tiers, assert_penality_routing, assert_penality_schedule,
+        assert_pool_storage_account, assert_signer, assert_stake_limits, assert_system_program,
+        constants::{MAX_TIMELOCK_DELAY, MAX_UNBONDING_DURATION},
+        get_pending_action_address_and_bump_seed, save_pending_action_account,
+        save_pool_storage_account, MAX_UNSTAKE_PENALITY_BASIS_POINTS,
+    },
+};
+use solana_program::{
+    account_info::{next_account_info, AccountInfo},
+    clock::Clock,
+    entrypoint::ProgramResult,
+    msg,
+    program_error::ProgramError,
+    pubkey::Pubkey,
+    sysvar::Sysvar,
+};
+
+pub fn process_queue_action(
+    accounts: &[AccountInfo],
+    action: PoolAction,
+    program_id: &Pubkey,
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+    let pool_owner_wallet_account = next_account_info(account_info_iter)?;
+    let cwar_pool_storage_account = next_account_info(account_info_iter)?;
+    let pending_action_account = next_account_info(account_info_iter)?;
+    let system_program_info = next_account_info(account_info_iter)?;
+
+    assert_signer(pool_owner_wallet_account)?;
+    assert_system_program(system_program_info)?;
+
+    let mut cwar_pool_data_byte_array = cwar_pool_storage_account.data.try_borrow_mut().unwrap();
+    let mut cwar_pool_data = assert_pool_storage_account(
+        &cwar_pool_data_byte_array,
+        cwar_pool_storage_account,
+        program_id,
+    )?;
+
+    if cwar_pool_data.owner_wallet != *pool_owner_wallet_account.key {
+        msg!("CryowarError::PoolOwnerMismatched");
+        return Err(CryowarError::PoolOwnerMismatched.into());
+    }
+
+    // catch out of range values early, everything else is checked against the pool on execution
+    match action {
+        PoolAction::UpdatePoolConfig {
+            unstake_penality_basis_points: Some(unstake_penality_basis_points),
+            ..
+        } if unstake_penality_basis_points > MAX_UNSTAKE_PENALITY_BASIS_POINTS => {
+            msg!("CryowarError::UnstakePenalityTooHigh");
+            return Err(CryowarError::UnstakePenalityTooHigh.into());
+        }
+        PoolAction::UpdatePoolConfig {
+            unbonding_duration: Some(unbonding_duration),
+            ..
+        } if unbonding_duration > MAX_UNBONDING_DURATION => {
+            msg!("CryowarError::InvalidUnbondingDuration");
+            return Err(CryowarError::InvalidUnbondingDuration.into());
+        }
+        PoolAction::SetTimelockDelay { timelock_delay } if timelock_delay > MAX_TIMELOCK_DELAY => {
+            msg!("CryowarError::InvalidTimelockDelay");
+            return Err(CryowarError::InvalidTimelockDelay.into());
+        }
+        PoolAction::SetLockTiers { lock_tiers } => {
+            assert_lock_tiers(&lock_tiers)?;
+        }
+        PoolAction::SetPenalitySchedule { penality_schedule } => {
+            assert_penality_schedule(&penality_schedule)?;
+        }
+        PoolAction::SetPenalityRouting { penality_routing } => {
+            assert_penality_routing(&penality_routing, &cwar_pool_data)?;
+        }
+        PoolAction::SetStakeLimits {
+            min_stake,
+            max_stake_per_user,
+            max_total_staked,
+        } => {
+            assert_stake_limits(min_stake, max_stake_per_user, max_total_staked)?;
+        }
+        _ => {}
+    }
+
+    let action_id = cwar_pool_data.next_action_id;
+    let (pending_action_address, bump_seed) = get_pending_action_address_and_bump_seed(
+        cwar_pool_storage_account.key,
+        action_id,
+        program_id,
+    );
+    if pending_action_address != *pending_action_account.key {
+        msg!("Error: Pending Action address does not match seed derivation");
+        return Err(ProgramError::InvalidSeeds);
+    }
+
+    create_and_allocate_account_raw(
+        *program_id,
+        pending_action_account,
+        system_program_info,
+        pool_owner_wallet_account,
+        PENDING_ACTION_STORAGE_TOTAL_BYTES,
+        &[
+            b"pending_action",
+            &cwar_pool_storage_account.key.to_bytes(),
+            &action_id.to_le_bytes(),
+            &[bump_seed],
+        ],
+    )?;
+
+    let now: u64 = Clock::get()?.unix_timestamp.try_into().unwrap();
+    let pending_action_data = PendingAction {
+        acc_type: AccTypesWithVersion::PendingActionDataV1 as u8,
+        cwar_pool: *cwar_pool_storage_account.key,
+        action_id,
+        queued_at: now,
+        eta: now
+            .checked_add(cwar_pool_data.timelock_delay)
+            .ok_or(CryowarError::AmountOverflow)?,
+        rent_payer: *pool_owner_wallet_account.key,
+        action,
+    };
+    msg!("action_id: {}", action_id);
+    msg!("eta: {}", pending_action_data.eta);
+
+    cwar_pool_data.next_action_id = action_id
+        .checked_add(1u64)
+        .ok_or(CryowarError::AmountOverflow)?;
+
+    let mut pending_action_byte_array = pending_action_account.data.try_borrow_mut().unwrap();
+    save_pending_action_account(&mut pending_action_byte_array, &pending_action_data)?;
+    save_pool_storage_account(&mut cwar_pool_data_byte_array, &cwar_pool_data)?;
+
+    Ok(())
+}
@@ -0,0 +1,154 @@
+use std::convert::TryInto;
+
+use crate::{
+    error::CryowarError,
+    utils::{
+        self, assert_pool_storage_account, assert_reward_vault, assert_signer,
+        assert_staking_vault, assert_token_program, get_pool_signer_address_and_bump_seed,
+        get_reward_liabilities, get_staking_vault_liabilities, get_staking_vault_surplus,
+        next_reward_schedule, save_pool_storage_account,
+    },
+};
+use solana_program::{
+    account_info::{next_account_info, AccountInfo},
+    clock::Clock,
+    entrypoint::ProgramResult,
+    msg,
+    program::invoke_signed,
+    program_pack::Pack,
+    pubkey::Pubkey,
+    sysvar::Sysvar,
+};
+use spl_token::state::Account as TokenAccount;
+
+pub fn process_recover_surplus(accounts: &[AccountInfo], program_id: &Pubkey) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+    let pool_owner_wallet_account = next_account_info(account_info_iter)?;
+    let cwar_pool_storage_account = next_account_info(account_info_iter)?;
+    let staking_vault = next_account_info(account_info_iter)?;
+    let staking_surplus_ata = next_account_info(account_info_iter)?;
+    let cwar_rewards_vault = next_account_info(account_info_iter)?;
+    let rewards_surplus_ata = next_account_info(account_info_iter)?;
+    let pool_signer_pda = next_account_info(account_info_iter)?;
+    let token_program = next_account_info(account_info_iter)?;
+
+    assert_signer(pool_owner_wallet_account)?;
+    assert_token_program(token_program)?;
+
+    let mut cwar_pool_data_byte_array = cwar_pool_storage_account.data.try_borrow_mut().unwrap();
+    let mut cwar_pool_data = assert_pool_storage_account(
+        &cwar_pool_data_byte_array,
+        cwar_pool_storage_account,
+        program_id,
+    )?;
+    let reward_schedule = next_reward_schedule(account_info_iter, &cwar_pool_data, program_id)?;
+
+    if cwar_pool_data.owner_wallet != *pool_owner_wallet_account.key {
+        msg!("CryowarError::PoolOwnerMismatched");
+        return Err(CryowarError::PoolOwnerMismatched.into());
+    }
+
+    let (pool_signer_address, bump_seed) =
+        get_pool_signer_address_and_bump_seed(cwar_pool_storage_account.key, program_id);
+    if pool_signer_address != *pool_signer_pda.key {
+        msg!("CryowarError::InvalidSignerPDA");
+        return Err(CryowarError::InvalidSignerPDA.into());
+    }
+
+    let cwar_staking_vault_data = TokenAccount::unpack(&staking_vault.data.borrow())?;
+    assert_staking_vault(
+        staking_vault,
+        &cwar_pool_data,
+        &cwar_staking_vault_data,
+        &pool_signer_address,
+    )?;
+    let cwar_rewards_vault_data = TokenAccount::unpack(&cwar_rewards_vault.data.borrow())?;
+    assert_reward_vault(
+        cwar_rewards_vault,
+        &cwar_pool_data,
+        &cwar_rewards_vault_data,
+        &pool_signer_address,
+    )?;
+
+    let now: u64 = Clock::get()?.unix_timestamp.try_into().unwrap();
+    utils::update_rewards(&mut cwar_pool_data, reward_schedule.as_ref(), None, now)?;
+    let reward_liabilities = get_reward_liabilities(&cwar_pool_data)?;
+
+    let (staking_surplus, rewards_surplus) = if staking_vault.key == cwar_rewards_vault.key {
+        let total_liabilities = get_staking_vault_liabilities(&cwar_pool_data)?
+            .checked_add(reward_liabilities)
+            .ok_or(CryowarError::AmountOverflow)?;
+        (
+            cwar_staking_vault_data
+                .amount
+                .saturating_sub(total_liabilities),
+            0u64,
+        )
+    } else {
+        (
+            get_staking_vault_surplus(cwar_staking_vault_data.amount, &cwar_pool_data),
+            cwar_rewards_vault_data
+                .amount
+                .saturating_sub(reward_liabilities),
+        )
+    };
+    msg!("staking_surplus: {}", staking_surplus);
+    msg!("rewards_surplus: {}", rewards_surplus);
+
+    if staking_surplus == 0u64 && rewards_surplus == 0u64 {
+        msg!("CryowarError::NoSurplusToRecover");
+        return Err(CryowarError::NoSurplusToRecover.into());
+    }
+
+    if staking_surplus > 0u64 {
+        msg!("Calling the token program to transfer surplus CWAR from Staking Vault...");
+        invoke_signed(
+            &spl_token::instruction::transfer(
+                token_program.key,
+                staking_vault.key,
+                staking_surplus_ata.key,
+                &pool_signer_address,
+                &[&pool_signer_address],
+                staking_surplus,
+            )?,
+            &[
+                staking_vault.clone(),
+                staking_surplus_ata.clone(),
+                pool_signer_pda.clone(),
+                token_program.clone(),
+            ],
+            &[&[&cwar_pool_storage_account.key.to_bytes(), &[bump_seed]]],
+        )?;
+    }
+
+    if rewards_surplus > 0u64 {
+        msg!("Calling the token program to transfer surplus rewards from Rewards Vault...");
+        invoke_signed(
+            &spl_token::instruction::transfer(
+                token_program.key,
+                cwar_rewards_vault.key,
+                rewards_surplus_ata.key,
+                &pool_signer_address,
+                &[&pool_signer_address],
+                rewards_surplus,
+            )?,
+            &[
+                cwar_rewards_vault.clone(),
+                rewards_surplus_ata.clone(),
+                pool_signer_pda.clone(),
+                token_program.clone(),
+            ],
+            &[&[&cwar_pool_storage_account.key.to_bytes(), &[bump_seed]]],
+        )?;
+    }
+
+    let cwar_staking_vault_data_after = TokenAccount::unpack(&staking_vault.data.borrow())?;
+    if cwar_staking_vault_data_after.amount < get_staking_vault_liabilities(&cwar_pool_data)? {
+        msg!("CryowarError::InvalidTransferOperation");
+        return Err(CryowarError::InvalidTransferOperation.into());
+    }
+
+    save_pool_storage_account(&mut cwar_pool_data_byte_array, &cwar_pool_data)?;
+
+    Ok(())
+}
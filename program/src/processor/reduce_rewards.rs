@@ -0,0 +1,184 @@
+use std::convert::TryInto;
+
+use crate::{
+    error::CryowarError,
+    state::CwarPool,
+    utils::{
+        self, assert_not_timelocked, assert_pool_storage_account, assert_reward_vault,
+        assert_signer, assert_token_program, get_pool_signer_address_and_bump_seed,
+        get_reward_liabilities, get_rewards_left_amount, get_staking_vault_liabilities,
+        next_reward_schedule, save_pool_storage_account,
+    },
+};
+use solana_program::{
+    account_info::{next_account_info, AccountInfo},
+    clock::Clock,
+    entrypoint::ProgramResult,
+    msg,
+    program::invoke_signed,
+    program_error::ProgramError,
+    program_pack::Pack,
+    pubkey::Pubkey,
+    sysvar::Sysvar,
+};
+use spl_token::state::Account as TokenAccount;
+
+pub fn process_reduce_rewards(
+    accounts: &[AccountInfo],
+    amount: u64,
+    new_reward_duration_end: u64,
+    program_id: &Pubkey,
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+    let pool_owner_wallet_account = next_account_info(account_info_iter)?;
+    let cwar_pool_storage_account = next_account_info(account_info_iter)?;
+    let cwar_rewards_vault = next_account_info(account_info_iter)?;
+    let cwar_rewards_refund_ata = next_account_info(account_info_iter)?;
+    let pool_signer_pda = next_account_info(account_info_iter)?;
+    let token_program = next_account_info(account_info_iter)?;
+
+    assert_signer(pool_owner_wallet_account)?;
+
+    let mut cwar_pool_data_byte_array = cwar_pool_storage_account.data.try_borrow_mut().unwrap();
+    let mut cwar_pool_data = assert_pool_storage_account(
+        &cwar_pool_data_byte_array,
+        cwar_pool_storage_account,
+        program_id,
+    )?;
+    let reward_schedule = next_reward_schedule(account_info_iter, &cwar_pool_data, program_id)?;
+
+    if cwar_pool_data.owner_wallet != *pool_owner_wallet_account.key {
+        msg!("CryowarError::PoolOwnerMismatched");
+        return Err(CryowarError::PoolOwnerMismatched.into());
+    }
+
+    assert_not_timelocked(&cwar_pool_data)?;
+
+    let now: u64 = Clock::get()?.unix_timestamp.try_into().unwrap();
+    utils::update_rewards(&mut cwar_pool_data, reward_schedule.as_ref(), None, now)?;
+    let rewards_to_refund = reduce_rewards(&mut cwar_pool_data, amount, new_reward_duration_end)?;
+    refund_rewards(
+        cwar_rewards_vault,
+        cwar_rewards_refund_ata,
+        cwar_pool_storage_account,
+        pool_signer_pda,
+        token_program,
+        &cwar_pool_data,
+        rewards_to_refund,
+        program_id,
+    )?;
+
+    save_pool_storage_account(&mut cwar_pool_data_byte_array, &cwar_pool_data)?;
+
+    Ok(())
+}
+
+/// Takes `amount` out of what the current stream has yet to emit and makes it end at
+/// `new_reward_duration_end`, spreading the rest evenly until then. Expects `update_rewards`
+/// to have checkpointed the pool, so rewards already earned are untouched. Returns the rewards
+/// no longer allocated, which includes the rounding left over by the new rate.
+pub fn reduce_rewards(
+    cwar_pool_data: &mut CwarPool,
+    amount: u64,
+    new_reward_duration_end: u64,
+) -> Result<u64, ProgramError> {
+    let rewards_left_amount = get_rewards_left_amount(cwar_pool_data)?;
+    let checkpoint = cwar_pool_data.total_stake_last_update_time;
+    msg!("rewards_left_amount: {}", rewards_left_amount);
+
+    if amount > rewards_left_amount
+        || new_reward_duration_end < checkpoint
+        || new_reward_duration_end > cwar_pool_data.reward_duration_end
+    {
+        msg!("CryowarError::InvalidRewardsReduction");
+        return Err(CryowarError::InvalidRewardsReduction.into());
+    }
+    let new_rewards_left_amount = rewards_left_amount - amount;
+    let new_reward_duration = new_reward_duration_end - checkpoint;
+    if new_reward_duration == 0u64 && new_rewards_left_amount > 0u64 {
+        msg!("CryowarError::InvalidRewardsReduction");
+        return Err(CryowarError::InvalidRewardsReduction.into());
+    }
+
+    cwar_pool_data.reward_rate = new_rewards_left_amount
+        .checked_div(new_reward_duration)
+        .unwrap_or(0u64);
+    cwar_pool_data.reward_duration_end = new_reward_duration_end;
+    if new_reward_duration > 0u64 {
+        cwar_pool_data.reward_duration = new_reward_duration;
+    }
+    msg!("cwar_pool_data.reward_rate: {}", cwar_pool_data.reward_rate);
+
+    let rewards_to_refund = rewards_left_amount
+        .checked_sub(get_rewards_left_amount(cwar_pool_data)?)
+        .ok_or(CryowarError::AmountOverflow)?;
+    if rewards_to_refund == 0u64 {
+        msg!("CryowarError::InvalidRewardsReduction");
+        return Err(CryowarError::InvalidRewardsReduction.into());
+    }
+    msg!("rewards_to_refund: {}", rewards_to_refund);
+    Ok(rewards_to_refund)
+}
+
+/// Sends rewards no longer allocated back out of the rewards vault, making sure the vault
+/// still covers everything owed afterwards
+#[allow(clippy::too_many_arguments)]
+pub fn refund_rewards<'a>(
+    cwar_rewards_vault: &AccountInfo<'a>,
+    cwar_rewards_refund_ata: &AccountInfo<'a>,
+    cwar_pool_storage_account: &AccountInfo<'a>,
+    pool_signer_pda: &AccountInfo<'a>,
+    token_program: &AccountInfo<'a>,
+    cwar_pool_data: &CwarPool,
+    rewards_to_refund: u64,
+    program_id: &Pubkey,
+) -> ProgramResult {
+    assert_token_program(token_program)?;
+
+    let (pool_signer_address, bump_seed) =
+        get_pool_signer_address_and_bump_seed(cwar_pool_storage_account.key, program_id);
+    if pool_signer_address != *pool_signer_pda.key {
+        msg!("CryowarError::InvalidSignerPDA");
+        return Err(CryowarError::InvalidSignerPDA.into());
+    }
+    let cwar_rewards_vault_data = TokenAccount::unpack(&cwar_rewards_vault.data.borrow())?;
+    assert_reward_vault(
+        cwar_rewards_vault,
+        cwar_pool_data,
+        &cwar_rewards_vault_data,
+        &pool_signer_address,
+    )?;
+
+    msg!("Calling the token program to transfer unallocated rewards from Rewards Vault...");
+    invoke_signed(
+        &spl_token::instruction::transfer(
+            token_program.key,
+            cwar_rewards_vault.key,
+            cwar_rewards_refund_ata.key,
+            &pool_signer_address,
+            &[&pool_signer_address],
+            rewards_to_refund,
+        )?,
+        &[
+            cwar_rewards_vault.clone(),
+            cwar_rewards_refund_ata.clone(),
+            pool_signer_pda.clone(),
+            token_program.clone(),
+        ],
+        &[&[&cwar_pool_storage_account.key.to_bytes(), &[bump_seed]]],
+    )?;
+
+    let mut liabilities = get_reward_liabilities(cwar_pool_data)?;
+    if cwar_pool_data.reward_vault == cwar_pool_data.staking_vault {
+        liabilities = liabilities
+            .checked_add(get_staking_vault_liabilities(cwar_pool_data)?)
+            .ok_or(CryowarError::AmountOverflow)?;
+    }
+    let cwar_rewards_vault_data_after = TokenAccount::unpack(&cwar_rewards_vault.data.borrow())?;
+    if cwar_rewards_vault_data_after.amount < liabilities {
+        msg!("CryowarError::InvalidTransferOperation");
+        return Err(CryowarError::InvalidTransferOperation.into());
+    }
+
+    Ok(())
+}
@@ -1,6 +1,10 @@
 use crate::{
     error::CryowarError,
-    utils::{assert_pool_storage_account, assert_signer, save_pool_storage_account},
+    state::CwarPool,
+    utils::{
+        assert_not_timelocked, assert_pool_storage_account, assert_signer,
+        save_pool_storage_account,
+    },
 };
 use solana_program::{
     account_info::{next_account_info, AccountInfo},
@@ -29,7 +33,20 @@ pub fn process_remove_funder(accounts: &[AccountInfo], program_id: &Pubkey) -> P
         return Err(CryowarError::PoolOwnerMismatched.into());
     }
 
-    if *funder_wallet_to_remove.key == cwar_pool_data.owner_wallet {
+    assert_not_timelocked(&cwar_pool_data)?;
+
+    remove_funder(&mut cwar_pool_data, funder_wallet_to_remove.key)?;
+
+    save_pool_storage_account(&mut cwar_pool_data_byte_array, &cwar_pool_data)?;
+
+    Ok(())
+}
+
+pub fn remove_funder(
+    cwar_pool_data: &mut CwarPool,
+    funder_wallet_to_remove: &Pubkey,
+) -> ProgramResult {
+    if *funder_wallet_to_remove == cwar_pool_data.owner_wallet {
         msg!("CryowarError::CannotRemovePoolOwner");
         return Err(CryowarError::CannotRemovePoolOwner.into());
     }
@@ -37,7 +54,7 @@ pub fn process_remove_funder(accounts: &[AccountInfo], program_id: &Pubkey) -> P
     if let Some(idx) = cwar_pool_data
         .funders
         .iter()
-        .position(|x| *x == *funder_wallet_to_remove.key)
+        .position(|x| *x == *funder_wallet_to_remove)
     {
         cwar_pool_data.funders[idx] = Pubkey::default();
     } else {
@@ -45,7 +62,5 @@ pub fn process_remove_funder(accounts: &[AccountInfo], program_id: &Pubkey) -> P
         return Err(CryowarError::FunderNotPresent.into());
     }
 
-    save_pool_storage_account(&mut cwar_pool_data_byte_array, &cwar_pool_data)?;
-
     Ok(())
 }
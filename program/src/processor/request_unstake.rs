@@ -0,0 +1,182 @@
+use std::convert::TryInto;
+
+use crate::{
+    error::CryowarError,
+    processor::create_user::create_and_allocate_account_raw,
+    state::{
+        AccTypesWithVersion, CwarPool, UnbondingRequest, User,
+        UNBONDING_REQUEST_STORAGE_TOTAL_BYTES,
+    },
+    utils::{
+        self, assert_pool_storage_account, assert_signer, assert_system_program,
+        assert_user_storage_account, get_unbonding_request_address_and_bump_seed,
+        next_reward_schedule, save_pool_storage_account, save_unbonding_request_account,
+        save_user_storage_account,
+    },
+};
+use solana_program::{
+    account_info::{next_account_info, AccountInfo},
+    clock::Clock,
+    entrypoint::ProgramResult,
+    msg,
+    program_error::ProgramError,
+    pubkey::Pubkey,
+    sysvar::Sysvar,
+};
+
+pub fn process_request_unstake(
+    accounts: &[AccountInfo],
+    amount: u64,
+    program_id: &Pubkey,
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+    let user_wallet_account = next_account_info(account_info_iter)?;
+    let user_storage_account = next_account_info(account_info_iter)?;
+    let cwar_pool_storage_account = next_account_info(account_info_iter)?;
+    let unbonding_request_account = next_account_info(account_info_iter)?;
+    let system_program_info = next_account_info(account_info_iter)?;
+
+    assert_signer(user_wallet_account)?;
+    assert_system_program(system_program_info)?;
+    let mut user_data_byte_array = user_storage_account.data.try_borrow_mut().unwrap();
+    let mut user_storage_data = assert_user_storage_account(
+        user_wallet_account,
+        cwar_pool_storage_account,
+        program_id,
+        user_storage_account,
+        &user_data_byte_array,
+    )?;
+
+    let mut cwar_pool_data_byte_array = cwar_pool_storage_account.data.try_borrow_mut().unwrap();
+    let mut cwar_pool_data = assert_pool_storage_account(
+        &cwar_pool_data_byte_array,
+        cwar_pool_storage_account,
+        program_id,
+    )?;
+    // the cooldown already delays the withdrawal and `EmergencyUnstake` is not available in
+    // unbonding pools, so requests ignore `PAUSE_UNSTAKE` to keep the principal withdrawable
+    let reward_schedule = next_reward_schedule(account_info_iter, &cwar_pool_data, program_id)?;
+
+    if cwar_pool_data.unbonding_duration == 0u64 {
+        msg!("CryowarError::UnbondingNotEnabled");
+        return Err(CryowarError::UnbondingNotEnabled.into());
+    }
+
+    let request_id = user_storage_data.next_unbonding_request_id;
+    let (unbonding_request_address, bump_seed) = get_unbonding_request_address_and_bump_seed(
+        user_storage_account.key,
+        request_id,
+        program_id,
+    );
+    if unbonding_request_address != *unbonding_request_account.key {
+        msg!("Error: Unbonding Request address does not match seed derivation");
+        return Err(ProgramError::InvalidSeeds);
+    }
+
+    let now: u64 = Clock::get()?.unix_timestamp.try_into().unwrap();
+    utils::update_rewards(
+        &mut cwar_pool_data,
+        reward_schedule.as_ref(),
+        Some(&mut user_storage_data),
+        now,
+    )?;
+    request_unstake(&mut cwar_pool_data, &mut user_storage_data, amount)?;
+    user_storage_data.next_unbonding_request_id = request_id
+        .checked_add(1u64)
+        .ok_or(CryowarError::AmountOverflow)?;
+
+    create_and_allocate_account_raw(
+        *program_id,
+        unbonding_request_account,
+        system_program_info,
+        user_wallet_account,
+        UNBONDING_REQUEST_STORAGE_TOTAL_BYTES,
+        &[
+            &user_storage_account.key.to_bytes(),
+            &request_id.to_le_bytes(),
+            &[bump_seed],
+        ],
+    )?;
+
+    let unbonding_request_data = UnbondingRequest {
+        acc_type: AccTypesWithVersion::UnbondingRequestDataV1 as u8,
+        user_storage: *user_storage_account.key,
+        request_id,
+        amount,
+        requested_at: now,
+        withdrawable_at: now
+            .checked_add(cwar_pool_data.unbonding_duration)
+            .ok_or(CryowarError::AmountOverflow)?,
+    };
+    msg!("request_id: {}", request_id);
+    msg!(
+        "withdrawable_at: {}",
+        unbonding_request_data.withdrawable_at
+    );
+
+    let mut unbonding_request_byte_array = unbonding_request_account.data.try_borrow_mut().unwrap();
+    save_unbonding_request_account(&mut unbonding_request_byte_array, &unbonding_request_data)?;
+    save_pool_storage_account(&mut cwar_pool_data_byte_array, &cwar_pool_data)?;
+    save_user_storage_account(&mut user_data_byte_array, &user_storage_data)?;
+
+    Ok(())
+}
+
+/// Moves `amount` of a checkpointed user's stake into unbonding, where it stops earning
+pub fn request_unstake(
+    cwar_pool_data: &mut CwarPool,
+    user_storage_data: &mut User,
+    amount: u64,
+) -> ProgramResult {
+    if amount == 0u64 {
+        msg!("CryowarError::AmountMustBeGreaterThanZero");
+        return Err(CryowarError::AmountMustBeGreaterThanZero.into());
+    }
+    if amount > user_storage_data.user_cwar_staked_amount {
+        msg!("CryowarError::InsufficientFundsToUnstake");
+        return Err(CryowarError::InsufficientFundsToUnstake.into());
+    }
+    user_storage_data.user_cwar_staked_amount -= amount;
+    user_storage_data.unbonding_amount = user_storage_data
+        .unbonding_amount
+        .checked_add(amount)
+        .ok_or(CryowarError::AmountOverflow)?;
+    cwar_pool_data.total_staked = cwar_pool_data
+        .total_staked
+        .checked_sub(amount)
+        .ok_or(CryowarError::AmountOverflow)?;
+    cwar_pool_data.total_unbonding = cwar_pool_data
+        .total_unbonding
+        .checked_add(amount)
+        .ok_or(CryowarError::AmountOverflow)?;
+    Ok(())
+}
+
+/// Takes a closed unbonding request of `amount` off the books, `restake` puts it back into the
+/// user's stake instead of releasing it
+pub fn settle_unbonding_request(
+    cwar_pool_data: &mut CwarPool,
+    user_storage_data: &mut User,
+    amount: u64,
+    restake: bool,
+) -> ProgramResult {
+    user_storage_data.unbonding_amount = user_storage_data
+        .unbonding_amount
+        .checked_sub(amount)
+        .ok_or(CryowarError::AmountOverflow)?;
+    cwar_pool_data.total_unbonding = cwar_pool_data
+        .total_unbonding
+        .checked_sub(amount)
+        .ok_or(CryowarError::AmountOverflow)?;
+    if restake {
+        user_storage_data.user_cwar_staked_amount = user_storage_data
+            .user_cwar_staked_amount
+            .checked_add(amount)
+            .ok_or(CryowarError::AmountOverflow)?;
+        cwar_pool_data.total_staked = cwar_pool_data
+            .total_staked
+            .checked_add(amount)
+            .ok_or(CryowarError::AmountOverflow)?;
+    }
+    Ok(())
+}
@@ -0,0 +1,55 @@
+use crate::{
+    error::CryowarError,
+    state::CwarPool,
+    utils::{
+        assert_not_timelocked, assert_pool_storage_account, assert_signer,
+        save_pool_storage_account,
+    },
+};
+use solana_program::{
+    account_info::{next_account_info, AccountInfo},
+    entrypoint::ProgramResult,
+    msg,
+    pubkey::Pubkey,
+};
+
+pub fn process_set_allowlist_root(
+    accounts: &[AccountInfo],
+    allowlist_root: [u8; 32],
+    program_id: &Pubkey,
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+    let pool_owner_wallet_account = next_account_info(account_info_iter)?;
+    let cwar_pool_storage_account = next_account_info(account_info_iter)?;
+
+    assert_signer(pool_owner_wallet_account)?;
+
+    let mut cwar_pool_data_byte_array = cwar_pool_storage_account.data.try_borrow_mut().unwrap();
+    let mut cwar_pool_data = assert_pool_storage_account(
+        &cwar_pool_data_byte_array,
+        cwar_pool_storage_account,
+        program_id,
+    )?;
+
+    if cwar_pool_data.owner_wallet != *pool_owner_wallet_account.key {
+        msg!("CryowarError::PoolOwnerMismatched");
+        return Err(CryowarError::PoolOwnerMismatched.into());
+    }
+
+    assert_not_timelocked(&cwar_pool_data)?;
+    set_allowlist_root(&mut cwar_pool_data, allowlist_root);
+
+    save_pool_storage_account(&mut cwar_pool_data_byte_array, &cwar_pool_data)?;
+
+    Ok(())
+}
+
+/// The allowlist is only checked when a user joins, rotating it keeps existing users in the pool
+pub fn set_allowlist_root(cwar_pool_data: &mut CwarPool, allowlist_root: [u8; 32]) {
+    msg!(
+        "allowlist_root: {:?} -> {:?}",
+        cwar_pool_data.allowlist_root,
+        allowlist_root
+    );
+    cwar_pool_data.allowlist_root = allowlist_root;
+}
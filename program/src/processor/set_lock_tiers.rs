@@ -0,0 +1,65 @@
+use crate::{
+    error::CryowarError,
+    state::{CwarPool, LockTier, MAX_LOCK_TIERS},
+    utils::{
+        assert_lock_tiers, assert_not_timelocked, assert_pool_storage_account, assert_signer,
+        save_pool_storage_account,
+    },
+};
+use solana_program::{
+    account_info::{next_account_info, AccountInfo},
+    entrypoint::ProgramResult,
+    msg,
+    pubkey::Pubkey,
+};
+
+pub fn process_set_lock_tiers(
+    accounts: &[AccountInfo],
+    lock_tiers: [LockTier; MAX_LOCK_TIERS],
+    program_id: &Pubkey,
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+    let pool_owner_wallet_account = next_account_info(account_info_iter)?;
+    let cwar_pool_storage_account = next_account_info(account_info_iter)?;
+
+    assert_signer(pool_owner_wallet_account)?;
+
+    let mut cwar_pool_data_byte_array = cwar_pool_storage_account.data.try_borrow_mut().unwrap();
+    let mut cwar_pool_data = assert_pool_storage_account(
+        &cwar_pool_data_byte_array,
+        cwar_pool_storage_account,
+        program_id,
+    )?;
+
+    if cwar_pool_data.owner_wallet != *pool_owner_wallet_account.key {
+        msg!("CryowarError::PoolOwnerMismatched");
+        return Err(CryowarError::PoolOwnerMismatched.into());
+    }
+
+    assert_not_timelocked(&cwar_pool_data)?;
+    set_lock_tiers(&mut cwar_pool_data, lock_tiers)?;
+
+    save_pool_storage_account(&mut cwar_pool_data_byte_array, &cwar_pool_data)?;
+
+    Ok(())
+}
+
+/// Tiers only apply to new deposits, open lock positions keep the duration and weight they
+/// were created with
+pub fn set_lock_tiers(
+    cwar_pool_data: &mut CwarPool,
+    lock_tiers: [LockTier; MAX_LOCK_TIERS],
+) -> ProgramResult {
+    assert_lock_tiers(&lock_tiers)?;
+    for (lock_tier_index, lock_tier) in lock_tiers.iter().enumerate() {
+        msg!(
+            "lock_tiers[{}]: duration {} multiplier_basis_points {}",
+            lock_tier_index,
+            lock_tier.duration,
+            lock_tier.multiplier_basis_points
+        );
+    }
+    cwar_pool_data.lock_tiers = lock_tiers;
+
+    Ok(())
+}
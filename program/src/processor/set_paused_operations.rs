@@ -0,0 +1,62 @@
+use crate::{
+    error::CryowarError,
+    state::PAUSE_ALL_OPERATIONS,
+    utils::{assert_pool_storage_account, assert_signer, save_pool_storage_account},
+};
+use solana_program::{
+    account_info::{next_account_info, AccountInfo},
+    entrypoint::ProgramResult,
+    msg,
+    pubkey::Pubkey,
+};
+
+pub fn process_set_paused_operations(
+    accounts: &[AccountInfo],
+    paused_operations: u8,
+    program_id: &Pubkey,
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+    let pause_authority_wallet_account = next_account_info(account_info_iter)?;
+    let cwar_pool_storage_account = next_account_info(account_info_iter)?;
+
+    assert_signer(pause_authority_wallet_account)?;
+
+    let mut cwar_pool_data_byte_array = cwar_pool_storage_account.data.try_borrow_mut().unwrap();
+    let mut cwar_pool_data = assert_pool_storage_account(
+        &cwar_pool_data_byte_array,
+        cwar_pool_storage_account,
+        program_id,
+    )?;
+
+    // `EmergencyUnstake` is never paused, so users can always get their principal back
+    if paused_operations & !PAUSE_ALL_OPERATIONS != 0u8 {
+        msg!("CryowarError::InvalidPauseFlags");
+        return Err(CryowarError::InvalidPauseFlags.into());
+    }
+
+    let is_owner = cwar_pool_data.owner_wallet == *pause_authority_wallet_account.key;
+    let is_pauser = cwar_pool_data.pauser != Pubkey::default()
+        && cwar_pool_data.pauser == *pause_authority_wallet_account.key;
+    if !is_owner && !is_pauser {
+        msg!("CryowarError::PauseAuthorityMismatched");
+        return Err(CryowarError::PauseAuthorityMismatched.into());
+    }
+    // the pauser can only pause more operations, lifting a pause is up to the owner
+    if !is_owner
+        && paused_operations & cwar_pool_data.paused_operations != cwar_pool_data.paused_operations
+    {
+        msg!("CryowarError::PauseAuthorityMismatched");
+        return Err(CryowarError::PauseAuthorityMismatched.into());
+    }
+
+    msg!(
+        "paused_operations: {:#07b} -> {:#07b}",
+        cwar_pool_data.paused_operations,
+        paused_operations
+    );
+    cwar_pool_data.paused_operations = paused_operations;
+
+    save_pool_storage_account(&mut cwar_pool_data_byte_array, &cwar_pool_data)?;
+
+    Ok(())
+}
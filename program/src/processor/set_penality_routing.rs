@@ -0,0 +1,61 @@
+use crate::{
+    error::CryowarError,
+    state::{CwarPool, PenalityRouting},
+    utils::{
+        assert_not_timelocked, assert_penality_routing, assert_pool_storage_account, assert_signer,
+        save_pool_storage_account,
+    },
+};
+use solana_program::{
+    account_info::{next_account_info, AccountInfo},
+    entrypoint::ProgramResult,
+    msg,
+    pubkey::Pubkey,
+};
+
+pub fn process_set_penality_routing(
+    accounts: &[AccountInfo],
+    penality_routing: PenalityRouting,
+    program_id: &Pubkey,
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+    let pool_owner_wallet_account = next_account_info(account_info_iter)?;
+    let cwar_pool_storage_account = next_account_info(account_info_iter)?;
+
+    assert_signer(pool_owner_wallet_account)?;
+
+    let mut cwar_pool_data_byte_array = cwar_pool_storage_account.data.try_borrow_mut().unwrap();
+    let mut cwar_pool_data = assert_pool_storage_account(
+        &cwar_pool_data_byte_array,
+        cwar_pool_storage_account,
+        program_id,
+    )?;
+
+    if cwar_pool_data.owner_wallet != *pool_owner_wallet_account.key {
+        msg!("CryowarError::PoolOwnerMismatched");
+        return Err(CryowarError::PoolOwnerMismatched.into());
+    }
+
+    assert_not_timelocked(&cwar_pool_data)?;
+    set_penality_routing(&mut cwar_pool_data, penality_routing)?;
+
+    save_pool_storage_account(&mut cwar_pool_data_byte_array, &cwar_pool_data)?;
+
+    Ok(())
+}
+
+/// Routing only decides where a penalty goes, not what a staker pays, so it can change at any time
+pub fn set_penality_routing(
+    cwar_pool_data: &mut CwarPool,
+    penality_routing: PenalityRouting,
+) -> ProgramResult {
+    assert_penality_routing(&penality_routing, cwar_pool_data)?;
+    msg!(
+        "penality_routing: {:?} -> {:?}",
+        cwar_pool_data.penality_routing,
+        penality_routing
+    );
+    cwar_pool_data.penality_routing = penality_routing;
+
+    Ok(())
+}
@@ -0,0 +1,72 @@
+use crate::{
+    error::CryowarError,
+    state::{CwarPool, PenalitySchedule, PENALITY_SCHEDULE_CLIFF},
+    utils::{
+        assert_not_timelocked, assert_penality_schedule, assert_pool_storage_account,
+        assert_signer, save_pool_storage_account,
+    },
+};
+use solana_program::{
+    account_info::{next_account_info, AccountInfo},
+    entrypoint::ProgramResult,
+    msg,
+    pubkey::Pubkey,
+};
+
+pub fn process_set_penality_schedule(
+    accounts: &[AccountInfo],
+    penality_schedule: PenalitySchedule,
+    program_id: &Pubkey,
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+    let pool_owner_wallet_account = next_account_info(account_info_iter)?;
+    let cwar_pool_storage_account = next_account_info(account_info_iter)?;
+
+    assert_signer(pool_owner_wallet_account)?;
+
+    let mut cwar_pool_data_byte_array = cwar_pool_storage_account.data.try_borrow_mut().unwrap();
+    let mut cwar_pool_data = assert_pool_storage_account(
+        &cwar_pool_data_byte_array,
+        cwar_pool_storage_account,
+        program_id,
+    )?;
+
+    if cwar_pool_data.owner_wallet != *pool_owner_wallet_account.key {
+        msg!("CryowarError::PoolOwnerMismatched");
+        return Err(CryowarError::PoolOwnerMismatched.into());
+    }
+
+    assert_not_timelocked(&cwar_pool_data)?;
+    set_penality_schedule(&mut cwar_pool_data, penality_schedule, false)?;
+
+    save_pool_storage_account(&mut cwar_pool_data_byte_array, &cwar_pool_data)?;
+
+    Ok(())
+}
+
+/// No schedule charges more than the cliff, so moving off the cliff is the only change that
+/// applies to existing stakers right away. Anything else needs the pool to be empty or stakers
+/// to have had time to exit.
+pub fn set_penality_schedule(
+    cwar_pool_data: &mut CwarPool,
+    penality_schedule: PenalitySchedule,
+    stakers_had_time_to_exit: bool,
+) -> ProgramResult {
+    assert_penality_schedule(&penality_schedule)?;
+    if !stakers_had_time_to_exit
+        && cwar_pool_data.total_staked > 0u64
+        && cwar_pool_data.penality_schedule.kind != PENALITY_SCHEDULE_CLIFF
+        && cwar_pool_data.penality_schedule != penality_schedule
+    {
+        msg!("CryowarError::PoolConfigChangeNotAllowedWhileStaked");
+        return Err(CryowarError::PoolConfigChangeNotAllowedWhileStaked.into());
+    }
+    msg!(
+        "penality_schedule: {:?} -> {:?}",
+        cwar_pool_data.penality_schedule,
+        penality_schedule
+    );
+    cwar_pool_data.penality_schedule = penality_schedule;
+
+    Ok(())
+}
@@ -0,0 +1,100 @@
+use crate::{
+    error::CryowarError,
+    state::CwarPool,
+    utils::{
+        assert_not_timelocked, assert_pool_storage_account, assert_receipt_mint_data,
+        assert_signer, get_pool_signer_address_and_bump_seed, save_pool_storage_account,
+    },
+};
+use solana_program::{
+    account_info::{next_account_info, AccountInfo},
+    entrypoint::ProgramResult,
+    msg,
+    program_pack::Pack,
+    pubkey::Pubkey,
+};
+use spl_token::state::Mint;
+
+pub fn process_set_receipt_mint(
+    accounts: &[AccountInfo],
+    receipt_mint: Pubkey,
+    program_id: &Pubkey,
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+    let pool_owner_wallet_account = next_account_info(account_info_iter)?;
+    let cwar_pool_storage_account = next_account_info(account_info_iter)?;
+    let receipt_mint_account = next_account_info(account_info_iter)?;
+    let staking_mint_account = next_account_info(account_info_iter)?;
+
+    assert_signer(pool_owner_wallet_account)?;
+
+    let mut cwar_pool_data_byte_array = cwar_pool_storage_account.data.try_borrow_mut().unwrap();
+    let mut cwar_pool_data = assert_pool_storage_account(
+        &cwar_pool_data_byte_array,
+        cwar_pool_storage_account,
+        program_id,
+    )?;
+
+    if cwar_pool_data.owner_wallet != *pool_owner_wallet_account.key {
+        msg!("CryowarError::PoolOwnerMismatched");
+        return Err(CryowarError::PoolOwnerMismatched.into());
+    }
+
+    assert_not_timelocked(&cwar_pool_data)?;
+    assert_receipt_mint_account(
+        receipt_mint_account,
+        staking_mint_account,
+        receipt_mint,
+        cwar_pool_storage_account.key,
+        &cwar_pool_data,
+        program_id,
+    )?;
+    set_receipt_mint(&mut cwar_pool_data, receipt_mint)?;
+
+    save_pool_storage_account(&mut cwar_pool_data_byte_array, &cwar_pool_data)?;
+
+    Ok(())
+}
+
+/// Checks the mint account behind `receipt_mint` with `assert_receipt_mint_data`
+pub fn assert_receipt_mint_account(
+    receipt_mint_account: &AccountInfo,
+    staking_mint_account: &AccountInfo,
+    receipt_mint: Pubkey,
+    cwar_pool_storage: &Pubkey,
+    cwar_pool_data: &CwarPool,
+    program_id: &Pubkey,
+) -> ProgramResult {
+    if *receipt_mint_account.key != receipt_mint || *receipt_mint_account.owner != spl_token::id() {
+        msg!("CryowarError::InvalidReceiptMint");
+        return Err(CryowarError::InvalidReceiptMint.into());
+    }
+    if *staking_mint_account.key != cwar_pool_data.staking_mint {
+        msg!("CryowarError::MintMismatched");
+        return Err(CryowarError::MintMismatched.into());
+    }
+    let (pool_signer_address, _bump_seed) =
+        get_pool_signer_address_and_bump_seed(cwar_pool_storage, program_id);
+    assert_receipt_mint_data(
+        &Mint::unpack(&receipt_mint_account.data.borrow())?,
+        &Mint::unpack(&staking_mint_account.data.borrow())?,
+        &pool_signer_address,
+    )
+}
+
+/// Receipts are only minted for stake made after this, so the receipt mint can only be set once
+/// and while nothing is staked. Unbonding is off for good since unbonding stake has no receipts.
+pub fn set_receipt_mint(cwar_pool_data: &mut CwarPool, receipt_mint: Pubkey) -> ProgramResult {
+    if cwar_pool_data.has_receipt_mint()
+        || receipt_mint == Pubkey::default()
+        || cwar_pool_data.total_staked != 0u64
+        || cwar_pool_data.total_unbonding != 0u64
+        || cwar_pool_data.unbonding_duration != 0u64
+    {
+        msg!("CryowarError::ReceiptMintChangeNotAllowed");
+        return Err(CryowarError::ReceiptMintChangeNotAllowed.into());
+    }
+    msg!("receipt_mint: {}", receipt_mint);
+    cwar_pool_data.receipt_mint = receipt_mint;
+    Ok(())
+}
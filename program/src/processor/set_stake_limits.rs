@@ -0,0 +1,78 @@
+use crate::{
+    error::CryowarError,
+    state::CwarPool,
+    utils::{
+        assert_not_timelocked, assert_pool_storage_account, assert_signer, assert_stake_limits,
+        save_pool_storage_account,
+    },
+};
+use solana_program::{
+    account_info::{next_account_info, AccountInfo},
+    entrypoint::ProgramResult,
+    msg,
+    pubkey::Pubkey,
+};
+
+pub fn process_set_stake_limits(
+    accounts: &[AccountInfo],
+    min_stake: u64,
+    max_stake_per_user: u64,
+    max_total_staked: u64,
+    program_id: &Pubkey,
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+    let pool_owner_wallet_account = next_account_info(account_info_iter)?;
+    let cwar_pool_storage_account = next_account_info(account_info_iter)?;
+
+    assert_signer(pool_owner_wallet_account)?;
+
+    let mut cwar_pool_data_byte_array = cwar_pool_storage_account.data.try_borrow_mut().unwrap();
+    let mut cwar_pool_data = assert_pool_storage_account(
+        &cwar_pool_data_byte_array,
+        cwar_pool_storage_account,
+        program_id,
+    )?;
+
+    if cwar_pool_data.owner_wallet != *pool_owner_wallet_account.key {
+        msg!("CryowarError::PoolOwnerMismatched");
+        return Err(CryowarError::PoolOwnerMismatched.into());
+    }
+
+    assert_not_timelocked(&cwar_pool_data)?;
+    set_stake_limits(
+        &mut cwar_pool_data,
+        min_stake,
+        max_stake_per_user,
+        max_total_staked,
+    )?;
+
+    save_pool_storage_account(&mut cwar_pool_data_byte_array, &cwar_pool_data)?;
+
+    Ok(())
+}
+
+/// Limits only apply to new stakes, anyone already above them keeps their stake
+pub fn set_stake_limits(
+    cwar_pool_data: &mut CwarPool,
+    min_stake: u64,
+    max_stake_per_user: u64,
+    max_total_staked: u64,
+) -> ProgramResult {
+    assert_stake_limits(min_stake, max_stake_per_user, max_total_staked)?;
+    msg!("min_stake: {} -> {}", cwar_pool_data.min_stake, min_stake);
+    msg!(
+        "max_stake_per_user: {} -> {}",
+        cwar_pool_data.max_stake_per_user,
+        max_stake_per_user
+    );
+    msg!(
+        "max_total_staked: {} -> {}",
+        cwar_pool_data.max_total_staked,
+        max_total_staked
+    );
+    cwar_pool_data.min_stake = min_stake;
+    cwar_pool_data.max_stake_per_user = max_stake_per_user;
+    cwar_pool_data.max_total_staked = max_total_staked;
+
+    Ok(())
+}
@@ -0,0 +1,64 @@
+use crate::{
+    error::CryowarError,
+    state::CwarPool,
+    utils::{
+        assert_pool_storage_account, assert_signer, constants::MAX_TIMELOCK_DELAY,
+        save_pool_storage_account,
+    },
+};
+use solana_program::{
+    account_info::{next_account_info, AccountInfo},
+    entrypoint::ProgramResult,
+    msg,
+    pubkey::Pubkey,
+};
+
+pub fn process_set_timelock_delay(
+    accounts: &[AccountInfo],
+    timelock_delay: u64,
+    program_id: &Pubkey,
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+    let pool_owner_wallet_account = next_account_info(account_info_iter)?;
+    let cwar_pool_storage_account = next_account_info(account_info_iter)?;
+
+    assert_signer(pool_owner_wallet_account)?;
+
+    let mut cwar_pool_data_byte_array = cwar_pool_storage_account.data.try_borrow_mut().unwrap();
+    let mut cwar_pool_data = assert_pool_storage_account(
+        &cwar_pool_data_byte_array,
+        cwar_pool_storage_account,
+        program_id,
+    )?;
+
+    if cwar_pool_data.owner_wallet != *pool_owner_wallet_account.key {
+        msg!("CryowarError::PoolOwnerMismatched");
+        return Err(CryowarError::PoolOwnerMismatched.into());
+    }
+
+    // lowering the delay shortens the exit window of stakers, so it goes through the timelock
+    if timelock_delay < cwar_pool_data.timelock_delay {
+        msg!("CryowarError::TimelockRequired");
+        return Err(CryowarError::TimelockRequired.into());
+    }
+    set_timelock_delay(&mut cwar_pool_data, timelock_delay)?;
+
+    save_pool_storage_account(&mut cwar_pool_data_byte_array, &cwar_pool_data)?;
+
+    Ok(())
+}
+
+pub fn set_timelock_delay(cwar_pool_data: &mut CwarPool, timelock_delay: u64) -> ProgramResult {
+    if timelock_delay > MAX_TIMELOCK_DELAY {
+        msg!("CryowarError::InvalidTimelockDelay");
+        return Err(CryowarError::InvalidTimelockDelay.into());
+    }
+    msg!(
+        "timelock_delay: {} -> {}",
+        cwar_pool_data.timelock_delay,
+        timelock_delay
+    );
+    cwar_pool_data.timelock_delay = timelock_delay;
+
+    Ok(())
+}
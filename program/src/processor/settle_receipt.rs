@@ -0,0 +1,166 @@
+use std::convert::TryInto;
+
+use crate::{
+    error::CryowarError,
+    processor::{
+        create_user::create_user_storage_account, transfer_position::merge_stake_timestamps,
+    },
+    state::{CwarPool, User, PAUSE_CREATE_USER, PAUSE_UNSTAKE},
+    utils::{
+        self, assert_no_allowlist, assert_not_paused, assert_pool_storage_account, assert_signer,
+        assert_user_storage_account, check_user_stake_limits, get_receipt_balance,
+        next_reward_schedule, save_pool_storage_account, save_user_storage_account,
+    },
+};
+use solana_program::{
+    account_info::{next_account_info, AccountInfo},
+    clock::Clock,
+    entrypoint::ProgramResult,
+    msg,
+    program_error::ProgramError,
+    pubkey::Pubkey,
+    sysvar::Sysvar,
+};
+
+/// Anyone can settle, so a source whose receipts were sold stops earning on them as soon as
+/// somebody notices. A missing holder user account is created like in `TransferPosition`, paid
+/// by the holder when they sign and by the trailing payer otherwise.
+pub fn process_settle_receipt(accounts: &[AccountInfo], program_id: &Pubkey) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+    let holder_wallet_account = next_account_info(account_info_iter)?;
+    let holder_storage_account = next_account_info(account_info_iter)?;
+    let cwar_pool_storage_account = next_account_info(account_info_iter)?;
+    let holder_receipt_ata = next_account_info(account_info_iter)?;
+    let source_wallet_account = next_account_info(account_info_iter)?;
+    let source_storage_account = next_account_info(account_info_iter)?;
+    let source_receipt_ata = next_account_info(account_info_iter)?;
+    let system_program_info = next_account_info(account_info_iter)?;
+
+    if source_wallet_account.key == holder_wallet_account.key {
+        msg!("CryowarError::InvalidPositionRecipient");
+        return Err(CryowarError::InvalidPositionRecipient.into());
+    }
+
+    let mut source_data_byte_array = source_storage_account.data.try_borrow_mut().unwrap();
+    let mut source_storage_data = assert_user_storage_account(
+        source_wallet_account,
+        cwar_pool_storage_account,
+        program_id,
+        source_storage_account,
+        &source_data_byte_array,
+    )?;
+
+    let mut cwar_pool_data_byte_array = cwar_pool_storage_account.data.try_borrow_mut().unwrap();
+    let mut cwar_pool_data = assert_pool_storage_account(
+        &cwar_pool_data_byte_array,
+        cwar_pool_storage_account,
+        program_id,
+    )?;
+    assert_not_paused(&cwar_pool_data, PAUSE_UNSTAKE)?;
+    let reward_schedule = next_reward_schedule(account_info_iter, &cwar_pool_data, program_id)?;
+    if !cwar_pool_data.has_receipt_mint() {
+        msg!("CryowarError::InvalidReceiptMint");
+        return Err(CryowarError::InvalidReceiptMint.into());
+    }
+
+    let holder_receipt_balance = get_receipt_balance(
+        holder_receipt_ata,
+        holder_wallet_account.key,
+        &cwar_pool_data,
+    )?;
+    let source_receipt_balance = get_receipt_balance(
+        source_receipt_ata,
+        source_wallet_account.key,
+        &cwar_pool_data,
+    )?;
+
+    let mut holder_storage_data = if holder_storage_account.data_is_empty() {
+        assert_not_paused(&cwar_pool_data, PAUSE_CREATE_USER)?;
+        assert_no_allowlist(&cwar_pool_data)?;
+        let payer_account = if holder_wallet_account.is_signer {
+            holder_wallet_account
+        } else {
+            next_account_info(account_info_iter)?
+        };
+        assert_signer(payer_account)?;
+        create_user_storage_account(
+            holder_wallet_account.key,
+            holder_storage_account,
+            cwar_pool_storage_account,
+            system_program_info,
+            payer_account,
+            &mut cwar_pool_data,
+            0u64,
+            program_id,
+        )?
+    } else {
+        assert_user_storage_account(
+            holder_wallet_account,
+            cwar_pool_storage_account,
+            program_id,
+            holder_storage_account,
+            &holder_storage_account.data.borrow(),
+        )?
+    };
+
+    let now: u64 = Clock::get()?.unix_timestamp.try_into().unwrap();
+    utils::update_rewards(
+        &mut cwar_pool_data,
+        reward_schedule.as_ref(),
+        Some(&mut source_storage_data),
+        now,
+    )?;
+    utils::update_rewards(
+        &mut cwar_pool_data,
+        reward_schedule.as_ref(),
+        Some(&mut holder_storage_data),
+        now,
+    )?;
+    let amount_settled = settle_receipt(
+        &cwar_pool_data,
+        &mut source_storage_data,
+        &mut holder_storage_data,
+        source_receipt_balance,
+        holder_receipt_balance,
+    )?;
+    msg!("amount_settled: {}", amount_settled);
+
+    save_pool_storage_account(&mut cwar_pool_data_byte_array, &cwar_pool_data)?;
+    save_user_storage_account(&mut source_data_byte_array, &source_storage_data)?;
+    let mut holder_data_byte_array = holder_storage_account.data.try_borrow_mut().unwrap();
+    save_user_storage_account(&mut holder_data_byte_array, &holder_storage_data)?;
+
+    Ok(())
+}
+
+/// Moves unlocked stake from a checkpointed source holding fewer receipts than it has staked to
+/// a holder checkpointed at the same time that holds more, as much as both sides are off by.
+/// Rewards earned so far stay with the source, the holder takes the source timestamps the way
+/// `transfer_position` does.
+pub fn settle_receipt(
+    cwar_pool_data: &CwarPool,
+    source_storage_data: &mut User,
+    holder_storage_data: &mut User,
+    source_receipt_balance: u64,
+    holder_receipt_balance: u64,
+) -> Result<u64, ProgramError> {
+    let amount = std::cmp::min(
+        source_storage_data
+            .user_cwar_staked_amount
+            .saturating_sub(source_receipt_balance),
+        holder_receipt_balance.saturating_sub(holder_storage_data.user_cwar_staked_amount),
+    );
+    if amount == 0u64 {
+        msg!("CryowarError::NothingToSettle");
+        return Err(CryowarError::NothingToSettle.into());
+    }
+    check_user_stake_limits(cwar_pool_data, holder_storage_data, amount)?;
+
+    source_storage_data.user_cwar_staked_amount -= amount;
+    merge_stake_timestamps(source_storage_data, holder_storage_data);
+    holder_storage_data.user_cwar_staked_amount = holder_storage_data
+        .user_cwar_staked_amount
+        .checked_add(amount)
+        .ok_or(CryowarError::AmountOverflow)?;
+    Ok(amount)
+}
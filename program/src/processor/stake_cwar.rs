@@ -2,10 +2,12 @@ use std::convert::TryInto;
 
 use crate::{
     error::CryowarError,
+    state::PAUSE_STAKE,
     utils::{
-        self, assert_pool_storage_account, assert_signer, assert_staking_vault,
-        assert_token_program, assert_user_storage_account, save_pool_storage_account,
-        save_user_storage_account,
+        self, assert_not_paused, assert_pool_storage_account, assert_signer, assert_staking_vault,
+        assert_token_program, assert_user_storage_account, check_stake_limits,
+        create_recipient_ata_if_missing, mint_receipt, next_reward_schedule, repay_receipt_debt,
+        save_pool_storage_account, save_user_storage_account,
     },
 };
 
@@ -51,6 +53,8 @@ pub fn process_stake_cwar(
         cwar_pool_storage_account,
         program_id,
     )?;
+    assert_not_paused(&cwar_pool_data, PAUSE_STAKE)?;
+    let reward_schedule = next_reward_schedule(account_info_iter, &cwar_pool_data, program_id)?;
 
     let (pool_signer_address, _bump_seed) =
         Pubkey::find_program_address(&[&cwar_pool_storage_account.key.to_bytes()], program_id);
@@ -71,12 +75,11 @@ pub fn process_stake_cwar(
     user_storage_data.unstake_penality_duration_end = cwar_pool_data.reward_duration_end;
     user_storage_data.last_staked_timestamp = now;
 
-    let total_cwar_staked = cwar_staking_vault_data.amount;
     utils::update_rewards(
         &mut cwar_pool_data,
+        reward_schedule.as_ref(),
         Some(&mut user_storage_data),
-        total_cwar_staked,
-        now
+        now,
     )?;
 
     msg!("Calling the token program to transfer CWAR to Staking Vault...");
@@ -102,10 +105,47 @@ pub fn process_stake_cwar(
         .amount
         .checked_sub(cwar_staking_vault_data.amount)
         .ok_or(CryowarError::AmountOverflow)?;
+    check_stake_limits(&cwar_pool_data, &user_storage_data, amount_deposited)?;
     user_storage_data.user_cwar_staked_amount = user_storage_data
         .user_cwar_staked_amount
         .checked_add(amount_deposited)
         .ok_or(CryowarError::AmountOverflow)?;
+    cwar_pool_data.total_staked = cwar_pool_data
+        .total_staked
+        .checked_add(amount_deposited)
+        .ok_or(CryowarError::AmountOverflow)?;
+
+    if cwar_pool_data.has_receipt_mint() {
+        let receipt_mint = next_account_info(account_info_iter)?;
+        let user_receipt_ata = next_account_info(account_info_iter)?;
+        let pool_signer_pda = next_account_info(account_info_iter)?;
+        let system_program_info = next_account_info(account_info_iter)?;
+        let associated_token_program = next_account_info(account_info_iter)?;
+        create_recipient_ata_if_missing(
+            user_receipt_ata,
+            user_wallet_account,
+            receipt_mint,
+            &cwar_pool_data.receipt_mint,
+            user_wallet_account,
+            system_program_info,
+            token_program,
+            associated_token_program,
+        )?;
+        // receipts still out from an emergency exit are backed again by this deposit
+        let receipts_to_mint = repay_receipt_debt(&mut user_storage_data, amount_deposited);
+        if receipts_to_mint > 0u64 {
+            mint_receipt(
+                receipt_mint,
+                user_receipt_ata,
+                cwar_pool_storage_account,
+                pool_signer_pda,
+                token_program,
+                &cwar_pool_data,
+                receipts_to_mint,
+                program_id,
+            )?;
+        }
+    }
     save_pool_storage_account(&mut cwar_pool_data_byte_array, &cwar_pool_data)?;
     save_user_storage_account(&mut user_data_byte_array, &user_storage_data)?;
 
@@ -0,0 +1,208 @@
+use std::convert::TryInto;
+
+use crate::{
+    error::CryowarError,
+    processor::create_user::create_and_allocate_account_raw,
+    state::{
+        AccTypesWithVersion, CwarPool, LockPosition, LockTier, User,
+        LOCK_POSITION_STORAGE_TOTAL_BYTES, PAUSE_STAKE,
+    },
+    utils::{
+        self, assert_not_paused, assert_pool_storage_account, assert_signer, assert_staking_vault,
+        assert_system_program, assert_token_program, assert_u128_to_u64_conversion,
+        assert_user_storage_account, check_stake_limits, get_lock_position_address_and_bump_seed,
+        get_lock_tier, get_pool_signer_address_and_bump_seed, next_reward_schedule,
+        save_lock_position_account, save_pool_storage_account, save_user_storage_account,
+        FRACTION_TO_BASIS_POINTS,
+    },
+};
+use solana_program::{
+    account_info::{next_account_info, AccountInfo},
+    clock::Clock,
+    entrypoint::ProgramResult,
+    msg,
+    program::invoke,
+    program_error::ProgramError,
+    program_pack::Pack,
+    pubkey::Pubkey,
+    sysvar::Sysvar,
+};
+use spl_token::state::Account as TokenAccount;
+
+pub fn process_stake_locked(
+    accounts: &[AccountInfo],
+    amount_to_deposit: u64,
+    lock_tier_index: u8,
+    program_id: &Pubkey,
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+    let user_wallet_account = next_account_info(account_info_iter)?;
+    let user_storage_account = next_account_info(account_info_iter)?;
+    let cwar_pool_storage_account = next_account_info(account_info_iter)?;
+    let staking_vault = next_account_info(account_info_iter)?;
+    let user_cwar_ata = next_account_info(account_info_iter)?;
+    let lock_position_account = next_account_info(account_info_iter)?;
+    let token_program = next_account_info(account_info_iter)?;
+    let system_program_info = next_account_info(account_info_iter)?;
+
+    assert_signer(user_wallet_account)?;
+    assert_token_program(token_program)?;
+    assert_system_program(system_program_info)?;
+    let mut user_data_byte_array = user_storage_account.data.try_borrow_mut().unwrap();
+    let mut user_storage_data = assert_user_storage_account(
+        user_wallet_account,
+        cwar_pool_storage_account,
+        program_id,
+        user_storage_account,
+        &user_data_byte_array,
+    )?;
+
+    let mut cwar_pool_data_byte_array = cwar_pool_storage_account.data.try_borrow_mut().unwrap();
+    let mut cwar_pool_data = assert_pool_storage_account(
+        &cwar_pool_data_byte_array,
+        cwar_pool_storage_account,
+        program_id,
+    )?;
+    assert_not_paused(&cwar_pool_data, PAUSE_STAKE)?;
+    let reward_schedule = next_reward_schedule(account_info_iter, &cwar_pool_data, program_id)?;
+    let lock_tier = get_lock_tier(&cwar_pool_data, lock_tier_index)?;
+
+    let (pool_signer_address, _bump_seed) =
+        get_pool_signer_address_and_bump_seed(cwar_pool_storage_account.key, program_id);
+    let cwar_staking_vault_data = TokenAccount::unpack(&staking_vault.data.borrow())?;
+    assert_staking_vault(
+        staking_vault,
+        &cwar_pool_data,
+        &cwar_staking_vault_data,
+        &pool_signer_address,
+    )?;
+
+    if amount_to_deposit == 0u64 {
+        msg!("CryowarError::AmountMustBeGreaterThanZero");
+        return Err(CryowarError::AmountMustBeGreaterThanZero.into());
+    }
+
+    let position_id = user_storage_data.next_lock_position_id;
+    let (lock_position_address, bump_seed) =
+        get_lock_position_address_and_bump_seed(user_storage_account.key, position_id, program_id);
+    if lock_position_address != *lock_position_account.key {
+        msg!("Error: Lock Position address does not match seed derivation");
+        return Err(ProgramError::InvalidSeeds);
+    }
+
+    let now: u64 = Clock::get()?.unix_timestamp.try_into().unwrap();
+    utils::update_rewards(
+        &mut cwar_pool_data,
+        reward_schedule.as_ref(),
+        Some(&mut user_storage_data),
+        now,
+    )?;
+
+    msg!("Calling the token program to transfer CWAR to Staking Vault...");
+    invoke(
+        &spl_token::instruction::transfer(
+            token_program.key,
+            user_cwar_ata.key,
+            staking_vault.key,
+            user_wallet_account.key,
+            &[],
+            amount_to_deposit,
+        )?,
+        &[
+            user_cwar_ata.clone(),
+            staking_vault.clone(),
+            user_wallet_account.clone(),
+            token_program.clone(),
+        ],
+    )?;
+
+    let cwar_staking_vault_data_after = TokenAccount::unpack(&staking_vault.data.borrow())?;
+    let amount_deposited = cwar_staking_vault_data_after
+        .amount
+        .checked_sub(cwar_staking_vault_data.amount)
+        .ok_or(CryowarError::AmountOverflow)?;
+    check_stake_limits(&cwar_pool_data, &user_storage_data, amount_deposited)?;
+    let weight = add_lock_position(
+        &mut cwar_pool_data,
+        &mut user_storage_data,
+        &lock_tier,
+        amount_deposited,
+    )?;
+    user_storage_data.next_lock_position_id = position_id
+        .checked_add(1u64)
+        .ok_or(CryowarError::AmountOverflow)?;
+
+    create_and_allocate_account_raw(
+        *program_id,
+        lock_position_account,
+        system_program_info,
+        user_wallet_account,
+        LOCK_POSITION_STORAGE_TOTAL_BYTES,
+        &[
+            b"lock_position",
+            &user_storage_account.key.to_bytes(),
+            &position_id.to_le_bytes(),
+            &[bump_seed],
+        ],
+    )?;
+
+    let lock_position_data = LockPosition {
+        acc_type: AccTypesWithVersion::LockPositionDataV1 as u8,
+        user_storage: *user_storage_account.key,
+        position_id,
+        amount: amount_deposited,
+        weight,
+        lock_tier_index,
+        locked_at: now,
+        unlock_at: now
+            .checked_add(lock_tier.duration)
+            .ok_or(CryowarError::AmountOverflow)?,
+    };
+    msg!("position_id: {}", position_id);
+    msg!("weight: {}", weight);
+    msg!("unlock_at: {}", lock_position_data.unlock_at);
+
+    let mut lock_position_byte_array = lock_position_account.data.try_borrow_mut().unwrap();
+    save_lock_position_account(&mut lock_position_byte_array, &lock_position_data)?;
+    save_pool_storage_account(&mut cwar_pool_data_byte_array, &cwar_pool_data)?;
+    save_user_storage_account(&mut user_data_byte_array, &user_storage_data)?;
+
+    Ok(())
+}
+
+/// Books a new lock position of `amount` for a checkpointed user and returns its reward weight
+pub fn add_lock_position(
+    cwar_pool_data: &mut CwarPool,
+    user_storage_data: &mut User,
+    lock_tier: &LockTier,
+    amount: u64,
+) -> Result<u64, ProgramError> {
+    let weight = assert_u128_to_u64_conversion(
+        (amount as u128)
+            .checked_mul(lock_tier.multiplier_basis_points as u128)
+            .ok_or(CryowarError::AmountOverflow)?
+            .checked_div(FRACTION_TO_BASIS_POINTS)
+            .ok_or(CryowarError::AmountOverflow)?,
+    )?;
+    user_storage_data.locked_amount = user_storage_data
+        .locked_amount
+        .checked_add(amount)
+        .ok_or(CryowarError::AmountOverflow)?;
+    user_storage_data.locked_weight = user_storage_data
+        .locked_weight
+        .checked_add(weight)
+        .ok_or(CryowarError::AmountOverflow)?;
+    cwar_pool_data.total_staked = cwar_pool_data
+        .total_staked
+        .checked_add(amount)
+        .ok_or(CryowarError::AmountOverflow)?;
+    cwar_pool_data.total_locked = cwar_pool_data
+        .total_locked
+        .checked_add(amount)
+        .ok_or(CryowarError::AmountOverflow)?;
+    cwar_pool_data.total_locked_weight = cwar_pool_data
+        .total_locked_weight
+        .checked_add(weight)
+        .ok_or(CryowarError::AmountOverflow)?;
+    Ok(weight)
+}
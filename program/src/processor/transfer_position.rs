@@ -0,0 +1,298 @@
+use std::convert::TryInto;
+
+use crate::{
+    error::CryowarError,
+    processor::create_user::{create_and_allocate_account_raw, create_user_storage_account},
+    state::{
+        CwarPool, LockPosition, User, LOCK_POSITION_STORAGE_TOTAL_BYTES, PAUSE_CREATE_USER,
+        PAUSE_UNSTAKE,
+    },
+    utils::{
+        self, assert_lock_position_account, assert_no_allowlist, assert_no_receipt_mint,
+        assert_not_paused, assert_pool_storage_account, assert_signer, assert_system_program,
+        assert_user_storage_account, check_user_stake_limits,
+        get_lock_position_address_and_bump_seed, next_reward_schedule, save_lock_position_account,
+        save_pool_storage_account, save_user_storage_account,
+    },
+};
+use solana_program::{
+    account_info::{next_account_info, AccountInfo},
+    clock::Clock,
+    entrypoint::ProgramResult,
+    msg,
+    program_error::ProgramError,
+    pubkey::Pubkey,
+    sysvar::Sysvar,
+};
+
+/// A missing recipient user account is created, paid by the current owner, unless the pool has
+/// an allowlist: allowlisted recipients have to join with `CreateAllowlistedUser` first. Every
+/// lock position of the user is closed and opened again under the recipient
+pub fn process_transfer_position(accounts: &[AccountInfo], program_id: &Pubkey) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+    let user_wallet_account = next_account_info(account_info_iter)?;
+    let user_storage_account = next_account_info(account_info_iter)?;
+    let cwar_pool_storage_account = next_account_info(account_info_iter)?;
+    let recipient_wallet_account = next_account_info(account_info_iter)?;
+    let recipient_storage_account = next_account_info(account_info_iter)?;
+    let system_program_info = next_account_info(account_info_iter)?;
+
+    assert_signer(user_wallet_account)?;
+    if recipient_wallet_account.key == user_wallet_account.key {
+        msg!("CryowarError::InvalidPositionRecipient");
+        return Err(CryowarError::InvalidPositionRecipient.into());
+    }
+
+    let mut user_data_byte_array = user_storage_account.data.try_borrow_mut().unwrap();
+    let mut user_storage_data = assert_user_storage_account(
+        user_wallet_account,
+        cwar_pool_storage_account,
+        program_id,
+        user_storage_account,
+        &user_data_byte_array,
+    )?;
+
+    let mut cwar_pool_data_byte_array = cwar_pool_storage_account.data.try_borrow_mut().unwrap();
+    let mut cwar_pool_data = assert_pool_storage_account(
+        &cwar_pool_data_byte_array,
+        cwar_pool_storage_account,
+        program_id,
+    )?;
+    assert_not_paused(&cwar_pool_data, PAUSE_UNSTAKE)?;
+    let reward_schedule = next_reward_schedule(account_info_iter, &cwar_pool_data, program_id)?;
+    // positions of receipt pools follow their receipts through `SettleReceipt`
+    assert_no_receipt_mint(&cwar_pool_data)?;
+
+    let mut recipient_storage_data = if recipient_storage_account.data_is_empty() {
+        assert_not_paused(&cwar_pool_data, PAUSE_CREATE_USER)?;
+        assert_no_allowlist(&cwar_pool_data)?;
+        create_user_storage_account(
+            recipient_wallet_account.key,
+            recipient_storage_account,
+            cwar_pool_storage_account,
+            system_program_info,
+            user_wallet_account,
+            &mut cwar_pool_data,
+            0u64,
+            program_id,
+        )?
+    } else {
+        assert_user_storage_account(
+            recipient_wallet_account,
+            cwar_pool_storage_account,
+            program_id,
+            recipient_storage_account,
+            &recipient_storage_account.data.borrow(),
+        )?
+    };
+
+    // the rest of the accounts are pairs of a lock position and its address under the recipient
+    let mut lock_position_accounts = vec![];
+    let mut lock_positions = vec![];
+    while let Ok(lock_position_account) = next_account_info(account_info_iter) {
+        let recipient_lock_position_account = next_account_info(account_info_iter)?;
+        lock_positions.push(assert_lock_position_account(
+            lock_position_account,
+            user_storage_account,
+            program_id,
+            &lock_position_account.data.borrow(),
+        )?);
+        lock_position_accounts.push((lock_position_account, recipient_lock_position_account));
+    }
+    if !lock_positions.is_empty() {
+        assert_system_program(system_program_info)?;
+    }
+
+    let now: u64 = Clock::get()?.unix_timestamp.try_into().unwrap();
+    utils::update_rewards(
+        &mut cwar_pool_data,
+        reward_schedule.as_ref(),
+        Some(&mut user_storage_data),
+        now,
+    )?;
+    utils::update_rewards(
+        &mut cwar_pool_data,
+        reward_schedule.as_ref(),
+        Some(&mut recipient_storage_data),
+        now,
+    )?;
+    msg!(
+        "amount_to_transfer: {}",
+        user_storage_data.user_cwar_staked_amount
+    );
+    transfer_position(
+        &cwar_pool_data,
+        &mut user_storage_data,
+        &mut recipient_storage_data,
+        recipient_storage_account.key,
+        &mut lock_positions,
+    )?;
+
+    for ((lock_position_account, recipient_lock_position_account), lock_position_data) in
+        lock_position_accounts
+            .into_iter()
+            .zip(lock_positions.iter())
+    {
+        let (recipient_lock_position_address, bump_seed) = get_lock_position_address_and_bump_seed(
+            recipient_storage_account.key,
+            lock_position_data.position_id,
+            program_id,
+        );
+        if recipient_lock_position_address != *recipient_lock_position_account.key {
+            msg!("Error: Lock Position address does not match seed derivation");
+            return Err(ProgramError::InvalidSeeds);
+        }
+        create_and_allocate_account_raw(
+            *program_id,
+            recipient_lock_position_account,
+            system_program_info,
+            user_wallet_account,
+            LOCK_POSITION_STORAGE_TOTAL_BYTES,
+            &[
+                b"lock_position",
+                &recipient_storage_account.key.to_bytes(),
+                &lock_position_data.position_id.to_le_bytes(),
+                &[bump_seed],
+            ],
+        )?;
+        save_lock_position_account(
+            &mut recipient_lock_position_account
+                .data
+                .try_borrow_mut()
+                .unwrap(),
+            lock_position_data,
+        )?;
+        msg!("position_id: {}", lock_position_data.position_id);
+
+        let mut lock_position_byte_array = lock_position_account.data.try_borrow_mut().unwrap();
+        lock_position_byte_array.fill(0u8);
+        utils::close_account(
+            lock_position_account,
+            user_wallet_account,
+            &mut lock_position_byte_array,
+        )?;
+    }
+
+    save_pool_storage_account(&mut cwar_pool_data_byte_array, &cwar_pool_data)?;
+    save_user_storage_account(&mut user_data_byte_array, &user_storage_data)?;
+    let mut recipient_data_byte_array = recipient_storage_account.data.try_borrow_mut().unwrap();
+    save_user_storage_account(&mut recipient_data_byte_array, &recipient_storage_data)?;
+
+    Ok(())
+}
+
+/// Moves the stake and pending rewards of a checkpointed user onto a recipient checkpointed at
+/// the same time. A recipient without stake takes the user's penality and locking timestamps,
+/// one with stake keeps the later of both so the transfer can neither shorten nor extend the
+/// windows of the moved stake. `lock_positions` has to hold every lock position of the user,
+/// they keep their amount, weight and unlock time and are renumbered under `recipient_storage`.
+/// Unbonding requests are not moved, they have to be withdrawn first.
+pub fn transfer_position(
+    cwar_pool_data: &CwarPool,
+    user_storage_data: &mut User,
+    recipient_storage_data: &mut User,
+    recipient_storage: &Pubkey,
+    lock_positions: &mut [LockPosition],
+) -> ProgramResult {
+    if user_storage_data.unbonding_amount != 0u64 {
+        msg!("CryowarError::PositionNotTransferable");
+        return Err(CryowarError::PositionNotTransferable.into());
+    }
+    let mut locked_amount = 0u64;
+    let mut locked_weight = 0u64;
+    for (index, lock_position_data) in lock_positions.iter().enumerate() {
+        if lock_positions[..index]
+            .iter()
+            .any(|x| x.position_id == lock_position_data.position_id)
+        {
+            msg!("CryowarError::PositionNotTransferable");
+            return Err(CryowarError::PositionNotTransferable.into());
+        }
+        locked_amount = locked_amount
+            .checked_add(lock_position_data.amount)
+            .ok_or(CryowarError::AmountOverflow)?;
+        locked_weight = locked_weight
+            .checked_add(lock_position_data.weight)
+            .ok_or(CryowarError::AmountOverflow)?;
+    }
+    // a position left behind would point at a user account without its weight
+    if locked_amount != user_storage_data.locked_amount
+        || locked_weight != user_storage_data.locked_weight
+    {
+        msg!("CryowarError::PositionNotTransferable");
+        msg!("locked_amount: {}", user_storage_data.locked_amount);
+        return Err(CryowarError::PositionNotTransferable.into());
+    }
+
+    let amount = user_storage_data
+        .user_cwar_staked_amount
+        .checked_add(locked_amount)
+        .ok_or(CryowarError::AmountOverflow)?;
+    if amount > 0u64 {
+        check_user_stake_limits(cwar_pool_data, recipient_storage_data, amount)?;
+    }
+    if user_storage_data.user_cwar_staked_amount > 0u64 {
+        merge_stake_timestamps(user_storage_data, recipient_storage_data);
+        recipient_storage_data.user_cwar_staked_amount = recipient_storage_data
+            .user_cwar_staked_amount
+            .checked_add(user_storage_data.user_cwar_staked_amount)
+            .ok_or(CryowarError::AmountOverflow)?;
+        user_storage_data.user_cwar_staked_amount = 0u64;
+    }
+
+    for lock_position_data in lock_positions.iter_mut() {
+        lock_position_data.user_storage = *recipient_storage;
+        lock_position_data.position_id = recipient_storage_data.next_lock_position_id;
+        recipient_storage_data.next_lock_position_id = recipient_storage_data
+            .next_lock_position_id
+            .checked_add(1u64)
+            .ok_or(CryowarError::AmountOverflow)?;
+    }
+    recipient_storage_data.locked_amount = recipient_storage_data
+        .locked_amount
+        .checked_add(locked_amount)
+        .ok_or(CryowarError::AmountOverflow)?;
+    recipient_storage_data.locked_weight = recipient_storage_data
+        .locked_weight
+        .checked_add(locked_weight)
+        .ok_or(CryowarError::AmountOverflow)?;
+    user_storage_data.locked_amount = 0u64;
+    user_storage_data.locked_weight = 0u64;
+
+    recipient_storage_data.rewards_amount_pending = recipient_storage_data
+        .rewards_amount_pending
+        .checked_add(user_storage_data.rewards_amount_pending)
+        .ok_or(CryowarError::AmountOverflow)?;
+    user_storage_data.rewards_amount_pending = 0u64;
+    for (recipient_reward_stream, user_reward_stream) in recipient_storage_data
+        .reward_streams
+        .iter_mut()
+        .zip(user_storage_data.reward_streams.iter_mut())
+    {
+        recipient_reward_stream.rewards_amount_pending = recipient_reward_stream
+            .rewards_amount_pending
+            .checked_add(user_reward_stream.rewards_amount_pending)
+            .ok_or(CryowarError::AmountOverflow)?;
+        user_reward_stream.rewards_amount_pending = 0u64;
+    }
+    Ok(())
+}
+
+/// Gives `recipient_storage_data` the penality and locking timestamps of stake moved over from
+/// `user_storage_data`, before the recipient's stake is credited
+pub fn merge_stake_timestamps(user_storage_data: &User, recipient_storage_data: &mut User) {
+    if recipient_storage_data.user_cwar_staked_amount == 0u64 {
+        recipient_storage_data.unstake_penality_duration_end =
+            user_storage_data.unstake_penality_duration_end;
+        recipient_storage_data.last_staked_timestamp = user_storage_data.last_staked_timestamp;
+        return;
+    }
+    recipient_storage_data.unstake_penality_duration_end = std::cmp::max(
+        recipient_storage_data.unstake_penality_duration_end,
+        user_storage_data.unstake_penality_duration_end,
+    );
+    recipient_storage_data.last_staked_timestamp = std::cmp::max(
+        recipient_storage_data.last_staked_timestamp,
+        user_storage_data.last_staked_timestamp,
+    );
+}
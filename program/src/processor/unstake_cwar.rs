@@ -2,12 +2,13 @@ use std::convert::TryInto;
 
 use crate::{
     error::CryowarError,
-    state::{CwarPool, User},
+    state::{CwarPool, User, PAUSE_UNSTAKE},
     utils::{
-        self, assert_penality_deposit_ata, assert_pool_storage_account, assert_signer,
-        assert_staking_vault, assert_token_program, assert_u128_to_u64_conversion,
-        assert_user_storage_account, save_pool_storage_account, save_user_storage_account,
-        FRACTION_TO_BASIS_POINTS,
+        self, assert_not_paused, assert_penality_deposit_ata, assert_pool_storage_account,
+        assert_reward_vault, assert_signer, assert_staking_vault, assert_token_program,
+        assert_user_storage_account, burn_receipt, create_recipient_ata_if_missing,
+        next_recipient_accounts, next_reward_schedule, save_pool_storage_account,
+        save_user_storage_account, PenalitySplit,
     },
 };
 
@@ -17,6 +18,7 @@ use solana_program::{
     entrypoint::ProgramResult,
     msg,
     program::invoke_signed,
+    program_error::ProgramError,
     program_pack::Pack,
     pubkey::Pubkey,
     sysvar::Sysvar,
@@ -37,6 +39,7 @@ pub fn process_unstake_cwar(
     let pool_signer_pda = next_account_info(account_info_iter)?;
     let token_program = next_account_info(account_info_iter)?;
     let authority_penality_deposit_ata_account = next_account_info(account_info_iter)?;
+    let recipient_accounts = next_recipient_accounts(account_info_iter)?;
 
     assert_signer(user_wallet_account)?;
     assert_token_program(token_program)?;
@@ -61,6 +64,13 @@ pub fn process_unstake_cwar(
         cwar_pool_storage_account,
         program_id,
     )?;
+    assert_not_paused(&cwar_pool_data, PAUSE_UNSTAKE)?;
+    let reward_schedule = next_reward_schedule(account_info_iter, &cwar_pool_data, program_id)?;
+
+    if cwar_pool_data.unbonding_duration > 0u64 {
+        msg!("CryowarError::UnbondingRequired");
+        return Err(CryowarError::UnbondingRequired.into());
+    }
 
     let cwar_staking_vault_data = TokenAccount::unpack(&staking_vault.data.borrow())?;
     let (pool_signer_address, bump_seed) =
@@ -75,62 +85,84 @@ pub fn process_unstake_cwar(
 
     assert_penality_deposit_ata(authority_penality_deposit_ata_account, &cwar_pool_data)?;
 
+    if let Some(recipient_accounts) = &recipient_accounts {
+        create_recipient_ata_if_missing(
+            user_cwar_ata,
+            recipient_accounts.recipient_wallet,
+            recipient_accounts.mint,
+            &cwar_pool_data.staking_mint,
+            user_wallet_account,
+            recipient_accounts.system_program_info,
+            token_program,
+            recipient_accounts.associated_token_program,
+        )?;
+    }
+
+    let staking_mint_account = if cwar_pool_data.penality_routing.burn_basis_points > 0u16 {
+        let staking_mint_account = next_account_info(account_info_iter)?;
+        if *staking_mint_account.key != cwar_pool_data.staking_mint {
+            msg!("CryowarError::MintMismatched");
+            return Err(CryowarError::MintMismatched.into());
+        }
+        Some(staking_mint_account)
+    } else {
+        None
+    };
+    let rewards_vault = if cwar_pool_data.penality_routing.uses_reward_vault() {
+        let rewards_vault = next_account_info(account_info_iter)?;
+        let rewards_vault_data = TokenAccount::unpack(&rewards_vault.data.borrow())?;
+        assert_reward_vault(
+            rewards_vault,
+            &cwar_pool_data,
+            &rewards_vault_data,
+            &pool_signer_address,
+        )?;
+        Some(rewards_vault)
+    } else {
+        None
+    };
+    let receipt_accounts = if cwar_pool_data.has_receipt_mint() {
+        Some((
+            next_account_info(account_info_iter)?,
+            next_account_info(account_info_iter)?,
+        ))
+    } else {
+        None
+    };
+
     if user_storage_data.user_cwar_staked_amount < amount_to_withdraw {
         msg!("CryowarError::InsufficientFundsToUnstake");
         return Err(CryowarError::InsufficientFundsToUnstake.into());
     }
 
     let now: u64 = Clock::get()?.unix_timestamp.try_into().unwrap();
-    let total_cwar_staked = cwar_staking_vault_data.amount;
     utils::update_rewards(
         &mut cwar_pool_data,
+        reward_schedule.as_ref(),
         Some(&mut user_storage_data),
-        total_cwar_staked,
         now,
     )?;
 
     let mut amount_sent_to_user = amount_to_withdraw;
 
-    if user_storage_data.unstake_penality_duration_end > now
-        && cwar_pool_data.unstake_penality_basis_points > 0u16
-    {
+    let penality_amount =
+        utils::get_unstake_penality(&cwar_pool_data, &user_storage_data, amount_to_withdraw, now)?;
+    if penality_amount > 0u64 {
         msg!("current timestamp: {}", now);
-        msg!("user_storage_data.unstake_penality_duration_end: {}", user_storage_data.unstake_penality_duration_end);
-        let penality_amount = assert_u128_to_u64_conversion(
-            (amount_to_withdraw as u128)
-                .checked_mul(cwar_pool_data.unstake_penality_basis_points as u128)
-                .ok_or(CryowarError::AmountOverflow)?
-                .checked_div(FRACTION_TO_BASIS_POINTS)
-                .ok_or(CryowarError::AmountOverflow)?,
-        )?;
+        msg!(
+            "user_storage_data.unstake_penality_duration_end: {}",
+            user_storage_data.unstake_penality_duration_end
+        );
+        msg!("penality_amount: {}", penality_amount);
         amount_sent_to_user = amount_to_withdraw
             .checked_sub(penality_amount)
             .ok_or(CryowarError::AmountOverflow)?;
-
-        msg!("Calling the token program to transfer CWAR to Unstake Penality ATA from Staking Vault...");
-        invoke_signed(
-            &spl_token::instruction::transfer(
-                token_program.key,
-                staking_vault.key,
-                authority_penality_deposit_ata_account.key,
-                &pool_signer_address,
-                &[&pool_signer_address],
-                penality_amount,
-            )?,
-            &[
-                staking_vault.clone(),
-                authority_penality_deposit_ata_account.clone(),
-                pool_signer_pda.clone(),
-                token_program.clone(),
-            ],
-            &[&[&cwar_pool_storage_account.key.to_bytes(), &[bump_seed]]],
-        )?;
     }
 
     check_locking_period(&user_storage_data, &cwar_pool_data, now)?;
 
     let cwar_staking_vault_data_before = TokenAccount::unpack(&staking_vault.data.borrow())?;
-    msg!("Calling the token program to transfer CWAR to User from Staking Vault...");
+    msg!("Calling the token program to transfer CWAR to Recipient from Staking Vault...");
     invoke_signed(
         &spl_token::instruction::transfer(
             token_program.key,
@@ -162,6 +194,100 @@ pub fn process_unstake_cwar(
         .user_cwar_staked_amount
         .checked_sub(amount_to_withdraw)
         .ok_or(CryowarError::AmountOverflow)?;
+    // the penalty leaves the vault as well, so the whole amount stops being staked
+    cwar_pool_data.total_staked = cwar_pool_data
+        .total_staked
+        .checked_sub(amount_to_withdraw)
+        .ok_or(CryowarError::AmountOverflow)?;
+
+    if let Some((receipt_mint, user_receipt_ata)) = receipt_accounts {
+        burn_receipt(
+            receipt_mint,
+            user_receipt_ata,
+            user_wallet_account,
+            token_program,
+            &cwar_pool_data,
+            amount_to_withdraw,
+        )?;
+    }
+
+    if penality_amount > 0u64 {
+        let penality_split = route_penality(&mut cwar_pool_data, penality_amount, now)?;
+        msg!("penality_split: {:?}", penality_split);
+        let signer_seeds: &[&[u8]] = &[&cwar_pool_storage_account.key.to_bytes(), &[bump_seed]];
+
+        if penality_split.burn > 0u64 {
+            let staking_mint_account =
+                staking_mint_account.ok_or(ProgramError::NotEnoughAccountKeys)?;
+            msg!("Calling the token program to burn CWAR from Staking Vault...");
+            invoke_signed(
+                &spl_token::instruction::burn(
+                    token_program.key,
+                    staking_vault.key,
+                    staking_mint_account.key,
+                    &pool_signer_address,
+                    &[&pool_signer_address],
+                    penality_split.burn,
+                )?,
+                &[
+                    staking_vault.clone(),
+                    staking_mint_account.clone(),
+                    pool_signer_pda.clone(),
+                    token_program.clone(),
+                ],
+                &[signer_seeds],
+            )?;
+        }
+
+        let amount_to_rewards_vault = penality_split
+            .reward_vault
+            .checked_add(penality_split.redistribute)
+            .ok_or(CryowarError::AmountOverflow)?;
+        if amount_to_rewards_vault > 0u64 {
+            let rewards_vault = rewards_vault.ok_or(ProgramError::NotEnoughAccountKeys)?;
+            msg!(
+                "Calling the token program to transfer CWAR to Rewards Vault from Staking Vault..."
+            );
+            invoke_signed(
+                &spl_token::instruction::transfer(
+                    token_program.key,
+                    staking_vault.key,
+                    rewards_vault.key,
+                    &pool_signer_address,
+                    &[&pool_signer_address],
+                    amount_to_rewards_vault,
+                )?,
+                &[
+                    staking_vault.clone(),
+                    rewards_vault.clone(),
+                    pool_signer_pda.clone(),
+                    token_program.clone(),
+                ],
+                &[signer_seeds],
+            )?;
+        }
+
+        if penality_split.treasury > 0u64 {
+            msg!("Calling the token program to transfer CWAR to Unstake Penality ATA from Staking Vault...");
+            invoke_signed(
+                &spl_token::instruction::transfer(
+                    token_program.key,
+                    staking_vault.key,
+                    authority_penality_deposit_ata_account.key,
+                    &pool_signer_address,
+                    &[&pool_signer_address],
+                    penality_split.treasury,
+                )?,
+                &[
+                    staking_vault.clone(),
+                    authority_penality_deposit_ata_account.clone(),
+                    pool_signer_pda.clone(),
+                    token_program.clone(),
+                ],
+                &[signer_seeds],
+            )?;
+        }
+    }
 
     save_pool_storage_account(&mut cwar_pool_data_byte_array, &cwar_pool_data)?;
     save_user_storage_account(&mut user_data_byte_array, &user_storage_data)?;
@@ -169,6 +295,52 @@ pub fn process_unstake_cwar(
     Ok(())
 }
 
+/// Applies the pool `PenalityRouting` to a penalty once the unstaked amount is off the books.
+/// A reward vault share after the reward period ended, or a redistributed share with nobody left
+/// staking, goes to the treasury instead.
+pub fn route_penality(
+    cwar_pool_data: &mut CwarPool,
+    penality_amount: u64,
+    now: u64,
+) -> Result<PenalitySplit, ProgramError> {
+    let mut penality_split =
+        utils::split_penality(penality_amount, &cwar_pool_data.penality_routing)?;
+
+    if penality_split.reward_vault > 0u64 {
+        if now < cwar_pool_data.reward_duration_end {
+            let remaining_duration = cwar_pool_data.reward_duration_end - now;
+            let rewards_left_amount = remaining_duration
+                .checked_mul(cwar_pool_data.reward_rate)
+                .ok_or(CryowarError::AmountOverflow)?;
+            cwar_pool_data.reward_rate = rewards_left_amount
+                .checked_add(penality_split.reward_vault)
+                .ok_or(CryowarError::AmountOverflow)?
+                .checked_div(remaining_duration)
+                .ok_or(CryowarError::AmountOverflow)?;
+        } else {
+            penality_split.treasury = penality_split
+                .treasury
+                .checked_add(penality_split.reward_vault)
+                .ok_or(CryowarError::AmountOverflow)?;
+            penality_split.reward_vault = 0u64;
+        }
+    }
+
+    if penality_split.redistribute > 0u64 {
+        if utils::get_total_reward_weight(cwar_pool_data)? > 0u64 {
+            utils::distribute_to_stakers(cwar_pool_data, penality_split.redistribute)?;
+        } else {
+            penality_split.treasury = penality_split
+                .treasury
+                .checked_add(penality_split.redistribute)
+                .ok_or(CryowarError::AmountOverflow)?;
+            penality_split.redistribute = 0u64;
+        }
+    }
+
+    Ok(penality_split)
+}
+
 pub fn check_locking_period(
     user_storage_data: &User,
     cwar_pool_data: &CwarPool,
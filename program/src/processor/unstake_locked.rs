@@ -0,0 +1,158 @@
+use std::convert::TryInto;
+
+use crate::{
+    error::CryowarError,
+    state::{CwarPool, LockPosition, User},
+    utils::{
+        self, assert_lock_position_account, assert_pool_storage_account, assert_signer,
+        assert_staking_vault, assert_token_program, assert_user_storage_account,
+        get_pool_signer_address_and_bump_seed, next_reward_schedule, save_pool_storage_account,
+        save_user_storage_account,
+    },
+};
+use solana_program::{
+    account_info::{next_account_info, AccountInfo},
+    clock::Clock,
+    entrypoint::ProgramResult,
+    msg,
+    program::invoke_signed,
+    program_pack::Pack,
+    pubkey::Pubkey,
+    sysvar::Sysvar,
+};
+use spl_token::state::Account as TokenAccount;
+
+/// Withdraws an unlocked position in full. The lock already served as the cooldown, so neither
+/// the unstake penalty nor unbonding apply to it. Only the principal leaves, so like
+/// `EmergencyUnstake` it ignores the pause flags and expired locks can always be withdrawn
+pub fn process_unstake_locked(accounts: &[AccountInfo], program_id: &Pubkey) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+    let user_wallet_account = next_account_info(account_info_iter)?;
+    let user_storage_account = next_account_info(account_info_iter)?;
+    let cwar_pool_storage_account = next_account_info(account_info_iter)?;
+    let lock_position_account = next_account_info(account_info_iter)?;
+    let staking_vault = next_account_info(account_info_iter)?;
+    let user_cwar_ata = next_account_info(account_info_iter)?;
+    let pool_signer_pda = next_account_info(account_info_iter)?;
+    let token_program = next_account_info(account_info_iter)?;
+
+    assert_signer(user_wallet_account)?;
+    assert_token_program(token_program)?;
+    let mut user_data_byte_array = user_storage_account.data.try_borrow_mut().unwrap();
+    let mut user_storage_data = assert_user_storage_account(
+        user_wallet_account,
+        cwar_pool_storage_account,
+        program_id,
+        user_storage_account,
+        &user_data_byte_array,
+    )?;
+
+    let mut cwar_pool_data_byte_array = cwar_pool_storage_account.data.try_borrow_mut().unwrap();
+    let mut cwar_pool_data = assert_pool_storage_account(
+        &cwar_pool_data_byte_array,
+        cwar_pool_storage_account,
+        program_id,
+    )?;
+    let reward_schedule = next_reward_schedule(account_info_iter, &cwar_pool_data, program_id)?;
+
+    let mut lock_position_byte_array = lock_position_account.data.try_borrow_mut().unwrap();
+    let lock_position_data = assert_lock_position_account(
+        lock_position_account,
+        user_storage_account,
+        program_id,
+        &lock_position_byte_array,
+    )?;
+
+    let now: u64 = Clock::get()?.unix_timestamp.try_into().unwrap();
+    if now < lock_position_data.unlock_at {
+        msg!("CryowarError::LockPositionNotUnlocked");
+        msg!("unlock_at: {}", lock_position_data.unlock_at);
+        return Err(CryowarError::LockPositionNotUnlocked.into());
+    }
+
+    let (pool_signer_address, bump_seed) =
+        get_pool_signer_address_and_bump_seed(cwar_pool_storage_account.key, program_id);
+    if pool_signer_address != *pool_signer_pda.key {
+        msg!("CryowarError::InvalidSignerPDA");
+        return Err(CryowarError::InvalidSignerPDA.into());
+    }
+    let cwar_staking_vault_data = TokenAccount::unpack(&staking_vault.data.borrow())?;
+    assert_staking_vault(
+        staking_vault,
+        &cwar_pool_data,
+        &cwar_staking_vault_data,
+        &pool_signer_address,
+    )?;
+
+    utils::update_rewards(
+        &mut cwar_pool_data,
+        reward_schedule.as_ref(),
+        Some(&mut user_storage_data),
+        now,
+    )?;
+    remove_lock_position(
+        &mut cwar_pool_data,
+        &mut user_storage_data,
+        &lock_position_data,
+    )?;
+
+    msg!("Calling the token program to transfer CWAR to User from Staking Vault...");
+    invoke_signed(
+        &spl_token::instruction::transfer(
+            token_program.key,
+            staking_vault.key,
+            user_cwar_ata.key,
+            &pool_signer_address,
+            &[&pool_signer_address],
+            lock_position_data.amount,
+        )?,
+        &[
+            staking_vault.clone(),
+            user_cwar_ata.clone(),
+            pool_signer_pda.clone(),
+            token_program.clone(),
+        ],
+        &[&[&cwar_pool_storage_account.key.to_bytes(), &[bump_seed]]],
+    )?;
+
+    save_pool_storage_account(&mut cwar_pool_data_byte_array, &cwar_pool_data)?;
+    save_user_storage_account(&mut user_data_byte_array, &user_storage_data)?;
+
+    lock_position_byte_array.fill(0u8);
+    utils::close_account(
+        lock_position_account,
+        user_wallet_account,
+        &mut lock_position_byte_array,
+    )?;
+
+    Ok(())
+}
+
+/// Takes a lock position of a checkpointed user off the books, along with its reward weight
+pub fn remove_lock_position(
+    cwar_pool_data: &mut CwarPool,
+    user_storage_data: &mut User,
+    lock_position_data: &LockPosition,
+) -> ProgramResult {
+    user_storage_data.locked_amount = user_storage_data
+        .locked_amount
+        .checked_sub(lock_position_data.amount)
+        .ok_or(CryowarError::AmountOverflow)?;
+    user_storage_data.locked_weight = user_storage_data
+        .locked_weight
+        .checked_sub(lock_position_data.weight)
+        .ok_or(CryowarError::AmountOverflow)?;
+    cwar_pool_data.total_staked = cwar_pool_data
+        .total_staked
+        .checked_sub(lock_position_data.amount)
+        .ok_or(CryowarError::AmountOverflow)?;
+    cwar_pool_data.total_locked = cwar_pool_data
+        .total_locked
+        .checked_sub(lock_position_data.amount)
+        .ok_or(CryowarError::AmountOverflow)?;
+    cwar_pool_data.total_locked_weight = cwar_pool_data
+        .total_locked_weight
+        .checked_sub(lock_position_data.weight)
+        .ok_or(CryowarError::AmountOverflow)?;
+    Ok(())
+}
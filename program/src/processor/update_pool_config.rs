@@ -0,0 +1,141 @@
+use crate::{
+    error::CryowarError,
+    state::CwarPool,
+    utils::{
+        assert_not_timelocked, assert_penality_deposit_ata_data, assert_pool_config,
+        assert_pool_storage_account, assert_signer, save_pool_storage_account,
+    },
+};
+use solana_program::{
+    account_info::{next_account_info, AccountInfo},
+    entrypoint::ProgramResult,
+    msg,
+    program_error::ProgramError,
+    pubkey::Pubkey,
+};
+
+pub fn process_update_pool_config(
+    accounts: &[AccountInfo],
+    unstake_penality_basis_points: Option<u16>,
+    locking_duration: Option<u64>,
+    authority_penality_deposit_ata: Option<Pubkey>,
+    unbonding_duration: Option<u64>,
+    program_id: &Pubkey,
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+    let pool_owner_wallet_account = next_account_info(account_info_iter)?;
+    let cwar_pool_storage_account = next_account_info(account_info_iter)?;
+
+    assert_signer(pool_owner_wallet_account)?;
+
+    let mut cwar_pool_data_byte_array = cwar_pool_storage_account.data.try_borrow_mut().unwrap();
+    let mut cwar_pool_data = assert_pool_storage_account(
+        &cwar_pool_data_byte_array,
+        cwar_pool_storage_account,
+        program_id,
+    )?;
+
+    if cwar_pool_data.owner_wallet != *pool_owner_wallet_account.key {
+        msg!("CryowarError::PoolOwnerMismatched");
+        return Err(CryowarError::PoolOwnerMismatched.into());
+    }
+
+    assert_not_timelocked(&cwar_pool_data)?;
+
+    let authority_penality_deposit_ata_account = match authority_penality_deposit_ata {
+        Some(_) => Some(next_account_info(account_info_iter)?),
+        None => None,
+    };
+    update_pool_config(
+        &mut cwar_pool_data,
+        unstake_penality_basis_points,
+        locking_duration,
+        authority_penality_deposit_ata,
+        authority_penality_deposit_ata_account,
+        unbonding_duration,
+        false,
+    )?;
+
+    save_pool_storage_account(&mut cwar_pool_data_byte_array, &cwar_pool_data)?;
+
+    Ok(())
+}
+
+/// Penalty, lock and unbonding changes apply to existing stakers on their next unstake, so
+/// unless the change went through the timelock and stakers had time to exit, they can only be
+/// relaxed while anything is staked. Open unbonding requests keep the time they were given.
+pub fn update_pool_config(
+    cwar_pool_data: &mut CwarPool,
+    unstake_penality_basis_points: Option<u16>,
+    locking_duration: Option<u64>,
+    authority_penality_deposit_ata: Option<Pubkey>,
+    authority_penality_deposit_ata_account: Option<&AccountInfo>,
+    unbonding_duration: Option<u64>,
+    stakers_had_time_to_exit: bool,
+) -> ProgramResult {
+    let new_unstake_penality_basis_points =
+        unstake_penality_basis_points.unwrap_or(cwar_pool_data.unstake_penality_basis_points);
+    let new_locking_duration = locking_duration.unwrap_or(cwar_pool_data.locking_duration);
+    let new_unbonding_duration = unbonding_duration.unwrap_or(cwar_pool_data.unbonding_duration);
+    assert_pool_config(
+        new_unstake_penality_basis_points,
+        new_locking_duration,
+        new_unbonding_duration,
+    )?;
+    // unbonding stake has no receipts to burn
+    if new_unbonding_duration > 0u64 && cwar_pool_data.has_receipt_mint() {
+        msg!("CryowarError::NotSupportedByReceiptPools");
+        return Err(CryowarError::NotSupportedByReceiptPools.into());
+    }
+
+    if !stakers_had_time_to_exit
+        && cwar_pool_data.total_staked > 0u64
+        && (new_unstake_penality_basis_points > cwar_pool_data.unstake_penality_basis_points
+            || new_locking_duration > cwar_pool_data.locking_duration
+            || new_unbonding_duration > cwar_pool_data.unbonding_duration)
+    {
+        msg!("CryowarError::PoolConfigChangeNotAllowedWhileStaked");
+        return Err(CryowarError::PoolConfigChangeNotAllowedWhileStaked.into());
+    }
+
+    if let Some(authority_penality_deposit_ata) = authority_penality_deposit_ata {
+        let authority_penality_deposit_ata_account =
+            authority_penality_deposit_ata_account.ok_or(ProgramError::NotEnoughAccountKeys)?;
+        if *authority_penality_deposit_ata_account.key != authority_penality_deposit_ata {
+            msg!("CryowarError::InvalidAuthorityPenalityDepositATA");
+            return Err(CryowarError::InvalidAuthorityPenalityDepositATA.into());
+        }
+        assert_penality_deposit_ata_data(
+            authority_penality_deposit_ata_account,
+            &cwar_pool_data.staking_mint,
+            &cwar_pool_data.owner_wallet,
+        )?;
+        msg!(
+            "authority_penality_deposit_ata: {} -> {}",
+            cwar_pool_data.authority_penality_deposit_ata,
+            authority_penality_deposit_ata
+        );
+        cwar_pool_data.authority_penality_deposit_ata = authority_penality_deposit_ata;
+    }
+
+    msg!(
+        "unstake_penality_basis_points: {} -> {}",
+        cwar_pool_data.unstake_penality_basis_points,
+        new_unstake_penality_basis_points
+    );
+    msg!(
+        "locking_duration: {} -> {}",
+        cwar_pool_data.locking_duration,
+        new_locking_duration
+    );
+    msg!(
+        "unbonding_duration: {} -> {}",
+        cwar_pool_data.unbonding_duration,
+        new_unbonding_duration
+    );
+    cwar_pool_data.unstake_penality_basis_points = new_unstake_penality_basis_points;
+    cwar_pool_data.locking_duration = new_locking_duration;
+    cwar_pool_data.unbonding_duration = new_unbonding_duration;
+
+    Ok(())
+}
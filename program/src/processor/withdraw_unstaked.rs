@@ -0,0 +1,126 @@
+use std::convert::TryInto;
+
+use crate::{
+    error::CryowarError,
+    processor::request_unstake::settle_unbonding_request,
+    utils::{
+        self, assert_pool_storage_account, assert_signer, assert_staking_vault,
+        assert_token_program, assert_unbonding_request_account, assert_user_storage_account,
+        get_pool_signer_address_and_bump_seed, save_pool_storage_account,
+        save_user_storage_account,
+    },
+};
+use solana_program::{
+    account_info::{next_account_info, AccountInfo},
+    clock::Clock,
+    entrypoint::ProgramResult,
+    msg,
+    program::invoke_signed,
+    program_pack::Pack,
+    pubkey::Pubkey,
+    sysvar::Sysvar,
+};
+use spl_token::state::Account as TokenAccount;
+
+/// Unbonded tokens no longer take part in the pool, so like `EmergencyUnstake` the withdrawal
+/// ignores pause flags
+pub fn process_withdraw_unstaked(accounts: &[AccountInfo], program_id: &Pubkey) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+    let user_wallet_account = next_account_info(account_info_iter)?;
+    let user_storage_account = next_account_info(account_info_iter)?;
+    let cwar_pool_storage_account = next_account_info(account_info_iter)?;
+    let unbonding_request_account = next_account_info(account_info_iter)?;
+    let staking_vault = next_account_info(account_info_iter)?;
+    let user_cwar_ata = next_account_info(account_info_iter)?;
+    let pool_signer_pda = next_account_info(account_info_iter)?;
+    let token_program = next_account_info(account_info_iter)?;
+
+    assert_signer(user_wallet_account)?;
+    assert_token_program(token_program)?;
+    let mut user_data_byte_array = user_storage_account.data.try_borrow_mut().unwrap();
+    let mut user_storage_data = assert_user_storage_account(
+        user_wallet_account,
+        cwar_pool_storage_account,
+        program_id,
+        user_storage_account,
+        &user_data_byte_array,
+    )?;
+
+    let mut cwar_pool_data_byte_array = cwar_pool_storage_account.data.try_borrow_mut().unwrap();
+    let mut cwar_pool_data = assert_pool_storage_account(
+        &cwar_pool_data_byte_array,
+        cwar_pool_storage_account,
+        program_id,
+    )?;
+
+    let mut unbonding_request_byte_array = unbonding_request_account.data.try_borrow_mut().unwrap();
+    let unbonding_request_data = assert_unbonding_request_account(
+        unbonding_request_account,
+        user_storage_account,
+        program_id,
+        &unbonding_request_byte_array,
+    )?;
+
+    let now: u64 = Clock::get()?.unix_timestamp.try_into().unwrap();
+    if now < unbonding_request_data.withdrawable_at {
+        msg!("CryowarError::UnbondingNotFinished");
+        msg!(
+            "withdrawable_at: {}",
+            unbonding_request_data.withdrawable_at
+        );
+        return Err(CryowarError::UnbondingNotFinished.into());
+    }
+
+    let (pool_signer_address, bump_seed) =
+        get_pool_signer_address_and_bump_seed(cwar_pool_storage_account.key, program_id);
+    if pool_signer_address != *pool_signer_pda.key {
+        msg!("CryowarError::InvalidSignerPDA");
+        return Err(CryowarError::InvalidSignerPDA.into());
+    }
+    let cwar_staking_vault_data = TokenAccount::unpack(&staking_vault.data.borrow())?;
+    assert_staking_vault(
+        staking_vault,
+        &cwar_pool_data,
+        &cwar_staking_vault_data,
+        &pool_signer_address,
+    )?;
+
+    let amount_to_withdraw = unbonding_request_data.amount;
+    settle_unbonding_request(
+        &mut cwar_pool_data,
+        &mut user_storage_data,
+        amount_to_withdraw,
+        false,
+    )?;
+
+    msg!("Calling the token program to transfer CWAR to User from Staking Vault...");
+    invoke_signed(
+        &spl_token::instruction::transfer(
+            token_program.key,
+            staking_vault.key,
+            user_cwar_ata.key,
+            &pool_signer_address,
+            &[&pool_signer_address],
+            amount_to_withdraw,
+        )?,
+        &[
+            staking_vault.clone(),
+            user_cwar_ata.clone(),
+            pool_signer_pda.clone(),
+            token_program.clone(),
+        ],
+        &[&[&cwar_pool_storage_account.key.to_bytes(), &[bump_seed]]],
+    )?;
+
+    save_pool_storage_account(&mut cwar_pool_data_byte_array, &cwar_pool_data)?;
+    save_user_storage_account(&mut user_data_byte_array, &user_storage_data)?;
+
+    unbonding_request_byte_array.fill(0u8);
+    utils::close_account(
+        unbonding_request_account,
+        user_wallet_account,
+        &mut unbonding_request_byte_array,
+    )?;
+
+    Ok(())
+}
@@ -4,9 +4,24 @@ use solana_program::pubkey::Pubkey;
 pub enum AccTypesWithVersion {
     CwarPoolDataV1 = 2,
     UserDataV1 = 3,
+    CwarPoolDataV2 = 4,
+    UserDataV2 = 5,
+    PendingActionDataV1 = 6,
+    UnbondingRequestDataV1 = 7,
+    LockPositionDataV1 = 8,
+    RewardScheduleDataV1 = 9,
 }
 
-pub const CWAR_POOL_STORAGE_TOTAL_BYTES: usize = 416;
+pub const CWAR_POOL_V1_STORAGE_TOTAL_BYTES: usize = 416;
+pub const CWAR_POOL_STORAGE_TOTAL_BYTES: usize = 1024;
+/// Zeroed space new pool fields are carved out of, keeping the account size fixed
+pub const CWAR_POOL_RESERVED_BYTES: usize = 6;
+/// Reward streams a pool can run next to its primary reward
+pub const MAX_EXTRA_REWARD_STREAMS: usize = 2;
+/// Lock tiers a pool can offer to `StakeLocked` deposits
+pub const MAX_LOCK_TIERS: usize = 4;
+/// Steps a `PENALITY_SCHEDULE_STEPPED` penalty schedule can have
+pub const MAX_PENALITY_STEPS: usize = 4;
 #[derive(Clone, BorshDeserialize, BorshSerialize, Copy, Debug)]
 pub struct CwarPool {
     pub acc_type: u8,
@@ -26,9 +41,237 @@ pub struct CwarPool {
     pub unstake_penality_basis_points: u16,
     pub locking_duration: u64,
     pub authority_penality_deposit_ata: Pubkey,
+    /// Sum of all user stakes, tokens sent straight to the vault are not counted
+    pub total_staked: u64,
+    /// Rewards allocated to stakers that have not been claimed yet
+    pub rewards_owed: u64,
+    /// Owner proposed by `ProposeNewOwner`, default until one is pending
+    pub pending_owner: Pubkey,
+    /// Key allowed to pause operations besides the owner, default if none
+    pub pauser: Pubkey,
+    /// Bitmask of `PAUSE_*` flags
+    pub paused_operations: u8,
+    /// Seconds a queued `PoolAction` has to wait before it can be executed, zero lets the owner
+    /// apply those changes directly
+    pub timelock_delay: u64,
+    /// Id of the next queued `PoolAction`, part of its pending action account address
+    pub next_action_id: u64,
+    /// Extra reward tokens paid to stakers alongside the primary reward, an unused slot has a
+    /// default `reward_mint`
+    pub reward_streams: [RewardStream; MAX_EXTRA_REWARD_STREAMS],
+    /// Seconds an `UnbondingRequest` waits before it can be withdrawn, zero unless the pool
+    /// runs in unbonding mode
+    pub unbonding_duration: u64,
+    /// Sum of all open unbonding requests, held in the staking vault but no longer staked
+    pub total_unbonding: u64,
+    /// Lock durations deposits can pick and the reward weight they get, an unused tier has a
+    /// zero `duration`
+    pub lock_tiers: [LockTier; MAX_LOCK_TIERS],
+    /// Part of `total_staked` held in lock positions
+    pub total_locked: u64,
+    /// Reward weight of all lock positions, the rest of `total_staked` weighs one per token
+    pub total_locked_weight: u64,
+    /// How the unstake penalty decays with the age of a user's stake
+    pub penality_schedule: PenalitySchedule,
+    /// Where unstake penalties go, all zero sends them to `authority_penality_deposit_ata`
+    pub penality_routing: PenalityRouting,
+    /// Smallest stake a user can hold after staking, zero means no minimum
+    pub min_stake: u64,
+    /// Largest stake a user can hold, locked positions included, zero means no limit
+    pub max_stake_per_user: u64,
+    /// Largest `total_staked` new stakes can take the pool to, zero means no cap
+    pub max_total_staked: u64,
+    /// Merkle root of the wallets allowed to create a user, all zero lets anyone join
+    pub allowlist_root: [u8; 32],
+    /// Receipt token minted 1:1 for unlocked stake with the pool signer as mint authority,
+    /// the default key means the pool has none
+    pub receipt_mint: Pubkey,
+    /// `RewardSchedule` whose phases emit on top of `reward_rate`, the default key means the
+    /// pool has none
+    pub reward_schedule: Pubkey,
+    /// Rewards funded for schedule phases that have not been emitted yet
+    pub scheduled_rewards_left: u64,
+    /// Time the schedule phases were last integrated up to
+    pub reward_schedule_last_update_time: u64,
+    /// End of the last schedule phase
+    pub reward_schedule_end: u64,
+    pub reserved: [u8; CWAR_POOL_RESERVED_BYTES],
 }
 
-pub const USER_STORAGE_TOTAL_BYTES: usize = 114;
+impl CwarPool {
+    pub fn has_receipt_mint(&self) -> bool {
+        self.receipt_mint != Pubkey::default()
+    }
+
+    pub fn has_reward_schedule(&self) -> bool {
+        self.reward_schedule != Pubkey::default()
+    }
+}
+
+#[derive(Clone, BorshDeserialize, BorshSerialize, Copy, Debug, Default, PartialEq)]
+pub struct LockTier {
+    pub duration: u64,
+    /// Reward weight of a locked token, 10_000 weighs the same as an unlocked one
+    pub multiplier_basis_points: u32,
+}
+
+impl LockTier {
+    pub fn is_active(&self) -> bool {
+        self.duration != 0u64
+    }
+}
+
+/// An extra reward token emitted to stakers pro rata, same bookkeeping as the primary reward
+#[derive(Clone, BorshDeserialize, BorshSerialize, Copy, Debug, Default, PartialEq)]
+pub struct RewardStream {
+    pub reward_mint: Pubkey,
+    pub reward_vault: Pubkey,
+    pub reward_rate: u64,
+    pub reward_duration_end: u64,
+    pub last_update_time: u64,
+    pub rewards_per_token_accumulated_till_now: u128,
+    /// Rewards of this stream allocated to stakers that have not been claimed yet
+    pub rewards_owed: u64,
+}
+
+impl RewardStream {
+    pub fn is_active(&self) -> bool {
+        self.reward_mint != Pubkey::default()
+    }
+}
+
+/// Full `unstake_penality_basis_points` until `unstake_penality_duration_end`
+pub const PENALITY_SCHEDULE_CLIFF: u8 = 0;
+/// Falls linearly from `unstake_penality_basis_points` to zero over `decay_duration`
+pub const PENALITY_SCHEDULE_LINEAR: u8 = 1;
+/// Basis points of the first step the stake is younger than, zero past the last one
+pub const PENALITY_SCHEDULE_STEPPED: u8 = 2;
+
+/// Shapes the unstake penalty by the time since the user last staked. Whatever the schedule,
+/// the penalty never exceeds `unstake_penality_basis_points` and ends with
+/// `unstake_penality_duration_end`
+#[derive(Clone, BorshDeserialize, BorshSerialize, Copy, Debug, Default, PartialEq)]
+pub struct PenalitySchedule {
+    /// One of the `PENALITY_SCHEDULE_*` kinds
+    pub kind: u8,
+    pub decay_duration: u64,
+    /// Ascending by `stake_age_below`, unused steps are all zero
+    pub steps: [PenalityStep; MAX_PENALITY_STEPS],
+}
+
+#[derive(Clone, BorshDeserialize, BorshSerialize, Copy, Debug, Default, PartialEq)]
+pub struct PenalityStep {
+    pub stake_age_below: u64,
+    pub basis_points: u16,
+}
+
+impl PenalityStep {
+    pub fn is_active(&self) -> bool {
+        self.stake_age_below != 0u64
+    }
+}
+
+/// Shares of an unstake penalty in basis points, they add up to 10_000 unless all are zero
+#[derive(Clone, BorshDeserialize, BorshSerialize, Copy, Debug, Default, PartialEq)]
+pub struct PenalityRouting {
+    pub burn_basis_points: u16,
+    /// Added to what is left of the current primary reward stream
+    pub reward_vault_basis_points: u16,
+    /// Paid out at once to the remaining stakers through the reward accumulator
+    pub redistribute_basis_points: u16,
+    /// Sent to `authority_penality_deposit_ata`
+    pub treasury_basis_points: u16,
+}
+
+impl PenalityRouting {
+    pub fn uses_reward_vault(&self) -> bool {
+        self.reward_vault_basis_points != 0u16 || self.redistribute_basis_points != 0u16
+    }
+}
+
+pub const PAUSE_STAKE: u8 = 1 << 0;
+pub const PAUSE_UNSTAKE: u8 = 1 << 1;
+pub const PAUSE_CLAIM: u8 = 1 << 2;
+pub const PAUSE_FUND: u8 = 1 << 3;
+pub const PAUSE_CREATE_USER: u8 = 1 << 4;
+pub const PAUSE_ALL_OPERATIONS: u8 =
+    PAUSE_STAKE | PAUSE_UNSTAKE | PAUSE_CLAIM | PAUSE_FUND | PAUSE_CREATE_USER;
+
+/// Pool layout before `total_staked` was tracked, only read by `MigratePool`
+#[derive(Clone, BorshDeserialize, BorshSerialize, Copy, Debug)]
+pub struct CwarPoolV1 {
+    pub acc_type: u8,
+    pub owner_wallet: Pubkey,
+    pub staking_vault: Pubkey,
+    pub staking_mint: Pubkey,
+    pub reward_vault: Pubkey,
+    pub reward_mint: Pubkey,
+    pub reward_rate: u64,
+    pub reward_duration: u64,
+    pub total_stake_last_update_time: u64,
+    pub rewards_per_token_accumulated_till_now: u128,
+    pub user_stake_count: u32,
+    pub pda_nonce: u8,
+    pub funders: [Pubkey; 5],
+    pub reward_duration_end: u64,
+    pub unstake_penality_basis_points: u16,
+    pub locking_duration: u64,
+    pub authority_penality_deposit_ata: Pubkey,
+}
+
+impl CwarPoolV1 {
+    pub fn into_current(self) -> CwarPool {
+        CwarPool {
+            acc_type: self.acc_type,
+            owner_wallet: self.owner_wallet,
+            staking_vault: self.staking_vault,
+            staking_mint: self.staking_mint,
+            reward_vault: self.reward_vault,
+            reward_mint: self.reward_mint,
+            reward_rate: self.reward_rate,
+            reward_duration: self.reward_duration,
+            total_stake_last_update_time: self.total_stake_last_update_time,
+            rewards_per_token_accumulated_till_now: self.rewards_per_token_accumulated_till_now,
+            user_stake_count: self.user_stake_count,
+            pda_nonce: self.pda_nonce,
+            funders: self.funders,
+            reward_duration_end: self.reward_duration_end,
+            unstake_penality_basis_points: self.unstake_penality_basis_points,
+            locking_duration: self.locking_duration,
+            authority_penality_deposit_ata: self.authority_penality_deposit_ata,
+            total_staked: 0u64,
+            rewards_owed: 0u64,
+            pending_owner: Pubkey::default(),
+            pauser: Pubkey::default(),
+            paused_operations: 0u8,
+            timelock_delay: 0u64,
+            next_action_id: 0u64,
+            reward_streams: [RewardStream::default(); MAX_EXTRA_REWARD_STREAMS],
+            unbonding_duration: 0u64,
+            total_unbonding: 0u64,
+            lock_tiers: [LockTier::default(); MAX_LOCK_TIERS],
+            total_locked: 0u64,
+            total_locked_weight: 0u64,
+            penality_schedule: PenalitySchedule::default(),
+            penality_routing: PenalityRouting::default(),
+            min_stake: 0u64,
+            max_stake_per_user: 0u64,
+            max_total_staked: 0u64,
+            allowlist_root: [0u8; 32],
+            receipt_mint: Pubkey::default(),
+            reward_schedule: Pubkey::default(),
+            scheduled_rewards_left: 0u64,
+            reward_schedule_last_update_time: 0u64,
+            reward_schedule_end: 0u64,
+            reserved: [0u8; CWAR_POOL_RESERVED_BYTES],
+        }
+    }
+}
+
+pub const USER_V1_STORAGE_TOTAL_BYTES: usize = 114;
+pub const USER_STORAGE_TOTAL_BYTES: usize = 256;
+/// Zeroed space new user fields are carved out of, V1 accounts are the same layout without it
+pub const USER_RESERVED_BYTES: usize = 38;
 #[derive(Clone, BorshDeserialize, BorshSerialize, Copy, Debug)]
 pub struct User {
     pub acc_type: u8,
@@ -40,4 +283,148 @@ pub struct User {
     pub rewards_per_token_accumulated_at_last_user_interaction: u128,
     pub unstake_penality_duration_end: u64,
     pub last_staked_timestamp: u64,
+    /// Checkpoints for the pool `reward_streams` of the same index
+    pub reward_streams: [UserRewardStream; MAX_EXTRA_REWARD_STREAMS],
+    /// Sum of the user's open unbonding requests
+    pub unbonding_amount: u64,
+    /// Id of the next `UnbondingRequest`, part of its account address
+    pub next_unbonding_request_id: u64,
+    /// Sum of the user's lock positions, not part of `user_cwar_staked_amount`
+    pub locked_amount: u64,
+    /// Reward weight of the user's lock positions
+    pub locked_weight: u64,
+    /// Id of the next `LockPosition`, part of its account address
+    pub next_lock_position_id: u64,
+    /// Stake cap from the allowlist leaf the user joined with, zero means none
+    pub allowlist_max_stake: u64,
+    /// Receipts an emergency exit could not burn, new stake pays them back before minting any
+    pub receipt_debt: u64,
+    pub reserved: [u8; USER_RESERVED_BYTES],
+}
+
+#[derive(Clone, BorshDeserialize, BorshSerialize, Copy, Debug, Default, PartialEq)]
+pub struct UserRewardStream {
+    pub rewards_amount_pending: u64,
+    pub rewards_per_token_accumulated_at_last_user_interaction: u128,
+}
+
+/// Owner changes that go through the timelock once `timelock_delay` is set
+#[derive(Clone, BorshDeserialize, BorshSerialize, Copy, Debug, PartialEq)]
+pub enum PoolAction {
+    RemoveFunder {
+        funder_wallet: Pubkey,
+    },
+    UpdatePoolConfig {
+        unstake_penality_basis_points: Option<u16>,
+        locking_duration: Option<u64>,
+        authority_penality_deposit_ata: Option<Pubkey>,
+        unbonding_duration: Option<u64>,
+    },
+    ClosePool {
+        staking_refund_ata: Pubkey,
+        rewards_refund_ata: Pubkey,
+    },
+    SetTimelockDelay {
+        timelock_delay: u64,
+    },
+    ReduceRewards {
+        amount: u64,
+        new_reward_duration_end: u64,
+        rewards_refund_ata: Pubkey,
+    },
+    CancelRewards {
+        rewards_refund_ata: Pubkey,
+    },
+    SetLockTiers {
+        lock_tiers: [LockTier; MAX_LOCK_TIERS],
+    },
+    SetPenalitySchedule {
+        penality_schedule: PenalitySchedule,
+    },
+    SetPenalityRouting {
+        penality_routing: PenalityRouting,
+    },
+    SetStakeLimits {
+        min_stake: u64,
+        max_stake_per_user: u64,
+        max_total_staked: u64,
+    },
+    SetAllowlistRoot {
+        allowlist_root: [u8; 32],
+    },
+    SetReceiptMint {
+        receipt_mint: Pubkey,
+    },
+}
+
+pub const PENDING_ACTION_STORAGE_TOTAL_BYTES: usize = 256;
+#[derive(Clone, BorshDeserialize, BorshSerialize, Copy, Debug)]
+pub struct PendingAction {
+    pub acc_type: u8,
+    pub cwar_pool: Pubkey,
+    pub action_id: u64,
+    pub queued_at: u64,
+    /// Earliest time anyone can execute the action, it expires `ACTION_EXECUTION_GRACE_PERIOD`
+    /// later
+    pub eta: u64,
+    /// Refunded the account rent once the action is executed or cancelled
+    pub rent_payer: Pubkey,
+    pub action: PoolAction,
+}
+
+pub const UNBONDING_REQUEST_STORAGE_TOTAL_BYTES: usize = 128;
+/// Stake taken out of the pool by `RequestUnstake`, it earns nothing and can be withdrawn once
+/// `withdrawable_at` has passed
+#[derive(Clone, BorshDeserialize, BorshSerialize, Copy, Debug)]
+pub struct UnbondingRequest {
+    pub acc_type: u8,
+    pub user_storage: Pubkey,
+    pub request_id: u64,
+    pub amount: u64,
+    pub requested_at: u64,
+    pub withdrawable_at: u64,
+}
+
+pub const LOCK_POSITION_STORAGE_TOTAL_BYTES: usize = 128;
+/// A deposit made by `StakeLocked`, it keeps the weight of its tier until it is withdrawn after
+/// `unlock_at`
+#[derive(Clone, BorshDeserialize, BorshSerialize, Copy, Debug)]
+pub struct LockPosition {
+    pub acc_type: u8,
+    pub user_storage: Pubkey,
+    pub position_id: u64,
+    pub amount: u64,
+    /// Reward weight of `amount` under the tier multiplier at the time of the deposit, `amount`
+    /// itself once the position expired
+    pub weight: u64,
+    pub lock_tier_index: u8,
+    pub locked_at: u64,
+    pub unlock_at: u64,
+}
+
+/// Phases a `RewardSchedule` can hold, ended phases are dropped when new ones are added
+pub const MAX_REWARD_PHASES: usize = 16;
+pub const REWARD_SCHEDULE_STORAGE_TOTAL_BYTES: usize = 512;
+/// Primary reward emitted at `reward_rate` per second from `start` until `end`, funded in full
+/// when it was added
+#[derive(Clone, BorshDeserialize, BorshSerialize, Copy, Debug, Default, PartialEq)]
+pub struct RewardPhase {
+    pub start: u64,
+    pub end: u64,
+    pub reward_rate: u64,
+}
+
+impl RewardPhase {
+    pub fn is_active(&self) -> bool {
+        self.end != 0u64
+    }
+}
+
+/// Reward phases of a pool, emitted to stakers on top of the pool `reward_rate`
+#[derive(Clone, BorshDeserialize, BorshSerialize, Copy, Debug)]
+pub struct RewardSchedule {
+    pub acc_type: u8,
+    pub cwar_pool: Pubkey,
+    /// Ascending and non-overlapping, unused phases are all zero and come last
+    pub phases: [RewardPhase; MAX_REWARD_PHASES],
 }
@@ -1,25 +1,47 @@
 use std::cell::RefMut;
 use std::convert::TryInto;
 
+use crate::allowlist::{get_allowlist_leaf, verify_allowlist_proof, MAX_ALLOWLIST_PROOF_LENGTH};
 use crate::error::CryowarError;
 use crate::processor::create_user::get_user_storage_address_and_bump_seed;
 use crate::state::{
-    AccTypesWithVersion, CwarPool, User, CWAR_POOL_STORAGE_TOTAL_BYTES, USER_STORAGE_TOTAL_BYTES,
+    AccTypesWithVersion, CwarPool, LockPosition, LockTier, PenalityRouting, PenalitySchedule,
+    PenalityStep, PendingAction, RewardSchedule, RewardStream, UnbondingRequest, User,
+    CWAR_POOL_STORAGE_TOTAL_BYTES, CWAR_POOL_V1_STORAGE_TOTAL_BYTES,
+    LOCK_POSITION_STORAGE_TOTAL_BYTES, MAX_LOCK_TIERS, PENALITY_SCHEDULE_CLIFF,
+    PENALITY_SCHEDULE_LINEAR, PENALITY_SCHEDULE_STEPPED, PENDING_ACTION_STORAGE_TOTAL_BYTES,
+    REWARD_SCHEDULE_STORAGE_TOTAL_BYTES, UNBONDING_REQUEST_STORAGE_TOTAL_BYTES,
+    USER_STORAGE_TOTAL_BYTES, USER_V1_STORAGE_TOTAL_BYTES,
 };
 use borsh::{BorshDeserialize, BorshSerialize};
-use solana_program::account_info::AccountInfo;
+use solana_program::account_info::{next_account_info, AccountInfo};
 use solana_program::entrypoint::ProgramResult;
+use solana_program::program::{invoke, invoke_signed};
 use solana_program::program_error::ProgramError;
+use solana_program::program_option::COption;
 use solana_program::program_pack::Pack;
 use solana_program::pubkey::Pubkey;
 use solana_program::{msg, system_program};
 // to avoid rounding errors
 const PRECISION: u128 = u64::MAX as u128;
 pub const FRACTION_TO_BASIS_POINTS: u128 = 10_000u128;
-use spl_token::state::Account as TokenAccount;
+/// Highest unstake penalty a pool can be configured with, 50%
+pub const MAX_UNSTAKE_PENALITY_BASIS_POINTS: u16 = 5_000u16;
+use spl_associated_token_account::{
+    get_associated_token_address, instruction::create_associated_token_account_idempotent,
+};
+use spl_token::state::{Account as TokenAccount, Mint};
 pub mod constants {
     pub const CRYOWAR_TOKEN_MINT_PUBKEY: &str = "HfYFjMKNZygfMC8LsQ8LtpPsPxEJoXJx4M6tqi75Hajo";
     pub const MIN_DURATION: u64 = 86400;
+    pub const MAX_TIMELOCK_DELAY: u64 = 30 * 86400;
+    /// How long a queued action stays executable after its eta, it can only be cancelled then
+    pub const ACTION_EXECUTION_GRACE_PERIOD: u64 = 14 * 86400;
+    pub const MAX_UNBONDING_DURATION: u64 = 60 * 86400;
+    pub const MAX_LOCK_DURATION: u64 = 365 * 86400;
+    /// Highest reward weight a lock tier can give, 5x
+    pub const MAX_LOCK_MULTIPLIER_BASIS_POINTS: u32 = 50_000;
+    pub const MAX_PENALITY_DECAY_DURATION: u64 = 365 * 86400;
 }
 
 pub fn close_account(
@@ -88,37 +110,349 @@ pub fn get_user_updated_pending_rewards(
     return Ok(updated_rewards_amount_pending);
 }
 
+/// Reward weight the accumulators are divided by, unlocked stake weighs one per token and lock
+/// positions the weight of their tier
+pub fn get_total_reward_weight(cwar_pool: &CwarPool) -> Result<u64, ProgramError> {
+    let total_reward_weight = cwar_pool
+        .total_staked
+        .checked_sub(cwar_pool.total_locked)
+        .and_then(|unlocked| unlocked.checked_add(cwar_pool.total_locked_weight))
+        .ok_or(CryowarError::AmountOverflow)?;
+    Ok(total_reward_weight)
+}
+
+/// The user's share of `get_total_reward_weight`
+pub fn get_user_reward_weight(user: &User) -> Result<u64, ProgramError> {
+    let user_reward_weight = user
+        .user_cwar_staked_amount
+        .checked_add(user.locked_weight)
+        .ok_or(CryowarError::AmountOverflow)?;
+    Ok(user_reward_weight)
+}
+
 pub fn update_rewards(
     cwar_pool: &mut CwarPool,
+    reward_schedule: Option<&RewardSchedule>,
     user: Option<&mut User>,
-    total_cwar_staked: u64,
     now: u64,
 ) -> ProgramResult {
     let last_time_reward_applicable =
         last_time_reward_applicable(cwar_pool.reward_duration_end, now);
+    let total_reward_weight = get_total_reward_weight(cwar_pool)?;
+    // rewards emitted while nobody is staked are never allocated and stay recoverable surplus
+    if total_reward_weight > 0 {
+        let rewards_emitted = last_time_reward_applicable
+            .checked_sub(cwar_pool.total_stake_last_update_time)
+            .and_then(|elapsed| elapsed.checked_mul(cwar_pool.reward_rate))
+            .ok_or(CryowarError::AmountOverflow)?;
+        cwar_pool.rewards_owed = cwar_pool
+            .rewards_owed
+            .checked_add(rewards_emitted)
+            .ok_or(CryowarError::AmountOverflow)?;
+    }
     cwar_pool.rewards_per_token_accumulated_till_now = updated_rewards_per_token_accumulated(
-        total_cwar_staked,
+        total_reward_weight,
         last_time_reward_applicable,
         cwar_pool.total_stake_last_update_time,
         cwar_pool.reward_rate,
         cwar_pool.rewards_per_token_accumulated_till_now,
     )?;
     cwar_pool.total_stake_last_update_time = last_time_reward_applicable;
+    if cwar_pool.has_reward_schedule() {
+        let reward_schedule = match reward_schedule {
+            Some(reward_schedule) => reward_schedule,
+            None => {
+                msg!("CryowarError::InvalidRewardSchedule");
+                return Err(CryowarError::InvalidRewardSchedule.into());
+            }
+        };
+        update_reward_schedule(cwar_pool, reward_schedule, total_reward_weight, now)?;
+    }
+    for reward_stream in cwar_pool.reward_streams.iter_mut() {
+        update_reward_stream(reward_stream, total_reward_weight, now)?;
+    }
 
     if let Some(u) = user {
+        let user_reward_weight = get_user_reward_weight(u)?;
         u.rewards_amount_pending = get_user_updated_pending_rewards(
-            u.user_cwar_staked_amount,
+            user_reward_weight,
             cwar_pool.rewards_per_token_accumulated_till_now,
             u.rewards_per_token_accumulated_at_last_user_interaction,
             u.rewards_amount_pending,
         )?;
         u.rewards_per_token_accumulated_at_last_user_interaction =
             cwar_pool.rewards_per_token_accumulated_till_now;
+        for (user_reward_stream, reward_stream) in u
+            .reward_streams
+            .iter_mut()
+            .zip(cwar_pool.reward_streams.iter())
+        {
+            user_reward_stream.rewards_amount_pending = get_user_updated_pending_rewards(
+                user_reward_weight,
+                reward_stream.rewards_per_token_accumulated_till_now,
+                user_reward_stream.rewards_per_token_accumulated_at_last_user_interaction,
+                user_reward_stream.rewards_amount_pending,
+            )?;
+            user_reward_stream.rewards_per_token_accumulated_at_last_user_interaction =
+                reward_stream.rewards_per_token_accumulated_till_now;
+        }
     }
 
     Ok(())
 }
 
+/// Integrates the schedule phases into the primary reward accumulator one piece at a time,
+/// each phase at its own rate over the part of it since the last checkpoint
+pub fn update_reward_schedule(
+    cwar_pool: &mut CwarPool,
+    reward_schedule: &RewardSchedule,
+    total_reward_weight: u64,
+    now: u64,
+) -> ProgramResult {
+    let last_update_time = cwar_pool.reward_schedule_last_update_time;
+    for phase in reward_schedule.phases.iter().filter(|x| x.is_active()) {
+        let start = std::cmp::max(phase.start, last_update_time);
+        let end = std::cmp::min(phase.end, now);
+        if end <= start {
+            continue;
+        }
+        let rewards_emitted = (end - start)
+            .checked_mul(phase.reward_rate)
+            .ok_or(CryowarError::AmountOverflow)?;
+        cwar_pool.scheduled_rewards_left = cwar_pool
+            .scheduled_rewards_left
+            .checked_sub(rewards_emitted)
+            .ok_or(CryowarError::AmountOverflow)?;
+        if total_reward_weight > 0 {
+            cwar_pool.rewards_owed = cwar_pool
+                .rewards_owed
+                .checked_add(rewards_emitted)
+                .ok_or(CryowarError::AmountOverflow)?;
+        }
+        cwar_pool.rewards_per_token_accumulated_till_now = updated_rewards_per_token_accumulated(
+            total_reward_weight,
+            end,
+            start,
+            phase.reward_rate,
+            cwar_pool.rewards_per_token_accumulated_till_now,
+        )?;
+    }
+    cwar_pool.reward_schedule_last_update_time = std::cmp::max(last_update_time, now);
+    Ok(())
+}
+
+/// Checkpoints an extra reward stream the same way `update_rewards` does the primary reward
+pub fn update_reward_stream(
+    reward_stream: &mut RewardStream,
+    total_reward_weight: u64,
+    now: u64,
+) -> ProgramResult {
+    if !reward_stream.is_active() {
+        return Ok(());
+    }
+    let last_time_reward_applicable =
+        last_time_reward_applicable(reward_stream.reward_duration_end, now);
+    if total_reward_weight > 0 {
+        let rewards_emitted = last_time_reward_applicable
+            .checked_sub(reward_stream.last_update_time)
+            .and_then(|elapsed| elapsed.checked_mul(reward_stream.reward_rate))
+            .ok_or(CryowarError::AmountOverflow)?;
+        reward_stream.rewards_owed = reward_stream
+            .rewards_owed
+            .checked_add(rewards_emitted)
+            .ok_or(CryowarError::AmountOverflow)?;
+    }
+    reward_stream.rewards_per_token_accumulated_till_now = updated_rewards_per_token_accumulated(
+        total_reward_weight,
+        last_time_reward_applicable,
+        reward_stream.last_update_time,
+        reward_stream.reward_rate,
+        reward_stream.rewards_per_token_accumulated_till_now,
+    )?;
+    reward_stream.last_update_time = last_time_reward_applicable;
+    Ok(())
+}
+
+/// Moves every reward checkpoint of the pool to `now` without accumulating anything, for when
+/// `update_rewards` cannot run but the reward weight has to change anyway. What was emitted
+/// since the last checkpoint is allocated to nobody, the accumulators never see it divided by
+/// the new weight
+pub fn freeze_rewards(cwar_pool: &mut CwarPool, now: u64) {
+    cwar_pool.total_stake_last_update_time = std::cmp::max(
+        cwar_pool.total_stake_last_update_time,
+        last_time_reward_applicable(cwar_pool.reward_duration_end, now),
+    );
+    cwar_pool.reward_schedule_last_update_time =
+        std::cmp::max(cwar_pool.reward_schedule_last_update_time, now);
+    for reward_stream in cwar_pool
+        .reward_streams
+        .iter_mut()
+        .filter(|x| x.is_active())
+    {
+        reward_stream.last_update_time = std::cmp::max(
+            reward_stream.last_update_time,
+            last_time_reward_applicable(reward_stream.reward_duration_end, now),
+        );
+    }
+}
+
+/// Tokens the staking vault has to hold for users, staked or waiting out their unbonding
+pub fn get_staking_vault_liabilities(cwar_pool: &CwarPool) -> Result<u64, ProgramError> {
+    let staking_vault_liabilities = cwar_pool
+        .total_staked
+        .checked_add(cwar_pool.total_unbonding)
+        .ok_or(CryowarError::AmountOverflow)?;
+    Ok(staking_vault_liabilities)
+}
+
+/// Tokens in the staking vault that do not back any user stake
+pub fn get_staking_vault_surplus(staking_vault_balance: u64, cwar_pool: &CwarPool) -> u64 {
+    staking_vault_balance
+        .saturating_sub(cwar_pool.total_staked)
+        .saturating_sub(cwar_pool.total_unbonding)
+}
+
+/// Rewards the current stream has yet to emit after the last checkpoint
+pub fn get_rewards_left_amount(cwar_pool: &CwarPool) -> Result<u64, ProgramError> {
+    let rewards_left_amount = cwar_pool
+        .reward_duration_end
+        .saturating_sub(cwar_pool.total_stake_last_update_time)
+        .checked_mul(cwar_pool.reward_rate)
+        .ok_or(CryowarError::AmountOverflow)?;
+    Ok(rewards_left_amount)
+}
+
+/// Rewards earned but not yet claimed plus what the current stream and the schedule phases have
+/// yet to emit, expects `update_rewards` to have checkpointed the pool
+pub fn get_reward_liabilities(cwar_pool: &CwarPool) -> Result<u64, ProgramError> {
+    let rewards_left_amount = get_rewards_left_amount(cwar_pool)?;
+    let reward_liabilities = cwar_pool
+        .rewards_owed
+        .checked_add(rewards_left_amount)
+        .and_then(|x| x.checked_add(cwar_pool.scheduled_rewards_left))
+        .ok_or(CryowarError::AmountOverflow)?;
+    Ok(reward_liabilities)
+}
+
+/// Same as `get_reward_liabilities` for an extra reward stream
+pub fn get_reward_stream_liabilities(reward_stream: &RewardStream) -> Result<u64, ProgramError> {
+    let rewards_left_amount = reward_stream
+        .reward_duration_end
+        .saturating_sub(reward_stream.last_update_time)
+        .checked_mul(reward_stream.reward_rate)
+        .ok_or(CryowarError::AmountOverflow)?;
+    let reward_liabilities = reward_stream
+        .rewards_owed
+        .checked_add(rewards_left_amount)
+        .ok_or(CryowarError::AmountOverflow)?;
+    Ok(reward_liabilities)
+}
+
+/// Token amounts an unstake penalty is split into by the pool `PenalityRouting`
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct PenalitySplit {
+    pub burn: u64,
+    pub reward_vault: u64,
+    pub redistribute: u64,
+    pub treasury: u64,
+}
+
+/// Splits `penality_amount` by basis points, rounding leftovers go to the treasury so the parts
+/// always add up to the penalty
+pub fn split_penality(
+    penality_amount: u64,
+    penality_routing: &PenalityRouting,
+) -> Result<PenalitySplit, ProgramError> {
+    let share = |basis_points: u16| -> Result<u64, ProgramError> {
+        assert_u128_to_u64_conversion(
+            (penality_amount as u128)
+                .checked_mul(basis_points as u128)
+                .ok_or(CryowarError::AmountOverflow)?
+                .checked_div(FRACTION_TO_BASIS_POINTS)
+                .ok_or(CryowarError::AmountOverflow)?,
+        )
+    };
+    let burn = share(penality_routing.burn_basis_points)?;
+    let reward_vault = share(penality_routing.reward_vault_basis_points)?;
+    let redistribute = share(penality_routing.redistribute_basis_points)?;
+    let treasury = penality_amount
+        .checked_sub(burn)
+        .and_then(|left| left.checked_sub(reward_vault))
+        .and_then(|left| left.checked_sub(redistribute))
+        .ok_or(CryowarError::AmountOverflow)?;
+    Ok(PenalitySplit {
+        burn,
+        reward_vault,
+        redistribute,
+        treasury,
+    })
+}
+
+/// Allocates `amount` of rewards to the current stakers right away, expects `update_rewards` to
+/// have checkpointed the pool
+pub fn distribute_to_stakers(cwar_pool: &mut CwarPool, amount: u64) -> ProgramResult {
+    let total_reward_weight = get_total_reward_weight(cwar_pool)?;
+    if total_reward_weight == 0u64 {
+        msg!("CryowarError::AmountOverflow");
+        return Err(CryowarError::AmountOverflow.into());
+    }
+    cwar_pool.rewards_per_token_accumulated_till_now = cwar_pool
+        .rewards_per_token_accumulated_till_now
+        .checked_add(
+            (amount as u128)
+                .checked_mul(PRECISION)
+                .ok_or(CryowarError::AmountOverflow)?
+                / (total_reward_weight as u128),
+        )
+        .ok_or(CryowarError::AmountOverflow)?;
+    cwar_pool.rewards_owed = cwar_pool
+        .rewards_owed
+        .checked_add(amount)
+        .ok_or(CryowarError::AmountOverflow)?;
+    Ok(())
+}
+
+/// Unstake penalty in basis points `user` would pay at `now`
+pub fn get_unstake_penality_basis_points(cwar_pool: &CwarPool, user: &User, now: u64) -> u16 {
+    if now >= user.unstake_penality_duration_end {
+        return 0u16;
+    }
+    let max_basis_points = cwar_pool.unstake_penality_basis_points;
+    let penality_schedule = &cwar_pool.penality_schedule;
+    let stake_age = now.saturating_sub(user.last_staked_timestamp);
+    match penality_schedule.kind {
+        PENALITY_SCHEDULE_LINEAR => {
+            let time_left = penality_schedule.decay_duration.saturating_sub(stake_age);
+            // the schedule is validated to have a non-zero decay duration
+            ((max_basis_points as u128) * (time_left as u128)
+                / (penality_schedule.decay_duration.max(1u64) as u128)) as u16
+        }
+        PENALITY_SCHEDULE_STEPPED => penality_schedule
+            .steps
+            .iter()
+            .find(|step| step.is_active() && stake_age < step.stake_age_below)
+            .map_or(0u16, |step| step.basis_points.min(max_basis_points)),
+        _ => max_basis_points,
+    }
+}
+
+/// Part of `amount` that goes to the penalty deposit ATA if `user` unstakes it at `now`
+pub fn get_unstake_penality(
+    cwar_pool: &CwarPool,
+    user: &User,
+    amount: u64,
+    now: u64,
+) -> Result<u64, ProgramError> {
+    let penality_basis_points = get_unstake_penality_basis_points(cwar_pool, user, now);
+    assert_u128_to_u64_conversion(
+        (amount as u128)
+            .checked_mul(penality_basis_points as u128)
+            .ok_or(CryowarError::AmountOverflow)?
+            .checked_div(FRACTION_TO_BASIS_POINTS)
+            .ok_or(CryowarError::AmountOverflow)?,
+    )
+}
+
 pub fn assert_u128_to_u64_conversion(value: u128) -> Result<u64, ProgramError> {
     if value < u64::MAX as u128 {
         return Ok(value as u64);
@@ -130,6 +464,66 @@ pub fn last_time_reward_applicable(reward_duration_end: u64, now_unix_timestamp:
     return std::cmp::min(now_unix_timestamp.try_into().unwrap(), reward_duration_end);
 }
 
+/// Derives the account holding the queued `PoolAction` with the given id
+pub fn get_pending_action_address_and_bump_seed(
+    pool_storage: &Pubkey,
+    action_id: u64,
+    program_id: &Pubkey,
+) -> (Pubkey, u8) {
+    Pubkey::find_program_address(
+        &[
+            b"pending_action",
+            &pool_storage.to_bytes(),
+            &action_id.to_le_bytes(),
+        ],
+        program_id,
+    )
+}
+
+/// Derives the account holding the unbonding request of a user with the given id
+pub fn get_unbonding_request_address_and_bump_seed(
+    user_storage: &Pubkey,
+    request_id: u64,
+    program_id: &Pubkey,
+) -> (Pubkey, u8) {
+    Pubkey::find_program_address(
+        &[&user_storage.to_bytes(), &request_id.to_le_bytes()],
+        program_id,
+    )
+}
+
+/// Derives the account holding the lock position of a user with the given id
+pub fn get_lock_position_address_and_bump_seed(
+    user_storage: &Pubkey,
+    position_id: u64,
+    program_id: &Pubkey,
+) -> (Pubkey, u8) {
+    Pubkey::find_program_address(
+        &[
+            b"lock_position",
+            &user_storage.to_bytes(),
+            &position_id.to_le_bytes(),
+        ],
+        program_id,
+    )
+}
+
+/// Derives the account holding the reward schedule of a pool
+pub fn get_reward_schedule_address_and_bump_seed(
+    pool_storage: &Pubkey,
+    program_id: &Pubkey,
+) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[b"reward_schedule", &pool_storage.to_bytes()], program_id)
+}
+
+/// Derives the PDA that owns the staking and rewards vaults of the given pool
+pub fn get_pool_signer_address_and_bump_seed(
+    pool_storage: &Pubkey,
+    program_id: &Pubkey,
+) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[&pool_storage.to_bytes()], program_id)
+}
+
 pub fn assert_signer(signer_wallet_to_check: &AccountInfo) -> ProgramResult {
     if !signer_wallet_to_check.is_signer {
         msg!("ProgramError::MissingRequiredSignature");
@@ -146,6 +540,16 @@ pub fn assert_token_program(token_program_input: &AccountInfo) -> ProgramResult
     Ok(())
 }
 
+pub fn assert_associated_token_program(
+    associated_token_program_input: &AccountInfo,
+) -> ProgramResult {
+    if associated_token_program_input.key != &spl_associated_token_account::id() {
+        msg!("CryowarError::InvalidAssociatedTokenProgram");
+        return Err(CryowarError::InvalidAssociatedTokenProgram.into());
+    }
+    Ok(())
+}
+
 pub fn assert_system_program(system_program_input: &AccountInfo) -> ProgramResult {
     if system_program_input.key != &system_program::id() {
         msg!("CryowarError::InvalidSystemProgram");
@@ -188,6 +592,252 @@ pub fn assert_penality_deposit_ata(
     Ok(())
 }
 
+pub fn assert_penality_deposit_ata_data(
+    authority_penality_deposit_ata_account: &AccountInfo,
+    staking_mint: &Pubkey,
+    pool_owner_wallet: &Pubkey,
+) -> ProgramResult {
+    let authority_penality_deposit_ata_data =
+        TokenAccount::unpack(&authority_penality_deposit_ata_account.data.borrow())?;
+    if authority_penality_deposit_ata_data.mint != *staking_mint {
+        msg!("CryowarError::MintMismatched");
+        return Err(CryowarError::MintMismatched.into());
+    }
+    if authority_penality_deposit_ata_data.owner != *pool_owner_wallet {
+        msg!("CryowarError::InvalidAuthorityPenalityDepositATA");
+        return Err(CryowarError::InvalidAuthorityPenalityDepositATA.into());
+    }
+    if authority_penality_deposit_ata_account.owner != &spl_token::id() {
+        msg!("CryowarError::AccountOwnerShouldBeTokenProgram");
+        return Err(CryowarError::AccountOwnerShouldBeTokenProgram.into());
+    }
+    Ok(())
+}
+
+/// A pool runs in at most one of the penalty, locking and unbonding modes
+pub fn assert_pool_config(
+    unstake_penality_basis_points: u16,
+    locking_duration: u64,
+    unbonding_duration: u64,
+) -> ProgramResult {
+    if unstake_penality_basis_points > MAX_UNSTAKE_PENALITY_BASIS_POINTS {
+        msg!("CryowarError::UnstakePenalityTooHigh");
+        return Err(CryowarError::UnstakePenalityTooHigh.into());
+    }
+    if unstake_penality_basis_points != 0u16 && locking_duration != 0u64 {
+        msg!("CryowarError::PoolEitherCanHaveUnstakePanelityOrLockingDuration");
+        return Err(CryowarError::PoolEitherCanHaveUnstakePanelityOrLockingDuration.into());
+    }
+    if unbonding_duration > constants::MAX_UNBONDING_DURATION
+        || (unbonding_duration != 0u64
+            && (unstake_penality_basis_points != 0u16 || locking_duration != 0u64))
+    {
+        msg!("CryowarError::InvalidUnbondingDuration");
+        return Err(CryowarError::InvalidUnbondingDuration.into());
+    }
+    Ok(())
+}
+
+/// Instructions that create a user account on the way have no allowlist proof to check, in an
+/// allowlisted pool the wallet has to join with `CreateAllowlistedUser` first
+pub fn assert_no_allowlist(cwar_pool: &CwarPool) -> ProgramResult {
+    if cwar_pool.allowlist_root != [0u8; 32] {
+        msg!("CryowarError::AllowlistedUserRequired");
+        return Err(CryowarError::AllowlistedUserRequired.into());
+    }
+    Ok(())
+}
+
+/// Wallets can only join an allowlisted pool with a proof of their leaf, the leaf's stake cap is
+/// returned so it can be stored on the user
+pub fn assert_allowlisted(
+    cwar_pool: &CwarPool,
+    user_wallet: &Pubkey,
+    allowlist_max_stake: u64,
+    allowlist_proof: &[[u8; 32]],
+) -> Result<u64, ProgramError> {
+    if cwar_pool.allowlist_root == [0u8; 32] {
+        return Ok(0u64);
+    }
+    if allowlist_proof.len() > MAX_ALLOWLIST_PROOF_LENGTH {
+        msg!("CryowarError::InvalidAllowlistProof");
+        return Err(CryowarError::InvalidAllowlistProof.into());
+    }
+    if !verify_allowlist_proof(
+        &cwar_pool.allowlist_root,
+        get_allowlist_leaf(user_wallet, allowlist_max_stake),
+        allowlist_proof,
+    ) {
+        msg!("CryowarError::WalletNotAllowlisted");
+        return Err(CryowarError::WalletNotAllowlisted.into());
+    }
+    Ok(allowlist_max_stake)
+}
+
+/// Limits left at zero are off, a minimum has to fit under the limits that are set
+pub fn assert_stake_limits(
+    min_stake: u64,
+    max_stake_per_user: u64,
+    max_total_staked: u64,
+) -> ProgramResult {
+    if (max_stake_per_user != 0u64 && min_stake > max_stake_per_user)
+        || (max_total_staked != 0u64
+            && (min_stake > max_total_staked || max_stake_per_user > max_total_staked))
+    {
+        msg!("CryowarError::InvalidStakeLimits");
+        return Err(CryowarError::InvalidStakeLimits.into());
+    }
+    Ok(())
+}
+
+/// Checks a new stake of `amount` against the pool limits and the user's allowlist cap, the
+/// user's stake counts its lock positions but not what is unbonding
+pub fn check_stake_limits(cwar_pool: &CwarPool, user: &User, amount: u64) -> ProgramResult {
+    check_user_stake_limits(cwar_pool, user, amount)?;
+    let total_staked = cwar_pool
+        .total_staked
+        .checked_add(amount)
+        .ok_or(CryowarError::AmountOverflow)?;
+    if cwar_pool.max_total_staked != 0u64 && total_staked > cwar_pool.max_total_staked {
+        msg!("CryowarError::PoolStakeCapExceeded");
+        msg!("max_total_staked: {}", cwar_pool.max_total_staked);
+        return Err(CryowarError::PoolStakeCapExceeded.into());
+    }
+    Ok(())
+}
+
+/// The part of `check_stake_limits` about the user's own position, for stake that is already in
+/// the pool
+pub fn check_user_stake_limits(cwar_pool: &CwarPool, user: &User, amount: u64) -> ProgramResult {
+    let user_stake = user
+        .user_cwar_staked_amount
+        .checked_add(user.locked_amount)
+        .and_then(|staked| staked.checked_add(amount))
+        .ok_or(CryowarError::AmountOverflow)?;
+    if user_stake < cwar_pool.min_stake {
+        msg!("CryowarError::StakeBelowMinimum");
+        msg!("min_stake: {}", cwar_pool.min_stake);
+        return Err(CryowarError::StakeBelowMinimum.into());
+    }
+    if cwar_pool.max_stake_per_user != 0u64 && user_stake > cwar_pool.max_stake_per_user {
+        msg!("CryowarError::UserStakeLimitExceeded");
+        msg!("max_stake_per_user: {}", cwar_pool.max_stake_per_user);
+        return Err(CryowarError::UserStakeLimitExceeded.into());
+    }
+    if user.allowlist_max_stake != 0u64 && user_stake > user.allowlist_max_stake {
+        msg!("CryowarError::UserStakeLimitExceeded");
+        msg!("allowlist_max_stake: {}", user.allowlist_max_stake);
+        return Err(CryowarError::UserStakeLimitExceeded.into());
+    }
+    Ok(())
+}
+
+/// Used tiers lock for at most `MAX_LOCK_DURATION` and weigh at least as much as unlocked
+/// stake, unused tiers are all zero
+pub fn assert_lock_tiers(lock_tiers: &[LockTier; MAX_LOCK_TIERS]) -> ProgramResult {
+    for lock_tier in lock_tiers.iter() {
+        let is_valid = if lock_tier.is_active() {
+            lock_tier.duration <= constants::MAX_LOCK_DURATION
+                && lock_tier.multiplier_basis_points >= FRACTION_TO_BASIS_POINTS as u32
+                && lock_tier.multiplier_basis_points <= constants::MAX_LOCK_MULTIPLIER_BASIS_POINTS
+        } else {
+            lock_tier.multiplier_basis_points == 0u32
+        };
+        if !is_valid {
+            msg!("CryowarError::InvalidLockTier");
+            return Err(CryowarError::InvalidLockTier.into());
+        }
+    }
+    Ok(())
+}
+
+/// A linear schedule decays over at most `MAX_PENALITY_DECAY_DURATION`, a stepped one has
+/// ascending steps up front, and fields the kind does not use are zero
+pub fn assert_penality_schedule(penality_schedule: &PenalitySchedule) -> ProgramResult {
+    let steps = &penality_schedule.steps;
+    let used_steps = steps.iter().take_while(|step| step.is_active()).count();
+    let unused_steps_are_zero = steps[used_steps..]
+        .iter()
+        .all(|step| *step == PenalityStep::default());
+    let is_valid = unused_steps_are_zero
+        && match penality_schedule.kind {
+            PENALITY_SCHEDULE_CLIFF => penality_schedule.decay_duration == 0u64 && used_steps == 0,
+            PENALITY_SCHEDULE_LINEAR => {
+                penality_schedule.decay_duration > 0u64
+                    && penality_schedule.decay_duration <= constants::MAX_PENALITY_DECAY_DURATION
+                    && used_steps == 0
+            }
+            PENALITY_SCHEDULE_STEPPED => {
+                penality_schedule.decay_duration == 0u64
+                    && used_steps > 0
+                    && steps[..used_steps]
+                        .windows(2)
+                        .all(|pair| pair[0].stake_age_below < pair[1].stake_age_below)
+                    && steps[..used_steps].iter().all(|step| {
+                        step.stake_age_below <= constants::MAX_PENALITY_DECAY_DURATION
+                            && step.basis_points <= MAX_UNSTAKE_PENALITY_BASIS_POINTS
+                    })
+            }
+            _ => false,
+        };
+    if !is_valid {
+        msg!("CryowarError::InvalidPenalitySchedule");
+        return Err(CryowarError::InvalidPenalitySchedule.into());
+    }
+    Ok(())
+}
+
+/// Shares add up to 10_000 or are all zero, and only a pool rewarding its staking token can
+/// route penalties to its reward vault
+pub fn assert_penality_routing(
+    penality_routing: &PenalityRouting,
+    cwar_pool_data: &CwarPool,
+) -> ProgramResult {
+    let total_basis_points = penality_routing.burn_basis_points as u32
+        + penality_routing.reward_vault_basis_points as u32
+        + penality_routing.redistribute_basis_points as u32
+        + penality_routing.treasury_basis_points as u32;
+    if (total_basis_points != 0u32 && total_basis_points != FRACTION_TO_BASIS_POINTS as u32)
+        || (penality_routing.uses_reward_vault()
+            && cwar_pool_data.reward_mint != cwar_pool_data.staking_mint)
+    {
+        msg!("CryowarError::InvalidPenalityRouting");
+        return Err(CryowarError::InvalidPenalityRouting.into());
+    }
+    Ok(())
+}
+
+/// Looks up the lock tier at `lock_tier_index`, it has to be in use
+pub fn get_lock_tier(
+    cwar_pool_data: &CwarPool,
+    lock_tier_index: u8,
+) -> Result<LockTier, ProgramError> {
+    match cwar_pool_data.lock_tiers.get(lock_tier_index as usize) {
+        Some(lock_tier) if lock_tier.is_active() => Ok(*lock_tier),
+        _ => {
+            msg!("CryowarError::InvalidLockTier");
+            Err(CryowarError::InvalidLockTier.into())
+        }
+    }
+}
+
+pub fn assert_not_paused(cwar_pool_data: &CwarPool, operation: u8) -> ProgramResult {
+    if cwar_pool_data.paused_operations & operation != 0u8 {
+        msg!("CryowarError::PoolPaused");
+        return Err(CryowarError::PoolPaused.into());
+    }
+    Ok(())
+}
+
+/// Changes covered by `PoolAction` have to be queued while the pool has a timelock
+pub fn assert_not_timelocked(cwar_pool_data: &CwarPool) -> ProgramResult {
+    if cwar_pool_data.timelock_delay > 0u64 {
+        msg!("CryowarError::TimelockRequired");
+        return Err(CryowarError::TimelockRequired.into());
+    }
+    Ok(())
+}
+
 pub fn assert_reward_vault(
     reward_vault_input: &AccountInfo,
     cwar_pool_data: &CwarPool,
@@ -209,6 +859,43 @@ pub fn assert_reward_vault(
     Ok(())
 }
 
+/// Looks up the extra reward stream at `stream_index`, it has to be in use
+pub fn get_reward_stream(
+    cwar_pool_data: &CwarPool,
+    stream_index: u8,
+) -> Result<RewardStream, ProgramError> {
+    match cwar_pool_data.reward_streams.get(stream_index as usize) {
+        Some(reward_stream) if reward_stream.is_active() => Ok(*reward_stream),
+        _ => {
+            msg!("CryowarError::InvalidRewardStream");
+            Err(CryowarError::InvalidRewardStream.into())
+        }
+    }
+}
+
+pub fn assert_reward_stream_vault(
+    reward_vault_input: &AccountInfo,
+    reward_stream: &RewardStream,
+    rewards_vault_data: &TokenAccount,
+    pool_signer_address: &Pubkey,
+) -> ProgramResult {
+    if rewards_vault_data.owner != *pool_signer_address
+        || rewards_vault_data.mint != reward_stream.reward_mint
+    {
+        msg!("CryowarError::InvalidRewardsVault");
+        return Err(CryowarError::InvalidRewardsVault.into());
+    }
+    if reward_vault_input.owner != &spl_token::id() {
+        msg!("CryowarError::AccountOwnerShouldBeTokenProgram");
+        return Err(CryowarError::AccountOwnerShouldBeTokenProgram.into());
+    }
+    if *reward_vault_input.key != reward_stream.reward_vault {
+        msg!("CryowarError::InvalidRewardsVault");
+        return Err(CryowarError::InvalidRewardsVault.into());
+    }
+    Ok(())
+}
+
 pub fn assert_pool_storage_account(
     cwar_pool_data_byte_array: &[u8],
     cwar_pool_storage_account: &AccountInfo,
@@ -218,6 +905,12 @@ pub fn assert_pool_storage_account(
         msg!("CryowarError::PoolAccountOwnerShouldBeCwarProgram");
         return Err(CryowarError::AccountOwnerShouldBeCwarProgram.into());
     }
+    if cwar_pool_data_byte_array.len() == CWAR_POOL_V1_STORAGE_TOTAL_BYTES
+        && cwar_pool_data_byte_array[0] == AccTypesWithVersion::CwarPoolDataV1 as u8
+    {
+        msg!("CryowarError::AccountNeedsMigration");
+        return Err(CryowarError::AccountNeedsMigration.into());
+    }
     if cwar_pool_data_byte_array.len() != CWAR_POOL_STORAGE_TOTAL_BYTES {
         msg!("CryowarError::DataSizeNotMatched");
         return Err(CryowarError::DataSizeNotMatched.into());
@@ -225,7 +918,7 @@ pub fn assert_pool_storage_account(
     let cwar_pool_data =
         CwarPool::try_from_slice(&cwar_pool_data_byte_array[0usize..CWAR_POOL_STORAGE_TOTAL_BYTES])
             .unwrap();
-    if cwar_pool_data.acc_type != AccTypesWithVersion::CwarPoolDataV1 as u8 {
+    if cwar_pool_data.acc_type != AccTypesWithVersion::CwarPoolDataV2 as u8 {
         msg!("CryowarError::ExpectedAccountTypeMismatched");
         return Err(CryowarError::ExpectedAccountTypeMismatched.into());
     }
@@ -252,16 +945,7 @@ pub fn assert_user_storage_account(
         msg!("Error: User Storage address does not match seed derivation");
         return Err(CryowarError::InvalidSeeds.into());
     }
-    if user_data_byte_array.len() != USER_STORAGE_TOTAL_BYTES {
-        msg!("CryowarError::DataSizeNotMatched");
-        return Err(CryowarError::DataSizeNotMatched.into());
-    }
-    let user_storage_data: User =
-        User::try_from_slice(&user_data_byte_array[0usize..USER_STORAGE_TOTAL_BYTES]).unwrap();
-    if user_storage_data.acc_type != AccTypesWithVersion::UserDataV1 as u8 {
-        msg!("CryowarError::ExpectedAccountTypeMismatched");
-        return Err(CryowarError::ExpectedAccountTypeMismatched.into());
-    }
+    let user_storage_data = unpack_user_storage_account(user_data_byte_array)?;
 
     if user_storage_data.user_wallet != *user_wallet_account.key {
         msg!("CryowarError::UserStorageAuthorityMismatched");
@@ -274,12 +958,250 @@ pub fn assert_user_storage_account(
     Ok(user_storage_data)
 }
 
+/// Reads a V1 or V2 user account, V1 accounts come back with zeroed reserved space
+pub fn unpack_user_storage_account(user_data_byte_array: &[u8]) -> Result<User, CryowarError> {
+    let expected_acc_type = match user_data_byte_array.len() {
+        USER_STORAGE_TOTAL_BYTES => AccTypesWithVersion::UserDataV2,
+        USER_V1_STORAGE_TOTAL_BYTES => AccTypesWithVersion::UserDataV1,
+        _ => {
+            msg!("CryowarError::DataSizeNotMatched");
+            return Err(CryowarError::DataSizeNotMatched);
+        }
+    };
+    let mut user_data = [0u8; USER_STORAGE_TOTAL_BYTES];
+    user_data[0usize..user_data_byte_array.len()].copy_from_slice(user_data_byte_array);
+    let user_storage_data = User::try_from_slice(&user_data).unwrap();
+    if user_storage_data.acc_type != expected_acc_type as u8 {
+        msg!("CryowarError::ExpectedAccountTypeMismatched");
+        return Err(CryowarError::ExpectedAccountTypeMismatched);
+    }
+    Ok(user_storage_data)
+}
+
+/// Writes the user back in the layout of the account, a V1 account can only hold
+/// users that do not use any of the V2 fields
 pub fn save_user_storage_account(
     user_data_byte_array: &mut [u8],
     user_storage_data: &User,
 ) -> ProgramResult {
-    user_data_byte_array[0usize..USER_STORAGE_TOTAL_BYTES]
-        .copy_from_slice(&user_storage_data.try_to_vec().unwrap());
+    let user_data = user_storage_data.try_to_vec().unwrap();
+    let account_size = user_data_byte_array.len();
+    if account_size != USER_STORAGE_TOTAL_BYTES && account_size != USER_V1_STORAGE_TOTAL_BYTES {
+        msg!("CryowarError::DataSizeNotMatched");
+        return Err(CryowarError::DataSizeNotMatched.into());
+    }
+    if user_data[account_size..].iter().any(|byte| *byte != 0u8) {
+        msg!("CryowarError::AccountNeedsMigration");
+        return Err(CryowarError::AccountNeedsMigration.into());
+    }
+    user_data_byte_array.copy_from_slice(&user_data[0usize..account_size]);
+    Ok(())
+}
+
+pub fn assert_pending_action_account(
+    pending_action_account: &AccountInfo,
+    cwar_pool_storage_account: &AccountInfo,
+    program_id: &Pubkey,
+    pending_action_byte_array: &[u8],
+) -> Result<PendingAction, CryowarError> {
+    if pending_action_account.owner != program_id {
+        msg!("CryowarError::PendingActionAccountOwnerShouldBeCwarProgram");
+        return Err(CryowarError::AccountOwnerShouldBeCwarProgram);
+    }
+    if pending_action_byte_array.len() != PENDING_ACTION_STORAGE_TOTAL_BYTES {
+        msg!("CryowarError::DataSizeNotMatched");
+        return Err(CryowarError::DataSizeNotMatched);
+    }
+    // actions differ in size, the rest of the account is zero padding
+    let pending_action_data =
+        PendingAction::deserialize(&mut &pending_action_byte_array[..]).unwrap();
+    if pending_action_data.acc_type != AccTypesWithVersion::PendingActionDataV1 as u8 {
+        msg!("CryowarError::ExpectedAccountTypeMismatched");
+        return Err(CryowarError::ExpectedAccountTypeMismatched);
+    }
+    if pending_action_data.cwar_pool != *cwar_pool_storage_account.key {
+        msg!("CryowarError::PendingActionPoolMismatched");
+        return Err(CryowarError::PendingActionPoolMismatched);
+    }
+    let (pending_action_address, _bump_seed) = get_pending_action_address_and_bump_seed(
+        cwar_pool_storage_account.key,
+        pending_action_data.action_id,
+        program_id,
+    );
+    if pending_action_address != *pending_action_account.key {
+        msg!("Error: Pending Action address does not match seed derivation");
+        return Err(CryowarError::InvalidSeeds);
+    }
+    Ok(pending_action_data)
+}
+
+/// A queued action can be executed from its eta until `ACTION_EXECUTION_GRACE_PERIOD` later
+pub fn assert_action_executable(pending_action: &PendingAction, now: u64) -> ProgramResult {
+    if now < pending_action.eta {
+        msg!("CryowarError::TimelockNotExpired");
+        msg!("eta: {}", pending_action.eta);
+        return Err(CryowarError::TimelockNotExpired.into());
+    }
+    if now - pending_action.eta > constants::ACTION_EXECUTION_GRACE_PERIOD {
+        msg!("CryowarError::ActionExpired");
+        msg!("eta: {}", pending_action.eta);
+        return Err(CryowarError::ActionExpired.into());
+    }
+    Ok(())
+}
+
+pub fn save_pending_action_account(
+    pending_action_byte_array: &mut [u8],
+    pending_action_data: &PendingAction,
+) -> ProgramResult {
+    let pending_action = pending_action_data.try_to_vec().unwrap();
+    pending_action_byte_array[0usize..pending_action.len()].copy_from_slice(&pending_action);
+    Ok(())
+}
+
+pub fn assert_unbonding_request_account(
+    unbonding_request_account: &AccountInfo,
+    user_storage_account: &AccountInfo,
+    program_id: &Pubkey,
+    unbonding_request_byte_array: &[u8],
+) -> Result<UnbondingRequest, CryowarError> {
+    if unbonding_request_account.owner != program_id {
+        msg!("CryowarError::UnbondingRequestAccountOwnerShouldBeCwarProgram");
+        return Err(CryowarError::AccountOwnerShouldBeCwarProgram);
+    }
+    if unbonding_request_byte_array.len() != UNBONDING_REQUEST_STORAGE_TOTAL_BYTES {
+        msg!("CryowarError::DataSizeNotMatched");
+        return Err(CryowarError::DataSizeNotMatched);
+    }
+    let unbonding_request_data =
+        UnbondingRequest::deserialize(&mut &unbonding_request_byte_array[..]).unwrap();
+    if unbonding_request_data.acc_type != AccTypesWithVersion::UnbondingRequestDataV1 as u8 {
+        msg!("CryowarError::ExpectedAccountTypeMismatched");
+        return Err(CryowarError::ExpectedAccountTypeMismatched);
+    }
+    if unbonding_request_data.user_storage != *user_storage_account.key {
+        msg!("CryowarError::UnbondingRequestUserMismatched");
+        return Err(CryowarError::UnbondingRequestUserMismatched);
+    }
+    let (unbonding_request_address, _bump_seed) = get_unbonding_request_address_and_bump_seed(
+        user_storage_account.key,
+        unbonding_request_data.request_id,
+        program_id,
+    );
+    if unbonding_request_address != *unbonding_request_account.key {
+        msg!("Error: Unbonding Request address does not match seed derivation");
+        return Err(CryowarError::InvalidSeeds);
+    }
+    Ok(unbonding_request_data)
+}
+
+pub fn save_unbonding_request_account(
+    unbonding_request_byte_array: &mut [u8],
+    unbonding_request_data: &UnbondingRequest,
+) -> ProgramResult {
+    let unbonding_request = unbonding_request_data.try_to_vec().unwrap();
+    unbonding_request_byte_array[0usize..unbonding_request.len()]
+        .copy_from_slice(&unbonding_request);
+    Ok(())
+}
+
+pub fn assert_reward_schedule_account(
+    reward_schedule_account: &AccountInfo,
+    cwar_pool_data: &CwarPool,
+    program_id: &Pubkey,
+    reward_schedule_byte_array: &[u8],
+) -> Result<RewardSchedule, CryowarError> {
+    if *reward_schedule_account.key != cwar_pool_data.reward_schedule {
+        msg!("CryowarError::InvalidRewardSchedule");
+        return Err(CryowarError::InvalidRewardSchedule);
+    }
+    if reward_schedule_account.owner != program_id {
+        msg!("CryowarError::RewardScheduleAccountOwnerShouldBeCwarProgram");
+        return Err(CryowarError::AccountOwnerShouldBeCwarProgram);
+    }
+    if reward_schedule_byte_array.len() != REWARD_SCHEDULE_STORAGE_TOTAL_BYTES {
+        msg!("CryowarError::DataSizeNotMatched");
+        return Err(CryowarError::DataSizeNotMatched);
+    }
+    let reward_schedule_data =
+        RewardSchedule::deserialize(&mut &reward_schedule_byte_array[..]).unwrap();
+    if reward_schedule_data.acc_type != AccTypesWithVersion::RewardScheduleDataV1 as u8 {
+        msg!("CryowarError::ExpectedAccountTypeMismatched");
+        return Err(CryowarError::ExpectedAccountTypeMismatched);
+    }
+    Ok(reward_schedule_data)
+}
+
+/// Reads the `RewardSchedule` pools with one take right after the fixed accounts of every
+/// instruction that checkpoints rewards
+pub fn next_reward_schedule<'a, 'b: 'a, I: Iterator<Item = &'a AccountInfo<'b>>>(
+    account_info_iter: &mut I,
+    cwar_pool_data: &CwarPool,
+    program_id: &Pubkey,
+) -> Result<Option<RewardSchedule>, ProgramError> {
+    if !cwar_pool_data.has_reward_schedule() {
+        return Ok(None);
+    }
+    let reward_schedule_account = next_account_info(account_info_iter)?;
+    let reward_schedule_data = assert_reward_schedule_account(
+        reward_schedule_account,
+        cwar_pool_data,
+        program_id,
+        &reward_schedule_account.data.borrow(),
+    )?;
+    Ok(Some(reward_schedule_data))
+}
+
+pub fn save_reward_schedule_account(
+    reward_schedule_byte_array: &mut [u8],
+    reward_schedule_data: &RewardSchedule,
+) -> ProgramResult {
+    let reward_schedule = reward_schedule_data.try_to_vec().unwrap();
+    reward_schedule_byte_array[0usize..reward_schedule.len()].copy_from_slice(&reward_schedule);
+    Ok(())
+}
+
+pub fn assert_lock_position_account(
+    lock_position_account: &AccountInfo,
+    user_storage_account: &AccountInfo,
+    program_id: &Pubkey,
+    lock_position_byte_array: &[u8],
+) -> Result<LockPosition, CryowarError> {
+    if lock_position_account.owner != program_id {
+        msg!("CryowarError::LockPositionAccountOwnerShouldBeCwarProgram");
+        return Err(CryowarError::AccountOwnerShouldBeCwarProgram);
+    }
+    if lock_position_byte_array.len() != LOCK_POSITION_STORAGE_TOTAL_BYTES {
+        msg!("CryowarError::DataSizeNotMatched");
+        return Err(CryowarError::DataSizeNotMatched);
+    }
+    let lock_position_data = LockPosition::deserialize(&mut &lock_position_byte_array[..]).unwrap();
+    if lock_position_data.acc_type != AccTypesWithVersion::LockPositionDataV1 as u8 {
+        msg!("CryowarError::ExpectedAccountTypeMismatched");
+        return Err(CryowarError::ExpectedAccountTypeMismatched);
+    }
+    if lock_position_data.user_storage != *user_storage_account.key {
+        msg!("CryowarError::LockPositionUserMismatched");
+        return Err(CryowarError::LockPositionUserMismatched);
+    }
+    let (lock_position_address, _bump_seed) = get_lock_position_address_and_bump_seed(
+        user_storage_account.key,
+        lock_position_data.position_id,
+        program_id,
+    );
+    if lock_position_address != *lock_position_account.key {
+        msg!("Error: Lock Position address does not match seed derivation");
+        return Err(CryowarError::InvalidSeeds);
+    }
+    Ok(lock_position_data)
+}
+
+pub fn save_lock_position_account(
+    lock_position_byte_array: &mut [u8],
+    lock_position_data: &LockPosition,
+) -> ProgramResult {
+    let lock_position = lock_position_data.try_to_vec().unwrap();
+    lock_position_byte_array[0usize..lock_position.len()].copy_from_slice(&lock_position);
     Ok(())
 }
 
@@ -292,6 +1214,231 @@ pub fn save_pool_storage_account(
     Ok(())
 }
 
+/// `recipient_ata` has to be the `mint` ATA of `recipient_wallet`, it is created when missing with
+/// `payer` covering the rent
+#[allow(clippy::too_many_arguments)]
+pub fn create_recipient_ata_if_missing<'a>(
+    recipient_ata: &AccountInfo<'a>,
+    recipient_wallet: &AccountInfo<'a>,
+    mint: &AccountInfo<'a>,
+    expected_mint: &Pubkey,
+    payer: &AccountInfo<'a>,
+    system_program_info: &AccountInfo<'a>,
+    token_program: &AccountInfo<'a>,
+    associated_token_program: &AccountInfo<'a>,
+) -> ProgramResult {
+    assert_system_program(system_program_info)?;
+    assert_associated_token_program(associated_token_program)?;
+    if mint.key != expected_mint {
+        msg!("CryowarError::MintMismatched");
+        return Err(CryowarError::MintMismatched.into());
+    }
+    if get_associated_token_address(recipient_wallet.key, mint.key) != *recipient_ata.key {
+        msg!("CryowarError::WrongTokenAccountPassed");
+        return Err(CryowarError::WrongTokenAccountPassed.into());
+    }
+    invoke(
+        &create_associated_token_account_idempotent(
+            payer.key,
+            recipient_wallet.key,
+            mint.key,
+            token_program.key,
+        ),
+        &[
+            payer.clone(),
+            recipient_ata.clone(),
+            recipient_wallet.clone(),
+            mint.clone(),
+            system_program_info.clone(),
+            token_program.clone(),
+            associated_token_program.clone(),
+        ],
+    )
+}
+
+/// Accounts passed right after the fixed accounts of `UnstakeCwar` and `ClaimRewards` to pay
+/// another wallet or create a missing ATA, recognized by the Associated Token Program leading them
+pub struct RecipientAccounts<'a, 'b> {
+    pub associated_token_program: &'a AccountInfo<'b>,
+    pub system_program_info: &'a AccountInfo<'b>,
+    pub recipient_wallet: &'a AccountInfo<'b>,
+    pub mint: &'a AccountInfo<'b>,
+}
+
+pub fn next_recipient_accounts<'a, 'b: 'a, I: Iterator<Item = &'a AccountInfo<'b>> + Clone>(
+    account_info_iter: &mut I,
+) -> Result<Option<RecipientAccounts<'a, 'b>>, ProgramError> {
+    match account_info_iter.clone().next() {
+        Some(account) if *account.key == spl_associated_token_account::id() => {}
+        _ => return Ok(None),
+    }
+    Ok(Some(RecipientAccounts {
+        associated_token_program: next_account_info(account_info_iter)?,
+        system_program_info: next_account_info(account_info_iter)?,
+        recipient_wallet: next_account_info(account_info_iter)?,
+        mint: next_account_info(account_info_iter)?,
+    }))
+}
+
+pub fn assert_token_account_mint(token_account: &AccountInfo, mint: &Pubkey) -> ProgramResult {
+    let token_account_data = TokenAccount::unpack(&token_account.data.borrow())?;
+    if token_account_data.mint != *mint {
+        msg!("CryowarError::MintMismatched");
+        return Err(CryowarError::MintMismatched.into());
+    }
+    Ok(())
+}
+
+/// Refuses what would move unlocked stake in a receipt pool without minting or burning receipts
+pub fn assert_no_receipt_mint(cwar_pool: &CwarPool) -> ProgramResult {
+    if cwar_pool.has_receipt_mint() {
+        msg!("CryowarError::NotSupportedByReceiptPools");
+        return Err(CryowarError::NotSupportedByReceiptPools.into());
+    }
+    Ok(())
+}
+
+/// A receipt mint starts empty, can only be minted by the pool signer, can't be frozen and uses
+/// the decimals of the staking mint
+pub fn assert_receipt_mint_data(
+    receipt_mint: &Mint,
+    staking_mint: &Mint,
+    pool_signer_address: &Pubkey,
+) -> ProgramResult {
+    if receipt_mint.mint_authority != COption::Some(*pool_signer_address)
+        || receipt_mint.freeze_authority.is_some()
+        || receipt_mint.supply != 0u64
+        || receipt_mint.decimals != staking_mint.decimals
+    {
+        msg!("CryowarError::InvalidReceiptMint");
+        return Err(CryowarError::InvalidReceiptMint.into());
+    }
+    Ok(())
+}
+
+/// The receipt balance of a wallet is what its receipt mint ATA holds, a closed ATA holds nothing
+pub fn get_receipt_balance(
+    receipt_ata: &AccountInfo,
+    wallet: &Pubkey,
+    cwar_pool: &CwarPool,
+) -> Result<u64, ProgramError> {
+    if get_associated_token_address(wallet, &cwar_pool.receipt_mint) != *receipt_ata.key {
+        msg!("CryowarError::WrongTokenAccountPassed");
+        return Err(CryowarError::WrongTokenAccountPassed.into());
+    }
+    if receipt_ata.data_is_empty() {
+        return Ok(0u64);
+    }
+    if *receipt_ata.owner != spl_token::id() {
+        msg!("CryowarError::WrongTokenAccountPassed");
+        return Err(CryowarError::WrongTokenAccountPassed.into());
+    }
+    Ok(TokenAccount::unpack(&receipt_ata.data.borrow())?.amount)
+}
+
+/// Mints `amount` receipts into `user_receipt_ata`, signed by the pool signer
+#[allow(clippy::too_many_arguments)]
+pub fn mint_receipt<'a>(
+    receipt_mint: &AccountInfo<'a>,
+    user_receipt_ata: &AccountInfo<'a>,
+    cwar_pool_storage_account: &AccountInfo<'a>,
+    pool_signer_pda: &AccountInfo<'a>,
+    token_program: &AccountInfo<'a>,
+    cwar_pool: &CwarPool,
+    amount: u64,
+    program_id: &Pubkey,
+) -> ProgramResult {
+    if *receipt_mint.key != cwar_pool.receipt_mint {
+        msg!("CryowarError::InvalidReceiptMint");
+        return Err(CryowarError::InvalidReceiptMint.into());
+    }
+    let (pool_signer_address, bump_seed) =
+        get_pool_signer_address_and_bump_seed(cwar_pool_storage_account.key, program_id);
+    if pool_signer_address != *pool_signer_pda.key {
+        msg!("CryowarError::InvalidSignerPDA");
+        return Err(CryowarError::InvalidSignerPDA.into());
+    }
+    msg!("Calling the token program to mint receipts to User...");
+    invoke_signed(
+        &spl_token::instruction::mint_to(
+            token_program.key,
+            receipt_mint.key,
+            user_receipt_ata.key,
+            &pool_signer_address,
+            &[&pool_signer_address],
+            amount,
+        )?,
+        &[
+            receipt_mint.clone(),
+            user_receipt_ata.clone(),
+            pool_signer_pda.clone(),
+            token_program.clone(),
+        ],
+        &[&[&cwar_pool_storage_account.key.to_bytes(), &[bump_seed]]],
+    )
+}
+
+/// Burns `amount` receipts from the receipt mint ATA of the signing user
+pub fn burn_receipt<'a>(
+    receipt_mint: &AccountInfo<'a>,
+    user_receipt_ata: &AccountInfo<'a>,
+    user_wallet_account: &AccountInfo<'a>,
+    token_program: &AccountInfo<'a>,
+    cwar_pool: &CwarPool,
+    amount: u64,
+) -> ProgramResult {
+    if *receipt_mint.key != cwar_pool.receipt_mint {
+        msg!("CryowarError::InvalidReceiptMint");
+        return Err(CryowarError::InvalidReceiptMint.into());
+    }
+    if get_associated_token_address(user_wallet_account.key, receipt_mint.key)
+        != *user_receipt_ata.key
+    {
+        msg!("CryowarError::WrongTokenAccountPassed");
+        return Err(CryowarError::WrongTokenAccountPassed.into());
+    }
+    msg!("Calling the token program to burn receipts of User...");
+    invoke(
+        &spl_token::instruction::burn(
+            token_program.key,
+            user_receipt_ata.key,
+            receipt_mint.key,
+            user_wallet_account.key,
+            &[],
+            amount,
+        )?,
+        &[
+            user_receipt_ata.clone(),
+            receipt_mint.clone(),
+            user_wallet_account.clone(),
+            token_program.clone(),
+        ],
+    )
+}
+
+/// Records the receipts an emergency exit of `amount` could not burn out of a wallet holding
+/// `receipt_balance`, returning how many can be burnt
+pub fn add_receipt_debt(
+    user: &mut User,
+    amount: u64,
+    receipt_balance: u64,
+) -> Result<u64, ProgramError> {
+    let receipts_to_burn = std::cmp::min(amount, receipt_balance);
+    user.receipt_debt = user
+        .receipt_debt
+        .checked_add(amount - receipts_to_burn)
+        .ok_or(CryowarError::AmountOverflow)?;
+    Ok(receipts_to_burn)
+}
+
+/// Pays the user's receipt debt back out of a deposit of `amount`, returning how many receipts
+/// are left to mint for it
+pub fn repay_receipt_debt(user: &mut User, amount: u64) -> u64 {
+    let receipt_debt_repaid = std::cmp::min(amount, user.receipt_debt);
+    user.receipt_debt -= receipt_debt_repaid;
+    amount - receipt_debt_repaid
+}
+
 pub fn assert_token_account_to_be_owned_by_signer(
     token_account: &AccountInfo,
     signer_wallet: &AccountInfo,
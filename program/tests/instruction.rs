@@ -0,0 +1,702 @@
+use cwar_token_staking::{
+    error::CryowarError,
+    instruction::{self, CryowarInstruction},
+    processor::create_user::get_user_storage_address,
+    state::{
+        LockTier, PenalityRouting, PenalitySchedule, PoolAction, MAX_LOCK_TIERS,
+        PENALITY_SCHEDULE_LINEAR,
+    },
+    utils::{
+        get_lock_position_address_and_bump_seed, get_pending_action_address_and_bump_seed,
+        get_pool_signer_address_and_bump_seed, get_reward_schedule_address_and_bump_seed,
+    },
+};
+use rand::Rng;
+use solana_program::{
+    instruction::AccountMeta, program_error::ProgramError, pubkey::Pubkey, system_program,
+};
+use spl_associated_token_account::get_associated_token_address;
+
+fn all_instructions() -> Vec<CryowarInstruction> {
+    vec![
+        CryowarInstruction::InitializeCwarPool {
+            reward_duration: 86400,
+            pool_nonce: 254,
+            unstake_penality_basis_points: 500,
+            locking_duration: 0,
+        },
+        CryowarInstruction::CreateUser { nonce: 255 },
+        CryowarInstruction::StakeCwar {
+            amount_to_deposit: 100_000_000_000,
+        },
+        CryowarInstruction::UnstakeCwar {
+            amount_to_withdraw: u64::MAX,
+        },
+        CryowarInstruction::ClaimRewards {},
+        CryowarInstruction::AddFunder {},
+        CryowarInstruction::RemoveFunder {},
+        CryowarInstruction::FundPool {
+            amount: 86_400_000_000_000,
+            extend_duration: 86400,
+        },
+        CryowarInstruction::ClosePool {},
+        CryowarInstruction::CloseUser {},
+        CryowarInstruction::MigratePool {},
+        CryowarInstruction::RecoverSurplus {},
+        CryowarInstruction::MigrateUser {},
+        CryowarInstruction::ProposeNewOwner {},
+        CryowarInstruction::AcceptOwnership {},
+        CryowarInstruction::CancelOwnershipTransfer {},
+        CryowarInstruction::SetPauser {},
+        CryowarInstruction::SetPausedOperations {
+            paused_operations: 0b1101,
+        },
+        CryowarInstruction::EmergencyUnstake {},
+        CryowarInstruction::UpdatePoolConfig {
+            unstake_penality_basis_points: Some(200),
+            locking_duration: None,
+            authority_penality_deposit_ata: Some(Pubkey::new_unique()),
+            unbonding_duration: None,
+        },
+        CryowarInstruction::UpdatePoolConfig {
+            unstake_penality_basis_points: None,
+            locking_duration: None,
+            authority_penality_deposit_ata: None,
+            unbonding_duration: Some(7 * 86400),
+        },
+        CryowarInstruction::SetTimelockDelay {
+            timelock_delay: 172_800,
+        },
+        CryowarInstruction::QueueAction {
+            action: PoolAction::ClosePool {
+                staking_refund_ata: Pubkey::new_unique(),
+                rewards_refund_ata: Pubkey::new_unique(),
+            },
+        },
+        CryowarInstruction::QueueAction {
+            action: PoolAction::UpdatePoolConfig {
+                unstake_penality_basis_points: None,
+                locking_duration: Some(3600),
+                authority_penality_deposit_ata: None,
+                unbonding_duration: None,
+            },
+        },
+        CryowarInstruction::ExecuteAction {},
+        CryowarInstruction::CancelAction {},
+        CryowarInstruction::ReduceRewards {
+            amount: 1_000_000_000,
+            new_reward_duration_end: 1_700_000_000,
+        },
+        CryowarInstruction::CancelRewards {},
+        CryowarInstruction::AddRewardStream {},
+        CryowarInstruction::FundRewardStream {
+            stream_index: 1,
+            amount: 86_400_000_000_000,
+            extend_duration: 86400,
+        },
+        CryowarInstruction::CloseRewardStream { stream_index: 0 },
+        CryowarInstruction::CompoundRewards {},
+        CryowarInstruction::RequestUnstake {
+            amount: 100_000_000_000,
+        },
+        CryowarInstruction::WithdrawUnstaked {},
+        CryowarInstruction::CancelUnstakeRequest {},
+        CryowarInstruction::SetLockTiers {
+            lock_tiers: [LockTier {
+                duration: 90 * 86400,
+                multiplier_basis_points: 15_000,
+            }; MAX_LOCK_TIERS],
+        },
+        CryowarInstruction::StakeLocked {
+            amount: 100_000_000_000,
+            lock_tier_index: 2,
+        },
+        CryowarInstruction::UnstakeLocked {},
+        CryowarInstruction::SetPenalitySchedule {
+            penality_schedule: PenalitySchedule {
+                kind: PENALITY_SCHEDULE_LINEAR,
+                decay_duration: 14 * 86400,
+                ..Default::default()
+            },
+        },
+        CryowarInstruction::SetPenalityRouting {
+            penality_routing: PenalityRouting {
+                burn_basis_points: 2_500,
+                redistribute_basis_points: 5_000,
+                treasury_basis_points: 2_500,
+                ..Default::default()
+            },
+        },
+        CryowarInstruction::SetStakeLimits {
+            min_stake: 100_000_000_000,
+            max_stake_per_user: 0,
+            max_total_staked: 1_000_000_000_000_000,
+        },
+        CryowarInstruction::SetAllowlistRoot {
+            allowlist_root: [7u8; 32],
+        },
+        CryowarInstruction::CreateAllowlistedUser {
+            nonce: 254,
+            allowlist_max_stake: 100_000_000_000,
+            allowlist_proof: vec![[1u8; 32], [2u8; 32]],
+        },
+        CryowarInstruction::Exit {},
+        CryowarInstruction::ClaimRewardsMulti {},
+        CryowarInstruction::TransferPosition {},
+        CryowarInstruction::SetReceiptMint {
+            receipt_mint: Pubkey::new_from_array([9u8; 32]),
+        },
+        CryowarInstruction::SettleReceipt {},
+        CryowarInstruction::AddRewardPhase {
+            start: 1_700_000_000,
+            end: 1_700_086_400,
+            reward_rate: 1_000_000,
+        },
+        CryowarInstruction::ExpireLockPosition {},
+        CryowarInstruction::QueueAction {
+            action: PoolAction::CancelRewards {
+                rewards_refund_ata: Pubkey::new_unique(),
+            },
+        },
+    ]
+}
+
+#[test]
+fn test_pack_unpack_round_trip() {
+    for ix in all_instructions() {
+        let packed = ix.pack();
+        assert_eq!(CryowarInstruction::unpack(&packed).unwrap(), ix);
+    }
+}
+
+#[test]
+fn test_pack_wire_format() {
+    let ix = CryowarInstruction::InitializeCwarPool {
+        reward_duration: 86400,
+        pool_nonce: 254,
+        unstake_penality_basis_points: 500,
+        locking_duration: 7,
+    };
+    let mut expected = vec![0u8];
+    expected.extend(86400u64.to_le_bytes());
+    expected.push(254);
+    expected.extend(500u16.to_le_bytes());
+    expected.extend(7u64.to_le_bytes());
+    assert_eq!(ix.pack(), expected);
+
+    let ix = CryowarInstruction::FundPool {
+        amount: 1,
+        extend_duration: 2,
+    };
+    let mut expected = vec![7u8];
+    expected.extend(1u64.to_le_bytes());
+    expected.extend(2u64.to_le_bytes());
+    assert_eq!(ix.pack(), expected);
+
+    assert_eq!(
+        CryowarInstruction::CreateUser { nonce: 9 }.pack(),
+        vec![1, 9]
+    );
+    assert_eq!(CryowarInstruction::CloseUser {}.pack(), vec![9]);
+}
+
+#[test]
+fn test_unpack_rejects_bad_length() {
+    let length_error: ProgramError = CryowarError::InvalidInstructionDataLength.into();
+    for ix in all_instructions() {
+        let packed = ix.pack();
+        if packed.len() > 1 {
+            assert_eq!(
+                CryowarInstruction::unpack(&packed[..packed.len() - 1]).unwrap_err(),
+                length_error
+            );
+        }
+        let mut with_trailing_byte = packed.clone();
+        with_trailing_byte.push(0);
+        assert_eq!(
+            CryowarInstruction::unpack(&with_trailing_byte).unwrap_err(),
+            length_error
+        );
+    }
+}
+
+#[test]
+fn test_unpack_rejects_empty_and_unknown_tag() {
+    let invalid_instruction: ProgramError = CryowarError::InvalidInstruction.into();
+    assert_eq!(
+        CryowarInstruction::unpack(&[]).unwrap_err(),
+        invalid_instruction
+    );
+    assert_eq!(
+        CryowarInstruction::unpack(&[255u8, 1, 2, 3]).unwrap_err(),
+        invalid_instruction
+    );
+    // `ExpireLockPosition` is the last variant
+    let last_tag = CryowarInstruction::ExpireLockPosition {}.pack()[0];
+    assert_eq!(
+        CryowarInstruction::unpack(&[last_tag + 1]).unwrap_err(),
+        invalid_instruction
+    );
+}
+
+#[test]
+fn test_unpack_rejects_malformed_payload() {
+    let mut packed = CryowarInstruction::UpdatePoolConfig {
+        unstake_penality_basis_points: None,
+        locking_duration: None,
+        authority_penality_deposit_ata: None,
+        unbonding_duration: None,
+    }
+    .pack();
+    // an `Option` flag other than 0 or 1
+    packed[1] = 2;
+    assert_eq!(
+        CryowarInstruction::unpack(&packed).unwrap_err(),
+        ProgramError::InvalidInstructionData
+    );
+}
+
+// arbitrary byte strings must never panic, and whatever decodes must encode back to the same bytes
+#[test]
+fn test_unpack_random_bytes() {
+    let mut rng = rand::thread_rng();
+    for _ in 0..100_000 {
+        let len = rng.gen_range(0..32);
+        let mut input: Vec<u8> = (0..len).map(|_| rng.gen()).collect();
+        if len > 0 && rng.gen_bool(0.9) {
+            input[0] = rng.gen_range(0..50);
+        }
+        if let Ok(ix) = CryowarInstruction::unpack(&input) {
+            assert_eq!(ix.pack(), input);
+        }
+    }
+}
+
+#[test]
+fn test_builders_derive_pdas_and_flags() {
+    let program_id = Pubkey::new_unique();
+    let user_wallet = Pubkey::new_unique();
+    let pool_storage = Pubkey::new_unique();
+    let staking_vault = Pubkey::new_unique();
+    let rewards_vault = Pubkey::new_unique();
+    let reward_mint = Pubkey::new_unique();
+    let (pool_signer, pool_nonce) =
+        get_pool_signer_address_and_bump_seed(&pool_storage, &program_id);
+    let user_storage = get_user_storage_address(&user_wallet, &pool_storage, &program_id);
+
+    let ix = instruction::create_user(&program_id, &user_wallet, &pool_storage);
+    assert_eq!(ix.program_id, program_id);
+    assert_eq!(ix.accounts[0].pubkey, user_wallet);
+    assert!(ix.accounts[0].is_signer && ix.accounts[0].is_writable);
+    assert_eq!(ix.accounts[1].pubkey, user_storage);
+    assert!(ix.accounts[1].is_writable && !ix.accounts[1].is_signer);
+    assert_eq!(ix.accounts[3].pubkey, system_program::id());
+
+    let ix = instruction::claim_rewards(
+        &program_id,
+        &user_wallet,
+        &pool_storage,
+        &staking_vault,
+        &rewards_vault,
+        &reward_mint,
+        &user_wallet,
+        false,
+        &[],
+    );
+    assert_eq!(ix.data, vec![4]);
+    assert_eq!(ix.accounts.len(), 12);
+    assert!(ix.accounts[0].is_signer && ix.accounts[0].is_writable);
+    assert_eq!(ix.accounts[1].pubkey, user_storage);
+    assert_eq!(
+        ix.accounts[5].pubkey,
+        get_associated_token_address(&user_wallet, &reward_mint)
+    );
+    assert_eq!(ix.accounts[6].pubkey, pool_signer);
+    assert!(!ix.accounts[6].is_signer && !ix.accounts[6].is_writable);
+    assert_eq!(ix.accounts[7].pubkey, spl_token::id());
+    // the recipient accounts follow the fixed accounts, led by the associated token program
+    assert_eq!(ix.accounts[8].pubkey, spl_associated_token_account::id());
+    assert_eq!(ix.accounts[9].pubkey, system_program::id());
+    assert_eq!(ix.accounts[10].pubkey, user_wallet);
+    assert_eq!(ix.accounts[11].pubkey, reward_mint);
+    let reward_stream_vault = Pubkey::new_unique();
+    let reward_stream_mint = Pubkey::new_unique();
+    let recipient_wallet = Pubkey::new_unique();
+    let recipient_reward_stream_ata = Pubkey::new_unique();
+    let ix = instruction::claim_rewards(
+        &program_id,
+        &user_wallet,
+        &pool_storage,
+        &staking_vault,
+        &rewards_vault,
+        &reward_mint,
+        &recipient_wallet,
+        false,
+        &[(reward_stream_vault, reward_stream_mint)],
+    );
+    assert_eq!(ix.accounts.len(), 15);
+    assert_eq!(
+        ix.accounts[5].pubkey,
+        get_associated_token_address(&recipient_wallet, &reward_mint)
+    );
+    assert_eq!(ix.accounts[10].pubkey, recipient_wallet);
+    assert_eq!(ix.accounts[12].pubkey, reward_stream_vault);
+    assert!(ix.accounts[13].pubkey == reward_stream_mint && !ix.accounts[13].is_writable);
+    assert_eq!(
+        ix.accounts[14].pubkey,
+        get_associated_token_address(&recipient_wallet, &reward_stream_mint)
+    );
+    assert!(ix.accounts[12].is_writable && ix.accounts[14].is_writable);
+
+    let staking_mint = Pubkey::new_unique();
+    let ix = instruction::unstake_cwar(
+        &program_id,
+        &user_wallet,
+        &pool_storage,
+        &staking_vault,
+        &staking_mint,
+        &Pubkey::new_unique(),
+        &recipient_wallet,
+        false,
+        &[staking_mint, rewards_vault],
+        None,
+        1,
+    );
+    assert_eq!(ix.accounts.len(), 14);
+    assert_eq!(
+        ix.accounts[4].pubkey,
+        get_associated_token_address(&recipient_wallet, &staking_mint)
+    );
+    assert_eq!(ix.accounts[8].pubkey, spl_associated_token_account::id());
+    assert_eq!(ix.accounts[10].pubkey, recipient_wallet);
+    assert!(ix.accounts[11].pubkey == staking_mint && !ix.accounts[11].is_writable);
+    assert!(ix.accounts[12].pubkey == staking_mint && ix.accounts[12].is_writable);
+    assert_eq!(ix.accounts[13].pubkey, rewards_vault);
+
+    let ix = instruction::exit(
+        &program_id,
+        &user_wallet,
+        &pool_storage,
+        &staking_vault,
+        &staking_mint,
+        &rewards_vault,
+        &reward_mint,
+        &Pubkey::new_unique(),
+        false,
+        None,
+        &[(reward_stream_vault, recipient_reward_stream_ata)],
+    );
+    assert_eq!(ix.data, vec![41]);
+    assert_eq!(ix.accounts.len(), 16);
+    assert!(ix.accounts[0].is_signer && ix.accounts[0].is_writable);
+    assert_eq!(ix.accounts[1].pubkey, user_storage);
+    assert_eq!(
+        ix.accounts[5].pubkey,
+        get_associated_token_address(&user_wallet, &staking_mint)
+    );
+    assert_eq!(
+        ix.accounts[6].pubkey,
+        get_associated_token_address(&user_wallet, &reward_mint)
+    );
+    assert_eq!(ix.accounts[7].pubkey, pool_signer);
+    assert_eq!(ix.accounts[14].pubkey, reward_stream_vault);
+
+    let other_pool_storage = Pubkey::new_unique();
+    let ix = instruction::claim_rewards_multi(
+        &program_id,
+        &user_wallet,
+        &[
+            (
+                pool_storage,
+                staking_vault,
+                rewards_vault,
+                reward_mint,
+                false,
+            ),
+            (
+                other_pool_storage,
+                Pubkey::new_unique(),
+                Pubkey::new_unique(),
+                staking_mint,
+                false,
+            ),
+        ],
+    );
+    assert_eq!(ix.data, vec![42]);
+    assert_eq!(ix.accounts.len(), 18);
+    assert!(ix.accounts[0].is_signer && ix.accounts[0].is_writable);
+    assert_eq!(ix.accounts[4].pubkey, user_storage);
+    assert_eq!(ix.accounts[9].pubkey, pool_signer);
+    assert_eq!(
+        ix.accounts[11].pubkey,
+        get_user_storage_address(&user_wallet, &other_pool_storage, &program_id)
+    );
+    assert_eq!(
+        ix.accounts[15].pubkey,
+        get_associated_token_address(&user_wallet, &staking_mint)
+    );
+
+    let recipient_wallet = Pubkey::new_unique();
+    let ix = instruction::transfer_position(
+        &program_id,
+        &user_wallet,
+        &pool_storage,
+        &recipient_wallet,
+        &[],
+        0,
+        false,
+    );
+    assert_eq!(ix.data, vec![43]);
+    assert_eq!(ix.accounts.len(), 6);
+    assert!(ix.accounts[0].is_signer && ix.accounts[0].is_writable);
+    assert_eq!(ix.accounts[1].pubkey, user_storage);
+    assert!(!ix.accounts[3].is_signer && !ix.accounts[3].is_writable);
+    assert_eq!(
+        ix.accounts[4].pubkey,
+        get_user_storage_address(&recipient_wallet, &pool_storage, &program_id)
+    );
+    assert!(ix.accounts[4].is_writable);
+
+    // lock positions come in pairs renumbered from the recipient's next position id
+    let ix = instruction::transfer_position(
+        &program_id,
+        &user_wallet,
+        &pool_storage,
+        &recipient_wallet,
+        &[2, 5],
+        3,
+        true,
+    );
+    assert_eq!(ix.accounts.len(), 11);
+    let recipient_storage = get_user_storage_address(&recipient_wallet, &pool_storage, &program_id);
+    assert_eq!(
+        ix.accounts[7].pubkey,
+        get_lock_position_address_and_bump_seed(&user_storage, 2, &program_id).0
+    );
+    assert_eq!(
+        ix.accounts[8].pubkey,
+        get_lock_position_address_and_bump_seed(&recipient_storage, 3, &program_id).0
+    );
+    assert_eq!(
+        ix.accounts[9].pubkey,
+        get_lock_position_address_and_bump_seed(&user_storage, 5, &program_id).0
+    );
+    assert_eq!(
+        ix.accounts[10].pubkey,
+        get_lock_position_address_and_bump_seed(&recipient_storage, 4, &program_id).0
+    );
+    assert!(ix.accounts[7..]
+        .iter()
+        .all(|x| x.is_writable && !x.is_signer));
+
+    // receipt pools append the receipt mint and the user's receipt ATA
+    let receipt_mint = Pubkey::new_unique();
+    let user_receipt_ata = get_associated_token_address(&user_wallet, &receipt_mint);
+    let ix = instruction::stake_cwar(
+        &program_id,
+        &user_wallet,
+        &pool_storage,
+        &staking_vault,
+        &Pubkey::new_unique(),
+        false,
+        Some(&receipt_mint),
+        1,
+    );
+    assert_eq!(ix.accounts.len(), 11);
+    assert!(ix.accounts[0].is_signer && ix.accounts[0].is_writable);
+    assert_eq!(ix.accounts[6].pubkey, receipt_mint);
+    assert_eq!(ix.accounts[7].pubkey, user_receipt_ata);
+    assert_eq!(ix.accounts[8].pubkey, pool_signer);
+    let ix = instruction::unstake_cwar(
+        &program_id,
+        &user_wallet,
+        &pool_storage,
+        &staking_vault,
+        &staking_mint,
+        &Pubkey::new_unique(),
+        &recipient_wallet,
+        false,
+        &[],
+        Some(&receipt_mint),
+        1,
+    );
+    assert_eq!(ix.accounts.len(), 14);
+    assert_eq!(ix.accounts[13].pubkey, user_receipt_ata);
+    let ix = instruction::exit(
+        &program_id,
+        &user_wallet,
+        &pool_storage,
+        &staking_vault,
+        &staking_mint,
+        &rewards_vault,
+        &reward_mint,
+        &Pubkey::new_unique(),
+        false,
+        Some(&receipt_mint),
+        &[(reward_stream_vault, recipient_reward_stream_ata)],
+    );
+    assert_eq!(ix.accounts.len(), 18);
+    assert_eq!(ix.accounts[15].pubkey, user_receipt_ata);
+    assert_eq!(ix.accounts[16].pubkey, reward_stream_vault);
+    let ix = instruction::settle_receipt(
+        &program_id,
+        &recipient_wallet,
+        &recipient_wallet,
+        &pool_storage,
+        &user_wallet,
+        &receipt_mint,
+        false,
+    );
+    assert_eq!(ix.data, vec![45]);
+    assert!(ix.accounts[0].is_signer && ix.accounts[0].is_writable);
+    assert_eq!(
+        ix.accounts[3].pubkey,
+        get_associated_token_address(&recipient_wallet, &receipt_mint)
+    );
+    assert_eq!(ix.accounts[5].pubkey, user_storage);
+    assert_eq!(ix.accounts[6].pubkey, user_receipt_ata);
+    assert_eq!(ix.accounts.len(), 8);
+
+    // anyone can settle, a payer other than the holder signs at the end instead
+    let payer = Pubkey::new_unique();
+    let ix = instruction::settle_receipt(
+        &program_id,
+        &payer,
+        &recipient_wallet,
+        &pool_storage,
+        &user_wallet,
+        &receipt_mint,
+        true,
+    );
+    assert!(!ix.accounts[0].is_signer && ix.accounts[0].is_writable);
+    assert_eq!(ix.accounts.len(), 10);
+    assert_eq!(ix.accounts[9].pubkey, payer);
+    assert!(ix.accounts[9].is_signer && ix.accounts[9].is_writable);
+
+    // schedule pools take the reward schedule right after the fixed accounts
+    let (reward_schedule, _) =
+        get_reward_schedule_address_and_bump_seed(&pool_storage, &program_id);
+    let ix = instruction::claim_rewards(
+        &program_id,
+        &user_wallet,
+        &pool_storage,
+        &staking_vault,
+        &rewards_vault,
+        &reward_mint,
+        &user_wallet,
+        true,
+        &[(reward_stream_vault, reward_stream_mint)],
+    );
+    assert_eq!(ix.accounts.len(), 16);
+    assert_eq!(ix.accounts[12].pubkey, reward_schedule);
+    assert!(!ix.accounts[12].is_signer && !ix.accounts[12].is_writable);
+    assert_eq!(ix.accounts[13].pubkey, reward_stream_vault);
+    let ix = instruction::stake_cwar(
+        &program_id,
+        &user_wallet,
+        &pool_storage,
+        &staking_vault,
+        &Pubkey::new_unique(),
+        true,
+        Some(&receipt_mint),
+        1,
+    );
+    assert_eq!(ix.accounts.len(), 12);
+    assert_eq!(ix.accounts[6].pubkey, reward_schedule);
+    assert_eq!(ix.accounts[7].pubkey, receipt_mint);
+    let ix = instruction::add_reward_phase(
+        &program_id,
+        &user_wallet,
+        &pool_storage,
+        &rewards_vault,
+        &Pubkey::new_unique(),
+        1,
+        2,
+        3,
+    );
+    assert_eq!(ix.data[0], 46);
+    assert!(ix.accounts[0].is_signer && ix.accounts[0].is_writable);
+    assert_eq!(ix.accounts[2].pubkey, reward_schedule);
+    assert!(ix.accounts[2].is_writable);
+    assert_eq!(ix.accounts[6].pubkey, system_program::id());
+
+    let ix = instruction::initialize_cwar_pool(
+        &program_id,
+        &Pubkey::new_unique(),
+        &pool_storage,
+        &Pubkey::new_unique(),
+        &staking_vault,
+        &Pubkey::new_unique(),
+        &rewards_vault,
+        &Pubkey::new_unique(),
+        86400,
+        0,
+        3600,
+    );
+    assert_eq!(
+        CryowarInstruction::unpack(&ix.data).unwrap(),
+        CryowarInstruction::InitializeCwarPool {
+            reward_duration: 86400,
+            pool_nonce,
+            unstake_penality_basis_points: 0,
+            locking_duration: 3600,
+        }
+    );
+    assert!(ix.accounts[0].is_signer);
+    assert_eq!(ix.accounts.iter().filter(|a| a.is_signer).count(), 1);
+
+    let new_owner = Pubkey::new_unique();
+    let ix = instruction::propose_new_owner(&program_id, &user_wallet, &pool_storage, &new_owner);
+    assert!(ix.accounts[0].is_signer);
+    assert_eq!(ix.accounts[2].pubkey, new_owner);
+    assert!(!ix.accounts[2].is_signer);
+    let new_owner_penality_deposit_ata = get_associated_token_address(&new_owner, &staking_mint);
+    let ix = instruction::accept_ownership(
+        &program_id,
+        &new_owner,
+        &pool_storage,
+        &new_owner_penality_deposit_ata,
+    );
+    assert_eq!(ix.accounts[0].pubkey, new_owner);
+    assert!(ix.accounts[0].is_signer && !ix.accounts[0].is_writable);
+    assert!(ix.accounts[1].is_writable);
+    assert_eq!(ix.accounts[2].pubkey, new_owner_penality_deposit_ata);
+    assert!(!ix.accounts[2].is_signer && !ix.accounts[2].is_writable);
+
+    let ix = instruction::queue_action(
+        &program_id,
+        &user_wallet,
+        &pool_storage,
+        7,
+        PoolAction::RemoveFunder {
+            funder_wallet: new_owner,
+        },
+    );
+    let (pending_action, _bump_seed) =
+        get_pending_action_address_and_bump_seed(&pool_storage, 7, &program_id);
+    assert_eq!(
+        pending_action,
+        Pubkey::find_program_address(
+            &[
+                b"pending_action",
+                &pool_storage.to_bytes(),
+                &7u64.to_le_bytes()
+            ],
+            &program_id
+        )
+        .0
+    );
+    assert_eq!(ix.accounts[2].pubkey, pending_action);
+    assert!(ix.accounts[0].is_signer && ix.accounts[0].is_writable);
+    let ix = instruction::execute_action(
+        &program_id,
+        &new_owner,
+        &pool_storage,
+        7,
+        &user_wallet,
+        vec![AccountMeta::new_readonly(spl_token::id(), false)],
+    );
+    assert_eq!(ix.accounts.len(), 5);
+    assert_eq!(ix.accounts[2].pubkey, pending_action);
+    assert_eq!(ix.accounts[4].pubkey, spl_token::id());
+}
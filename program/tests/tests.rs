@@ -3,11 +3,14 @@
 
 use arrayref::{array_ref, array_refs};
 use borsh::BorshDeserialize;
-use cwar_token_staking::{state::*, utils::update_rewards, *};
+use cwar_token_staking::{
+    state::*,
+    utils::{get_pending_action_address_and_bump_seed, update_rewards},
+    *,
+};
 use rand::Rng;
-use solana_program::{system_instruction, system_program};
+use solana_program::{instruction::Instruction, system_instruction};
 use solana_sdk::{
-    instruction::{AccountMeta, Instruction},
     pubkey::Pubkey,
     signature::{Keypair, Signer},
     transaction::Transaction,
@@ -19,6 +22,7 @@ use std::{
     cmp::{max, min},
     convert::TryInto,
     println,
+    rc::Rc,
     str::FromStr,
     thread::{self},
     time::{Duration, Instant},
@@ -26,7 +30,7 @@ use std::{
 pub const SPL_TOKEN_STATE_ACCOUNT_LEN: usize = 165;
 pub const SPL_TOKEN_STATE_MINT_LEN: usize = 82;
 
-use solana_client::rpc_client::RpcClient;
+use solana_client::{client_error::ClientError, rpc_client::RpcClient};
 use solana_validator::test_validator::*;
 
 pub const TO_RAW_TOKEN: u64 = 1000_000_000u64;
@@ -97,9 +101,34 @@ impl ManualRandomTestPool {
                 "iAV39sXyuCBYqmHRg7zEZ4Y1V6qKFwCm52fTAyuucsU",
             )
             .unwrap(),
+            total_staked: 0,
+            rewards_owed: 0,
+            pending_owner: Pubkey::default(),
+            pauser: Pubkey::default(),
+            paused_operations: 0,
+            timelock_delay: 0,
+            next_action_id: 0,
+            reward_streams: [RewardStream::default(); MAX_EXTRA_REWARD_STREAMS],
+            unbonding_duration: 0,
+            total_unbonding: 0,
+            lock_tiers: [LockTier::default(); MAX_LOCK_TIERS],
+            total_locked: 0,
+            total_locked_weight: 0,
+            penality_schedule: PenalitySchedule::default(),
+            penality_routing: PenalityRouting::default(),
+            min_stake: 0,
+            max_stake_per_user: 0,
+            max_total_staked: 0,
+            allowlist_root: [0u8; 32],
+            receipt_mint: Pubkey::default(),
+            reward_schedule: Pubkey::default(),
+            scheduled_rewards_left: 0,
+            reward_schedule_last_update_time: 0,
+            reward_schedule_end: 0,
+            reserved: [0u8; CWAR_POOL_RESERVED_BYTES],
         };
 
-        update_rewards(&mut pool_data, None, 0, current_time).unwrap();
+        update_rewards(&mut pool_data, None, None, current_time).unwrap();
 
         // fund the pool at t = 3
         pool_data.reward_rate = reward_rate;
@@ -124,6 +153,15 @@ impl ManualRandomTestPool {
                 rewards_per_token_accumulated_at_last_user_interaction: 0,
                 unstake_penality_duration_end: reward_duration + current_time,
                 last_staked_timestamp: 0,
+                reward_streams: [UserRewardStream::default(); MAX_EXTRA_REWARD_STREAMS],
+                unbonding_amount: 0,
+                next_unbonding_request_id: 0,
+                locked_amount: 0,
+                locked_weight: 0,
+                next_lock_position_id: 0,
+                allowlist_max_stake: 0,
+                receipt_debt: 0,
+                reserved: [0u8; USER_RESERVED_BYTES],
             })
         }
         Self {
@@ -139,8 +177,8 @@ impl ManualRandomTestPool {
     pub fn update_onchain_rewards(&mut self, user_index: usize, current_timestamp: u64) {
         update_rewards(
             &mut self.pool_data,
+            None,
             Some(&mut self.users_data[user_index]),
-            self.total_cwar_staked,
             current_timestamp,
         )
         .unwrap();
@@ -158,6 +196,7 @@ impl ManualRandomTestPool {
         self.users_data[user_index].last_staked_timestamp = current_timestamp;
         self.update_onchain_rewards(user_index, current_timestamp);
         self.total_cwar_staked += amount_to_stake;
+        self.pool_data.total_staked += amount_to_stake;
         self.users_data[user_index].user_cwar_staked_amount += amount_to_stake;
     }
 
@@ -174,6 +213,7 @@ impl ManualRandomTestPool {
     ) {
         self.update_onchain_rewards(user_index, current_timestamp);
         self.total_cwar_staked -= amount_to_unstake;
+        self.pool_data.total_staked -= amount_to_unstake;
         self.users_data[user_index].user_cwar_staked_amount -= amount_to_unstake;
     }
 
@@ -550,9 +590,33 @@ pub fn get_token_balance(rpc_client: &mut RpcClient, token: &Pubkey) -> u64 {
     return amount;
 }
 
+// signs with the payer and `signers` and returns the error of a failed transaction
+pub fn send_transaction(
+    rpc_client: &mut RpcClient,
+    payer: &Keypair,
+    instructions: &[Instruction],
+    signers: &[&Keypair],
+) -> Result<(), ClientError> {
+    let mut transaction = Transaction::new_with_payer(instructions, Some(&payer.pubkey()));
+    let mut all_signers = vec![payer];
+    all_signers.extend_from_slice(signers);
+    let recent_blockhash = rpc_client.get_latest_blockhash().unwrap();
+    transaction.sign(&all_signers, recent_blockhash);
+    rpc_client.send_and_confirm_transaction(&transaction)?;
+    Ok(())
+}
+
+pub fn account_exists(rpc_client: &mut RpcClient, account: &Pubkey) -> bool {
+    rpc_client
+        .get_account_with_commitment(account, rpc_client.commitment())
+        .unwrap()
+        .value
+        .is_some()
+}
+
 pub struct TestPool {
     pub program_id: Pubkey,
-    pub test_validator: TestValidator,
+    pub test_validator: Rc<TestValidator>,
     pub payer: Keypair,
     pub pool_info_account: Keypair,
     pub staking_vault_account: Keypair,
@@ -575,11 +639,26 @@ impl TestPool {
     pub fn new() -> Self {
         let program_id_keyp = Keypair::new();
         let (test_validator, payer) = program_test(program_id_keyp.pubkey());
+        Self::new_on_validator(program_id_keyp.pubkey(), Rc::new(test_validator), payer)
+    }
+
+    /// Another pool of the same program, on the validator of this one
+    pub fn new_pool_on_same_validator(&self) -> Self {
+        Self::new_on_validator(
+            self.program_id,
+            self.test_validator.clone(),
+            Keypair::from_bytes(&self.payer.to_bytes()).unwrap(),
+        )
+    }
+
+    fn new_on_validator(
+        program_id: Pubkey,
+        test_validator: Rc<TestValidator>,
+        payer: Keypair,
+    ) -> Self {
         let pool_info_account = Keypair::new();
-        let (pool_signer_address, bump_seed) = Pubkey::find_program_address(
-            &[&pool_info_account.pubkey().to_bytes()],
-            &program_id_keyp.pubkey(),
-        );
+        let (pool_signer_address, bump_seed) =
+            Pubkey::find_program_address(&[&pool_info_account.pubkey().to_bytes()], &program_id);
         let staking_mint_account = Keypair::new();
         let owner_wallet = Keypair::new();
         let authority_unstake_penality_deposit_ata_calculated =
@@ -589,7 +668,7 @@ impl TestPool {
             );
 
         Self {
-            program_id: program_id_keyp.pubkey(),
+            program_id,
             test_validator,
             payer,
             pool_info_account,
@@ -680,29 +759,20 @@ impl TestPool {
         .unwrap();
 
         //initialize pool
-        let mut data = vec![0u8];
-        data.extend(self.reward_duration.to_le_bytes());
-        data.extend(self.pool_nonce.to_le_bytes());
-        data.extend(self.unstake_penality_basis_points.to_le_bytes());
-        data.extend(self.locking_duration.to_le_bytes());
-
-        let accounts = vec![
-            AccountMeta::new(self.owner_account.pubkey(), true),
-            AccountMeta::new(self.pool_info_account.pubkey(), false),
-            AccountMeta::new_readonly(self.staking_mint_account.pubkey(), false),
-            AccountMeta::new(self.staking_vault_account.pubkey(), false),
-            AccountMeta::new_readonly(self.rewards_mint_account.pubkey(), false),
-            AccountMeta::new(self.rewards_vault_account.pubkey(), false),
-            AccountMeta::new_readonly(spl_token::id(), false),
-            AccountMeta::new(self.authority_unstake_penality_deposit_ata, false),
-        ];
-
         let mut transaction = Transaction::new_with_payer(
-            &[Instruction {
-                program_id: self.program_id,
-                accounts,
-                data,
-            }],
+            &[instruction::initialize_cwar_pool(
+                &self.program_id,
+                &self.owner_account.pubkey(),
+                &self.pool_info_account.pubkey(),
+                &self.staking_mint_account.pubkey(),
+                &self.staking_vault_account.pubkey(),
+                &self.rewards_mint_account.pubkey(),
+                &self.rewards_vault_account.pubkey(),
+                &self.authority_unstake_penality_deposit_ata,
+                self.reward_duration,
+                self.unstake_penality_basis_points,
+                self.locking_duration,
+            )],
             Some(&self.payer.pubkey()),
         );
         let recent_blockhash = rpc_client.get_latest_blockhash().unwrap();
@@ -742,25 +812,18 @@ impl TestPool {
         .unwrap();
 
         //fund pool with 86400 rewards token and 1 day (86400 seconds)
-        let mut data = vec![7u8];
-        data.extend((86400u64 * TO_RAW_TOKEN).to_le_bytes());
-        data.extend(86400u64.to_le_bytes());
-
-        let accounts = vec![
-            AccountMeta::new(self.owner_account.pubkey(), true),
-            AccountMeta::new(self.pool_info_account.pubkey(), false),
-            AccountMeta::new(self.staking_vault_account.pubkey(), false),
-            AccountMeta::new(self.rewards_vault_account.pubkey(), false),
-            AccountMeta::new(funder_reward_ata_calculated, false),
-            AccountMeta::new_readonly(spl_token::id(), false),
-        ];
-
         let mut transaction = Transaction::new_with_payer(
-            &[Instruction {
-                program_id: self.program_id,
-                accounts,
-                data,
-            }],
+            &[instruction::fund_pool(
+                &self.program_id,
+                &self.owner_account.pubkey(),
+                &self.pool_info_account.pubkey(),
+                &self.staking_vault_account.pubkey(),
+                &self.rewards_vault_account.pubkey(),
+                &funder_reward_ata_calculated,
+                false,
+                86400u64 * TO_RAW_TOKEN,
+                86400u64,
+            )],
             Some(&self.payer.pubkey()),
         );
         let recent_blockhash = rpc_client.get_latest_blockhash().unwrap();
@@ -772,9 +835,6 @@ impl TestPool {
     }
 
     pub fn create_user(&self, rpc_client: &mut RpcClient, user_wallet: &Keypair) {
-        let (user_storage_address, bump_seed) =
-            self.get_user_storage_address(&user_wallet.pubkey());
-
         let user_staking_ata = self.get_user_staking_ata(&user_wallet.pubkey());
 
         //create user rewards ata
@@ -803,23 +863,13 @@ impl TestPool {
             500u64 * TO_RAW_TOKEN,
         )
         .unwrap();
-        let mut data = vec![1u8];
-        data.push(bump_seed);
-
         airdrop_1_sol(rpc_client, &self.payer, &user_wallet.pubkey()).unwrap();
-        let accounts = vec![
-            AccountMeta::new(user_wallet.pubkey(), true),
-            AccountMeta::new(user_storage_address, false),
-            AccountMeta::new(self.pool_info_account.pubkey(), false),
-            AccountMeta::new_readonly(system_program::id(), false),
-        ];
-
         let mut transaction = Transaction::new_with_payer(
-            &[Instruction {
-                program_id: self.program_id,
-                accounts,
-                data,
-            }],
+            &[instruction::create_user(
+                &self.program_id,
+                &user_wallet.pubkey(),
+                &self.pool_info_account.pubkey(),
+            )],
             Some(&self.payer.pubkey()),
         );
         let recent_blockhash = rpc_client.get_latest_blockhash().unwrap();
@@ -888,28 +938,19 @@ impl TestPool {
         );
         let stake_after_millis = Duration::from_millis(stake_after_seconds * 1000);
         thread::sleep(stake_after_millis);
-        let user_storage_address = self.get_user_storage_address(&user_wallet.pubkey()).0;
-
         let user_staking_ata = self.get_user_staking_ata(&user_wallet.pubkey());
 
-        let mut data = vec![2u8];
-        data.extend(amount_to_stake.to_le_bytes());
-
-        let accounts = vec![
-            AccountMeta::new(user_wallet.pubkey(), true),
-            AccountMeta::new(user_storage_address, false),
-            AccountMeta::new(self.pool_info_account.pubkey(), false),
-            AccountMeta::new(self.staking_vault_account.pubkey(), false),
-            AccountMeta::new(user_staking_ata, false),
-            AccountMeta::new_readonly(spl_token::id(), false),
-        ];
-
         let mut transaction = Transaction::new_with_payer(
-            &[Instruction {
-                program_id: self.program_id,
-                accounts,
-                data,
-            }],
+            &[instruction::stake_cwar(
+                &self.program_id,
+                &user_wallet.pubkey(),
+                &self.pool_info_account.pubkey(),
+                &self.staking_vault_account.pubkey(),
+                &user_staking_ata,
+                false,
+                None,
+                amount_to_stake,
+            )],
             Some(&self.payer.pubkey()),
         );
         let recent_blockhash = rpc_client.get_latest_blockhash().unwrap();
@@ -934,30 +975,21 @@ impl TestPool {
         );
         let unstake_after_millis = Duration::from_millis(unstake_after_seconds * 1000);
         thread::sleep(unstake_after_millis);
-        let user_storage_address = self.get_user_storage_address(&user_wallet.pubkey()).0;
-
-        let user_staking_ata = self.get_user_staking_ata(&user_wallet.pubkey());
-
-        let mut data = vec![3u8];
-        data.extend(amount_to_unstake.to_le_bytes());
-
-        let accounts = vec![
-            AccountMeta::new(user_wallet.pubkey(), true),
-            AccountMeta::new(user_storage_address, false),
-            AccountMeta::new(self.pool_info_account.pubkey(), false),
-            AccountMeta::new(self.staking_vault_account.pubkey(), false),
-            AccountMeta::new(user_staking_ata, false),
-            AccountMeta::new_readonly(self.pool_signer_addr, false),
-            AccountMeta::new_readonly(spl_token::id(), false),
-            AccountMeta::new(self.authority_unstake_penality_deposit_ata, false),
-        ];
 
         let mut transaction = Transaction::new_with_payer(
-            &[Instruction {
-                program_id: self.program_id,
-                accounts,
-                data,
-            }],
+            &[instruction::unstake_cwar(
+                &self.program_id,
+                &user_wallet.pubkey(),
+                &self.pool_info_account.pubkey(),
+                &self.staking_vault_account.pubkey(),
+                &self.staking_mint_account.pubkey(),
+                &self.authority_unstake_penality_deposit_ata,
+                &user_wallet.pubkey(),
+                false,
+                &[],
+                None,
+                amount_to_unstake,
+            )],
             Some(&self.payer.pubkey()),
         );
         let recent_blockhash = rpc_client.get_latest_blockhash().unwrap();
@@ -1026,29 +1058,18 @@ impl TestPool {
     }
 
     pub fn claim_rewards(&self, rpc_client: &mut RpcClient, user_wallet: &Keypair) {
-        let user_storage_address = self.get_user_storage_address(&user_wallet.pubkey()).0;
-
-        let user_rewards_ata = self.get_user_rewards_ata(&user_wallet.pubkey());
-
-        let data = vec![4u8];
-
-        let accounts = vec![
-            AccountMeta::new(user_wallet.pubkey(), true),
-            AccountMeta::new(user_storage_address, false),
-            AccountMeta::new(self.pool_info_account.pubkey(), false),
-            AccountMeta::new(self.staking_vault_account.pubkey(), false),
-            AccountMeta::new(self.rewards_vault_account.pubkey(), false),
-            AccountMeta::new(user_rewards_ata, false),
-            AccountMeta::new_readonly(self.pool_signer_addr, false),
-            AccountMeta::new_readonly(spl_token::id(), false),
-        ];
-
         let mut transaction = Transaction::new_with_payer(
-            &[Instruction {
-                program_id: self.program_id,
-                accounts,
-                data,
-            }],
+            &[instruction::claim_rewards(
+                &self.program_id,
+                &user_wallet.pubkey(),
+                &self.pool_info_account.pubkey(),
+                &self.staking_vault_account.pubkey(),
+                &self.rewards_vault_account.pubkey(),
+                &self.rewards_mint_account.pubkey(),
+                &user_wallet.pubkey(),
+                false,
+                &[],
+            )],
             Some(&self.payer.pubkey()),
         );
         let recent_blockhash = rpc_client.get_latest_blockhash().unwrap();
@@ -1058,6 +1079,60 @@ impl TestPool {
             .map_err(|err| format!("error: send transaction: {}", err))
             .unwrap();
     }
+
+    pub fn get_claim_rewards_instruction(
+        &self,
+        user_wallet: &Pubkey,
+        recipient_wallet: &Pubkey,
+    ) -> Instruction {
+        instruction::claim_rewards(
+            &self.program_id,
+            user_wallet,
+            &self.pool_info_account.pubkey(),
+            &self.staking_vault_account.pubkey(),
+            &self.rewards_vault_account.pubkey(),
+            &self.rewards_mint_account.pubkey(),
+            recipient_wallet,
+            false,
+            &[],
+        )
+    }
+
+    /// The pool's entry of a `ClaimRewardsMulti` instruction
+    pub fn get_claim_accounts(&self) -> (Pubkey, Pubkey, Pubkey, Pubkey, bool) {
+        (
+            self.pool_info_account.pubkey(),
+            self.staking_vault_account.pubkey(),
+            self.rewards_vault_account.pubkey(),
+            self.rewards_mint_account.pubkey(),
+            false,
+        )
+    }
+
+    pub fn exit(
+        &self,
+        rpc_client: &mut RpcClient,
+        user_wallet: &Keypair,
+    ) -> Result<(), ClientError> {
+        send_transaction(
+            rpc_client,
+            &self.payer,
+            &[instruction::exit(
+                &self.program_id,
+                &user_wallet.pubkey(),
+                &self.pool_info_account.pubkey(),
+                &self.staking_vault_account.pubkey(),
+                &self.staking_mint_account.pubkey(),
+                &self.rewards_vault_account.pubkey(),
+                &self.rewards_mint_account.pubkey(),
+                &self.authority_unstake_penality_deposit_ata,
+                false,
+                None,
+                &[],
+            )],
+            &[user_wallet],
+        )
+    }
 }
 
 //#[test]
@@ -1178,3 +1253,671 @@ fn test_claim_reward() {
         pool.get_user_rewards_token_balanace_ui(&mut rpc_client, &pool.user_wallet2.pubkey())
     );
 }
+
+#[test]
+#[cfg_attr(not(feature = "test-bpf"), ignore)]
+fn test_exit() {
+    let pool = TestPool::new();
+    let mut rpc_client = pool.test_validator.get_rpc_client();
+    pool.init_pool(&mut rpc_client);
+    pool.fund_pool(&mut rpc_client);
+    pool.create_user(&mut rpc_client, &pool.user_wallet1);
+    pool.stake_cwar(
+        &mut rpc_client,
+        &pool.user_wallet1,
+        100u64 * TO_RAW_TOKEN,
+        0u64,
+    )
+    .unwrap();
+    thread::sleep(Duration::from_millis(3 * 1000));
+
+    let user_wallet = pool.user_wallet1.pubkey();
+    let (user_storage, _) = pool.get_user_storage_address(&user_wallet);
+    let user_storage_lamports = rpc_client.get_balance(&user_storage).unwrap();
+    let user_wallet_lamports = rpc_client.get_balance(&user_wallet).unwrap();
+    pool.exit(&mut rpc_client, &pool.user_wallet1).unwrap();
+
+    // the user account only closes once its stake and pending rewards are both zero
+    assert!(!account_exists(&mut rpc_client, &user_storage));
+    assert_eq!(
+        rpc_client.get_balance(&user_wallet).unwrap(),
+        user_wallet_lamports + user_storage_lamports
+    );
+    assert_eq!(
+        pool.get_user_staking_token_balanace(&mut rpc_client, &user_wallet),
+        500u64 * TO_RAW_TOKEN
+    );
+    assert!(pool.get_user_rewards_token_balanace(&mut rpc_client, &user_wallet) > 0);
+    let pool_data = pool.get_pool_data(&mut rpc_client);
+    assert_eq!(pool_data.total_staked, 0);
+    assert_eq!(pool_data.user_stake_count, 0);
+}
+
+#[test]
+#[cfg_attr(not(feature = "test-bpf"), ignore)]
+fn test_exit_during_locking_period() {
+    let mut pool = TestPool::new();
+    pool.locking_duration = 3600u64;
+    let mut rpc_client = pool.test_validator.get_rpc_client();
+    pool.init_pool(&mut rpc_client);
+    pool.fund_pool(&mut rpc_client);
+    pool.create_user(&mut rpc_client, &pool.user_wallet1);
+    pool.stake_cwar(
+        &mut rpc_client,
+        &pool.user_wallet1,
+        100u64 * TO_RAW_TOKEN,
+        0u64,
+    )
+    .unwrap();
+
+    let user_wallet = pool.user_wallet1.pubkey();
+    assert!(pool.exit(&mut rpc_client, &pool.user_wallet1).is_err());
+
+    // nothing of the exit sticks
+    let user_data = pool.get_user_data(&mut rpc_client, &user_wallet);
+    assert_eq!(user_data.user_cwar_staked_amount, 100u64 * TO_RAW_TOKEN);
+    assert_eq!(
+        pool.get_user_staking_token_balanace(&mut rpc_client, &user_wallet),
+        400u64 * TO_RAW_TOKEN
+    );
+    assert_eq!(
+        pool.get_user_rewards_token_balanace(&mut rpc_client, &user_wallet),
+        0
+    );
+    assert_eq!(pool.get_pool_data(&mut rpc_client).user_stake_count, 1);
+}
+
+// funded pools on one validator, `user_wallet1` of the first pool staking 100 tokens in each
+fn new_pools_with_staker(count: usize) -> Vec<TestPool> {
+    let mut pools = vec![TestPool::new()];
+    for _ in 1..count {
+        pools.push(pools[0].new_pool_on_same_validator());
+    }
+    let mut rpc_client = pools[0].test_validator.get_rpc_client();
+    for pool in &pools {
+        pool.init_pool(&mut rpc_client);
+        pool.fund_pool(&mut rpc_client);
+        pool.create_user(&mut rpc_client, &pools[0].user_wallet1);
+        pool.stake_cwar(
+            &mut rpc_client,
+            &pools[0].user_wallet1,
+            100u64 * TO_RAW_TOKEN,
+            0u64,
+        )
+        .unwrap();
+    }
+    pools
+}
+
+#[test]
+#[cfg_attr(not(feature = "test-bpf"), ignore)]
+fn test_claim_rewards_multi() {
+    let pools = new_pools_with_staker(3);
+    let mut rpc_client = pools[0].test_validator.get_rpc_client();
+    let user_wallet = &pools[0].user_wallet1;
+    thread::sleep(Duration::from_millis(3 * 1000));
+
+    send_transaction(
+        &mut rpc_client,
+        &pools[0].payer,
+        &[instruction::claim_rewards_multi(
+            &pools[0].program_id,
+            &user_wallet.pubkey(),
+            &[pools[0].get_claim_accounts(), pools[2].get_claim_accounts()],
+        )],
+        &[user_wallet],
+    )
+    .unwrap();
+
+    for pool in [&pools[0], &pools[2]] {
+        assert!(pool.get_user_rewards_token_balanace(&mut rpc_client, &user_wallet.pubkey()) > 0);
+        assert_eq!(
+            pool.get_user_data(&mut rpc_client, &user_wallet.pubkey())
+                .rewards_amount_pending,
+            0
+        );
+    }
+    // the pool left out of the list keeps its rewards pending
+    assert_eq!(
+        pools[1].get_user_rewards_token_balanace(&mut rpc_client, &user_wallet.pubkey()),
+        0
+    );
+}
+
+#[test]
+#[cfg_attr(not(feature = "test-bpf"), ignore)]
+fn test_claim_rewards_multi_rejects_mismatched_pair() {
+    let pools = new_pools_with_staker(3);
+    let mut rpc_client = pools[0].test_validator.get_rpc_client();
+    let user_wallet = &pools[0].user_wallet1;
+    thread::sleep(Duration::from_millis(3 * 1000));
+
+    let mut claim_instruction = instruction::claim_rewards_multi(
+        &pools[0].program_id,
+        &user_wallet.pubkey(),
+        &[
+            pools[0].get_claim_accounts(),
+            pools[1].get_claim_accounts(),
+            pools[2].get_claim_accounts(),
+        ],
+    );
+    // the second group, after the 4 fixed accounts and the 7 of the first group, gets the user
+    // account of the first pool
+    claim_instruction.accounts[4 + 7].pubkey =
+        pools[0].get_user_storage_address(&user_wallet.pubkey()).0;
+    assert!(send_transaction(
+        &mut rpc_client,
+        &pools[0].payer,
+        &[claim_instruction],
+        &[user_wallet],
+    )
+    .is_err());
+
+    // the groups around it aren't paid either
+    for pool in &pools {
+        assert_eq!(
+            pool.get_user_rewards_token_balanace(&mut rpc_client, &user_wallet.pubkey()),
+            0
+        );
+    }
+}
+
+#[test]
+#[cfg_attr(not(feature = "test-bpf"), ignore)]
+fn test_claim_rewards_to_recipient() {
+    let pool = TestPool::new();
+    let mut rpc_client = pool.test_validator.get_rpc_client();
+    pool.init_pool(&mut rpc_client);
+    pool.fund_pool(&mut rpc_client);
+    pool.create_user(&mut rpc_client, &pool.user_wallet1);
+    pool.stake_cwar(
+        &mut rpc_client,
+        &pool.user_wallet1,
+        100u64 * TO_RAW_TOKEN,
+        0u64,
+    )
+    .unwrap();
+    thread::sleep(Duration::from_millis(3 * 1000));
+
+    // a third party wallet without a rewards ATA, the user pays for it
+    let user_wallet = pool.user_wallet1.pubkey();
+    let recipient_wallet = Keypair::new().pubkey();
+    let recipient_rewards_ata = pool.get_user_rewards_ata(&recipient_wallet);
+    assert!(!account_exists(&mut rpc_client, &recipient_rewards_ata));
+    send_transaction(
+        &mut rpc_client,
+        &pool.payer,
+        &[pool.get_claim_rewards_instruction(&user_wallet, &recipient_wallet)],
+        &[&pool.user_wallet1],
+    )
+    .unwrap();
+    let first_claim = get_token_balance(&mut rpc_client, &recipient_rewards_ata);
+    assert!(first_claim > 0);
+    assert_eq!(
+        pool.get_user_rewards_token_balanace(&mut rpc_client, &user_wallet),
+        0
+    );
+
+    // the ATA exists now and is reused
+    thread::sleep(Duration::from_millis(2 * 1000));
+    send_transaction(
+        &mut rpc_client,
+        &pool.payer,
+        &[pool.get_claim_rewards_instruction(&user_wallet, &recipient_wallet)],
+        &[&pool.user_wallet1],
+    )
+    .unwrap();
+    assert!(get_token_balance(&mut rpc_client, &recipient_rewards_ata) > first_claim);
+    assert_eq!(
+        pool.get_user_data(&mut rpc_client, &user_wallet)
+            .rewards_amount_pending,
+        0
+    );
+}
+
+#[test]
+#[cfg_attr(not(feature = "test-bpf"), ignore)]
+fn test_claim_and_unstake_without_recipient_accounts() {
+    let pool = TestPool::new();
+    let mut rpc_client = pool.test_validator.get_rpc_client();
+    pool.init_pool(&mut rpc_client);
+    pool.fund_pool(&mut rpc_client);
+    pool.create_user(&mut rpc_client, &pool.user_wallet1);
+    pool.stake_cwar(
+        &mut rpc_client,
+        &pool.user_wallet1,
+        100u64 * TO_RAW_TOKEN,
+        0u64,
+    )
+    .unwrap();
+    thread::sleep(Duration::from_millis(3 * 1000));
+    let user_wallet = pool.user_wallet1.pubkey();
+
+    // the layout from before recipients, paying the user's existing ATAs
+    let mut claim_instruction = pool.get_claim_rewards_instruction(&user_wallet, &user_wallet);
+    claim_instruction.accounts.truncate(8);
+    claim_instruction.accounts[0].is_writable = false;
+    send_transaction(
+        &mut rpc_client,
+        &pool.payer,
+        &[claim_instruction],
+        &[&pool.user_wallet1],
+    )
+    .unwrap();
+    assert!(pool.get_user_rewards_token_balanace(&mut rpc_client, &user_wallet) > 0);
+
+    let mut unstake_instruction = instruction::unstake_cwar(
+        &pool.program_id,
+        &user_wallet,
+        &pool.pool_info_account.pubkey(),
+        &pool.staking_vault_account.pubkey(),
+        &pool.staking_mint_account.pubkey(),
+        &pool.authority_unstake_penality_deposit_ata,
+        &user_wallet,
+        false,
+        &[],
+        None,
+        100u64 * TO_RAW_TOKEN,
+    );
+    unstake_instruction.accounts.truncate(8);
+    unstake_instruction.accounts[0].is_writable = false;
+    send_transaction(
+        &mut rpc_client,
+        &pool.payer,
+        &[unstake_instruction],
+        &[&pool.user_wallet1],
+    )
+    .unwrap();
+    assert_eq!(
+        pool.get_user_staking_token_balanace(&mut rpc_client, &user_wallet),
+        500u64 * TO_RAW_TOKEN
+    );
+}
+
+#[test]
+#[cfg_attr(not(feature = "test-bpf"), ignore)]
+fn test_claim_rewards_rejects_wrong_recipient_ata() {
+    let pool = TestPool::new();
+    let mut rpc_client = pool.test_validator.get_rpc_client();
+    pool.init_pool(&mut rpc_client);
+    pool.fund_pool(&mut rpc_client);
+    pool.create_user(&mut rpc_client, &pool.user_wallet1);
+    pool.stake_cwar(
+        &mut rpc_client,
+        &pool.user_wallet1,
+        100u64 * TO_RAW_TOKEN,
+        0u64,
+    )
+    .unwrap();
+    thread::sleep(Duration::from_millis(3 * 1000));
+    let user_wallet = pool.user_wallet1.pubkey();
+    let recipient_wallet = Keypair::new().pubkey();
+
+    // an ATA of the recipient, but of the staking mint
+    let mut wrong_mint = pool.get_claim_rewards_instruction(&user_wallet, &user_wallet);
+    wrong_mint.accounts[5].pubkey = pool.get_user_staking_ata(&user_wallet);
+    assert!(send_transaction(
+        &mut rpc_client,
+        &pool.payer,
+        &[wrong_mint],
+        &[&pool.user_wallet1],
+    )
+    .is_err());
+
+    // a rewards ATA, but of the user instead of the recipient
+    let mut wrong_owner = pool.get_claim_rewards_instruction(&user_wallet, &recipient_wallet);
+    wrong_owner.accounts[5].pubkey = pool.get_user_rewards_ata(&user_wallet);
+    assert!(send_transaction(
+        &mut rpc_client,
+        &pool.payer,
+        &[wrong_owner],
+        &[&pool.user_wallet1],
+    )
+    .is_err());
+
+    assert_eq!(
+        pool.get_user_rewards_token_balanace(&mut rpc_client, &user_wallet),
+        0
+    );
+    assert_eq!(
+        pool.get_user_staking_token_balanace(&mut rpc_client, &user_wallet),
+        400u64 * TO_RAW_TOKEN
+    );
+    assert!(!account_exists(
+        &mut rpc_client,
+        &pool.get_user_rewards_ata(&recipient_wallet)
+    ));
+}
+
+#[test]
+#[cfg_attr(not(feature = "test-bpf"), ignore)]
+fn test_timelock() {
+    let pool = TestPool::new();
+    let mut rpc_client = pool.test_validator.get_rpc_client();
+    pool.init_pool(&mut rpc_client);
+    let owner_wallet = pool.owner_account.pubkey();
+    let pool_storage = pool.pool_info_account.pubkey();
+    // the owner pays for the pending action accounts
+    airdrop_1_sol(&mut rpc_client, &pool.payer, &owner_wallet).unwrap();
+    send_transaction(
+        &mut rpc_client,
+        &pool.payer,
+        &[instruction::set_timelock_delay(
+            &pool.program_id,
+            &owner_wallet,
+            &pool_storage,
+            5u64,
+        )],
+        &[&pool.owner_account],
+    )
+    .unwrap();
+
+    // the direct setters have to go through the queue now
+    assert!(send_transaction(
+        &mut rpc_client,
+        &pool.payer,
+        &[instruction::set_stake_limits(
+            &pool.program_id,
+            &owner_wallet,
+            &pool_storage,
+            TO_RAW_TOKEN,
+            200u64 * TO_RAW_TOKEN,
+            0u64,
+        )],
+        &[&pool.owner_account],
+    )
+    .is_err());
+    assert!(send_transaction(
+        &mut rpc_client,
+        &pool.payer,
+        &[instruction::update_pool_config(
+            &pool.program_id,
+            &owner_wallet,
+            &pool_storage,
+            None,
+            Some(3600u64),
+            None,
+            None,
+        )],
+        &[&pool.owner_account],
+    )
+    .is_err());
+    let pool_data = pool.get_pool_data(&mut rpc_client);
+    assert_eq!(pool_data.min_stake, 0);
+    assert_eq!(pool_data.locking_duration, 0);
+
+    let action_id = pool_data.next_action_id;
+    let (pending_action, _) =
+        get_pending_action_address_and_bump_seed(&pool_storage, action_id, &pool.program_id);
+    assert_eq!(
+        pending_action,
+        Pubkey::find_program_address(
+            &[
+                b"pending_action",
+                &pool_storage.to_bytes(),
+                &action_id.to_le_bytes()
+            ],
+            &pool.program_id
+        )
+        .0
+    );
+    send_transaction(
+        &mut rpc_client,
+        &pool.payer,
+        &[instruction::queue_action(
+            &pool.program_id,
+            &owner_wallet,
+            &pool_storage,
+            action_id,
+            PoolAction::SetStakeLimits {
+                min_stake: TO_RAW_TOKEN,
+                max_stake_per_user: 200u64 * TO_RAW_TOKEN,
+                max_total_staked: 0u64,
+            },
+        )],
+        &[&pool.owner_account],
+    )
+    .unwrap();
+    let execute_action = instruction::execute_action(
+        &pool.program_id,
+        &pool.payer.pubkey(),
+        &pool_storage,
+        action_id,
+        &owner_wallet,
+        vec![],
+    );
+
+    // before the eta
+    assert!(
+        send_transaction(&mut rpc_client, &pool.payer, &[execute_action.clone()], &[],).is_err()
+    );
+    assert_eq!(pool.get_pool_data(&mut rpc_client).min_stake, 0);
+
+    // anyone can execute it after the eta
+    thread::sleep(Duration::from_millis(7 * 1000));
+    send_transaction(&mut rpc_client, &pool.payer, &[execute_action], &[]).unwrap();
+    let pool_data = pool.get_pool_data(&mut rpc_client);
+    assert_eq!(pool_data.min_stake, TO_RAW_TOKEN);
+    assert_eq!(pool_data.max_stake_per_user, 200u64 * TO_RAW_TOKEN);
+    assert!(!account_exists(&mut rpc_client, &pending_action));
+}
+
+#[test]
+#[cfg_attr(not(feature = "test-bpf"), ignore)]
+fn test_timelock_cancel_action() {
+    let pool = TestPool::new();
+    let mut rpc_client = pool.test_validator.get_rpc_client();
+    pool.init_pool(&mut rpc_client);
+    let owner_wallet = pool.owner_account.pubkey();
+    let pool_storage = pool.pool_info_account.pubkey();
+    airdrop_1_sol(&mut rpc_client, &pool.payer, &owner_wallet).unwrap();
+    send_transaction(
+        &mut rpc_client,
+        &pool.payer,
+        &[instruction::set_timelock_delay(
+            &pool.program_id,
+            &owner_wallet,
+            &pool_storage,
+            5u64,
+        )],
+        &[&pool.owner_account],
+    )
+    .unwrap();
+
+    let owner_lamports = rpc_client.get_balance(&owner_wallet).unwrap();
+    let action_id = pool.get_pool_data(&mut rpc_client).next_action_id;
+    let (pending_action, _) =
+        get_pending_action_address_and_bump_seed(&pool_storage, action_id, &pool.program_id);
+    send_transaction(
+        &mut rpc_client,
+        &pool.payer,
+        &[instruction::queue_action(
+            &pool.program_id,
+            &owner_wallet,
+            &pool_storage,
+            action_id,
+            PoolAction::SetStakeLimits {
+                min_stake: TO_RAW_TOKEN,
+                max_stake_per_user: 200u64 * TO_RAW_TOKEN,
+                max_total_staked: 0u64,
+            },
+        )],
+        &[&pool.owner_account],
+    )
+    .unwrap();
+    assert!(account_exists(&mut rpc_client, &pending_action));
+
+    send_transaction(
+        &mut rpc_client,
+        &pool.payer,
+        &[instruction::cancel_action(
+            &pool.program_id,
+            &owner_wallet,
+            &pool_storage,
+            action_id,
+            &owner_wallet,
+        )],
+        &[&pool.owner_account],
+    )
+    .unwrap();
+    // the rent goes back to the owner
+    assert!(!account_exists(&mut rpc_client, &pending_action));
+    assert_eq!(
+        rpc_client.get_balance(&owner_wallet).unwrap(),
+        owner_lamports
+    );
+
+    // and the action can't be executed anymore once its eta has passed
+    thread::sleep(Duration::from_millis(7 * 1000));
+    assert!(send_transaction(
+        &mut rpc_client,
+        &pool.payer,
+        &[instruction::execute_action(
+            &pool.program_id,
+            &pool.payer.pubkey(),
+            &pool_storage,
+            action_id,
+            &owner_wallet,
+            vec![],
+        )],
+        &[],
+    )
+    .is_err());
+    assert_eq!(pool.get_pool_data(&mut rpc_client).min_stake, 0);
+}
+
+#[test]
+#[cfg_attr(not(feature = "test-bpf"), ignore)]
+fn test_ownership_transfer() {
+    let pool = TestPool::new();
+    let mut rpc_client = pool.test_validator.get_rpc_client();
+    pool.init_pool(&mut rpc_client);
+    let owner_wallet = pool.owner_account.pubkey();
+    let pool_storage = pool.pool_info_account.pubkey();
+    let new_owner = Keypair::new();
+    send_transaction(
+        &mut rpc_client,
+        &pool.payer,
+        &[
+            instruction::add_funder(
+                &pool.program_id,
+                &owner_wallet,
+                &pool_storage,
+                &new_owner.pubkey(),
+            ),
+            instruction::propose_new_owner(
+                &pool.program_id,
+                &owner_wallet,
+                &pool_storage,
+                &new_owner.pubkey(),
+            ),
+        ],
+        &[&pool.owner_account],
+    )
+    .unwrap();
+    assert_eq!(
+        pool.get_pool_data(&mut rpc_client).pending_owner,
+        new_owner.pubkey()
+    );
+
+    // a cancelled proposal can't be accepted
+    send_transaction(
+        &mut rpc_client,
+        &pool.payer,
+        &[instruction::cancel_ownership_transfer(
+            &pool.program_id,
+            &owner_wallet,
+            &pool_storage,
+        )],
+        &[&pool.owner_account],
+    )
+    .unwrap();
+    assert_eq!(
+        pool.get_pool_data(&mut rpc_client).pending_owner,
+        Pubkey::default()
+    );
+    let staking_mint = pool.staking_mint_account.pubkey();
+    create_ata(
+        &mut rpc_client,
+        &pool.payer,
+        &new_owner.pubkey(),
+        &staking_mint,
+    )
+    .unwrap();
+    let new_owner_penality_deposit_ata = spl_associated_token_account::get_associated_token_address(
+        &new_owner.pubkey(),
+        &staking_mint,
+    );
+    let accept_ownership = instruction::accept_ownership(
+        &pool.program_id,
+        &new_owner.pubkey(),
+        &pool_storage,
+        &new_owner_penality_deposit_ata,
+    );
+    assert!(send_transaction(
+        &mut rpc_client,
+        &pool.payer,
+        &[accept_ownership.clone()],
+        &[&new_owner],
+    )
+    .is_err());
+
+    send_transaction(
+        &mut rpc_client,
+        &pool.payer,
+        &[instruction::propose_new_owner(
+            &pool.program_id,
+            &owner_wallet,
+            &pool_storage,
+            &new_owner.pubkey(),
+        )],
+        &[&pool.owner_account],
+    )
+    .unwrap();
+    // the penalty deposit account has to belong to the new owner
+    let owner_penality_deposit_ata = pool
+        .get_pool_data(&mut rpc_client)
+        .authority_penality_deposit_ata;
+    assert!(send_transaction(
+        &mut rpc_client,
+        &pool.payer,
+        &[instruction::accept_ownership(
+            &pool.program_id,
+            &new_owner.pubkey(),
+            &pool_storage,
+            &owner_penality_deposit_ata,
+        )],
+        &[&new_owner],
+    )
+    .is_err());
+    send_transaction(
+        &mut rpc_client,
+        &pool.payer,
+        &[accept_ownership],
+        &[&new_owner],
+    )
+    .unwrap();
+    let pool_data = pool.get_pool_data(&mut rpc_client);
+    assert_eq!(pool_data.owner_wallet, new_owner.pubkey());
+    assert_eq!(pool_data.pending_owner, Pubkey::default());
+    assert_eq!(
+        pool_data.authority_penality_deposit_ata,
+        new_owner_penality_deposit_ata
+    );
+    // accepting leaves the funders as they were
+    assert!(pool_data.funders.contains(&new_owner.pubkey()));
+
+    // the previous owner is out
+    assert!(send_transaction(
+        &mut rpc_client,
+        &pool.payer,
+        &[instruction::propose_new_owner(
+            &pool.program_id,
+            &owner_wallet,
+            &pool_storage,
+            &owner_wallet,
+        )],
+        &[&pool.owner_account],
+    )
+    .is_err());
+}
@@ -0,0 +1,1892 @@
+use borsh::{BorshDeserialize, BorshSerialize};
+use cwar_token_staking::{
+    allowlist::{
+        get_allowlist_leaf, verify_allowlist_proof, AllowlistTree, MAX_ALLOWLIST_PROOF_LENGTH,
+    },
+    error::CryowarError,
+    processor::{
+        add_reward_phase::add_reward_phase,
+        cancel_rewards::cancel_rewards,
+        close_pool::check_pool_closable,
+        compound_rewards::compound_rewards,
+        emergency_unstake::emergency_unstake,
+        expire_lock_position::expire_lock_position,
+        fund_reward_stream::fund_reward_stream,
+        migrate_pool::migrate_pool_totals,
+        reduce_rewards::reduce_rewards,
+        request_unstake::{request_unstake, settle_unbonding_request},
+        set_receipt_mint::set_receipt_mint,
+        settle_receipt::settle_receipt,
+        stake_locked::add_lock_position,
+        transfer_position::transfer_position,
+        unstake_cwar::route_penality,
+        unstake_locked::remove_lock_position,
+    },
+    state::{
+        AccTypesWithVersion, CwarPool, LockPosition, LockTier, PenalityRouting, PenalitySchedule,
+        PenalityStep, PendingAction, PoolAction, RewardPhase, RewardSchedule, RewardStream,
+        UnbondingRequest, User, UserRewardStream, CWAR_POOL_RESERVED_BYTES,
+        CWAR_POOL_STORAGE_TOTAL_BYTES, LOCK_POSITION_STORAGE_TOTAL_BYTES, MAX_EXTRA_REWARD_STREAMS,
+        MAX_LOCK_TIERS, MAX_PENALITY_STEPS, MAX_REWARD_PHASES, PAUSE_CLAIM, PAUSE_CREATE_USER,
+        PAUSE_FUND, PAUSE_STAKE, PAUSE_UNSTAKE, PENALITY_SCHEDULE_LINEAR,
+        PENALITY_SCHEDULE_STEPPED, PENDING_ACTION_STORAGE_TOTAL_BYTES,
+        UNBONDING_REQUEST_STORAGE_TOTAL_BYTES, USER_RESERVED_BYTES, USER_STORAGE_TOTAL_BYTES,
+        USER_V1_STORAGE_TOTAL_BYTES,
+    },
+    utils::{
+        add_receipt_debt, assert_action_executable, assert_allowlisted, assert_lock_tiers,
+        assert_no_allowlist, assert_not_paused, assert_penality_routing, assert_penality_schedule,
+        assert_pool_config, assert_receipt_mint_data, assert_stake_limits, check_stake_limits,
+        constants::{
+            ACTION_EXECUTION_GRACE_PERIOD, MAX_LOCK_DURATION, MAX_LOCK_MULTIPLIER_BASIS_POINTS,
+            MAX_PENALITY_DECAY_DURATION, MAX_UNBONDING_DURATION,
+        },
+        get_reward_liabilities, get_staking_vault_surplus, get_unstake_penality,
+        get_unstake_penality_basis_points, repay_receipt_debt, save_user_storage_account,
+        split_penality, unpack_user_storage_account, update_rewards,
+        MAX_UNSTAKE_PENALITY_BASIS_POINTS,
+    },
+};
+use rand::Rng;
+use solana_program::{program_option::COption, pubkey::Pubkey};
+use spl_token::state::Mint;
+
+pub const TO_RAW_TOKEN: u64 = 1_000_000_000u64;
+
+pub fn new_funded_pool(reward_rate: u64, now: u64, reward_duration: u64) -> CwarPool {
+    CwarPool {
+        acc_type: AccTypesWithVersion::CwarPoolDataV2 as u8,
+        owner_wallet: Pubkey::new_unique(),
+        staking_vault: Pubkey::new_unique(),
+        staking_mint: Pubkey::new_unique(),
+        reward_vault: Pubkey::new_unique(),
+        reward_mint: Pubkey::new_unique(),
+        reward_rate,
+        reward_duration,
+        total_stake_last_update_time: now,
+        rewards_per_token_accumulated_till_now: 0,
+        user_stake_count: 0,
+        pda_nonce: 255,
+        funders: [Pubkey::default(); 5],
+        reward_duration_end: now + reward_duration,
+        unstake_penality_basis_points: 0,
+        locking_duration: 0,
+        authority_penality_deposit_ata: Pubkey::new_unique(),
+        total_staked: 0,
+        rewards_owed: 0,
+        pending_owner: Pubkey::default(),
+        pauser: Pubkey::default(),
+        paused_operations: 0,
+        timelock_delay: 0,
+        next_action_id: 0,
+        reward_streams: [RewardStream::default(); MAX_EXTRA_REWARD_STREAMS],
+        unbonding_duration: 0,
+        total_unbonding: 0,
+        lock_tiers: [LockTier::default(); MAX_LOCK_TIERS],
+        total_locked: 0,
+        total_locked_weight: 0,
+        penality_schedule: PenalitySchedule::default(),
+        penality_routing: PenalityRouting::default(),
+        min_stake: 0,
+        max_stake_per_user: 0,
+        max_total_staked: 0,
+        allowlist_root: [0u8; 32],
+        receipt_mint: Pubkey::default(),
+        reward_schedule: Pubkey::default(),
+        scheduled_rewards_left: 0,
+        reward_schedule_last_update_time: 0,
+        reward_schedule_end: 0,
+        reserved: [0u8; CWAR_POOL_RESERVED_BYTES],
+    }
+}
+
+pub fn new_user(pool: &CwarPool) -> User {
+    User {
+        acc_type: AccTypesWithVersion::UserDataV2 as u8,
+        user_wallet: Pubkey::new_unique(),
+        cwar_pool: Pubkey::new_unique(),
+        user_cwar_staked_amount: 0,
+        nonce: 255,
+        rewards_amount_pending: 0,
+        rewards_per_token_accumulated_at_last_user_interaction: 0,
+        unstake_penality_duration_end: pool.reward_duration_end,
+        last_staked_timestamp: 0,
+        reward_streams: [UserRewardStream::default(); MAX_EXTRA_REWARD_STREAMS],
+        unbonding_amount: 0,
+        next_unbonding_request_id: 0,
+        locked_amount: 0,
+        locked_weight: 0,
+        next_lock_position_id: 0,
+        allowlist_max_stake: 0,
+        receipt_debt: 0,
+        reserved: [0u8; USER_RESERVED_BYTES],
+    }
+}
+
+// mirrors the bookkeeping of the stake, unstake and claim processors
+pub struct SimulatedPool {
+    pub pool: CwarPool,
+    pub users: Vec<User>,
+    pub staking_vault_balance: u64,
+    pub rewards_vault_balance: u64,
+}
+
+impl SimulatedPool {
+    pub fn new(num_users: usize, reward_rate: u64, now: u64, reward_duration: u64) -> Self {
+        let pool = new_funded_pool(reward_rate, now, reward_duration);
+        let users = (0..num_users).map(|_| new_user(&pool)).collect();
+        Self {
+            pool,
+            users,
+            staking_vault_balance: 0,
+            rewards_vault_balance: reward_rate * reward_duration,
+        }
+    }
+
+    pub fn stake(&mut self, user_index: usize, amount: u64, now: u64) {
+        update_rewards(&mut self.pool, None, Some(&mut self.users[user_index]), now).unwrap();
+        self.users[user_index].user_cwar_staked_amount += amount;
+        self.pool.total_staked += amount;
+        self.staking_vault_balance += amount;
+    }
+
+    pub fn unstake(&mut self, user_index: usize, amount: u64, now: u64) {
+        update_rewards(&mut self.pool, None, Some(&mut self.users[user_index]), now).unwrap();
+        self.users[user_index].user_cwar_staked_amount -= amount;
+        self.pool.total_staked -= amount;
+        self.staking_vault_balance -= amount;
+    }
+
+    pub fn claim(&mut self, user_index: usize, now: u64) -> u64 {
+        update_rewards(&mut self.pool, None, Some(&mut self.users[user_index]), now).unwrap();
+        let reward_amount = self.users[user_index].rewards_amount_pending;
+        self.users[user_index].rewards_amount_pending = 0;
+        self.rewards_vault_balance -= reward_amount;
+        self.pool.rewards_owed = self.pool.rewards_owed.saturating_sub(reward_amount);
+        reward_amount
+    }
+
+    pub fn emergency_unstake(&mut self, user_index: usize, now: u64) -> u64 {
+        let amount =
+            emergency_unstake(&mut self.pool, None, &mut self.users[user_index], now).unwrap();
+        self.staking_vault_balance -= amount;
+        amount
+    }
+
+    pub fn total_user_rewards_pending(&mut self, now: u64) -> u64 {
+        let mut total = 0u64;
+        for user in self.users.iter_mut() {
+            update_rewards(&mut self.pool, None, Some(user), now).unwrap();
+            total += user.rewards_amount_pending;
+        }
+        total
+    }
+}
+
+#[test]
+fn test_staking_surplus_never_touches_principal() {
+    let mut rng = rand::thread_rng();
+    let mut sim = SimulatedPool::new(5, TO_RAW_TOKEN, 0, 86400);
+    let mut donated = 0u64;
+    for now in (1u64..90000).step_by(97) {
+        let user_index = rng.gen_range(0..sim.users.len());
+        match rng.gen_range(0..4) {
+            0 => sim.stake(user_index, rng.gen_range(1..1000 * TO_RAW_TOKEN), now),
+            1 => {
+                let staked = sim.users[user_index].user_cwar_staked_amount;
+                if staked > 0 {
+                    sim.unstake(user_index, rng.gen_range(1..=staked), now);
+                }
+            }
+            2 => {
+                let donation = rng.gen_range(1..10 * TO_RAW_TOKEN);
+                sim.staking_vault_balance += donation;
+                donated += donation;
+            }
+            _ => {
+                let surplus = get_staking_vault_surplus(sim.staking_vault_balance, &sim.pool);
+                assert_eq!(surplus, donated);
+                sim.staking_vault_balance -= surplus;
+                donated = 0;
+            }
+        }
+        let principal: u64 = sim.users.iter().map(|u| u.user_cwar_staked_amount).sum();
+        assert_eq!(sim.pool.total_staked, principal);
+        assert_eq!(
+            sim.staking_vault_balance
+                - get_staking_vault_surplus(sim.staking_vault_balance, &sim.pool),
+            principal
+        );
+    }
+}
+
+#[test]
+fn test_rewards_surplus_never_touches_earned_rewards() {
+    let mut rng = rand::thread_rng();
+    let mut sim = SimulatedPool::new(5, TO_RAW_TOKEN, 0, 86400);
+    // nobody stakes for the first hour, so those rewards are never allocated
+    for now in (3600u64..100000).step_by(113) {
+        let user_index = rng.gen_range(0..sim.users.len());
+        match rng.gen_range(0..5) {
+            0 => sim.stake(user_index, rng.gen_range(1..1000 * TO_RAW_TOKEN), now),
+            1 => {
+                let staked = sim.users[user_index].user_cwar_staked_amount;
+                if staked > 0 {
+                    sim.unstake(user_index, rng.gen_range(1..=staked), now);
+                }
+            }
+            2 => {
+                sim.claim(user_index, now);
+            }
+            3 => sim.rewards_vault_balance += rng.gen_range(1..10 * TO_RAW_TOKEN),
+            _ => {
+                update_rewards(&mut sim.pool, None, None, now).unwrap();
+                let liabilities = get_reward_liabilities(&sim.pool).unwrap();
+                sim.rewards_vault_balance -= sim.rewards_vault_balance.saturating_sub(liabilities);
+            }
+        }
+        let earned = sim.total_user_rewards_pending(now);
+        let liabilities = get_reward_liabilities(&sim.pool).unwrap();
+        assert!(earned <= sim.pool.rewards_owed);
+        assert!(liabilities <= sim.rewards_vault_balance);
+    }
+    for user_index in 0..sim.users.len() {
+        sim.claim(user_index, 100000);
+    }
+}
+
+#[test]
+fn test_layout_sizes() {
+    let pool = new_funded_pool(TO_RAW_TOKEN, 0, 86400);
+    assert_eq!(
+        pool.try_to_vec().unwrap().len(),
+        CWAR_POOL_STORAGE_TOTAL_BYTES
+    );
+    assert_eq!(
+        new_user(&pool).try_to_vec().unwrap().len(),
+        USER_STORAGE_TOTAL_BYTES
+    );
+}
+
+#[test]
+fn test_v1_user_round_trip() {
+    let pool = new_funded_pool(TO_RAW_TOKEN, 0, 86400);
+    let mut user = new_user(&pool);
+    user.acc_type = AccTypesWithVersion::UserDataV1 as u8;
+    user.user_cwar_staked_amount = 100 * TO_RAW_TOKEN;
+    user.rewards_per_token_accumulated_at_last_user_interaction = u128::MAX / 3;
+    let v1_data = user.try_to_vec().unwrap()[..USER_V1_STORAGE_TOTAL_BYTES].to_vec();
+
+    let unpacked = unpack_user_storage_account(&v1_data).unwrap();
+    assert_eq!(unpacked.try_to_vec().unwrap(), user.try_to_vec().unwrap());
+
+    let mut saved = vec![0u8; USER_V1_STORAGE_TOTAL_BYTES];
+    save_user_storage_account(&mut saved, &unpacked).unwrap();
+    assert_eq!(saved, v1_data);
+
+    // a V1 account has no room for anything stored in the reserved space
+    let mut extended = unpacked;
+    extended.reserved[0] = 1;
+    assert_eq!(
+        save_user_storage_account(&mut saved, &extended).unwrap_err(),
+        CryowarError::AccountNeedsMigration.into()
+    );
+
+    // the account type has to match the layout
+    let mut v2_data = user.try_to_vec().unwrap();
+    assert!(unpack_user_storage_account(&v2_data).is_err());
+    v2_data[0] = AccTypesWithVersion::UserDataV2 as u8;
+    assert_eq!(
+        unpack_user_storage_account(&v2_data)
+            .unwrap()
+            .user_cwar_staked_amount,
+        100 * TO_RAW_TOKEN
+    );
+}
+
+#[test]
+fn test_pause_flags_are_independent() {
+    let mut pool = new_funded_pool(TO_RAW_TOKEN, 0, 86400);
+    let operations = [
+        PAUSE_STAKE,
+        PAUSE_UNSTAKE,
+        PAUSE_CLAIM,
+        PAUSE_FUND,
+        PAUSE_CREATE_USER,
+    ];
+    for &paused in operations.iter() {
+        pool.paused_operations = paused;
+        for &operation in operations.iter() {
+            let result = assert_not_paused(&pool, operation);
+            if operation == paused {
+                assert_eq!(result.unwrap_err(), CryowarError::PoolPaused.into());
+            } else {
+                assert!(result.is_ok());
+            }
+        }
+    }
+}
+
+#[test]
+fn test_emergency_unstake_keeps_remaining_stakers_whole() {
+    let mut sim = SimulatedPool::new(3, TO_RAW_TOKEN, 0, 86400);
+    sim.stake(0, 100 * TO_RAW_TOKEN, 0);
+    sim.stake(1, 300 * TO_RAW_TOKEN, 0);
+    sim.stake(2, 600 * TO_RAW_TOKEN, 1000);
+
+    assert_eq!(sim.emergency_unstake(1, 5000), 300 * TO_RAW_TOKEN);
+    assert_eq!(sim.users[1].rewards_amount_pending, 0);
+    assert_eq!(sim.pool.total_staked, 700 * TO_RAW_TOKEN);
+    assert_eq!(
+        get_staking_vault_surplus(sim.staking_vault_balance, &sim.pool),
+        0
+    );
+
+    // the forfeited share stays in the vault as surplus and the others keep earning
+    update_rewards(&mut sim.pool, None, None, 86400).unwrap();
+    let earned = sim.total_user_rewards_pending(86400);
+    assert!(earned <= sim.pool.rewards_owed);
+    assert!(get_reward_liabilities(&sim.pool).unwrap() < sim.rewards_vault_balance);
+    assert_eq!(sim.claim(1, 86400), 0);
+    assert!(sim.claim(0, 86400) > 0);
+    assert!(sim.claim(2, 86400) > 0);
+}
+
+#[test]
+fn test_emergency_unstake_survives_failed_checkpoint() {
+    // a reward rate this large overflows the next checkpoint
+    let mut pool = new_funded_pool(u64::MAX, 0, 86400);
+    pool.locking_duration = 86400;
+    pool.rewards_owed = 50;
+    pool.total_staked = 100 * TO_RAW_TOKEN;
+    let mut user = new_user(&pool);
+    user.user_cwar_staked_amount = 100 * TO_RAW_TOKEN;
+    user.rewards_amount_pending = 50;
+    assert!(update_rewards(&mut pool.clone(), None, None, 10).is_err());
+
+    // the stake leaves within the locking period and the skipped emissions are never divided by
+    // the smaller weight, the pool is frozen at the time of the exit
+    let rewards_per_token_before = pool.rewards_per_token_accumulated_till_now;
+    assert_eq!(
+        emergency_unstake(&mut pool, None, &mut user, 10).unwrap(),
+        100 * TO_RAW_TOKEN
+    );
+    assert_eq!(pool.total_stake_last_update_time, 10);
+    assert_eq!(
+        pool.rewards_per_token_accumulated_till_now,
+        rewards_per_token_before
+    );
+    assert_eq!(pool.total_staked, 0);
+    assert_eq!(pool.rewards_owed, 0);
+    assert_eq!(user.user_cwar_staked_amount, 0);
+    assert_eq!(user.rewards_amount_pending, 0);
+    assert_eq!(
+        user.rewards_per_token_accumulated_at_last_user_interaction,
+        pool.rewards_per_token_accumulated_till_now
+    );
+    assert_eq!(
+        emergency_unstake(&mut pool, None, &mut user, 20).unwrap_err(),
+        CryowarError::InsufficientFundsToUnstake.into()
+    );
+}
+
+#[test]
+fn test_emergency_unstake_receipt_debt() {
+    let pool = new_funded_pool(TO_RAW_TOKEN, 0, 86400);
+    let mut user = new_user(&pool);
+
+    // receipts sold on leave the user owing them instead of blocking the exit
+    assert_eq!(
+        add_receipt_debt(&mut user, 100 * TO_RAW_TOKEN, 40 * TO_RAW_TOKEN).unwrap(),
+        40 * TO_RAW_TOKEN
+    );
+    assert_eq!(user.receipt_debt, 60 * TO_RAW_TOKEN);
+    assert_eq!(
+        add_receipt_debt(&mut user, 10 * TO_RAW_TOKEN, 20 * TO_RAW_TOKEN).unwrap(),
+        10 * TO_RAW_TOKEN
+    );
+    assert_eq!(user.receipt_debt, 60 * TO_RAW_TOKEN);
+
+    // new stake backs the receipts still out before any more are minted
+    assert_eq!(repay_receipt_debt(&mut user, 50 * TO_RAW_TOKEN), 0);
+    assert_eq!(user.receipt_debt, 10 * TO_RAW_TOKEN);
+    assert_eq!(
+        repay_receipt_debt(&mut user, 50 * TO_RAW_TOKEN),
+        40 * TO_RAW_TOKEN
+    );
+    assert_eq!(user.receipt_debt, 0);
+}
+
+#[test]
+fn test_reducing_rewards_never_touches_earned_rewards() {
+    let mut sim = SimulatedPool::new(2, TO_RAW_TOKEN, 0, 86400);
+    sim.stake(0, 100 * TO_RAW_TOKEN, 0);
+    sim.stake(1, 300 * TO_RAW_TOKEN, 1000);
+
+    update_rewards(&mut sim.pool, None, None, 20_000).unwrap();
+    let liabilities_before = get_reward_liabilities(&sim.pool).unwrap();
+    assert_eq!(
+        reduce_rewards(&mut sim.pool, 0, 10_000).unwrap_err(),
+        CryowarError::InvalidRewardsReduction.into()
+    );
+    assert_eq!(
+        reduce_rewards(&mut sim.pool, 0, 86401).unwrap_err(),
+        CryowarError::InvalidRewardsReduction.into()
+    );
+    assert_eq!(
+        reduce_rewards(&mut sim.pool, liabilities_before, 86400).unwrap_err(),
+        CryowarError::InvalidRewardsReduction.into()
+    );
+
+    let refund = reduce_rewards(&mut sim.pool, 10_000 * TO_RAW_TOKEN, 50_000).unwrap();
+    assert!(refund >= 10_000 * TO_RAW_TOKEN);
+    sim.rewards_vault_balance -= refund;
+    assert_eq!(
+        get_reward_liabilities(&sim.pool).unwrap(),
+        liabilities_before - refund
+    );
+    assert_eq!(sim.pool.reward_duration_end, 50_000);
+
+    update_rewards(&mut sim.pool, None, None, 30_000).unwrap();
+    let liabilities_before = get_reward_liabilities(&sim.pool).unwrap();
+    let refund = cancel_rewards(&mut sim.pool).unwrap();
+    sim.rewards_vault_balance -= refund;
+    assert_eq!(sim.pool.reward_duration_end, 30_000);
+    assert_eq!(
+        get_reward_liabilities(&sim.pool).unwrap(),
+        sim.pool.rewards_owed
+    );
+    assert_eq!(sim.pool.rewards_owed, liabilities_before - refund);
+    assert_eq!(
+        cancel_rewards(&mut sim.pool).unwrap_err(),
+        CryowarError::InvalidRewardsReduction.into()
+    );
+
+    // nothing is emitted after the cancellation and everyone earned so far can still claim
+    let earned = sim.total_user_rewards_pending(86400);
+    assert!(earned <= sim.pool.rewards_owed);
+    assert!(sim.pool.rewards_owed <= sim.rewards_vault_balance);
+    let claimed = sim.claim(0, 86400) + sim.claim(1, 86400);
+    assert_eq!(claimed, earned);
+}
+
+#[test]
+fn test_reward_streams_accrue_alongside_primary_reward() {
+    let mut sim = SimulatedPool::new(3, TO_RAW_TOKEN, 0, 86400);
+    sim.stake(0, 100 * TO_RAW_TOKEN, 0);
+    sim.stake(1, 300 * TO_RAW_TOKEN, 1000);
+
+    // a stream added later only pays for the time after it was funded
+    update_rewards(&mut sim.pool, None, None, 10_000).unwrap();
+    sim.pool.reward_streams[1] = RewardStream {
+        reward_mint: Pubkey::new_unique(),
+        reward_vault: Pubkey::new_unique(),
+        last_update_time: 10_000,
+        ..RewardStream::default()
+    };
+    fund_reward_stream(
+        &mut sim.pool.reward_streams[1],
+        2 * 76_400 * TO_RAW_TOKEN,
+        76_400,
+        10_000,
+    )
+    .unwrap();
+    assert_eq!(sim.pool.reward_streams[1].reward_rate, 2 * TO_RAW_TOKEN);
+    assert!(!sim.pool.reward_streams[0].is_active());
+    sim.stake(2, 600 * TO_RAW_TOKEN, 20_000);
+    sim.unstake(1, 300 * TO_RAW_TOKEN, 50_000);
+
+    sim.total_user_rewards_pending(86400);
+    let stream = sim.pool.reward_streams[1];
+    let earned: u64 = sim
+        .users
+        .iter()
+        .map(|user| user.reward_streams[1].rewards_amount_pending)
+        .sum();
+    assert!(earned <= stream.rewards_owed);
+    assert!(stream.rewards_owed <= 2 * 76_400 * TO_RAW_TOKEN);
+    assert!(stream.rewards_owed - earned < sim.users.len() as u64);
+    assert_eq!(stream.last_update_time, 86400);
+    assert!(sim
+        .users
+        .iter()
+        .all(|user| user.reward_streams[0] == UserRewardStream::default()));
+
+    // user 0 staked alone for the first 1000s, which only the primary reward paid for
+    let user = sim.users[0];
+    assert!(user.rewards_amount_pending > user.reward_streams[1].rewards_amount_pending / 2);
+    assert!(sim.users[2].reward_streams[1].rewards_amount_pending > 0);
+}
+
+#[test]
+fn test_compounding_keeps_stake_and_rewards_backed() {
+    let mut sim = SimulatedPool::new(2, TO_RAW_TOKEN, 0, 86400);
+    sim.pool.reward_mint = sim.pool.staking_mint;
+    sim.stake(0, 100 * TO_RAW_TOKEN, 0);
+    sim.stake(1, 100 * TO_RAW_TOKEN, 0);
+    sim.users[0].last_staked_timestamp = 0;
+
+    update_rewards(&mut sim.pool, None, Some(&mut sim.users[0]), 10_000).unwrap();
+    let pending = sim.users[0].rewards_amount_pending;
+    let compounded = compound_rewards(&mut sim.pool, &mut sim.users[0]).unwrap();
+    assert_eq!(compounded, pending);
+    sim.rewards_vault_balance -= compounded;
+    sim.staking_vault_balance += compounded;
+    assert_eq!(
+        sim.users[0].user_cwar_staked_amount,
+        100 * TO_RAW_TOKEN + compounded
+    );
+    assert_eq!(sim.users[0].last_staked_timestamp, 0);
+    assert_eq!(sim.users[0].rewards_amount_pending, 0);
+    assert_eq!(
+        compound_rewards(&mut sim.pool, &mut sim.users[0]).unwrap_err(),
+        CryowarError::AmountMustBeGreaterThanZero.into()
+    );
+    assert_eq!(
+        get_staking_vault_surplus(sim.staking_vault_balance, &sim.pool),
+        0
+    );
+
+    // the compounded stake earns its share of everything emitted afterwards
+    let earned = sim.total_user_rewards_pending(86400);
+    assert!(earned <= sim.pool.rewards_owed);
+    assert!(get_reward_liabilities(&sim.pool).unwrap() <= sim.rewards_vault_balance);
+    assert!(sim.users[0].rewards_amount_pending > sim.users[1].rewards_amount_pending);
+}
+
+#[test]
+fn test_compounding_respects_stake_limits() {
+    let mut pool = new_funded_pool(TO_RAW_TOKEN, 0, 86400);
+    pool.reward_mint = pool.staking_mint;
+    let mut user = new_user(&pool);
+    user.user_cwar_staked_amount = 100 * TO_RAW_TOKEN;
+    user.rewards_amount_pending = 10 * TO_RAW_TOKEN;
+    pool.total_staked = 100 * TO_RAW_TOKEN;
+    pool.rewards_owed = 10 * TO_RAW_TOKEN;
+
+    let mut user_capped_pool = pool;
+    user_capped_pool.max_stake_per_user = 105 * TO_RAW_TOKEN;
+    let mut capped_pool = pool;
+    capped_pool.max_total_staked = 105 * TO_RAW_TOKEN;
+    let mut allowlisted_user = user;
+    allowlisted_user.allowlist_max_stake = 105 * TO_RAW_TOKEN;
+    for (mut limited_pool, mut limited_user, error) in [
+        (user_capped_pool, user, CryowarError::UserStakeLimitExceeded),
+        (capped_pool, user, CryowarError::PoolStakeCapExceeded),
+        (pool, allowlisted_user, CryowarError::UserStakeLimitExceeded),
+    ] {
+        assert_eq!(
+            compound_rewards(&mut limited_pool, &mut limited_user).unwrap_err(),
+            error.into()
+        );
+        // nothing moved
+        assert_eq!(limited_user.rewards_amount_pending, 10 * TO_RAW_TOKEN);
+        assert_eq!(limited_pool.total_staked, 100 * TO_RAW_TOKEN);
+    }
+
+    pool.max_stake_per_user = 110 * TO_RAW_TOKEN;
+    pool.max_total_staked = 110 * TO_RAW_TOKEN;
+    assert_eq!(
+        compound_rewards(&mut pool, &mut user).unwrap(),
+        10 * TO_RAW_TOKEN
+    );
+    assert_eq!(pool.total_staked, 110 * TO_RAW_TOKEN);
+}
+
+#[test]
+fn test_unbonding_stake_stops_earning_and_stays_reserved() {
+    let mut sim = SimulatedPool::new(2, TO_RAW_TOKEN, 0, 86400);
+    sim.pool.unbonding_duration = 7 * 86400;
+    sim.stake(0, 100 * TO_RAW_TOKEN, 0);
+    sim.stake(1, 100 * TO_RAW_TOKEN, 0);
+
+    update_rewards(&mut sim.pool, None, Some(&mut sim.users[0]), 10_000).unwrap();
+    assert_eq!(
+        request_unstake(&mut sim.pool, &mut sim.users[0], 100 * TO_RAW_TOKEN + 1).unwrap_err(),
+        CryowarError::InsufficientFundsToUnstake.into()
+    );
+    request_unstake(&mut sim.pool, &mut sim.users[0], 60 * TO_RAW_TOKEN).unwrap();
+    request_unstake(&mut sim.pool, &mut sim.users[0], 40 * TO_RAW_TOKEN).unwrap();
+    assert_eq!(sim.users[0].user_cwar_staked_amount, 0);
+    assert_eq!(sim.users[0].unbonding_amount, 100 * TO_RAW_TOKEN);
+    assert_eq!(sim.pool.total_staked, 100 * TO_RAW_TOKEN);
+    assert_eq!(sim.pool.total_unbonding, 100 * TO_RAW_TOKEN);
+    // unbonding tokens are still owed to the user, they are not surplus
+    assert_eq!(
+        get_staking_vault_surplus(sim.staking_vault_balance, &sim.pool),
+        0
+    );
+
+    // the remaining staker gets everything emitted while the first user unbonds
+    let pending_before = sim.users[0].rewards_amount_pending;
+    update_rewards(&mut sim.pool, None, Some(&mut sim.users[0]), 20_000).unwrap();
+    assert_eq!(sim.users[0].rewards_amount_pending, pending_before);
+
+    // cancelling restakes, withdrawing releases
+    settle_unbonding_request(&mut sim.pool, &mut sim.users[0], 40 * TO_RAW_TOKEN, true).unwrap();
+    settle_unbonding_request(&mut sim.pool, &mut sim.users[0], 60 * TO_RAW_TOKEN, false).unwrap();
+    sim.staking_vault_balance -= 60 * TO_RAW_TOKEN;
+    assert_eq!(sim.users[0].user_cwar_staked_amount, 40 * TO_RAW_TOKEN);
+    assert_eq!(sim.users[0].unbonding_amount, 0);
+    assert_eq!(sim.pool.total_staked, 140 * TO_RAW_TOKEN);
+    assert_eq!(sim.pool.total_unbonding, 0);
+    assert_eq!(
+        get_staking_vault_surplus(sim.staking_vault_balance, &sim.pool),
+        0
+    );
+    assert!(sim.total_user_rewards_pending(86400) <= sim.pool.rewards_owed);
+}
+
+#[test]
+fn test_unbonding_requests_fit_their_account() {
+    let unbonding_request = UnbondingRequest {
+        acc_type: AccTypesWithVersion::UnbondingRequestDataV1 as u8,
+        user_storage: Pubkey::new_unique(),
+        request_id: u64::MAX,
+        amount: u64::MAX,
+        requested_at: u64::MAX,
+        withdrawable_at: u64::MAX,
+    };
+    let unbonding_request_data = unbonding_request.try_to_vec().unwrap();
+    assert!(unbonding_request_data.len() <= UNBONDING_REQUEST_STORAGE_TOTAL_BYTES);
+}
+
+#[test]
+fn test_lock_positions_fit_their_account() {
+    let lock_position = LockPosition {
+        acc_type: AccTypesWithVersion::LockPositionDataV1 as u8,
+        user_storage: Pubkey::new_unique(),
+        position_id: u64::MAX,
+        amount: u64::MAX,
+        weight: u64::MAX,
+        lock_tier_index: u8::MAX,
+        locked_at: u64::MAX,
+        unlock_at: u64::MAX,
+    };
+    let lock_position_data = lock_position.try_to_vec().unwrap();
+    assert!(lock_position_data.len() <= LOCK_POSITION_STORAGE_TOTAL_BYTES);
+}
+
+#[test]
+fn test_lock_positions_earn_by_weight() {
+    let mut sim = SimulatedPool::new(2, TO_RAW_TOKEN, 0, 86400);
+    let lock_tier = LockTier {
+        duration: 365 * 86400,
+        multiplier_basis_points: 30_000,
+    };
+    sim.stake(1, 100 * TO_RAW_TOKEN, 0);
+    update_rewards(&mut sim.pool, None, Some(&mut sim.users[0]), 0).unwrap();
+    let weight = add_lock_position(
+        &mut sim.pool,
+        &mut sim.users[0],
+        &lock_tier,
+        100 * TO_RAW_TOKEN,
+    )
+    .unwrap();
+    sim.staking_vault_balance += 100 * TO_RAW_TOKEN;
+    assert_eq!(weight, 300 * TO_RAW_TOKEN);
+    assert_eq!(sim.pool.total_staked, 200 * TO_RAW_TOKEN);
+    assert_eq!(
+        get_staking_vault_surplus(sim.staking_vault_balance, &sim.pool),
+        0
+    );
+
+    // the locked user holds three quarters of the weight and earns three quarters of the rewards
+    let earned = sim.total_user_rewards_pending(40_000);
+    assert!(earned <= sim.pool.rewards_owed);
+    assert!(sim.pool.rewards_owed - earned < sim.users.len() as u64);
+    let locked_rewards = sim.users[0].rewards_amount_pending;
+    let unlocked_rewards = sim.users[1].rewards_amount_pending;
+    assert!(locked_rewards.abs_diff(3 * unlocked_rewards) < 3);
+
+    let lock_position = LockPosition {
+        acc_type: AccTypesWithVersion::LockPositionDataV1 as u8,
+        user_storage: Pubkey::new_unique(),
+        position_id: 0,
+        amount: 100 * TO_RAW_TOKEN,
+        weight,
+        lock_tier_index: 0,
+        locked_at: 0,
+        unlock_at: lock_tier.duration,
+    };
+    update_rewards(&mut sim.pool, None, Some(&mut sim.users[0]), 40_000).unwrap();
+    remove_lock_position(&mut sim.pool, &mut sim.users[0], &lock_position).unwrap();
+    sim.staking_vault_balance -= 100 * TO_RAW_TOKEN;
+    assert_eq!(sim.users[0].locked_amount, 0);
+    assert_eq!(sim.users[0].locked_weight, 0);
+    assert_eq!(sim.pool.total_locked, 0);
+    assert_eq!(sim.pool.total_locked_weight, 0);
+    assert_eq!(sim.pool.total_staked, 100 * TO_RAW_TOKEN);
+
+    // without lock positions the weight is the stake again
+    let earned = sim.total_user_rewards_pending(86400);
+    assert!(earned <= sim.pool.rewards_owed);
+    assert_eq!(sim.users[0].rewards_amount_pending, locked_rewards);
+    assert!(get_reward_liabilities(&sim.pool).unwrap() <= sim.rewards_vault_balance);
+}
+
+#[test]
+fn test_expired_lock_positions_earn_like_unlocked_stake() {
+    let mut sim = SimulatedPool::new(2, TO_RAW_TOKEN, 0, 86400);
+    let lock_tier = LockTier {
+        duration: 3600,
+        multiplier_basis_points: 30_000,
+    };
+    sim.stake(1, 100 * TO_RAW_TOKEN, 0);
+    update_rewards(&mut sim.pool, None, Some(&mut sim.users[0]), 0).unwrap();
+    let weight = add_lock_position(
+        &mut sim.pool,
+        &mut sim.users[0],
+        &lock_tier,
+        100 * TO_RAW_TOKEN,
+    )
+    .unwrap();
+    sim.staking_vault_balance += 100 * TO_RAW_TOKEN;
+    let mut lock_position = LockPosition {
+        acc_type: AccTypesWithVersion::LockPositionDataV1 as u8,
+        user_storage: Pubkey::new_unique(),
+        position_id: 0,
+        amount: 100 * TO_RAW_TOKEN,
+        weight,
+        lock_tier_index: 0,
+        locked_at: 0,
+        unlock_at: lock_tier.duration,
+    };
+
+    // still locked
+    update_rewards(&mut sim.pool, None, Some(&mut sim.users[0]), 3599).unwrap();
+    assert_eq!(
+        expire_lock_position(&mut sim.pool, &mut sim.users[0], &mut lock_position, 3599)
+            .unwrap_err(),
+        CryowarError::LockPositionNotUnlocked.into()
+    );
+
+    update_rewards(&mut sim.pool, None, Some(&mut sim.users[0]), 3600).unwrap();
+    expire_lock_position(&mut sim.pool, &mut sim.users[0], &mut lock_position, 3600).unwrap();
+    assert_eq!(lock_position.weight, 100 * TO_RAW_TOKEN);
+    assert_eq!(sim.users[0].locked_weight, 100 * TO_RAW_TOKEN);
+    assert_eq!(sim.pool.total_locked_weight, 100 * TO_RAW_TOKEN);
+    assert_eq!(sim.pool.total_locked, 100 * TO_RAW_TOKEN);
+    // expiring again changes nothing
+    expire_lock_position(&mut sim.pool, &mut sim.users[0], &mut lock_position, 4000).unwrap();
+    assert_eq!(sim.pool.total_locked_weight, 100 * TO_RAW_TOKEN);
+
+    // both users earn the same from the expiry on
+    sim.total_user_rewards_pending(3600);
+    let locked_rewards_before = sim.users[0].rewards_amount_pending;
+    let unlocked_rewards_before = sim.users[1].rewards_amount_pending;
+    let earned = sim.total_user_rewards_pending(86400);
+    assert!(earned <= sim.pool.rewards_owed);
+    let locked_rewards = sim.users[0].rewards_amount_pending - locked_rewards_before;
+    let unlocked_rewards = sim.users[1].rewards_amount_pending - unlocked_rewards_before;
+    assert!(locked_rewards.abs_diff(unlocked_rewards) < 2);
+
+    update_rewards(&mut sim.pool, None, Some(&mut sim.users[0]), 86400).unwrap();
+    remove_lock_position(&mut sim.pool, &mut sim.users[0], &lock_position).unwrap();
+    assert_eq!(sim.users[0].locked_weight, 0);
+    assert_eq!(sim.pool.total_locked_weight, 0);
+}
+
+#[test]
+fn test_lock_tier_bounds() {
+    let mut lock_tiers = [LockTier::default(); MAX_LOCK_TIERS];
+    assert!(assert_lock_tiers(&lock_tiers).is_ok());
+    lock_tiers[0] = LockTier {
+        duration: 30 * 86400,
+        multiplier_basis_points: 10_000,
+    };
+    lock_tiers[3] = LockTier {
+        duration: MAX_LOCK_DURATION,
+        multiplier_basis_points: MAX_LOCK_MULTIPLIER_BASIS_POINTS,
+    };
+    assert!(assert_lock_tiers(&lock_tiers).is_ok());
+    for lock_tier in [
+        LockTier {
+            duration: MAX_LOCK_DURATION + 1,
+            multiplier_basis_points: 20_000,
+        },
+        LockTier {
+            duration: 90 * 86400,
+            multiplier_basis_points: 9_999,
+        },
+        LockTier {
+            duration: 90 * 86400,
+            multiplier_basis_points: MAX_LOCK_MULTIPLIER_BASIS_POINTS + 1,
+        },
+        LockTier {
+            duration: 0,
+            multiplier_basis_points: 20_000,
+        },
+    ] {
+        let mut invalid_lock_tiers = lock_tiers;
+        invalid_lock_tiers[1] = lock_tier;
+        assert_eq!(
+            assert_lock_tiers(&invalid_lock_tiers).unwrap_err(),
+            CryowarError::InvalidLockTier.into()
+        );
+    }
+}
+
+#[test]
+fn test_unstake_penality_schedules() {
+    let mut pool = new_funded_pool(TO_RAW_TOKEN, 0, 100 * 86400);
+    pool.unstake_penality_basis_points = 1_000;
+    let mut user = new_user(&pool);
+    user.last_staked_timestamp = 10 * 86400;
+    let week = 7 * 86400;
+
+    // the cliff charges the full penalty until the end of the penalty window
+    assert_eq!(
+        get_unstake_penality_basis_points(&pool, &user, 10 * 86400),
+        1_000
+    );
+    assert_eq!(
+        get_unstake_penality_basis_points(&pool, &user, 100 * 86400 - 1),
+        1_000
+    );
+    assert_eq!(
+        get_unstake_penality_basis_points(&pool, &user, 100 * 86400),
+        0
+    );
+
+    pool.penality_schedule = PenalitySchedule {
+        kind: PENALITY_SCHEDULE_LINEAR,
+        decay_duration: 2 * week,
+        ..Default::default()
+    };
+    assert_eq!(
+        get_unstake_penality_basis_points(&pool, &user, 10 * 86400),
+        1_000
+    );
+    assert_eq!(
+        get_unstake_penality_basis_points(&pool, &user, 10 * 86400 + week),
+        500
+    );
+    assert_eq!(
+        get_unstake_penality_basis_points(&pool, &user, 10 * 86400 + 2 * week),
+        0
+    );
+    assert_eq!(
+        get_unstake_penality(&pool, &user, 100 * TO_RAW_TOKEN, 10 * 86400 + week).unwrap(),
+        5 * TO_RAW_TOKEN
+    );
+
+    // 10% in week 1, 5% in week 2, capped by the pool penalty
+    let mut steps = [PenalityStep::default(); MAX_PENALITY_STEPS];
+    steps[0] = PenalityStep {
+        stake_age_below: week,
+        basis_points: 1_000,
+    };
+    steps[1] = PenalityStep {
+        stake_age_below: 2 * week,
+        basis_points: 500,
+    };
+    pool.penality_schedule = PenalitySchedule {
+        kind: PENALITY_SCHEDULE_STEPPED,
+        decay_duration: 0,
+        steps,
+    };
+    assert!(assert_penality_schedule(&pool.penality_schedule).is_ok());
+    assert_eq!(
+        get_unstake_penality_basis_points(&pool, &user, 10 * 86400 + week - 1),
+        1_000
+    );
+    assert_eq!(
+        get_unstake_penality_basis_points(&pool, &user, 10 * 86400 + week),
+        500
+    );
+    assert_eq!(
+        get_unstake_penality_basis_points(&pool, &user, 10 * 86400 + 2 * week),
+        0
+    );
+    pool.unstake_penality_basis_points = 800;
+    assert_eq!(
+        get_unstake_penality_basis_points(&pool, &user, 10 * 86400),
+        800
+    );
+    pool.unstake_penality_basis_points = 0;
+    assert_eq!(
+        get_unstake_penality_basis_points(&pool, &user, 10 * 86400),
+        0
+    );
+}
+
+#[test]
+fn test_penality_schedule_bounds() {
+    assert!(assert_penality_schedule(&PenalitySchedule::default()).is_ok());
+    let step = PenalityStep {
+        stake_age_below: 86400,
+        basis_points: 1_000,
+    };
+    let mut unordered_steps = [step; MAX_PENALITY_STEPS];
+    unordered_steps[1].stake_age_below = 86400;
+    let mut gapped_steps = [PenalityStep::default(); MAX_PENALITY_STEPS];
+    gapped_steps[1] = step;
+    let mut too_high_steps = [PenalityStep::default(); MAX_PENALITY_STEPS];
+    too_high_steps[0].stake_age_below = 86400;
+    too_high_steps[0].basis_points = MAX_UNSTAKE_PENALITY_BASIS_POINTS + 1;
+    for penality_schedule in [
+        PenalitySchedule {
+            decay_duration: 86400,
+            ..Default::default()
+        },
+        PenalitySchedule {
+            kind: PENALITY_SCHEDULE_LINEAR,
+            ..Default::default()
+        },
+        PenalitySchedule {
+            kind: PENALITY_SCHEDULE_LINEAR,
+            decay_duration: MAX_PENALITY_DECAY_DURATION + 1,
+            ..Default::default()
+        },
+        PenalitySchedule {
+            kind: PENALITY_SCHEDULE_STEPPED,
+            ..Default::default()
+        },
+        PenalitySchedule {
+            kind: PENALITY_SCHEDULE_STEPPED,
+            decay_duration: 0,
+            steps: unordered_steps,
+        },
+        PenalitySchedule {
+            kind: PENALITY_SCHEDULE_STEPPED,
+            decay_duration: 0,
+            steps: gapped_steps,
+        },
+        PenalitySchedule {
+            kind: PENALITY_SCHEDULE_STEPPED,
+            decay_duration: 0,
+            steps: too_high_steps,
+        },
+        PenalitySchedule {
+            kind: 3,
+            ..Default::default()
+        },
+    ] {
+        assert_eq!(
+            assert_penality_schedule(&penality_schedule).unwrap_err(),
+            CryowarError::InvalidPenalitySchedule.into()
+        );
+    }
+}
+
+#[test]
+fn test_penality_routing_splits_the_whole_penality() {
+    let mut rng = rand::thread_rng();
+    for _ in 0..1000 {
+        let burn_basis_points = rng.gen_range(0..=10_000);
+        let reward_vault_basis_points = rng.gen_range(0..=10_000 - burn_basis_points);
+        let redistribute_basis_points =
+            rng.gen_range(0..=10_000 - burn_basis_points - reward_vault_basis_points);
+        let penality_routing = PenalityRouting {
+            burn_basis_points,
+            reward_vault_basis_points,
+            redistribute_basis_points,
+            treasury_basis_points: 10_000
+                - burn_basis_points
+                - reward_vault_basis_points
+                - redistribute_basis_points,
+        };
+        let penality_amount = rng.gen_range(0..1_000_000 * TO_RAW_TOKEN);
+        let split = split_penality(penality_amount, &penality_routing).unwrap();
+        assert_eq!(
+            split.burn + split.reward_vault + split.redistribute + split.treasury,
+            penality_amount
+        );
+
+        // routing in a live pool keeps the sum and never promises more than it pays in
+        let now = rng.gen_range(0..2 * 86400);
+        let mut pool = new_funded_pool(TO_RAW_TOKEN, 0, 86400);
+        pool.reward_mint = pool.staking_mint;
+        pool.penality_routing = penality_routing;
+        pool.total_staked = rng.gen_range(0..2) * rng.gen_range(1..1000 * TO_RAW_TOKEN);
+        update_rewards(&mut pool, None, None, now).unwrap();
+        let liabilities_before = get_reward_liabilities(&pool).unwrap();
+        let routed = route_penality(&mut pool, penality_amount, now).unwrap();
+        assert_eq!(
+            routed.burn + routed.reward_vault + routed.redistribute + routed.treasury,
+            penality_amount
+        );
+        assert_eq!(routed.burn, split.burn);
+        assert!(
+            get_reward_liabilities(&pool).unwrap()
+                <= liabilities_before + routed.reward_vault + routed.redistribute
+        );
+        if pool.total_staked == 0 {
+            assert_eq!(routed.redistribute, 0);
+        }
+        if now >= pool.reward_duration_end {
+            assert_eq!(routed.reward_vault, 0);
+        }
+    }
+
+    // all zero is the legacy routing, the whole penalty goes to the treasury
+    let split = split_penality(12345, &PenalityRouting::default()).unwrap();
+    assert_eq!(split.treasury, 12345);
+}
+
+#[test]
+fn test_penality_routing_bounds() {
+    let mut pool = new_funded_pool(TO_RAW_TOKEN, 0, 86400);
+    assert!(assert_penality_routing(&PenalityRouting::default(), &pool).is_ok());
+    let burn_and_treasury = PenalityRouting {
+        burn_basis_points: 5_000,
+        treasury_basis_points: 5_000,
+        ..Default::default()
+    };
+    assert!(assert_penality_routing(&burn_and_treasury, &pool).is_ok());
+    let redistribute = PenalityRouting {
+        redistribute_basis_points: 10_000,
+        ..Default::default()
+    };
+    let reward_vault = PenalityRouting {
+        reward_vault_basis_points: 10_000,
+        ..Default::default()
+    };
+    for penality_routing in [
+        redistribute,
+        reward_vault,
+        PenalityRouting {
+            burn_basis_points: 5_000,
+            ..Default::default()
+        },
+        PenalityRouting {
+            burn_basis_points: 5_000,
+            treasury_basis_points: 5_001,
+            ..Default::default()
+        },
+        PenalityRouting {
+            burn_basis_points: u16::MAX,
+            treasury_basis_points: u16::MAX,
+            reward_vault_basis_points: u16::MAX,
+            redistribute_basis_points: u16::MAX,
+        },
+    ] {
+        assert_eq!(
+            assert_penality_routing(&penality_routing, &pool).unwrap_err(),
+            CryowarError::InvalidPenalityRouting.into()
+        );
+    }
+
+    // penalties can only feed rewards paid in the staking token
+    pool.reward_mint = pool.staking_mint;
+    assert!(assert_penality_routing(&redistribute, &pool).is_ok());
+    assert!(assert_penality_routing(&reward_vault, &pool).is_ok());
+}
+
+#[test]
+fn test_stake_limits() {
+    let mut pool = new_funded_pool(TO_RAW_TOKEN, 0, 86400);
+    let mut user = new_user(&pool);
+    assert!(check_stake_limits(&pool, &user, u64::MAX).is_ok());
+
+    pool.min_stake = 100 * TO_RAW_TOKEN;
+    pool.max_stake_per_user = 1_000 * TO_RAW_TOKEN;
+    pool.max_total_staked = 5_000 * TO_RAW_TOKEN;
+    assert_eq!(
+        check_stake_limits(&pool, &user, 100 * TO_RAW_TOKEN - 1).unwrap_err(),
+        CryowarError::StakeBelowMinimum.into()
+    );
+    assert!(check_stake_limits(&pool, &user, 100 * TO_RAW_TOKEN).is_ok());
+
+    // top ups only need the position to stay above the minimum, locked stake counts too
+    user.user_cwar_staked_amount = 100 * TO_RAW_TOKEN;
+    user.locked_amount = 400 * TO_RAW_TOKEN;
+    pool.total_staked = 4_000 * TO_RAW_TOKEN;
+    assert!(check_stake_limits(&pool, &user, 1).is_ok());
+    assert!(check_stake_limits(&pool, &user, 500 * TO_RAW_TOKEN).is_ok());
+    assert_eq!(
+        check_stake_limits(&pool, &user, 500 * TO_RAW_TOKEN + 1).unwrap_err(),
+        CryowarError::UserStakeLimitExceeded.into()
+    );
+
+    // unbonding stake has left the pool and frees up room
+    user.unbonding_amount = 400 * TO_RAW_TOKEN;
+    pool.total_staked = 4_600 * TO_RAW_TOKEN;
+    assert!(check_stake_limits(&pool, &user, 400 * TO_RAW_TOKEN).is_ok());
+    assert_eq!(
+        check_stake_limits(&pool, &user, 400 * TO_RAW_TOKEN + 1).unwrap_err(),
+        CryowarError::PoolStakeCapExceeded.into()
+    );
+
+    // the allowlist cap of a user applies on top of the pool limit
+    user.allowlist_max_stake = 600 * TO_RAW_TOKEN;
+    assert!(check_stake_limits(&pool, &user, 100 * TO_RAW_TOKEN).is_ok());
+    assert_eq!(
+        check_stake_limits(&pool, &user, 100 * TO_RAW_TOKEN + 1).unwrap_err(),
+        CryowarError::UserStakeLimitExceeded.into()
+    );
+}
+
+#[test]
+fn test_allowlist_proofs() {
+    let mut rng = rand::thread_rng();
+    for num_wallets in 1..=20usize {
+        let entries: Vec<(Pubkey, u64)> = (0..num_wallets)
+            .map(|_| {
+                (
+                    Pubkey::new_unique(),
+                    rng.gen_range(0..2) * rng.gen_range(1..u64::MAX),
+                )
+            })
+            .collect();
+        let tree = AllowlistTree::new(&entries);
+        let root = tree.root();
+        for (index, (user_wallet, max_stake)) in entries.iter().enumerate() {
+            let proof = tree.proof(index);
+            assert!(proof.len() <= MAX_ALLOWLIST_PROOF_LENGTH);
+            assert!(verify_allowlist_proof(
+                &root,
+                get_allowlist_leaf(user_wallet, *max_stake),
+                &proof
+            ));
+            assert!(!verify_allowlist_proof(
+                &root,
+                get_allowlist_leaf(user_wallet, max_stake.wrapping_add(1)),
+                &proof
+            ));
+            assert!(!verify_allowlist_proof(
+                &root,
+                get_allowlist_leaf(&Pubkey::new_unique(), *max_stake),
+                &proof
+            ));
+        }
+    }
+}
+
+#[test]
+fn test_allowlisted_pools() {
+    let mut pool = new_funded_pool(TO_RAW_TOKEN, 0, 86400);
+    let user_wallet = Pubkey::new_unique();
+    assert_eq!(
+        assert_allowlisted(&pool, &user_wallet, 5 * TO_RAW_TOKEN, &[]).unwrap(),
+        0
+    );
+    assert!(assert_no_allowlist(&pool).is_ok());
+
+    let entries = [
+        (Pubkey::new_unique(), 0),
+        (user_wallet, 5 * TO_RAW_TOKEN),
+        (Pubkey::new_unique(), TO_RAW_TOKEN),
+    ];
+    let tree = AllowlistTree::new(&entries);
+    pool.allowlist_root = tree.root();
+    // user accounts created on the way cannot be allowlisted without a proof
+    assert_eq!(
+        assert_no_allowlist(&pool).unwrap_err(),
+        CryowarError::AllowlistedUserRequired.into()
+    );
+    assert_eq!(
+        assert_allowlisted(&pool, &user_wallet, 5 * TO_RAW_TOKEN, &tree.proof(1)).unwrap(),
+        5 * TO_RAW_TOKEN
+    );
+    assert_eq!(
+        assert_allowlisted(&pool, &entries[0].0, 0, &tree.proof(0)).unwrap(),
+        0
+    );
+    assert_eq!(
+        assert_allowlisted(&pool, &user_wallet, 0, &tree.proof(1)).unwrap_err(),
+        CryowarError::WalletNotAllowlisted.into()
+    );
+    assert_eq!(
+        assert_allowlisted(&pool, &entries[0].0, 0, &tree.proof(1)).unwrap_err(),
+        CryowarError::WalletNotAllowlisted.into()
+    );
+    assert_eq!(
+        assert_allowlisted(
+            &pool,
+            &user_wallet,
+            5 * TO_RAW_TOKEN,
+            &[[0u8; 32]; MAX_ALLOWLIST_PROOF_LENGTH + 1]
+        )
+        .unwrap_err(),
+        CryowarError::InvalidAllowlistProof.into()
+    );
+}
+
+#[test]
+fn test_transfer_position() {
+    let mut pool = new_funded_pool(TO_RAW_TOKEN, 0, 86400);
+    let recipient_storage = Pubkey::new_unique();
+    let mut user = new_user(&pool);
+    let mut recipient = new_user(&pool);
+    user.user_cwar_staked_amount = 300 * TO_RAW_TOKEN;
+    user.last_staked_timestamp = 500;
+    recipient.user_cwar_staked_amount = 100 * TO_RAW_TOKEN;
+    recipient.last_staked_timestamp = 100;
+    recipient.unstake_penality_duration_end = pool.reward_duration_end + 1;
+    pool.total_staked = 400 * TO_RAW_TOKEN;
+
+    update_rewards(&mut pool, None, Some(&mut user), 1000).unwrap();
+    update_rewards(&mut pool, None, Some(&mut recipient), 1000).unwrap();
+    user.reward_streams[0].rewards_amount_pending = 7;
+    let user_rewards = user.rewards_amount_pending;
+    let recipient_rewards = recipient.rewards_amount_pending;
+    assert!(user_rewards > 0 && recipient_rewards > 0);
+
+    // the recipient keeps the later timestamps, the pool totals don't move
+    transfer_position(
+        &pool,
+        &mut user,
+        &mut recipient,
+        &recipient_storage,
+        &mut [],
+    )
+    .unwrap();
+    assert_eq!(user.user_cwar_staked_amount, 0);
+    assert_eq!(user.rewards_amount_pending, 0);
+    assert_eq!(user.reward_streams[0].rewards_amount_pending, 0);
+    assert_eq!(recipient.user_cwar_staked_amount, 400 * TO_RAW_TOKEN);
+    assert_eq!(
+        recipient.rewards_amount_pending,
+        user_rewards + recipient_rewards
+    );
+    assert_eq!(recipient.reward_streams[0].rewards_amount_pending, 7);
+    assert_eq!(recipient.last_staked_timestamp, 500);
+    assert_eq!(
+        recipient.unstake_penality_duration_end,
+        pool.reward_duration_end + 1
+    );
+    assert_eq!(pool.total_staked, 400 * TO_RAW_TOKEN);
+
+    // from here on the recipient earns on the whole position
+    update_rewards(&mut pool, None, Some(&mut recipient), 2000).unwrap();
+    assert!(
+        (user_rewards + recipient_rewards + 1000 * TO_RAW_TOKEN)
+            .abs_diff(recipient.rewards_amount_pending)
+            < 3
+    );
+
+    // the recipient's own limits apply, every lock position has to move and unbonding blocks
+    // the transfer
+    let mut user = new_user(&pool);
+    user.user_cwar_staked_amount = 200 * TO_RAW_TOKEN;
+    pool.max_stake_per_user = 500 * TO_RAW_TOKEN;
+    assert_eq!(
+        transfer_position(
+            &pool,
+            &mut user,
+            &mut recipient,
+            &recipient_storage,
+            &mut []
+        )
+        .unwrap_err(),
+        CryowarError::UserStakeLimitExceeded.into()
+    );
+    user.locked_amount = 1;
+    assert_eq!(
+        transfer_position(
+            &pool,
+            &mut user,
+            &mut new_user(&pool),
+            &recipient_storage,
+            &mut [],
+        )
+        .unwrap_err(),
+        CryowarError::PositionNotTransferable.into()
+    );
+    user.locked_amount = 0;
+    user.unbonding_amount = 1;
+    assert_eq!(
+        transfer_position(
+            &pool,
+            &mut user,
+            &mut new_user(&pool),
+            &recipient_storage,
+            &mut [],
+        )
+        .unwrap_err(),
+        CryowarError::PositionNotTransferable.into()
+    );
+
+    // a recipient without stake takes the user's timestamps rather than the pool's end
+    let mut user = new_user(&pool);
+    user.user_cwar_staked_amount = 200 * TO_RAW_TOKEN;
+    user.last_staked_timestamp = 1500;
+    user.unstake_penality_duration_end = 3000;
+    let mut recipient = new_user(&pool);
+    assert!(recipient.unstake_penality_duration_end > user.unstake_penality_duration_end);
+    transfer_position(
+        &pool,
+        &mut user,
+        &mut recipient,
+        &recipient_storage,
+        &mut [],
+    )
+    .unwrap();
+    assert_eq!(recipient.unstake_penality_duration_end, 3000);
+    assert_eq!(recipient.last_staked_timestamp, 1500);
+}
+
+#[test]
+fn test_transfer_lock_positions() {
+    let mut pool = new_funded_pool(TO_RAW_TOKEN, 0, 86400);
+    let lock_tier = LockTier {
+        duration: 3600,
+        multiplier_basis_points: 20_000,
+    };
+    let recipient_storage = Pubkey::new_unique();
+    let mut user = new_user(&pool);
+    let mut lock_positions = vec![];
+    for position_id in 0..2u64 {
+        let weight =
+            add_lock_position(&mut pool, &mut user, &lock_tier, 100 * TO_RAW_TOKEN).unwrap();
+        lock_positions.push(LockPosition {
+            acc_type: AccTypesWithVersion::LockPositionDataV1 as u8,
+            user_storage: Pubkey::new_unique(),
+            position_id: position_id + 4,
+            amount: 100 * TO_RAW_TOKEN,
+            weight,
+            lock_tier_index: 0,
+            locked_at: 0,
+            unlock_at: lock_tier.duration,
+        });
+    }
+    let mut recipient = new_user(&pool);
+    recipient.next_lock_position_id = 7;
+    let pool_before = pool;
+
+    // leaving a position behind, or passing one twice, is refused
+    assert_eq!(
+        transfer_position(
+            &pool,
+            &mut user.clone(),
+            &mut recipient.clone(),
+            &recipient_storage,
+            &mut lock_positions[..1],
+        )
+        .unwrap_err(),
+        CryowarError::PositionNotTransferable.into()
+    );
+    let mut duplicated = [lock_positions[0], lock_positions[0]];
+    assert_eq!(
+        transfer_position(
+            &pool,
+            &mut user.clone(),
+            &mut recipient.clone(),
+            &recipient_storage,
+            &mut duplicated,
+        )
+        .unwrap_err(),
+        CryowarError::PositionNotTransferable.into()
+    );
+
+    // the positions keep their weight and unlock time under the recipient's next ids
+    transfer_position(
+        &pool,
+        &mut user,
+        &mut recipient,
+        &recipient_storage,
+        &mut lock_positions,
+    )
+    .unwrap();
+    assert_eq!(user.locked_amount, 0);
+    assert_eq!(user.locked_weight, 0);
+    assert_eq!(recipient.locked_amount, 200 * TO_RAW_TOKEN);
+    assert_eq!(recipient.locked_weight, 400 * TO_RAW_TOKEN);
+    assert_eq!(recipient.next_lock_position_id, 9);
+    assert_eq!(lock_positions[0].position_id, 7);
+    assert_eq!(lock_positions[1].position_id, 8);
+    assert!(lock_positions
+        .iter()
+        .all(|x| x.user_storage == recipient_storage
+            && x.weight == 200 * TO_RAW_TOKEN
+            && x.unlock_at == 3600));
+    assert_eq!(pool.total_locked, pool_before.total_locked);
+    assert_eq!(pool.total_locked_weight, pool_before.total_locked_weight);
+
+    // a recipient whose cap has no room for the locked stake is refused
+    let mut user = recipient;
+    let mut capped_recipient = new_user(&pool);
+    capped_recipient.allowlist_max_stake = 150 * TO_RAW_TOKEN;
+    assert_eq!(
+        transfer_position(
+            &pool,
+            &mut user,
+            &mut capped_recipient,
+            &recipient_storage,
+            &mut lock_positions,
+        )
+        .unwrap_err(),
+        CryowarError::UserStakeLimitExceeded.into()
+    );
+}
+
+#[test]
+fn test_receipt_mint() {
+    let pool_signer = Pubkey::new_unique();
+    let staking_mint = Mint {
+        decimals: 9,
+        is_initialized: true,
+        ..Mint::default()
+    };
+    let receipt_mint = Mint {
+        mint_authority: COption::Some(pool_signer),
+        ..staking_mint
+    };
+    assert!(assert_receipt_mint_data(&receipt_mint, &staking_mint, &pool_signer).is_ok());
+    let invalid_receipt_mints = [
+        Mint {
+            mint_authority: COption::Some(Pubkey::new_unique()),
+            ..receipt_mint
+        },
+        Mint {
+            freeze_authority: COption::Some(pool_signer),
+            ..receipt_mint
+        },
+        Mint {
+            supply: 1,
+            ..receipt_mint
+        },
+        Mint {
+            decimals: 6,
+            ..receipt_mint
+        },
+    ];
+    for invalid_receipt_mint in invalid_receipt_mints.iter() {
+        assert_eq!(
+            assert_receipt_mint_data(invalid_receipt_mint, &staking_mint, &pool_signer)
+                .unwrap_err(),
+            CryowarError::InvalidReceiptMint.into()
+        );
+    }
+
+    // only once, and only while the pool has no stake without receipts
+    let receipt_mint = Pubkey::new_unique();
+    for setup in [
+        |pool: &mut CwarPool| pool.total_staked = 1,
+        |pool: &mut CwarPool| pool.total_unbonding = 1,
+        |pool: &mut CwarPool| pool.unbonding_duration = 1,
+    ]
+    .iter()
+    {
+        let mut pool = new_funded_pool(TO_RAW_TOKEN, 0, 86400);
+        setup(&mut pool);
+        assert_eq!(
+            set_receipt_mint(&mut pool, receipt_mint).unwrap_err(),
+            CryowarError::ReceiptMintChangeNotAllowed.into()
+        );
+    }
+    let mut pool = new_funded_pool(TO_RAW_TOKEN, 0, 86400);
+    assert_eq!(
+        set_receipt_mint(&mut pool, Pubkey::default()).unwrap_err(),
+        CryowarError::ReceiptMintChangeNotAllowed.into()
+    );
+    set_receipt_mint(&mut pool, receipt_mint).unwrap();
+    assert!(pool.has_receipt_mint());
+    assert_eq!(
+        set_receipt_mint(&mut pool, Pubkey::new_unique()).unwrap_err(),
+        CryowarError::ReceiptMintChangeNotAllowed.into()
+    );
+    assert_eq!(pool.receipt_mint, receipt_mint);
+}
+
+#[test]
+fn test_settle_receipt() {
+    let mut pool = new_funded_pool(TO_RAW_TOKEN, 0, 86400);
+    pool.receipt_mint = Pubkey::new_unique();
+    let mut source = new_user(&pool);
+    let mut holder = new_user(&pool);
+    source.user_cwar_staked_amount = 300 * TO_RAW_TOKEN;
+    source.last_staked_timestamp = 500;
+    holder.user_cwar_staked_amount = 100 * TO_RAW_TOKEN;
+    holder.last_staked_timestamp = 100;
+    pool.total_staked = 400 * TO_RAW_TOKEN;
+    update_rewards(&mut pool, None, Some(&mut source), 1000).unwrap();
+    update_rewards(&mut pool, None, Some(&mut holder), 1000).unwrap();
+    let source_rewards = source.rewards_amount_pending;
+
+    // balances that match the stakes leave nothing to settle
+    assert_eq!(
+        settle_receipt(
+            &pool,
+            &mut source,
+            &mut holder,
+            300 * TO_RAW_TOKEN,
+            100 * TO_RAW_TOKEN
+        )
+        .unwrap_err(),
+        CryowarError::NothingToSettle.into()
+    );
+
+    // the source sent 200 receipts, 50 of them to the holder
+    assert_eq!(
+        settle_receipt(
+            &pool,
+            &mut source,
+            &mut holder,
+            100 * TO_RAW_TOKEN,
+            150 * TO_RAW_TOKEN
+        )
+        .unwrap(),
+        50 * TO_RAW_TOKEN
+    );
+    assert_eq!(source.user_cwar_staked_amount, 250 * TO_RAW_TOKEN);
+    assert_eq!(holder.user_cwar_staked_amount, 150 * TO_RAW_TOKEN);
+    assert_eq!(holder.last_staked_timestamp, 500);
+    assert_eq!(source.rewards_amount_pending, source_rewards);
+    assert_eq!(pool.total_staked, 400 * TO_RAW_TOKEN);
+
+    // a holder without surplus receipts can't pull stake, whatever the source is short of
+    assert_eq!(
+        settle_receipt(
+            &pool,
+            &mut source,
+            &mut holder,
+            100 * TO_RAW_TOKEN,
+            150 * TO_RAW_TOKEN
+        )
+        .unwrap_err(),
+        CryowarError::NothingToSettle.into()
+    );
+
+    // the holder's own limits apply
+    pool.max_stake_per_user = 200 * TO_RAW_TOKEN;
+    assert_eq!(
+        settle_receipt(
+            &pool,
+            &mut source,
+            &mut holder,
+            100 * TO_RAW_TOKEN,
+            300 * TO_RAW_TOKEN
+        )
+        .unwrap_err(),
+        CryowarError::UserStakeLimitExceeded.into()
+    );
+    assert_eq!(
+        settle_receipt(
+            &pool,
+            &mut source,
+            &mut holder,
+            200 * TO_RAW_TOKEN,
+            300 * TO_RAW_TOKEN
+        )
+        .unwrap(),
+        50 * TO_RAW_TOKEN
+    );
+
+    // a holder created to settle takes the source timestamps rather than the pool's end
+    let mut holder = new_user(&pool);
+    source.unstake_penality_duration_end = 3000;
+    assert!(holder.unstake_penality_duration_end > source.unstake_penality_duration_end);
+    assert_eq!(
+        settle_receipt(&pool, &mut source, &mut holder, 0, 10 * TO_RAW_TOKEN).unwrap(),
+        10 * TO_RAW_TOKEN
+    );
+    assert_eq!(holder.unstake_penality_duration_end, 3000);
+    assert_eq!(holder.last_staked_timestamp, source.last_staked_timestamp);
+}
+
+#[test]
+fn test_stake_limits_bounds() {
+    assert!(assert_stake_limits(0, 0, 0).is_ok());
+    assert!(assert_stake_limits(u64::MAX, 0, 0).is_ok());
+    assert!(assert_stake_limits(10, 10, 10).is_ok());
+    assert!(assert_stake_limits(10, 0, 100).is_ok());
+    for (min_stake, max_stake_per_user, max_total_staked) in
+        [(11, 10, 0), (11, 0, 10), (0, 11, 10), (10, 100, 50)]
+    {
+        assert_eq!(
+            assert_stake_limits(min_stake, max_stake_per_user, max_total_staked).unwrap_err(),
+            CryowarError::InvalidStakeLimits.into()
+        );
+    }
+}
+
+#[test]
+fn test_pool_config_bounds() {
+    assert!(assert_pool_config(0, 0, 0).is_ok());
+    assert!(assert_pool_config(MAX_UNSTAKE_PENALITY_BASIS_POINTS, 0, 0).is_ok());
+    assert!(assert_pool_config(0, 86400, 0).is_ok());
+    assert!(assert_pool_config(0, 0, MAX_UNBONDING_DURATION).is_ok());
+    assert_eq!(
+        assert_pool_config(MAX_UNSTAKE_PENALITY_BASIS_POINTS + 1, 0, 0).unwrap_err(),
+        CryowarError::UnstakePenalityTooHigh.into()
+    );
+    assert_eq!(
+        assert_pool_config(500, 86400, 0).unwrap_err(),
+        CryowarError::PoolEitherCanHaveUnstakePanelityOrLockingDuration.into()
+    );
+    for (unstake_penality_basis_points, locking_duration, unbonding_duration) in [
+        (500, 0, 86400),
+        (0, 86400, 86400),
+        (0, 0, MAX_UNBONDING_DURATION + 1),
+    ] {
+        assert_eq!(
+            assert_pool_config(
+                unstake_penality_basis_points,
+                locking_duration,
+                unbonding_duration
+            )
+            .unwrap_err(),
+            CryowarError::InvalidUnbondingDuration.into()
+        );
+    }
+}
+
+#[test]
+fn test_pending_actions_fit_their_account() {
+    let actions = [
+        PoolAction::RemoveFunder {
+            funder_wallet: Pubkey::new_unique(),
+        },
+        PoolAction::UpdatePoolConfig {
+            unstake_penality_basis_points: Some(100),
+            locking_duration: Some(86400),
+            authority_penality_deposit_ata: Some(Pubkey::new_unique()),
+            unbonding_duration: Some(86400),
+        },
+        PoolAction::ClosePool {
+            staking_refund_ata: Pubkey::new_unique(),
+            rewards_refund_ata: Pubkey::new_unique(),
+        },
+        PoolAction::SetTimelockDelay {
+            timelock_delay: 86400,
+        },
+        PoolAction::ReduceRewards {
+            amount: u64::MAX,
+            new_reward_duration_end: u64::MAX,
+            rewards_refund_ata: Pubkey::new_unique(),
+        },
+        PoolAction::CancelRewards {
+            rewards_refund_ata: Pubkey::new_unique(),
+        },
+        PoolAction::SetLockTiers {
+            lock_tiers: [LockTier {
+                duration: MAX_LOCK_DURATION,
+                multiplier_basis_points: MAX_LOCK_MULTIPLIER_BASIS_POINTS,
+            }; MAX_LOCK_TIERS],
+        },
+        PoolAction::SetPenalitySchedule {
+            penality_schedule: PenalitySchedule {
+                kind: PENALITY_SCHEDULE_STEPPED,
+                decay_duration: u64::MAX,
+                steps: [PenalityStep {
+                    stake_age_below: u64::MAX,
+                    basis_points: u16::MAX,
+                }; MAX_PENALITY_STEPS],
+            },
+        },
+        PoolAction::SetPenalityRouting {
+            penality_routing: PenalityRouting {
+                burn_basis_points: u16::MAX,
+                reward_vault_basis_points: u16::MAX,
+                redistribute_basis_points: u16::MAX,
+                treasury_basis_points: u16::MAX,
+            },
+        },
+        PoolAction::SetStakeLimits {
+            min_stake: u64::MAX,
+            max_stake_per_user: u64::MAX,
+            max_total_staked: u64::MAX,
+        },
+        PoolAction::SetAllowlistRoot {
+            allowlist_root: [u8::MAX; 32],
+        },
+        PoolAction::SetReceiptMint {
+            receipt_mint: Pubkey::new_from_array([u8::MAX; 32]),
+        },
+    ];
+    for action in actions.iter() {
+        let pending_action = PendingAction {
+            acc_type: AccTypesWithVersion::PendingActionDataV1 as u8,
+            cwar_pool: Pubkey::new_unique(),
+            action_id: 3,
+            queued_at: 1000,
+            eta: 1000 + 86400,
+            rent_payer: Pubkey::new_unique(),
+            action: *action,
+        };
+        let mut pending_action_data = pending_action.try_to_vec().unwrap();
+        assert!(pending_action_data.len() <= PENDING_ACTION_STORAGE_TOTAL_BYTES);
+        pending_action_data.resize(PENDING_ACTION_STORAGE_TOTAL_BYTES, 0u8);
+        let unpacked = PendingAction::deserialize(&mut &pending_action_data[..]).unwrap();
+        assert_eq!(unpacked.action, *action);
+        assert_eq!(unpacked.eta, pending_action.eta);
+    }
+}
+
+#[test]
+fn test_pending_action_grace_period() {
+    let pending_action = PendingAction {
+        acc_type: AccTypesWithVersion::PendingActionDataV1 as u8,
+        cwar_pool: Pubkey::new_unique(),
+        action_id: 0,
+        queued_at: 1000,
+        eta: 1000 + 86400,
+        rent_payer: Pubkey::new_unique(),
+        action: PoolAction::SetTimelockDelay { timelock_delay: 0 },
+    };
+    assert_eq!(
+        assert_action_executable(&pending_action, pending_action.eta - 1).unwrap_err(),
+        CryowarError::TimelockNotExpired.into()
+    );
+    assert!(assert_action_executable(&pending_action, pending_action.eta).is_ok());
+    let expiry = pending_action.eta + ACTION_EXECUTION_GRACE_PERIOD;
+    assert!(assert_action_executable(&pending_action, expiry).is_ok());
+    assert_eq!(
+        assert_action_executable(&pending_action, expiry + 1).unwrap_err(),
+        CryowarError::ActionExpired.into()
+    );
+}
+
+#[test]
+fn test_reward_schedule() {
+    let mut pool = new_funded_pool(0, 1000, 86400);
+    pool.reward_schedule = Pubkey::new_unique();
+    pool.reward_schedule_last_update_time = 1000;
+    let mut schedule = RewardSchedule {
+        acc_type: AccTypesWithVersion::RewardScheduleDataV1 as u8,
+        cwar_pool: Pubkey::new_unique(),
+        phases: [RewardPhase::default(); MAX_REWARD_PHASES],
+    };
+    let phase = |start, end, reward_rate| RewardPhase {
+        start,
+        end,
+        reward_rate,
+    };
+
+    // phases can't start in the past, be empty or emit nothing
+    for invalid_phase in [
+        phase(900, 2000, 10),
+        phase(1500, 1500, 10),
+        phase(1000, 2000, 0),
+    ] {
+        assert_eq!(
+            add_reward_phase(&mut pool, &mut schedule, invalid_phase, 1000).unwrap_err(),
+            CryowarError::InvalidRewardPhase.into()
+        );
+    }
+
+    assert_eq!(
+        add_reward_phase(
+            &mut pool,
+            &mut schedule,
+            phase(1000, 2000, 10 * TO_RAW_TOKEN),
+            1000
+        )
+        .unwrap(),
+        10_000 * TO_RAW_TOKEN
+    );
+    assert_eq!(
+        add_reward_phase(
+            &mut pool,
+            &mut schedule,
+            phase(2000, 4000, 5 * TO_RAW_TOKEN),
+            1000
+        )
+        .unwrap(),
+        10_000 * TO_RAW_TOKEN
+    );
+    assert_eq!(pool.scheduled_rewards_left, 20_000 * TO_RAW_TOKEN);
+    assert_eq!(pool.reward_schedule_end, 4000);
+    assert_eq!(
+        get_reward_liabilities(&pool).unwrap(),
+        20_000 * TO_RAW_TOKEN
+    );
+    // phases can't overlap the ones already scheduled
+    assert_eq!(
+        add_reward_phase(&mut pool, &mut schedule, phase(3000, 5000, 1), 1000).unwrap_err(),
+        CryowarError::InvalidRewardPhase.into()
+    );
+
+    let mut user = new_user(&pool);
+    user.user_cwar_staked_amount = 100 * TO_RAW_TOKEN;
+    pool.total_staked = 100 * TO_RAW_TOKEN;
+    // schedule pools can't be checkpointed without their schedule
+    assert_eq!(
+        update_rewards(&mut pool, None, Some(&mut user), 3000).unwrap_err(),
+        CryowarError::InvalidRewardSchedule.into()
+    );
+
+    // the whole first phase and half of the second one are emitted
+    update_rewards(&mut pool, Some(&schedule), Some(&mut user), 3000).unwrap();
+    assert_eq!(pool.reward_schedule_last_update_time, 3000);
+    assert_eq!(pool.scheduled_rewards_left, 5_000 * TO_RAW_TOKEN);
+    assert_eq!(pool.rewards_owed, 15_000 * TO_RAW_TOKEN);
+    assert_eq!(user.rewards_amount_pending, 15_000 * TO_RAW_TOKEN);
+
+    // checkpoints past the last phase emit nothing more
+    update_rewards(&mut pool, Some(&schedule), Some(&mut user), 10_000).unwrap();
+    assert_eq!(pool.scheduled_rewards_left, 0);
+    assert_eq!(user.rewards_amount_pending, 20_000 * TO_RAW_TOKEN);
+
+    // emitted phases make room for new ones
+    let mut pool = new_funded_pool(0, 3000, 86400);
+    pool.reward_schedule = Pubkey::new_unique();
+    pool.reward_schedule_last_update_time = 3000;
+    pool.reward_schedule_end = 4000;
+    schedule.phases = [RewardPhase::default(); MAX_REWARD_PHASES];
+    schedule.phases[0] = phase(1000, 2000, 1);
+    schedule.phases[1] = phase(2000, 4000, 1);
+    for i in 0..(MAX_REWARD_PHASES as u64 - 1) {
+        add_reward_phase(
+            &mut pool,
+            &mut schedule,
+            phase(4000 + i * 10, 4010 + i * 10, 1),
+            3000,
+        )
+        .unwrap();
+    }
+    assert_eq!(schedule.phases[0], phase(2000, 4000, 1));
+    assert_eq!(
+        add_reward_phase(&mut pool, &mut schedule, phase(5000, 6000, 1), 3000).unwrap_err(),
+        CryowarError::RewardScheduleFull.into()
+    );
+}
+
+#[test]
+fn test_close_pool_gates_on_accounted_stake() {
+    let mut pool = new_funded_pool(TO_RAW_TOKEN, 0, 86400);
+    // the vault balance is no input, only the stake the pool accounts for
+    assert!(check_pool_closable(&pool, 86401).is_ok());
+    assert_eq!(
+        check_pool_closable(&pool, 86400).unwrap_err(),
+        CryowarError::PoolStillActive.into()
+    );
+
+    for mutate in [
+        |pool: &mut CwarPool| pool.total_staked = 1,
+        |pool: &mut CwarPool| pool.total_unbonding = 1,
+        |pool: &mut CwarPool| pool.user_stake_count = 1,
+        |pool: &mut CwarPool| pool.reward_schedule_end = 90000,
+    ] {
+        let mut active_pool = pool;
+        mutate(&mut active_pool);
+        assert_eq!(
+            check_pool_closable(&active_pool, 86401).unwrap_err(),
+            CryowarError::PoolStillActive.into()
+        );
+    }
+
+    // a pool that was never funded has no emission to wait for
+    pool.reward_duration_end = 0;
+    assert!(check_pool_closable(&pool, 1).is_ok());
+    pool.user_stake_count = 1;
+    assert_eq!(
+        check_pool_closable(&pool, 1).unwrap_err(),
+        CryowarError::PoolStillActive.into()
+    );
+}
+
+#[test]
+fn test_migrate_pool_totals() {
+    let mut pool = new_funded_pool(TO_RAW_TOKEN, 0, 86400);
+    pool.acc_type = AccTypesWithVersion::CwarPoolDataV1 as u8;
+    pool.user_stake_count = 2;
+
+    // the whole staking vault counts as stake, so no principal is left to recover
+    migrate_pool_totals(&mut pool, 350 * TO_RAW_TOKEN, 100_000 * TO_RAW_TOKEN, 1000).unwrap();
+    assert_eq!(pool.acc_type, AccTypesWithVersion::CwarPoolDataV2 as u8);
+    assert_eq!(pool.total_staked, 350 * TO_RAW_TOKEN);
+    assert_eq!(get_staking_vault_surplus(350 * TO_RAW_TOKEN, &pool), 0);
+    assert_eq!(pool.total_stake_last_update_time, 1000);
+    assert_eq!(pool.rewards_owed, (100_000 - 85_400) * TO_RAW_TOKEN);
+
+    // without users the vault is all surplus
+    let mut empty_pool = new_funded_pool(TO_RAW_TOKEN, 0, 86400);
+    empty_pool.acc_type = AccTypesWithVersion::CwarPoolDataV1 as u8;
+    migrate_pool_totals(&mut empty_pool, 5, 100_000 * TO_RAW_TOKEN, 1000).unwrap();
+    assert_eq!(empty_pool.total_staked, 0);
+    assert_eq!(get_staking_vault_surplus(5, &empty_pool), 5);
+}